@@ -0,0 +1,381 @@
+use crate::error::Error;
+
+use std::path::Path;
+use std::str::FromStr;
+
+use coin_store::{AssetRegistryEntry, AssetRegistryStore, MAX_ASSET_PRECISION, Store};
+use serde::Deserialize;
+use simplicityhl::elements::AssetId;
+use simplicityhl::elements::hex::ToHex;
+use simplicityhl_core::LIQUID_TESTNET_BITCOIN_ASSET;
+
+const NATIVE_TICKER: &str = "LBTC";
+const NATIVE_PRECISION: u8 = 8;
+
+const REGISTRY_TIMEOUT_SECS: u64 = 30;
+
+/// Tickers and precisions of the assets registered in the wallet, for display and amount
+/// parsing. The native asset is always known.
+#[derive(Debug, Clone, Default)]
+pub struct AssetRegistry {
+    entries: Vec<AssetRegistryEntry>,
+}
+
+impl AssetRegistry {
+    #[must_use]
+    pub const fn new(entries: Vec<AssetRegistryEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Load the wallet's asset registry. Without a wallet database only the native asset is known.
+    pub async fn load(db_path: &Path) -> Self {
+        if !Store::exists(db_path) {
+            return Self::default();
+        }
+
+        let entries = match Store::connect(db_path).await {
+            Ok(store) => store.list_assets().await,
+            Err(e) => Err(e),
+        };
+
+        entries
+            .inspect_err(|e| tracing::debug!("Could not load asset registry: {e}"))
+            .map(Self::new)
+            .unwrap_or_default()
+    }
+
+    /// Ticker and precision of an asset, if registered.
+    fn lookup(&self, asset_id: AssetId) -> Option<(&str, u8)> {
+        if let Some(entry) = self.entries.iter().find(|entry| entry.asset_id == asset_id) {
+            return Some((entry.ticker.as_str(), entry.precision));
+        }
+
+        (asset_id == *LIQUID_TESTNET_BITCOIN_ASSET).then_some((NATIVE_TICKER, NATIVE_PRECISION))
+    }
+
+    fn lookup_ticker(&self, ticker: &str) -> Option<AssetId> {
+        if let Some(entry) = self.entries.iter().find(|entry| entry.ticker.eq_ignore_ascii_case(ticker)) {
+            return Some(entry.asset_id);
+        }
+
+        ticker.eq_ignore_ascii_case(NATIVE_TICKER).then_some(*LIQUID_TESTNET_BITCOIN_ASSET)
+    }
+
+    /// Ticker of an asset, or a truncated asset id for unregistered assets.
+    #[must_use]
+    pub fn label(&self, asset_id: &AssetId) -> String {
+        self.lookup(*asset_id).map_or_else(
+            || {
+                let hex = asset_id.to_hex();
+                format!("({})...", &hex[..hex.len().min(8)])
+            },
+            |(ticker, _)| ticker.to_string(),
+        )
+    }
+
+    /// Format a base-unit amount in whole units of its asset, e.g. "1.5 USDt".
+    /// Amounts of unregistered assets are shown in base units.
+    #[must_use]
+    pub fn format_amount(&self, amount: u64, asset_id: &AssetId) -> String {
+        format!("{} {}", self.format_units(amount, asset_id), self.label(asset_id))
+    }
+
+    /// Like [`Self::format_amount`] without the ticker, for columns that already name the asset.
+    #[must_use]
+    pub fn format_units(&self, amount: u64, asset_id: &AssetId) -> String {
+        format_decimal(amount, self.precision(asset_id))
+    }
+
+    /// Like [`Self::format_units`] for a signed amount, such as a flow out of the wallet.
+    #[must_use]
+    pub fn format_signed_units(&self, amount: i128, asset_id: &AssetId) -> String {
+        let units = self.format_units(u64::try_from(amount.unsigned_abs()).unwrap_or(u64::MAX), asset_id);
+        if amount < 0 { format!("-{units}") } else { units }
+    }
+
+    /// Decimal places of an asset; unregistered assets are counted in base units.
+    #[must_use]
+    pub fn precision(&self, asset_id: &AssetId) -> u8 {
+        self.lookup(*asset_id).map_or(0, |(_, precision)| precision)
+    }
+
+    /// Registered ticker of an asset.
+    #[must_use]
+    pub fn ticker(&self, asset_id: &AssetId) -> Option<&str> {
+        self.lookup(*asset_id).map(|(ticker, _)| ticker)
+    }
+
+    /// Resolve a registered ticker or a full asset id.
+    #[must_use]
+    pub fn resolve(&self, value: &str) -> Option<AssetId> {
+        self.lookup_ticker(value).or_else(|| value.parse().ok())
+    }
+}
+
+/// Format `amount` base units with `precision` decimal places, dropping trailing zeros.
+#[must_use]
+pub fn format_decimal(amount: u64, precision: u8) -> String {
+    if precision == 0 {
+        return amount.to_string();
+    }
+
+    let scale = 10u64.pow(u32::from(precision));
+    let fraction = format!("{:0width$}", amount % scale, width = usize::from(precision));
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        (amount / scale).to_string()
+    } else {
+        format!("{}.{fraction}", amount / scale)
+    }
+}
+
+/// Parse a decimal amount into base units of an asset with `precision` decimal places.
+pub fn parse_decimal(value: &str, precision: u8) -> Result<u64, Error> {
+    let invalid = || Error::Config(format!("Invalid amount '{value}'"));
+
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let is_numeric = whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_numeric {
+        return Err(invalid());
+    }
+    if fraction.len() > usize::from(precision) {
+        return Err(Error::Config(format!("Amount '{value}' has more than {precision} decimal places")));
+    }
+
+    let scale = 10u64.pow(u32::from(precision));
+    let whole: u64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| invalid())? };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        let padded = format!("{fraction:0<width$}", width = usize::from(precision));
+        padded.parse().map_err(|_| invalid())?
+    };
+
+    whole
+        .checked_mul(scale)
+        .and_then(|base| base.checked_add(fraction))
+        .ok_or_else(|| Error::Config(format!("Amount '{value}' is too large")))
+}
+
+/// An amount given on the command line.
+///
+/// Plain integers are base units (`1500`). With a ticker the amount is in whole units of
+/// that asset and may have decimals (`"1.5 USDt"` or `1.5USDt`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetAmount {
+    value: String,
+    ticker: Option<String>,
+}
+
+impl FromStr for AssetAmount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+        let (value, ticker) = (&s[..split], s[split..].trim());
+
+        if value.is_empty() {
+            return Err(format!("Invalid amount '{s}', expected e.g. 1500 or \"1.5 USDt\""));
+        }
+        if ticker.is_empty() && value.contains('.') {
+            return Err(format!("Amount '{s}' has decimals but no ticker; add the asset ticker or give base units"));
+        }
+
+        Ok(Self {
+            value: value.to_string(),
+            ticker: (!ticker.is_empty()).then(|| ticker.to_string()),
+        })
+    }
+}
+
+impl AssetAmount {
+    /// Asset named by the ticker, if one was given.
+    pub fn ticker_asset(&self, assets: &AssetRegistry) -> Result<Option<AssetId>, Error> {
+        self.ticker
+            .as_deref()
+            .map(|ticker| {
+                assets.lookup_ticker(ticker).ok_or_else(|| {
+                    Error::Config(format!("Unknown asset ticker '{ticker}'; register it with `asset add`"))
+                })
+            })
+            .transpose()
+    }
+
+    /// Convert to base units of `asset_id`, checking that a given ticker names that asset.
+    pub fn to_base_units(&self, assets: &AssetRegistry, asset_id: AssetId) -> Result<u64, Error> {
+        let Some(ticker_asset) = self.ticker_asset(assets)? else {
+            return self
+                .value
+                .parse()
+                .map_err(|_| Error::Config(format!("Invalid amount '{}'", self.value)));
+        };
+
+        if ticker_asset != asset_id {
+            return Err(Error::Config(format!(
+                "Amount is in {} but the asset is {}",
+                assets.label(&ticker_asset),
+                assets.label(&asset_id)
+            )));
+        }
+
+        parse_decimal(&self.value, assets.precision(&asset_id))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryEntity {
+    domain: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RegistryContract {
+    ticker: Option<String>,
+    name: Option<String>,
+    precision: Option<u8>,
+    entity: Option<RegistryEntity>,
+}
+
+/// An asset as published by the Liquid asset registry.
+#[derive(Debug, Deserialize)]
+struct RegistryAsset {
+    asset_id: Option<String>,
+    ticker: Option<String>,
+    name: Option<String>,
+    precision: Option<u8>,
+    entity: Option<RegistryEntity>,
+    #[serde(default)]
+    contract: RegistryContract,
+}
+
+impl RegistryAsset {
+    fn into_entry(self, key: Option<&str>) -> Option<AssetRegistryEntry> {
+        let asset_id = self.asset_id.as_deref().or(key)?.parse().ok()?;
+        let ticker = self.ticker.or(self.contract.ticker)?;
+        let precision = self.precision.or(self.contract.precision).unwrap_or(0);
+        if precision > MAX_ASSET_PRECISION {
+            return None;
+        }
+
+        Some(AssetRegistryEntry {
+            asset_id,
+            name: self.name.or(self.contract.name).unwrap_or_else(|| ticker.clone()),
+            ticker,
+            precision,
+            issuer: self.entity.or(self.contract.entity).and_then(|entity| entity.domain),
+        })
+    }
+}
+
+/// Parse assets in the Liquid asset registry format: a single asset, a list of assets,
+/// or the registry index keyed by asset id. Assets without a ticker are skipped.
+pub fn parse_registry_json(json: &str) -> Result<Vec<AssetRegistryEntry>, Error> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+
+    let assets: Vec<(Option<String>, serde_json::Value)> = match value {
+        serde_json::Value::Array(items) => items.into_iter().map(|item| (None, item)).collect(),
+        serde_json::Value::Object(map) if map.contains_key("asset_id") => vec![(None, serde_json::Value::Object(map))],
+        serde_json::Value::Object(map) => map.into_iter().map(|(key, item)| (Some(key), item)).collect(),
+        _ => return Err(Error::Config("Asset registry JSON must be an object or an array".to_string())),
+    };
+
+    assets
+        .into_iter()
+        .map(|(key, item)| Ok::<_, Error>(serde_json::from_value::<RegistryAsset>(item)?.into_entry(key.as_deref())))
+        .filter_map(Result::transpose)
+        .collect()
+}
+
+/// Read registry JSON from a file, or download it if `source` is an HTTP(S) URL.
+pub fn read_registry_source(source: &str) -> Result<String, Error> {
+    if !(source.starts_with("http://") || source.starts_with("https://")) {
+        return Ok(std::fs::read_to_string(source)?);
+    }
+
+    let response = minreq::get(source)
+        .with_header("User-Agent", "simplicity-dex/1.0")
+        .with_timeout(REGISTRY_TIMEOUT_SECS)
+        .send()
+        .map_err(|e| Error::Config(format!("Failed to fetch asset registry: {e}")))?;
+
+    if response.status_code != 200 {
+        return Err(Error::Config(format!(
+            "Failed to fetch asset registry: HTTP {}",
+            response.status_code
+        )));
+    }
+
+    response
+        .as_str()
+        .map(ToString::to_string)
+        .map_err(|e| Error::Config(format!("Failed to read asset registry response: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_round_trip() {
+        assert_eq!(format_decimal(150_000_000, 8), "1.5");
+        assert_eq!(format_decimal(100_000_000, 8), "1");
+        assert_eq!(format_decimal(1, 8), "0.00000001");
+        assert_eq!(format_decimal(1234, 0), "1234");
+
+        assert_eq!(parse_decimal("1.5", 8).unwrap(), 150_000_000);
+        assert_eq!(parse_decimal(".25", 2).unwrap(), 25);
+        assert_eq!(parse_decimal("7", 2).unwrap(), 700);
+        assert!(parse_decimal("1.001", 2).is_err());
+        assert!(parse_decimal("1.2.3", 8).is_err());
+        assert!(parse_decimal(".", 8).is_err());
+        assert!(parse_decimal("184467440737.1", 8).is_err());
+    }
+
+    #[test]
+    fn test_asset_amount_parsing() {
+        let other = AssetId::from_slice(&[7; 32]).unwrap();
+        let assets = AssetRegistry::default();
+
+        let amount: AssetAmount = "1.5 LBTC".parse().unwrap();
+        assert_eq!(amount, "1.5LBTC".parse().unwrap());
+        assert_eq!(amount.ticker_asset(&assets).unwrap(), Some(*LIQUID_TESTNET_BITCOIN_ASSET));
+        assert_eq!(amount.to_base_units(&assets, *LIQUID_TESTNET_BITCOIN_ASSET).unwrap(), 150_000_000);
+
+        let raw: AssetAmount = "1500".parse().unwrap();
+        assert_eq!(raw.ticker_asset(&assets).unwrap(), None);
+        assert_eq!(raw.to_base_units(&assets, other).unwrap(), 1500);
+
+        assert!("1.5".parse::<AssetAmount>().is_err());
+        assert!("USDt".parse::<AssetAmount>().is_err());
+        assert!("1 NOPE".parse::<AssetAmount>().unwrap().ticker_asset(&assets).is_err());
+        assert!(amount.to_base_units(&assets, other).is_err());
+    }
+
+    #[test]
+    fn test_parse_registry_json() {
+        let id = "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2";
+        let single = format!(
+            r#"{{"asset_id":"{id}","contract":{{"entity":{{"domain":"tether.to"}},"name":"Tether USD","precision":8,"ticker":"USDt"}}}}"#
+        );
+
+        let entries = parse_registry_json(&single).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].asset_id.to_hex(), id);
+        assert_eq!(entries[0].ticker, "USDt");
+        assert_eq!(entries[0].name, "Tether USD");
+        assert_eq!(entries[0].precision, 8);
+        assert_eq!(entries[0].issuer.as_deref(), Some("tether.to"));
+
+        let index = format!(
+            r#"{{"{id}":{{"name":"Tether USD","ticker":"USDt","precision":8,"entity":{{"domain":"tether.to"}}}},
+                "{}":{{"name":"No ticker","precision":0}}}}"#,
+            "11".repeat(32)
+        );
+        let entries = parse_registry_json(&index).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].ticker, "USDt");
+
+        assert!(parse_registry_json("42").is_err());
+    }
+}
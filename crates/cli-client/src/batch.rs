@@ -0,0 +1,341 @@
+use crate::assets::{AssetAmount, AssetRegistry};
+use crate::error::Error;
+
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use coin_store::UtxoEntry;
+use simplicityhl::elements::bitcoin;
+use simplicityhl::elements::confidential::{AssetBlindingFactor, ValueBlindingFactor};
+use simplicityhl::elements::pset::{Input, Output, PartiallySignedTransaction};
+use simplicityhl::elements::secp256k1_zkp::{self as secp256k1, PublicKey};
+use simplicityhl::elements::{Address, AssetId, Script, TxOut, TxOutSecrets};
+use simplicityhl_core::LIQUID_TESTNET_BITCOIN_ASSET;
+
+/// One payment of a batch transfer, given as `ADDRESS,AMOUNT[,ASSET]` on the command line or in a CSV row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipient {
+    pub address: Address,
+    pub amount: AssetAmount,
+    /// Asset of the optional third column, a ticker or an asset id.
+    pub asset: Option<String>,
+}
+
+impl FromStr for Recipient {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split(',').map(str::trim).collect();
+        let (address, amount, asset) = match fields.as_slice() {
+            [address, amount] | [address, amount, ""] => (*address, *amount, None),
+            [address, amount, asset] => (*address, *amount, Some(*asset)),
+            _ => return Err(format!("Invalid recipient '{s}', expected ADDRESS,AMOUNT[,ASSET]")),
+        };
+
+        let address = address
+            .parse::<Address>()
+            .map_err(|e| format!("Invalid address '{address}': {e}"))?;
+        let amount = amount.parse()?;
+        let asset = asset.map(ToString::to_string);
+
+        Ok(Self { address, amount, asset })
+    }
+}
+
+impl Recipient {
+    /// Asset and amount in base units. The asset column wins over the ticker of the amount; without
+    /// either the payment is in LBTC.
+    pub fn resolve(&self, assets: &AssetRegistry) -> Result<(AssetId, u64), Error> {
+        let column_asset = self
+            .asset
+            .as_deref()
+            .map(|asset| {
+                assets.resolve(asset).ok_or_else(|| {
+                    Error::Config(format!("Unknown asset '{asset}'; use a registered ticker or an asset id"))
+                })
+            })
+            .transpose()?;
+        let asset = column_asset
+            .or(self.amount.ticker_asset(assets)?)
+            .unwrap_or(*LIQUID_TESTNET_BITCOIN_ASSET);
+
+        Ok((asset, self.amount.to_base_units(assets, asset)?))
+    }
+}
+
+/// Parse recipients from CSV with the columns `address,amount[,asset]`, skipping a header row,
+/// blank lines and lines starting with `#`.
+pub fn parse_recipients_csv(input: &str) -> Result<Vec<Recipient>, Error> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| {
+            !line.is_empty() && !line.starts_with('#') && !line.to_ascii_lowercase().starts_with("address")
+        })
+        .map(|(i, line)| {
+            line.parse()
+                .map_err(|e| Error::Config(format!("Invalid recipient on line {}: {e}", i + 1)))
+        })
+        .collect()
+}
+
+/// A batch transaction ready to sign.
+#[derive(Debug)]
+pub struct BatchPset {
+    pub pst: PartiallySignedTransaction,
+    /// Outputs spent by the inputs, in input order.
+    pub utxos: Vec<TxOut>,
+    /// Asset and value blinding factors of the blinded outputs, by output index.
+    pub blinders: BTreeMap<usize, (AssetBlindingFactor, ValueBlindingFactor)>,
+    /// Indices of the change outputs.
+    pub change_outputs: Vec<usize>,
+}
+
+/// Build one transaction paying every `(address, asset, amount)` from `inputs`.
+///
+/// Payments come first, in order, then one change output per asset to `change_script` and the
+/// LBTC fee. Payments to confidential addresses are blinded to the address's blinding key.
+/// Confidential inputs only balance against a blinded output, so without a confidential
+/// recipient their change is blinded to `change_blinder` instead.
+pub fn build_batch_pset(
+    assets: &AssetRegistry,
+    inputs: &[UtxoEntry],
+    payments: &[(Address, AssetId, u64)],
+    change_script: &Script,
+    change_blinder: PublicKey,
+    fee: u64,
+) -> Result<BatchPset, Error> {
+    let lbtc = *LIQUID_TESTNET_BITCOIN_ASSET;
+
+    let mut pst = PartiallySignedTransaction::new_v2();
+    let mut utxos = Vec::with_capacity(inputs.len());
+    let mut secrets = HashMap::new();
+    let mut change: BTreeMap<AssetId, u64> = BTreeMap::new();
+    let mut has_confidential_input = false;
+
+    for (index, entry) in inputs.iter().enumerate() {
+        let (Some(asset), Some(value)) = (entry.asset(), entry.value()) else {
+            return Err(Error::Config(format!("Input {} cannot be unblinded", entry.outpoint())));
+        };
+
+        let mut input = Input::from_prevout(*entry.outpoint());
+        input.witness_utxo = Some(entry.txout().clone());
+        pst.add_input(input);
+        utxos.push(entry.txout().clone());
+
+        has_confidential_input |= entry.secrets().is_some();
+        let input_secrets = entry.secrets().copied().unwrap_or_else(|| {
+            TxOutSecrets::new(asset, AssetBlindingFactor::zero(), value, ValueBlindingFactor::zero())
+        });
+        secrets.insert(index, input_secrets);
+        *change.entry(asset).or_default() += value;
+    }
+
+    let mut needed: BTreeMap<AssetId, u64> = BTreeMap::from([(lbtc, fee)]);
+    for (address, asset, amount) in payments {
+        *needed.entry(*asset).or_default() += amount;
+
+        let blinding_key = address.blinding_pubkey.map(bitcoin::PublicKey::new);
+        let mut output = Output::new_explicit(address.script_pubkey(), *amount, *asset, blinding_key);
+        if blinding_key.is_some() {
+            output.blinder_index = Some(0);
+        }
+        pst.add_output(output);
+    }
+
+    for (asset, amount) in &needed {
+        let available = change.get(asset).copied().unwrap_or_default();
+        let remaining = available.checked_sub(*amount).ok_or_else(|| {
+            Error::Config(format!(
+                "Insufficient {}: inputs hold {}, payments and fee need {}",
+                assets.label(asset),
+                assets.format_amount(available, asset),
+                assets.format_amount(*amount, asset)
+            ))
+        })?;
+        change.insert(*asset, remaining);
+    }
+
+    let blind_change = has_confidential_input && !payments.iter().any(|(address, _, _)| address.is_blinded());
+    let mut change_outputs = Vec::new();
+    for (asset, amount) in change.into_iter().filter(|(_, amount)| *amount > 0) {
+        let blinding_key = blind_change.then(|| bitcoin::PublicKey::new(change_blinder));
+        let mut output = Output::new_explicit(change_script.clone(), amount, asset, blinding_key);
+        if blind_change {
+            output.blinder_index = Some(0);
+        }
+        change_outputs.push(pst.outputs().len());
+        pst.add_output(output);
+    }
+
+    pst.add_output(Output::from_txout(TxOut::new_fee(fee, lbtc)));
+
+    let blinders = if pst.outputs().iter().any(|output| output.blinding_key.is_some()) {
+        pst.blind_last(&mut secp256k1::rand::thread_rng(), secp256k1::SECP256K1, &secrets)?
+            .into_iter()
+            .map(|(index, (abf, vbf, _))| (index, (abf, vbf)))
+            .collect()
+    } else if has_confidential_input {
+        return Err(Error::Config(
+            "Confidential inputs need change or a confidential recipient to balance against".to_string(),
+        ));
+    } else {
+        BTreeMap::new()
+    };
+
+    Ok(BatchPset {
+        pst,
+        utxos,
+        blinders,
+        change_outputs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use simplicityhl::elements::confidential::{Asset, Nonce, Value};
+    use simplicityhl::elements::hashes::Hash;
+    use simplicityhl::elements::{AddressParams, OutPoint, TxOutWitness, Txid};
+    use simplicityhl_core::derive_public_blinder_key;
+
+    fn address(byte: u8) -> Address {
+        Address::p2wsh(&Script::from(vec![byte]), None, &AddressParams::LIQUID_TESTNET)
+    }
+
+    fn entry(vout: u32, asset: AssetId, value: u64) -> UtxoEntry {
+        let txout = TxOut {
+            asset: Asset::Explicit(asset),
+            value: Value::Explicit(value),
+            nonce: Nonce::Null,
+            script_pubkey: Script::new(),
+            witness: TxOutWitness::default(),
+        };
+        UtxoEntry::new_explicit(OutPoint::new(Txid::from_byte_array([1; 32]), vout), txout)
+    }
+
+    /// An output of `value` blinded to `blinding_key`, paid by a batch from an explicit input.
+    fn confidential_entry(asset: AssetId, value: u64, blinding_key: &secp256k1::SecretKey) -> UtxoEntry {
+        let blinding_pubkey = PublicKey::from_secret_key(secp256k1::SECP256K1, blinding_key);
+        let address = Address::p2wsh(&Script::new(), Some(blinding_pubkey), &AddressParams::LIQUID_TESTNET);
+        let payments = [(address, asset, value)];
+
+        let batch = build_batch_pset(
+            &AssetRegistry::default(),
+            &[entry(9, asset, value + 100)],
+            &payments,
+            &Script::new(),
+            blinding_pubkey,
+            100,
+        )
+        .unwrap();
+        let tx = batch.pst.extract_tx().unwrap();
+        let secrets = tx.output[0].unblind(secp256k1::SECP256K1, *blinding_key).unwrap();
+
+        UtxoEntry::new_confidential(OutPoint::new(tx.txid(), 0), tx.output[0].clone(), secrets)
+    }
+
+    #[test]
+    fn test_parse_recipients() {
+        let token = AssetId::from_slice(&[7; 32]).unwrap();
+        let (first, second) = (address(0x51), address(0x52));
+        let assets = AssetRegistry::default();
+
+        let recipient: Recipient = format!("{first}, 1500").parse().unwrap();
+        assert_eq!(recipient.resolve(&assets).unwrap(), (*LIQUID_TESTNET_BITCOIN_ASSET, 1500));
+
+        let recipient: Recipient = format!("{first},0.5 LBTC,").parse().unwrap();
+        assert_eq!(recipient.resolve(&assets).unwrap(), (*LIQUID_TESTNET_BITCOIN_ASSET, 50_000_000));
+
+        let csv = format!("address,amount,asset\n\n# payouts\n{first},10,{token}\n{second},2.5 LBTC\n");
+        let recipients = parse_recipients_csv(&csv).unwrap();
+        assert_eq!(recipients.len(), 2);
+        assert_eq!(recipients[0].address, first);
+        assert_eq!(recipients[0].resolve(&assets).unwrap(), (token, 10));
+        assert_eq!(recipients[1].resolve(&assets).unwrap(), (*LIQUID_TESTNET_BITCOIN_ASSET, 250_000_000));
+
+        assert!(format!("{first}").parse::<Recipient>().is_err());
+        assert!(format!("{first},1,NOPE").parse::<Recipient>().unwrap().resolve(&assets).is_err());
+        assert!(format!("{first},1.5 LBTC,{token}").parse::<Recipient>().unwrap().resolve(&assets).is_err());
+        let err = parse_recipients_csv(&format!("{first},1\nnot-an-address,1\n")).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_build_batch_pset() {
+        let lbtc = *LIQUID_TESTNET_BITCOIN_ASSET;
+        let token = AssetId::from_slice(&[7; 32]).unwrap();
+        let change_script = Script::from(vec![0x53; 34]);
+        let secret_key = secp256k1::SecretKey::from_slice(&[3; 32]).unwrap();
+        let change_blinder = PublicKey::from_secret_key(secp256k1::SECP256K1, &secret_key);
+
+        let inputs = [entry(0, lbtc, 10_000), entry(1, token, 500)];
+        let payments = [(address(0x51), lbtc, 3000), (address(0x52), token, 200), (address(0x51), token, 100)];
+
+        let assets = AssetRegistry::default();
+        let batch = build_batch_pset(&assets, &inputs, &payments, &change_script, change_blinder, 150).unwrap();
+        assert!(batch.blinders.is_empty());
+        assert_eq!(batch.utxos.len(), 2);
+        assert_eq!(batch.change_outputs, vec![3, 4]);
+
+        let tx = batch.pst.extract_tx().unwrap();
+        assert_eq!(tx.output.len(), 6);
+        for (output, (address, asset, amount)) in tx.output.iter().zip(&payments) {
+            assert_eq!(output.script_pubkey, address.script_pubkey());
+            assert_eq!((output.asset, output.value), (Asset::Explicit(*asset), Value::Explicit(*amount)));
+        }
+
+        let change: BTreeMap<AssetId, u64> = tx.output[3..5]
+            .iter()
+            .inspect(|output| assert_eq!(output.script_pubkey, change_script))
+            .map(|output| (output.asset.explicit().unwrap(), output.value.explicit().unwrap()))
+            .collect();
+        assert_eq!(change, BTreeMap::from([(lbtc, 6850), (token, 200)]));
+        assert!(tx.output[5].is_fee());
+
+        let short = [(address(0x51), token, 600)];
+        assert!(build_batch_pset(&assets, &inputs, &short, &change_script, change_blinder, 150).is_err());
+
+        // A confidential input without a confidential recipient leaves change blinded to `change_blinder`.
+        let received_key = secp256k1::SecretKey::from_slice(&[4; 32]).unwrap();
+        let inputs = [confidential_entry(lbtc, 5000, &received_key), entry(2, lbtc, 1000)];
+        let payments = [(address(0x51), lbtc, 2000)];
+
+        let batch = build_batch_pset(&assets, &inputs, &payments, &change_script, change_blinder, 150).unwrap();
+        assert_eq!(batch.blinders.keys().copied().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(batch.change_outputs, vec![1]);
+
+        let tx = batch.pst.extract_tx().unwrap();
+        assert_eq!(tx.output.len(), 3);
+        assert_eq!((tx.output[0].asset, tx.output[0].value), (Asset::Explicit(lbtc), Value::Explicit(2000)));
+        assert_eq!(tx.output[1].script_pubkey, change_script);
+        assert!(tx.output[1].asset.is_confidential() && tx.output[1].value.is_confidential());
+        let change = tx.output[1].unblind(secp256k1::SECP256K1, secret_key).unwrap();
+        assert_eq!((change.asset, change.value), (lbtc, 3850));
+        assert!(tx.output[2].is_fee());
+
+        // Spent exactly, a confidential input has no blinded output to balance against.
+        let exact = [(address(0x51), lbtc, 4850)];
+        let err = build_batch_pset(&assets, &inputs[..1], &exact, &change_script, change_blinder, 150).unwrap_err();
+        assert!(err.to_string().contains("balance against"));
+
+        // A payment to a confidential address unblinds with the recipient's key only, not the public blinder key.
+        let recipient_key = secp256k1::SecretKey::from_slice(&[5; 32]).unwrap();
+        let recipient_pubkey = PublicKey::from_secret_key(secp256k1::SECP256K1, &recipient_key);
+        let recipient = Address::p2wsh(&Script::new(), Some(recipient_pubkey), &AddressParams::LIQUID_TESTNET);
+        let payments = [(recipient, lbtc, 2500)];
+
+        let inputs = [entry(3, lbtc, 5000)];
+        let batch = build_batch_pset(&assets, &inputs, &payments, &change_script, change_blinder, 150).unwrap();
+        assert_eq!(batch.blinders.keys().copied().collect::<Vec<_>>(), vec![0]);
+
+        let tx = batch.pst.extract_tx().unwrap();
+        let paid = tx.output[0].unblind(secp256k1::SECP256K1, recipient_key).unwrap();
+        assert_eq!((paid.asset, paid.value), (lbtc, 2500));
+        let public_key = derive_public_blinder_key().secret_key();
+        assert!(tx.output[0].unblind(secp256k1::SECP256K1, public_key).is_err());
+        assert_eq!(tx.output[1].value, Value::Explicit(2350));
+    }
+}
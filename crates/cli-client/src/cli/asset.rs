@@ -0,0 +1,88 @@
+use crate::assets::{parse_registry_json, read_registry_source};
+use crate::cli::tables::display_asset_registry_table;
+use crate::cli::{AssetCommand, Cli};
+use crate::config::Config;
+use crate::error::Error;
+
+use coin_store::{AssetRegistryEntry, AssetRegistryStore, StoreError};
+
+/// Display struct for registered assets
+#[derive(Debug, Clone)]
+pub struct AssetRegistryDisplay {
+    pub ticker: String,
+    pub name: String,
+    pub precision: String,
+    pub issuer: String,
+    pub asset_id: String,
+}
+
+impl Cli {
+    pub(crate) async fn run_asset(&self, config: Config, command: &AssetCommand) -> Result<(), Error> {
+        let wallet = self.get_wallet(&config).await?;
+        let store = wallet.store();
+
+        match command {
+            AssetCommand::List => {
+                let displays: Vec<AssetRegistryDisplay> = store
+                    .list_assets()
+                    .await?
+                    .into_iter()
+                    .map(|entry| AssetRegistryDisplay {
+                        ticker: entry.ticker,
+                        name: entry.name,
+                        precision: entry.precision.to_string(),
+                        issuer: entry.issuer.unwrap_or_else(|| "-".to_string()),
+                        asset_id: entry.asset_id.to_string(),
+                    })
+                    .collect();
+
+                display_asset_registry_table(&displays);
+                Ok(())
+            }
+            AssetCommand::Add {
+                asset_id,
+                ticker,
+                name,
+                precision,
+                issuer,
+            } => {
+                let entry = AssetRegistryEntry {
+                    asset_id: *asset_id,
+                    ticker: ticker.clone(),
+                    name: name.clone().unwrap_or_else(|| ticker.clone()),
+                    precision: *precision,
+                    issuer: issuer.clone(),
+                };
+                store.upsert_asset(&entry).await?;
+
+                println!("Registered {ticker} ({asset_id}) with {precision} decimal place(s)");
+                Ok(())
+            }
+            AssetCommand::Remove { asset_id } => {
+                if store.remove_asset(*asset_id).await? {
+                    println!("Removed {asset_id} from the asset registry");
+                } else {
+                    println!("{asset_id} is not in the asset registry");
+                }
+                Ok(())
+            }
+            AssetCommand::Import { source } => {
+                let entries = parse_registry_json(&read_registry_source(source)?)?;
+
+                let mut imported = 0;
+                for entry in &entries {
+                    match store.upsert_asset(entry).await {
+                        Ok(()) => imported += 1,
+                        Err(e @ StoreError::AssetTickerTaken(_)) => {
+                            println!("Skipping {}: {e}", entry.asset_id);
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+
+                println!("Imported {imported} of {} asset(s) from {source}", entries.len());
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use crate::assets::AssetRegistry;
+use crate::batch::{Recipient, build_batch_pset, parse_recipients_csv};
+use crate::cli::interactive::truncate_with_ellipsis;
+use crate::cli::tables::{BatchPaymentDisplay, display_batch_payment_table};
+use crate::cli::{Cli, CoinControlArgs};
+use crate::coin_control::CoinControl;
+use crate::config::Config;
+use crate::error::Error;
+use crate::fee::{PLACEHOLDER_FEE, estimate_fee_signed};
+use crate::signing::sign_p2pk_inputs;
+use crate::wallet::Wallet;
+
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use coin_store::{UtxoEntry, UtxoFilter, UtxoQueryResult, UtxoStore};
+use simplicityhl::elements::pset::serialize::Serialize;
+use simplicityhl::elements::{Address, AssetId, Script};
+use simplicityhl::simplicity::hex::DisplayHex;
+use simplicityhl_core::LIQUID_TESTNET_BITCOIN_ASSET;
+
+impl Cli {
+    /// Pay every `(address, asset, amount)` with one transaction, blinding the outputs to
+    /// confidential addresses, and report how each payment was blinded.
+    #[allow(clippy::too_many_lines)]
+    pub(crate) async fn send_payments(
+        &self,
+        config: &Config,
+        assets: &AssetRegistry,
+        payments: Vec<(Address, AssetId, u64)>,
+        fee: Option<&u64>,
+        broadcast: bool,
+        coin_control: &CoinControlArgs,
+    ) -> Result<(), Error> {
+        let wallet = self.get_wallet(config).await?;
+        let wallet_address = wallet.signer().p2pk_address(config.network())?;
+        let script_pubkey = wallet_address.script_pubkey();
+        let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
+
+        if let Some((address, _, _)) = payments.iter().find(|(address, _, _)| address.params != wallet_address.params) {
+            return Err(Error::Config(format!("Recipient {address} is for another network")));
+        }
+
+        let lbtc = *LIQUID_TESTNET_BITCOIN_ASSET;
+        let mut sent: BTreeMap<AssetId, u64> = BTreeMap::from([(lbtc, 0)]);
+        for (_, asset, amount) in &payments {
+            *sent.entry(*asset).or_default() += amount;
+        }
+
+        // Confidential wallet inputs without a confidential recipient leave blinded change,
+        // blinded to the wallet's own key derived from the seed.
+        let change_blinder = wallet.change_blinding_keypair()?;
+
+        // Select again while the estimated fee outgrows the LBTC selected. The fee only grows
+        // between rounds, and selection fails once the wallet cannot cover it.
+        let mut fee_guess = fee.copied().unwrap_or(PLACEHOLDER_FEE);
+        let (inputs, actual_fee) = loop {
+            let mut required = sent.clone();
+            *required.entry(lbtc).or_default() += fee_guess;
+            let inputs = select_inputs(&wallet, assets, &coin_control, &script_pubkey, &required).await?;
+
+            let change_outputs = OnceCell::new();
+            let actual_fee = estimate_fee_signed(
+                fee,
+                config.get_fee_rate(),
+                |f| {
+                    let batch = build_batch_pset(
+                        assets,
+                        &inputs,
+                        &payments,
+                        &script_pubkey,
+                        change_blinder.public_key(),
+                        f,
+                    )?;
+                    let _ = change_outputs.set(batch.change_outputs);
+                    Ok((batch.pst, batch.utxos))
+                },
+                |tx, utxos| {
+                    let change = change_outputs.get().map(Vec::as_slice).unwrap_or_default();
+                    let tx = coin_control.redirect_outputs(tx, change);
+                    sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0)
+                },
+            )?;
+
+            let lbtc_in: u64 = inputs
+                .iter()
+                .filter(|entry| entry.asset() == Some(lbtc))
+                .filter_map(UtxoEntry::value)
+                .sum();
+            if fee.is_some() || lbtc_in >= sent[&lbtc] + actual_fee {
+                break (inputs, actual_fee);
+            }
+            fee_guess = actual_fee;
+        };
+
+        let batch = build_batch_pset(
+            assets,
+            &inputs,
+            &payments,
+            &script_pubkey,
+            change_blinder.public_key(),
+            actual_fee,
+        )?;
+        let tx = coin_control.redirect_outputs(batch.pst.extract_tx()?, &batch.change_outputs);
+        let tx = sign_p2pk_inputs(tx, &batch.utxos, &wallet, config.network(), 0)?;
+
+        let displays: Vec<BatchPaymentDisplay> = payments
+            .iter()
+            .enumerate()
+            .map(|(vout, (address, asset, amount))| BatchPaymentDisplay {
+                vout: vout.to_string(),
+                address: truncate_with_ellipsis(&address.to_string(), 48),
+                amount: assets.format_amount(*amount, asset),
+                blinding: if batch.blinders.contains_key(&vout) {
+                    "confidential".to_string()
+                } else {
+                    "explicit".to_string()
+                },
+            })
+            .collect();
+
+        println!("Paying {} recipient(s):", payments.len());
+        display_batch_payment_table(&displays);
+        for (vout, (abf, vbf)) in batch.blinders.range(..payments.len()) {
+            println!("  vout {vout}: asset blinder {abf}, value blinder {vbf}");
+        }
+        for (asset, amount) in sent.iter().filter(|(_, amount)| **amount > 0) {
+            println!("Total {}: {}", assets.label(asset), assets.format_amount(*amount, asset));
+        }
+
+        match broadcast {
+            false => {
+                println!("{}", tx.serialize().to_lower_hex_string());
+            }
+            true => {
+                config.esplora().broadcast_transaction(&tx)?;
+
+                println!("Broadcasted: {}", tx.txid());
+
+                let blinder_keys = batch
+                    .blinders
+                    .range(payments.len()..)
+                    .map(|(vout, _)| (*vout, change_blinder))
+                    .collect();
+                wallet.store().insert_transaction(&tx, blinder_keys).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Recipients of `tx batch-transfer` from `--to` and the CSV file, as payments.
+pub(crate) fn batch_payments(
+    assets: &AssetRegistry,
+    recipients: &[Recipient],
+    file: Option<&Path>,
+    explicit: bool,
+) -> Result<Vec<(Address, AssetId, u64)>, Error> {
+    let mut recipients = recipients.to_vec();
+    if let Some(path) = file {
+        recipients.extend(parse_recipients_csv(&std::fs::read_to_string(path)?)?);
+    }
+    if recipients.is_empty() {
+        return Err(Error::Config("Give recipients with --to or --file".to_string()));
+    }
+
+    recipients
+        .into_iter()
+        .map(|recipient| {
+            let (asset, amount) = recipient.resolve(assets)?;
+            if amount == 0 {
+                return Err(Error::Config(format!("Amount to {} is zero", recipient.address)));
+            }
+            let address = if explicit {
+                recipient.address.to_unconfidential()
+            } else {
+                recipient.address
+            };
+            Ok((address, asset, amount))
+        })
+        .collect()
+}
+
+/// Select wallet outputs covering the `required` amount of each asset.
+async fn select_inputs(
+    wallet: &Wallet,
+    assets: &AssetRegistry,
+    coin_control: &CoinControl,
+    script_pubkey: &Script,
+    required: &BTreeMap<AssetId, u64>,
+) -> Result<Vec<UtxoEntry>, Error> {
+    let filters: Vec<UtxoFilter> = required
+        .iter()
+        .map(|(asset, amount)| {
+            coin_control.filter(
+                UtxoFilter::new()
+                    .asset_id(*asset)
+                    .script_pubkey(script_pubkey.clone())
+                    .required_value(*amount),
+            )
+        })
+        .collect();
+
+    let results = <_ as UtxoStore>::query_utxos(wallet.store(), &filters).await?;
+
+    let mut inputs = Vec::new();
+    for ((asset, amount), result) in required.iter().zip(results) {
+        match result {
+            UtxoQueryResult::Found(entries, _) => inputs.extend(entries),
+            UtxoQueryResult::InsufficientValue(entries, _) => {
+                let available: u64 = entries.iter().filter_map(UtxoEntry::value).sum();
+                return Err(Error::Config(format!(
+                    "Insufficient {}: have {}, need {}. Try using 'merge' command first.",
+                    assets.label(asset),
+                    assets.format_amount(available, asset),
+                    assets.format_amount(*amount, asset)
+                )));
+            }
+            UtxoQueryResult::Empty => {
+                return Err(Error::Config(format!("No UTXOs found for asset {}", assets.label(asset))));
+            }
+        }
+    }
+
+    Ok(inputs)
+}
@@ -1,42 +1,215 @@
-use crate::cli::Cli;
-use crate::cli::interactive::{TokenDisplay, format_relative_time, format_settlement_asset, truncate_with_ellipsis};
-use crate::cli::option_offer::ActiveOptionOfferDisplay;
-use crate::cli::tables::{display_active_option_offers_table, display_token_table};
+use crate::assets::AssetRegistry;
+use crate::cli::{Cli, PricingArgs};
+use crate::cli::interactive::{TokenDisplay, format_relative_time, parse_expiry};
+use crate::cli::reputation::ReputationBook;
+use crate::cli::tables::{display_order_book_table, display_token_table};
 use crate::config::Config;
 use crate::error::Error;
+use crate::event_cache::{load_cached_actions, load_cached_option_offers, load_cached_options, refresh_event_cache};
+use crate::explorer::EsploraError;
+use crate::price_fetcher::AssetPair;
+use crate::price_oracle::spot_prices;
+use crate::pricing::{black_scholes_call, whole_unit_price, years_to_expiry};
 
-use options_relay::{OptionCreatedEvent, OptionOfferCreatedEvent};
+use std::collections::HashMap;
+
+use nostr::{EventId, Timestamp};
+use options_relay::events::filters;
+use options_relay::{
+    ActionCompletedEvent, BookEntry, BookSort, ListingStatus, ListingValidator, OfferDepth, OptionCreatedEvent,
+    OptionOfferCreatedEvent, OrderBook,
+};
 use simplicityhl::elements::AssetId;
 use simplicityhl::elements::hex::ToHex;
-use simplicityhl_core::LIQUID_TESTNET_BITCOIN_ASSET;
+
+/// Decimal places of offer prices, which are ratios of base units and rarely whole.
+const PRICE_DECIMALS: usize = 4;
+
+pub struct OrderBookDisplay {
+    pub index: usize,
+    pub offering: String,
+    pub price: String,
+    pub effective: String,
+    pub premium: String,
+    pub fair: String,
+    pub premium_vs_fair: String,
+    pub depth: String,
+    pub expires: String,
+    pub seller: String,
+}
 
 impl Cli {
-    pub(crate) async fn run_browse(&self, config: Config) -> Result<(), Error> {
+    #[allow(clippy::too_many_lines, clippy::too_many_arguments)]
+    pub(crate) async fn run_browse(
+        &self,
+        config: Config,
+        show_invalid: bool,
+        no_verify: bool,
+        market: Option<&str>,
+        max_expiry: Option<&str>,
+        sort: BookSort,
+        pricing: PricingArgs,
+    ) -> Result<(), Error> {
+        let market = market.map(parse_market).transpose()?;
+        let max_expiry = max_expiry.map(parse_expiry).transpose()?;
+        let assets = AssetRegistry::load(&config.database_path()).await;
+
         let client = self.get_read_only_client(&config).await?;
 
         println!("Browsing available options and option offers from NOSTR...");
         println!();
 
-        let options_results = client.fetch_options(config.network()).await?;
-        let valid_options: Vec<OptionCreatedEvent> = options_results.into_iter().filter_map(Result::ok).collect();
+        // Prefer the local event cache so only new events are downloaded; fall back to
+        // fetching everything when no wallet database is available.
+        let wallet = match self.get_wallet(&config).await {
+            Ok(wallet) => Some(wallet),
+            Err(e) => {
+                eprintln!("Warning: not using the local event cache: {e}");
+                None
+            }
+        };
+        let (valid_options, valid_offers, actions) = if let Some(wallet) = &wallet {
+            refresh_event_cache(wallet.store(), &client).await?;
+            (
+                load_cached_options(wallet.store(), config.network()).await?,
+                load_cached_option_offers(wallet.store(), config.network()).await?,
+                load_cached_actions(wallet.store()).await?,
+            )
+        } else {
+            let options_results = client.fetch_options(config.network()).await?;
+            let offers_results = client.fetch_option_offers(config.network()).await?;
+            let action_events = client.fetch_events(filters::action_completed()).await?;
+            let actions: Vec<ActionCompletedEvent> = action_events
+                .iter()
+                .filter_map(|e| ActionCompletedEvent::from_event(e).ok())
+                .collect();
+
+            (
+                options_results.into_iter().filter_map(Result::ok).collect::<Vec<OptionCreatedEvent>>(),
+                offers_results
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .collect::<Vec<OptionOfferCreatedEvent>>(),
+                actions,
+            )
+        };
+
+        client.disconnect().await;
+
+        // Listings from blocked counterparties are never shown.
+        let reputation = match &wallet {
+            Some(wallet) => ReputationBook::load(wallet.store()).await?,
+            None => ReputationBook::default(),
+        };
+        let blocked = valid_options
+            .iter()
+            .map(|event| event.pubkey)
+            .chain(valid_offers.iter().map(|event| event.pubkey))
+            .filter(|pubkey| reputation.is_blocked(&pubkey.to_hex()))
+            .count();
+        let valid_options: Vec<OptionCreatedEvent> = valid_options
+            .into_iter()
+            .filter(|event| !reputation.is_blocked(&event.pubkey.to_hex()))
+            .collect();
+        let valid_offers: Vec<OptionOfferCreatedEvent> = valid_offers
+            .into_iter()
+            .filter(|event| !reputation.is_blocked(&event.pubkey.to_hex()))
+            .collect();
+
+        // Check each listing against the chain; listings that cannot be checked stay visible.
+        let validator = ListingValidator::new(config.esplora());
+        let mut hidden = 0;
+
+        let valid_options: Vec<(OptionCreatedEvent, Option<ListingStatus>)> = valid_options
+            .into_iter()
+            .map(|event| {
+                let status = (!no_verify)
+                    .then(|| checked(event.event_id, validator.validate_option(&event)))
+                    .flatten();
+                (event, status)
+            })
+            .filter(|(_, status)| keep_listing(*status, show_invalid, &mut hidden))
+            .collect();
+
+        // Show the live remaining size of each offer rather than its original deposit. Fills are
+        // only counted once confirmed on chain, so without verification none are.
+        let valid_offers: Vec<(OptionOfferCreatedEvent, OfferDepth, Option<ListingStatus>)> = valid_offers
+            .into_iter()
+            .map(|event| {
+                let verified = (!no_verify)
+                    .then(|| checked(event.event_id, validator.validate_option_offer(&event, &actions)))
+                    .flatten();
+                let (status, depth) = verified.map_or_else(
+                    || (None, event.depth(&actions, &HashMap::new())),
+                    |(status, depth)| (Some(status), depth),
+                );
+                (event, depth, status)
+            })
+            .filter(|(_, depth, _)| !depth.is_exhausted())
+            .filter(|(_, _, status)| keep_listing(*status, show_invalid, &mut hidden))
+            .collect();
+
+        let in_view = |entry: &BookEntry| {
+            market.is_none_or(|(collateral, settlement)| {
+                asset_matches(&assets, collateral, entry.collateral_asset)
+                    && asset_matches(&assets, settlement, entry.settlement_asset)
+            }) && max_expiry.is_none_or(|max| i64::from(entry.expiry_time) <= max)
+        };
+
+        let option_book =
+            OrderBook::new(valid_options.iter().map(|(event, _)| BookEntry::from_option(event))).filter(in_view);
+        let offer_book = OrderBook::new(
+            valid_offers
+                .iter()
+                .map(|(event, depth, _)| BookEntry::from_option_offer(event, depth)),
+        )
+        .filter(in_view);
+
+        // Offers are valued as calls struck at their price.
+        let params = config.pricing.params(pricing.volatility)?;
+        let pairs = offer_book
+            .markets(sort)
+            .iter()
+            .map(|(key, _)| AssetPair::new(key.collateral_asset, key.settlement_asset))
+            .collect();
+        let spots = spot_prices(&config, &assets, pricing.spot, pairs).await;
+        let now = Timestamp::now().as_u64();
+
+        let options: HashMap<EventId, &(OptionCreatedEvent, Option<ListingStatus>)> = valid_options
+            .iter()
+            .map(|listing| (listing.0.event_id, listing))
+            .collect();
+        let offers: HashMap<EventId, &(OptionOfferCreatedEvent, OfferDepth, Option<ListingStatus>)> = valid_offers
+            .iter()
+            .map(|listing| (listing.0.event_id, listing))
+            .collect();
 
         println!("Available Options:");
         println!("------------------");
 
-        if valid_options.is_empty() {
+        if option_book.is_empty() {
             println!("  (No options found)");
         } else {
-            let option_displays: Vec<TokenDisplay> = valid_options
+            let option_displays: Vec<TokenDisplay> = option_book
+                .markets(sort)
                 .iter()
+                .flat_map(|(_, levels)| levels)
+                .filter_map(|level| options.get(&level.entry.event_id))
                 .enumerate()
-                .map(|(idx, event)| {
+                .map(|(idx, (event, status))| {
                     let args = &event.options_args;
                     TokenDisplay {
                         index: idx + 1,
-                        collateral: format_asset_amount(args.collateral_per_contract(), args.get_collateral_asset_id()),
-                        settlement: format_asset_amount(args.settlement_per_contract(), args.get_settlement_asset_id()),
+                        collateral: assets
+                            .format_amount(args.collateral_per_contract(), &args.get_collateral_asset_id()),
+                        settlement: assets
+                            .format_amount(args.settlement_per_contract(), &args.get_settlement_asset_id()),
                         expires: format_relative_time(i64::from(args.expiry_time())),
-                        status: format!("by {}", truncate_with_ellipsis(&event.pubkey.to_hex(), 12)),
+                        status: format!(
+                            "by {}{}",
+                            reputation.seller(&event.pubkey.to_hex()),
+                            listing_flag(*status)
+                        ),
                     }
                 })
                 .collect();
@@ -46,36 +219,96 @@ impl Cli {
 
         println!();
 
-        let offers_results = client.fetch_option_offers(config.network()).await?;
-        let valid_offers: Vec<OptionOfferCreatedEvent> = offers_results.into_iter().filter_map(Result::ok).collect();
-
-        println!("Available Option Offers (from NOSTR):");
-        println!("-------------------------------------");
+        println!("Option Offer Order Book (from NOSTR, sorted by {sort}):");
+        println!("-------------------------------------------------------");
+        println!(
+            "Fair values: Black-Scholes call at the offer price, volatility {:.0}%, rate {:.1}%",
+            params.volatility * 100.0,
+            params.risk_free_rate * 100.0
+        );
 
-        if valid_offers.is_empty() {
+        if offer_book.is_empty() {
             println!("  (No option offers found)");
         } else {
-            let offer_displays: Vec<ActiveOptionOfferDisplay> = valid_offers
-                .iter()
-                .enumerate()
-                .map(|(idx, event)| {
-                    let args = &event.option_offer_args;
-                    ActiveOptionOfferDisplay {
-                        index: idx + 1,
-                        offering: format_asset_amount(args.collateral_per_contract(), args.get_collateral_asset_id()),
-                        price: args.collateral_per_contract().to_string(),
-                        wants: format_settlement_asset(&args.get_settlement_asset_id()),
-                        expires: format_relative_time(i64::from(args.expiry_time())),
-                        seller: truncate_with_ellipsis(&event.pubkey.to_hex(), 12),
-                    }
-                })
-                .collect();
+            let mut index = 0;
+            for (key, levels) in offer_book.markets(sort) {
+                let quote = spots.get(&AssetPair::new(key.collateral_asset, key.settlement_asset));
+                let spot = quote.map(|quote| quote.price);
+
+                println!();
+                println!(
+                    "{}/{} expiring {} | best {} | depth {} | spot {}",
+                    assets.label(&key.collateral_asset),
+                    assets.label(&key.settlement_asset),
+                    format_expiry_bucket(key.expiry_bucket),
+                    offer_book
+                        .best_price(&key)
+                        .map_or_else(|| "-".to_string(), format_price),
+                    offer_book.total_depth(&key),
+                    quote.map_or_else(|| "unknown (use --spot)".to_string(), ToString::to_string),
+                );
 
-            display_active_option_offers_table(&offer_displays);
+                let level_displays: Vec<OrderBookDisplay> = levels
+                    .iter()
+                    .filter_map(|level| offers.get(&level.entry.event_id).map(|listing| (level, listing)))
+                    .map(|(level, (event, depth, status))| {
+                        index += 1;
+                        let entry = level.entry;
+                        let offering = match (depth.remaining, depth.deposited) {
+                            (Some(remaining), Some(deposited)) if depth.fill_count > 0 => format!(
+                                "{} of {deposited}",
+                                assets.format_amount(remaining, &entry.collateral_asset)
+                            ),
+                            (Some(remaining), _) => assets.format_amount(remaining, &entry.collateral_asset),
+                            (None, _) => "unknown".to_string(),
+                        };
+                        let strike =
+                            whole_unit_price(&assets, entry.price, &entry.collateral_asset, &entry.settlement_asset);
+                        let fair = spot.map(|spot| {
+                            black_scholes_call(spot, strike, years_to_expiry(entry.expiry_time, now), params).fair_value
+                        });
+                        let premium = spot.and_then(|spot| premium_value(&assets, entry, spot));
+                        OrderBookDisplay {
+                            index,
+                            offering,
+                            price: format_price(entry.price),
+                            effective: format_price(entry.effective_price()),
+                            premium: entry.premium.map_or_else(
+                                || "-".to_string(),
+                                |(amount, asset)| assets.format_amount(amount, &asset),
+                            ),
+                            fair: fair.map_or_else(|| "-".to_string(), |fair| format!("{fair:.4}")),
+                            premium_vs_fair: match (premium, fair) {
+                                (Some(premium), Some(fair)) if fair > 0.0 => format!("{:.0}%", premium / fair * 100.0),
+                                _ => "-".to_string(),
+                            },
+                            depth: level.cumulative_depth.to_string(),
+                            expires: format_relative_time(i64::from(entry.expiry_time)),
+                            seller: format!("{}{}", reputation.seller(&event.pubkey.to_hex()), listing_flag(*status)),
+                        }
+                    })
+                    .collect();
+
+                display_order_book_table(&level_displays);
+            }
             println!("  (Note: Actual availability shown in `option-offer take` after syncing)");
         }
 
-        client.disconnect().await;
+        let filtered = valid_options.len() + valid_offers.len() - option_book.len() - offer_book.len();
+        if filtered > 0 {
+            println!();
+            println!("({filtered} listing(s) outside the selected market or expiry hidden)");
+        }
+
+        if hidden > 0 {
+            println!();
+            println!("({hidden} listing(s) hidden: not backed by an unspent, funded contract output; use --show-invalid)");
+        }
+
+        if blocked > 0 {
+            println!();
+            println!("({blocked} listing(s) from blocked counterparties hidden; see `reputation show`)");
+        }
 
         println!();
         println!("To interact with these offers:");
@@ -87,12 +320,66 @@ impl Cli {
     }
 }
 
-fn format_asset_amount(amount: u64, asset_id: AssetId) -> String {
-    if asset_id == *LIQUID_TESTNET_BITCOIN_ASSET {
-        format!("{amount} LBTC")
+/// Log and drop chain backend errors; the listing is then shown as unchecked.
+fn checked<T>(event_id: EventId, result: Result<T, EsploraError>) -> Option<T> {
+    result
+        .inspect_err(|e| tracing::debug!("Could not validate listing {event_id} on chain: {e}"))
+        .ok()
+}
+
+fn keep_listing(status: Option<ListingStatus>, show_invalid: bool, hidden: &mut usize) -> bool {
+    let keep = show_invalid || status.is_none_or(|s| s.is_live());
+    if !keep {
+        *hidden += 1;
+    }
+    keep
+}
+
+fn listing_flag(status: Option<ListingStatus>) -> String {
+    match status {
+        Some(status) if !status.is_live() => format!(" [{status}]"),
+        _ => String::new(),
+    }
+}
+
+/// Parse a `COLLATERAL/SETTLEMENT` market, where each side is a ticker or a prefix of the asset id in hex.
+fn parse_market(market: &str) -> Result<(&str, &str), Error> {
+    market
+        .split_once('/')
+        .filter(|(collateral, settlement)| !collateral.is_empty() && !settlement.is_empty())
+        .ok_or_else(|| Error::Config(format!("Invalid market '{market}'. Use COLLATERAL/SETTLEMENT, e.g. LBTC/1a2b3c")))
+}
+
+fn asset_matches(assets: &AssetRegistry, pattern: &str, asset_id: AssetId) -> bool {
+    assets.label(&asset_id).eq_ignore_ascii_case(pattern)
+        || asset_id.to_hex().starts_with(&pattern.to_ascii_lowercase())
+}
+
+/// Premium per whole unit of collateral, in whole settlement units. Premiums in a third asset
+/// cannot be valued.
+#[allow(clippy::cast_precision_loss)]
+fn premium_value(assets: &AssetRegistry, entry: &BookEntry, spot: f64) -> Option<f64> {
+    let (premium, asset) = entry.premium?;
+
+    if asset == entry.settlement_asset {
+        Some(whole_unit_price(
+            assets,
+            premium as f64,
+            &entry.collateral_asset,
+            &entry.settlement_asset,
+        ))
+    } else if asset == entry.collateral_asset {
+        Some(premium as f64 * spot)
     } else {
-        let hex = asset_id.to_hex();
-        let prefix = &hex[..hex.len().min(8)];
-        format!("{amount} ({prefix}...)")
+        None
     }
 }
+
+fn format_price(price: f64) -> String {
+    format!("{price:.PRICE_DECIMALS$}")
+}
+
+fn format_expiry_bucket(bucket: u32) -> String {
+    let datetime = Timestamp::from(u64::from(bucket)).to_human_datetime();
+    datetime.get(..10).unwrap_or(&datetime).to_string()
+}
@@ -1,15 +1,21 @@
-use clap::Subcommand;
-use simplicityhl::elements::{Address, AssetId, OutPoint};
+use crate::assets::AssetAmount;
+use crate::batch::Recipient;
+use crate::ledger::ExportFormat;
+
+use clap::{Args, Subcommand};
+use options_relay::BookSort;
+use simplicityhl::elements::{Address, AssetId, OutPoint, Txid};
+use std::path::PathBuf;
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
-    /// Wallet management (init, address, balance, utxos, import, spend)
+    /// Wallet management (init, address, balance, utxos, import, spend, freeze, unfreeze, history)
     Wallet {
         #[command(subcommand)]
         command: WalletCommand,
     },
 
-    /// Basic transactions (transfer, split, merge, issue, reissue)
+    /// Basic transactions (transfer, batch-transfer, split, merge, consolidate, issue, reissue)
     Tx {
         #[command(subcommand)]
         command: TxCommand,
@@ -28,10 +34,36 @@ pub enum Command {
     },
 
     /// Fetch options/swaps from NOSTR, sync to coin-store, display
-    Browse,
+    Browse {
+        /// Also list contracts whose output is spent, underfunded or does not match the listing
+        #[arg(long)]
+        show_invalid: bool,
+
+        /// Skip checking listings against the chain
+        #[arg(long)]
+        no_verify: bool,
 
-    /// Show my holdings with expiration warnings
-    Positions,
+        /// Only show one market, as COLLATERAL/SETTLEMENT (a ticker or an asset id prefix on either side)
+        #[arg(long)]
+        market: Option<String>,
+
+        /// Hide listings expiring after this time (Unix timestamp or relative: +30d, +2h)
+        #[arg(long)]
+        max_expiry: Option<String>,
+
+        /// Order within each market: price, expiry or depth
+        #[arg(long, default_value_t = BookSort::Price)]
+        sort: BookSort,
+
+        #[command(flatten)]
+        pricing: PricingArgs,
+    },
+
+    /// Show my holdings with expiration warnings and their mark-to-market value
+    Positions {
+        #[command(flatten)]
+        pricing: PricingArgs,
+    },
 
     /// Sync coin-store with blockchain via Esplora and/or NOSTR
     Sync {
@@ -39,6 +71,65 @@ pub enum Command {
         command: SyncCommand,
     },
 
+    /// Relay diagnostics (status)
+    Relay {
+        #[command(subcommand)]
+        command: RelayCommand,
+    },
+
+    /// Encrypted negotiation with offer makers and takers (quote, counter, accept, pset, inbox)
+    Message {
+        #[command(subcommand)]
+        command: MessageCommand,
+    },
+
+    /// Counterparty reputation and trust / block lists
+    Reputation {
+        #[command(subcommand)]
+        command: ReputationCommand,
+    },
+
+    /// Asset registry: tickers and decimal precision (list, add, remove, import)
+    Asset {
+        #[command(subcommand)]
+        command: AssetCommand,
+    },
+
+    /// Export wallet movements, fees and contract actions as a CSV or JSON ledger for accounting
+    Export {
+        /// Ledger format: csv or json
+        #[arg(long, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+
+        /// File to write the ledger to (defaults to stdout)
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+
+        /// Value each movement in this asset at its trade date, e.g. USDt (a ticker or an asset id)
+        #[arg(long)]
+        fiat: Option<String>,
+
+        /// Only export rows at or after this time (Unix timestamp or relative: -30d)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Only export rows at or before this time (Unix timestamp or relative: -1d)
+        #[arg(long)]
+        to: Option<String>,
+    },
+
+    /// Profit and loss reports (pnl)
+    Report {
+        #[command(subcommand)]
+        command: ReportCommand,
+    },
+
+    /// Labels for transactions, outputs, addresses and contracts (set, remove, list, export, import)
+    Label {
+        #[command(subcommand)]
+        command: LabelCommand,
+    },
+
     /// Show current configuration
     Config,
 }
@@ -50,7 +141,11 @@ pub enum WalletCommand {
     Init,
 
     /// Show wallet details
-    Address,
+    Address {
+        /// Also create a confidential receive address with the next blinding key derived from the seed
+        #[arg(long)]
+        confidential: bool,
+    },
 
     /// Show wallet balance
     Balance,
@@ -58,7 +153,7 @@ pub enum WalletCommand {
     /// List all UTXOs stored in wallet
     Utxos,
 
-    /// Import a UTXO into the wallet
+    /// Import a UTXO into the wallet, recording the transaction that created it
     Import {
         /// Outpoint (txid:vout)
         #[arg(long, short = 'o')]
@@ -75,6 +170,39 @@ pub enum WalletCommand {
         #[arg(long, short = 'o')]
         outpoint: OutPoint,
     },
+
+    /// Keep an output out of automatic coin selection
+    Freeze {
+        /// Outpoint to freeze (txid:vout)
+        #[arg(long, short = 'o')]
+        outpoint: OutPoint,
+
+        /// Why the output is set aside, e.g. "collateral for client X"
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Make a frozen output available to coin selection again
+    Unfreeze {
+        /// Outpoint to unfreeze (txid:vout)
+        #[arg(long, short = 'o')]
+        outpoint: OutPoint,
+    },
+
+    /// List wallet transactions with their net change per asset
+    History {
+        /// Only transactions moving this asset (a ticker or an asset id)
+        #[arg(long)]
+        asset: Option<String>,
+
+        /// Only transactions at or after this time (Unix timestamp or relative: -30d)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Only transactions at or before this time (Unix timestamp or relative: -1d)
+        #[arg(long)]
+        to: Option<String>,
+    },
 }
 
 /// Basic transaction commands
@@ -88,15 +216,44 @@ pub enum TxCommand {
         /// Recipient address
         #[arg(long)]
         to: Address,
-        /// Amount to send
+        /// Amount to send: base units, or a decimal with the asset ticker (e.g. "1.5 USDt")
         #[arg(long)]
-        amount: u64,
+        amount: AssetAmount,
+        /// Send an explicit output even if the address is confidential
+        #[arg(long)]
+        explicit: bool,
+        /// Fee amount in satoshis (auto-estimated if not specified)
+        #[arg(long)]
+        fee: Option<u64>,
+        /// Broadcast transaction
+        #[arg(long)]
+        broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
+    },
+
+    /// Pay several recipients, in any assets, with one transaction
+    BatchTransfer {
+        /// Recipient as ADDRESS,AMOUNT[,ASSET]; the asset is a ticker or id and defaults to the
+        /// amount's ticker, then LBTC (repeatable)
+        #[arg(long = "to", value_name = "RECIPIENT")]
+        recipients: Vec<Recipient>,
+        /// CSV file of recipients with the columns address,amount[,asset]
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Send explicit outputs even to confidential addresses
+        #[arg(long)]
+        explicit: bool,
         /// Fee amount in satoshis (auto-estimated if not specified)
         #[arg(long)]
         fee: Option<u64>,
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 
     /// Split LBTC into multiple UTXOs
@@ -110,6 +267,9 @@ pub enum TxCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 
     /// Merge multiple UTXOs of the same asset into one
@@ -126,6 +286,9 @@ pub enum TxCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 
     /// Issue a new asset
@@ -139,6 +302,9 @@ pub enum TxCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 
     /// Reissue an existing asset using reissuance token
@@ -155,6 +321,22 @@ pub enum TxCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
+    },
+
+    /// Merge small UTXOs of every asset per the [consolidation] config (only shows the plan without --broadcast)
+    Consolidate {
+        /// Fee rate in sats/kvb to plan at (defaults to the current estimate)
+        #[arg(long)]
+        fee_rate: Option<f32>,
+        /// Merge an asset once it has this many UTXOs (defaults to the config)
+        #[arg(long)]
+        min_utxos: Option<usize>,
+        /// Broadcast the merges
+        #[arg(long)]
+        broadcast: bool,
     },
 }
 
@@ -166,18 +348,18 @@ pub enum OptionCommand {
         /// Collateral asset ID
         #[arg(long)]
         collateral_asset: AssetId,
-        /// Total collateral to lock in the contract
+        /// Total collateral to lock in the contract (base units, or a decimal with the ticker)
         #[arg(long)]
-        total_collateral: u64,
+        total_collateral: AssetAmount,
         /// Number of option contracts (tokens) to issue
         #[arg(long)]
         num_contracts: u64,
         /// Settlement asset ID
         #[arg(long)]
         settlement_asset: AssetId,
-        /// Total strike price (settlement needed to exercise ALL contracts)
+        /// Total strike price (settlement needed to exercise ALL contracts; base units, or a decimal with the ticker)
         #[arg(long)]
-        total_strike: u64,
+        total_strike: AssetAmount,
         /// Expiry time as Unix timestamp or duration (e.g., +30d)
         #[arg(long)]
         expiry: String,
@@ -187,6 +369,9 @@ pub enum OptionCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 
     /// Exercise an option before expiration (deposit settlement, get collateral, burn option)
@@ -200,6 +385,9 @@ pub enum OptionCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 
     /// Expire an option after expiration (use Grantor Token to get collateral)
@@ -213,6 +401,9 @@ pub enum OptionCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 
     /// Claim settlement after options were exercised (use Grantor Token to get settlement asset)
@@ -226,6 +417,9 @@ pub enum OptionCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 
     /// Cancel an option (requires both Option + Grantor tokens)
@@ -239,6 +433,9 @@ pub enum OptionCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 }
 
@@ -252,19 +449,19 @@ pub enum OptionOfferCommand {
         collateral_asset: Option<AssetId>,
         /// Amount of collateral to deposit (prompted if not provided)
         #[arg(long)]
-        collateral_amount: Option<u64>,
+        collateral_amount: Option<AssetAmount>,
         /// Premium asset ID (interactive selection if not provided, excludes contract tokens)
         #[arg(long)]
         premium_asset: Option<AssetId>,
         /// Total premium amount to deposit (used to calculate `premium_per_collateral`)
         #[arg(long)]
-        premium_amount: Option<u64>,
+        premium_amount: Option<AssetAmount>,
         /// Settlement asset ID (interactive selection if not provided, excludes contract tokens)
         #[arg(long)]
         settlement_asset: Option<AssetId>,
         /// Total settlement amount expected (used to calculate `collateral_per_contract`)
         #[arg(long)]
-        settlement_amount: Option<u64>,
+        settlement_amount: Option<AssetAmount>,
         /// Expiry time as Unix timestamp or duration (e.g., +30d)
         #[arg(long)]
         expiry: String,
@@ -274,6 +471,9 @@ pub enum OptionOfferCommand {
         /// Broadcast transaction and publish to NOSTR
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 
     /// Take an option offer (pay settlement to receive collateral + premium)
@@ -287,6 +487,9 @@ pub enum OptionOfferCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 
     /// Cancel an option offer after expiry (reclaim collateral + premium)
@@ -300,6 +503,16 @@ pub enum OptionOfferCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
+    },
+
+    /// Republish your offers at their remaining size after fills, deleting fully taken ones
+    Republish {
+        /// Offer event ID from NOSTR (all of your published offers if not provided)
+        #[arg(long)]
+        offer_event: Option<String>,
     },
 
     /// Withdraw settlement after offer was taken (claim your payment)
@@ -313,6 +526,9 @@ pub enum OptionOfferCommand {
         /// Broadcast transaction
         #[arg(long)]
         broadcast: bool,
+
+        #[command(flatten)]
+        coin_control: CoinControlArgs,
     },
 }
 
@@ -334,3 +550,275 @@ pub enum SyncCommand {
     /// Only sync action history for existing contracts from NOSTR (does not populate UTXOs)
     History,
 }
+
+/// Relay diagnostics commands
+#[derive(Debug, Subcommand)]
+pub enum RelayCommand {
+    /// Check latency, NIP-11 info, publish acks and stored events for every configured relay
+    Status {
+        /// Do not publish an ephemeral probe event
+        #[arg(long)]
+        skip_publish: bool,
+    },
+}
+
+/// Encrypted negotiation commands (NIP-17 private direct messages)
+#[derive(Debug, Subcommand)]
+pub enum MessageCommand {
+    /// Ask an offer's maker to quote a fill
+    Quote {
+        /// Option offer event ID
+        #[arg(long)]
+        offer: String,
+
+        /// Collateral amount to take
+        #[arg(long)]
+        amount: u64,
+    },
+
+    /// Propose a price for a fill
+    Counter {
+        /// Option offer event ID
+        #[arg(long)]
+        offer: String,
+
+        /// Collateral amount to take
+        #[arg(long)]
+        amount: u64,
+
+        /// Proposed settlement per collateral
+        #[arg(long)]
+        price: u64,
+
+        /// Recipient public key (defaults to the offer's maker)
+        #[arg(long)]
+        to: Option<String>,
+    },
+
+    /// Accept the last proposed terms
+    Accept {
+        /// Option offer event ID
+        #[arg(long)]
+        offer: String,
+
+        /// Collateral amount to take
+        #[arg(long)]
+        amount: u64,
+
+        /// Agreed settlement per collateral
+        #[arg(long)]
+        price: u64,
+
+        /// Recipient public key (defaults to the offer's maker)
+        #[arg(long)]
+        to: Option<String>,
+    },
+
+    /// Send a base64-encoded PSET to the counterparty
+    Pset {
+        /// Option offer event ID
+        #[arg(long)]
+        offer: String,
+
+        /// Base64-encoded PSET
+        #[arg(long)]
+        pset: String,
+
+        /// Recipient public key (defaults to the offer's maker)
+        #[arg(long)]
+        to: Option<String>,
+    },
+
+    /// List negotiation messages received
+    Inbox {
+        /// Only show messages about this option offer event ID
+        #[arg(long)]
+        offer: Option<String>,
+
+        /// Print attached PSETs in full
+        #[arg(long)]
+        show_pset: bool,
+    },
+}
+
+/// Counterparty reputation commands
+#[derive(Debug, Subcommand)]
+pub enum ReputationCommand {
+    /// Recompute counterparty statistics from cached NOSTR history and the chain
+    Refresh,
+
+    /// Show cached statistics and list membership
+    Show {
+        /// Only show this public key (hex or npub)
+        #[arg(long)]
+        pubkey: Option<String>,
+    },
+
+    /// Add a counterparty to the trust list
+    Trust {
+        /// Public key (hex or npub)
+        pubkey: String,
+    },
+
+    /// Add a counterparty to the block list; their listings are hidden
+    Block {
+        /// Public key (hex or npub)
+        pubkey: String,
+    },
+
+    /// Remove a counterparty from the trust and block lists
+    Forget {
+        /// Public key (hex or npub)
+        pubkey: String,
+    },
+}
+
+/// Asset registry commands
+#[derive(Debug, Subcommand)]
+pub enum AssetCommand {
+    /// List registered assets
+    List,
+
+    /// Register an asset or update its metadata
+    Add {
+        /// Asset ID
+        #[arg(long)]
+        asset_id: AssetId,
+
+        /// Ticker used in tables and amounts (e.g. USDt)
+        #[arg(long)]
+        ticker: String,
+
+        /// Full name (defaults to the ticker)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Number of decimal places (0-8)
+        #[arg(long, default_value_t = 0)]
+        precision: u8,
+
+        /// Issuer domain
+        #[arg(long)]
+        issuer: Option<String>,
+    },
+
+    /// Remove an asset from the registry
+    Remove {
+        /// Asset ID
+        #[arg(long)]
+        asset_id: AssetId,
+    },
+
+    /// Import assets from Liquid asset registry JSON (file path or URL)
+    Import {
+        /// Path or http(s) URL of a single asset, a list of assets, or the registry index
+        source: String,
+    },
+}
+
+/// Report commands
+#[derive(Debug, Subcommand)]
+pub enum ReportCommand {
+    /// Realized and unrealized P&L per contract and per asset, from contract history and wallet transactions
+    Pnl {
+        /// Only count actions at or after this time (Unix timestamp or relative: -30d).
+        /// Open contracts are left out when a bound is given
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Only count actions at or before this time (Unix timestamp or relative: -1d).
+        /// Open contracts are left out when a bound is given
+        #[arg(long)]
+        to: Option<String>,
+
+        #[command(flatten)]
+        pricing: PricingArgs,
+    },
+}
+
+/// Label commands
+#[derive(Debug, Subcommand)]
+pub enum LabelCommand {
+    /// Attach a label, replacing any previous one
+    Set {
+        #[command(flatten)]
+        target: LabelTarget,
+
+        /// Label text
+        #[arg(long)]
+        label: String,
+    },
+
+    /// Remove a label
+    Remove {
+        #[command(flatten)]
+        target: LabelTarget,
+    },
+
+    /// List all labels
+    List,
+
+    /// Write all labels as BIP-329 JSON lines; contracts are exported as their address
+    Export {
+        /// File to write the labels to (defaults to stdout)
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
+
+    /// Read labels from a BIP-329 JSON lines file, replacing existing labels of the same items
+    Import {
+        /// BIP-329 file exported by this or another wallet
+        #[arg(long, short = 'i')]
+        input: PathBuf,
+    },
+}
+
+/// What a label is attached to; exactly one must be given
+#[derive(Debug, Clone, Args)]
+#[group(required = true, multiple = false)]
+pub struct LabelTarget {
+    /// Transaction id
+    #[arg(long)]
+    pub tx: Option<Txid>,
+
+    /// Output (txid:vout)
+    #[arg(long)]
+    pub output: Option<OutPoint>,
+
+    /// Address; the label applies to its script on any network
+    #[arg(long)]
+    pub address: Option<Address>,
+
+    /// Contract address, as shown by `positions` and `browse`
+    #[arg(long)]
+    pub contract: Option<Address>,
+}
+
+/// Inputs for option fair values, overriding the `[pricing]` config
+#[derive(Debug, Clone, Copy, Args)]
+pub struct PricingArgs {
+    /// Annualized volatility for fair values, e.g. 0.6 for 60% (defaults to the config)
+    #[arg(long)]
+    pub volatility: Option<f64>,
+
+    /// Collateral price in whole settlement units (defaults to BTC/USD for LBTC collateral)
+    #[arg(long)]
+    pub spot: Option<f64>,
+}
+
+/// Coin selection overrides for commands that spend wallet outputs
+#[derive(Debug, Clone, Default, Args)]
+pub struct CoinControlArgs {
+    /// Only fund the transaction from these wallet outputs (repeatable); they must cover the fee too.
+    /// Frozen outputs may be given here
+    #[arg(long = "utxo", value_name = "OUTPOINT")]
+    pub utxos: Vec<OutPoint>,
+
+    /// Never spend this wallet output (repeatable)
+    #[arg(long = "exclude-utxo", value_name = "OUTPOINT")]
+    pub exclude_utxos: Vec<OutPoint>,
+
+    /// Send change to this unconfidential address instead of back to the wallet
+    #[arg(long)]
+    pub change_address: Option<Address>,
+}
@@ -0,0 +1,212 @@
+use crate::assets::AssetRegistry;
+use crate::cli::Cli;
+use crate::cli::interactive::extract_entries_from_results;
+use crate::cli::tables::{ConsolidationDisplay, display_consolidation_table};
+use crate::cli::tx::build_merge_pset;
+use crate::config::{Config, ConsolidationConfig};
+use crate::consolidation::{ConsolidationPolicy, PlannedMerge, plan_consolidation};
+use crate::error::Error;
+use crate::fee::estimate_fee_signed;
+use crate::signing::sign_p2pk_inputs;
+use crate::wallet::Wallet;
+
+use std::collections::HashMap;
+
+use coin_store::{UtxoEntry, UtxoFilter, UtxoQueryResult, UtxoStore};
+use simplicityhl::elements::{AssetId, OutPoint, Script};
+use simplicityhl_core::LIQUID_TESTNET_BITCOIN_ASSET;
+
+impl Cli {
+    pub(crate) async fn run_consolidate(
+        &self,
+        config: &Config,
+        fee_rate: Option<f32>,
+        min_utxos: Option<usize>,
+        broadcast: bool,
+    ) -> Result<(), Error> {
+        let wallet = self.get_wallet(config).await?;
+        let assets = AssetRegistry::load(&config.database_path()).await;
+        let fee_rate = fee_rate.unwrap_or_else(|| config.get_fee_rate());
+        let policy = consolidation_policy(&config.consolidation, &assets, fee_rate, min_utxos)?;
+
+        consolidate(&wallet, config, &assets, &policy, broadcast).await
+    }
+
+    /// Consolidate at the end of `sync full` if fees are low enough. A failure is only reported,
+    /// so the sync itself still succeeds.
+    pub(crate) async fn run_auto_consolidation(&self, config: &Config) {
+        let Some(fee_rate) = config.live_fee_rate() else {
+            println!("Skipping consolidation: no live fee estimate (set [fee] confirmation_target)");
+            return;
+        };
+        let max_fee_rate = config.consolidation.max_fee_rate;
+        if fee_rate > max_fee_rate {
+            println!("Skipping consolidation: fee rate {fee_rate} sats/kvb is above max_fee_rate {max_fee_rate}");
+            return;
+        }
+
+        println!("Consolidating at {fee_rate} sats/kvb...");
+        if let Err(e) = self.run_consolidate(config, Some(fee_rate), None, true).await {
+            eprintln!("Warning: consolidation failed: {e}");
+        }
+    }
+}
+
+fn consolidation_policy(
+    config: &ConsolidationConfig,
+    assets: &AssetRegistry,
+    fee_rate: f32,
+    min_utxos: Option<usize>,
+) -> Result<ConsolidationPolicy, Error> {
+    let thresholds = config
+        .thresholds
+        .iter()
+        .map(|(key, threshold)| {
+            assets
+                .resolve(key)
+                .map(|asset| (asset, *threshold))
+                .ok_or_else(|| Error::Config(format!("Unknown asset '{key}' in [consolidation.thresholds]")))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(ConsolidationPolicy {
+        fee_rate,
+        min_utxos: min_utxos.unwrap_or(config.min_utxos),
+        thresholds,
+        max_inputs: config.max_inputs,
+    })
+}
+
+/// Show the consolidation plan for the wallet and, with `broadcast`, carry it out.
+async fn consolidate(
+    wallet: &Wallet,
+    config: &Config,
+    assets: &AssetRegistry,
+    policy: &ConsolidationPolicy,
+    broadcast: bool,
+) -> Result<(), Error> {
+    let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
+
+    // Confidential outputs cannot be merged into an explicit one, and frozen ones are left out by the filter.
+    let filter = UtxoFilter::new().script_pubkey(script_pubkey.clone());
+    let mut entries: HashMap<OutPoint, UtxoEntry> =
+        extract_entries_from_results(<_ as UtxoStore>::query_utxos(wallet.store(), &[filter]).await?)
+            .into_iter()
+            .map(|entry| (*entry.outpoint(), entry))
+            .collect();
+    let utxos: Vec<(OutPoint, AssetId, u64)> = entries
+        .values()
+        .filter_map(|entry| {
+            let txout = entry.txout();
+            Some((*entry.outpoint(), txout.asset.explicit()?, txout.value.explicit()?))
+        })
+        .collect();
+
+    let plan = plan_consolidation(&utxos, policy);
+    let displays: Vec<ConsolidationDisplay> = plan
+        .iter()
+        .map(|merge| ConsolidationDisplay {
+            asset: assets.label(&merge.asset),
+            inputs: merge.inputs.len().to_string(),
+            total: assets.format_amount(merge.total, &merge.asset),
+            fee: assets.format_amount(merge.fee, &LIQUID_TESTNET_BITCOIN_ASSET),
+            dust: if merge.dust == 0 { String::new() } else { merge.dust.to_string() },
+        })
+        .collect();
+
+    println!("Consolidation plan at {} sats/kvb:", policy.fee_rate);
+    display_consolidation_table(&displays);
+
+    if plan.is_empty() {
+        return Ok(());
+    }
+    if !broadcast {
+        println!("Run with --broadcast to merge");
+        return Ok(());
+    }
+
+    let lbtc_inputs: Vec<OutPoint> = plan
+        .iter()
+        .filter(|merge| merge.asset == *LIQUID_TESTNET_BITCOIN_ASSET)
+        .flat_map(|merge| merge.inputs.iter().copied())
+        .collect();
+
+    for merge in &plan {
+        let merge_entries: Vec<UtxoEntry> =
+            merge.inputs.iter().filter_map(|outpoint| entries.remove(outpoint)).collect();
+        execute_merge(
+            wallet,
+            config,
+            assets,
+            &script_pubkey,
+            policy.fee_rate,
+            merge,
+            &merge_entries,
+            &lbtc_inputs,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Merge the outputs of one planned merge, paying the fee of other assets from an LBTC output
+/// the LBTC merge does not spend.
+#[allow(clippy::too_many_arguments)]
+async fn execute_merge(
+    wallet: &Wallet,
+    config: &Config,
+    assets: &AssetRegistry,
+    script_pubkey: &Script,
+    fee_rate: f32,
+    merge: &PlannedMerge,
+    entries: &[UtxoEntry],
+    lbtc_inputs: &[OutPoint],
+) -> Result<(), Error> {
+    let fee_entry = if merge.asset == *LIQUID_TESTNET_BITCOIN_ASSET {
+        None
+    } else {
+        let filter = UtxoFilter::new()
+            .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+            .script_pubkey(script_pubkey.clone())
+            .exclude_outpoints(lbtc_inputs.iter().copied())
+            .required_value(merge.fee)
+            .limit(1);
+
+        match <_ as UtxoStore>::query_utxos(wallet.store(), &[filter]).await?.pop() {
+            Some(UtxoQueryResult::Found(mut found, _)) if !found.is_empty() => Some(found.swap_remove(0)),
+            _ => {
+                return Err(Error::Config(format!(
+                    "No LBTC UTXO of at least {} sats left to pay for merging {}",
+                    merge.fee,
+                    assets.label(&merge.asset)
+                )));
+            }
+        }
+    };
+
+    let actual_fee = estimate_fee_signed(
+        None,
+        fee_rate,
+        |f| build_merge_pset(entries, fee_entry.as_ref(), script_pubkey, merge.asset, f),
+        |tx, utxos| sign_p2pk_inputs(tx, utxos, wallet, config.network(), 0),
+    )?;
+
+    if let Some(fee_input_value) = fee_entry.as_ref().and_then(UtxoEntry::value)
+        && fee_input_value < actual_fee
+    {
+        return Err(Error::Config(format!(
+            "Fee UTXO value ({fee_input_value} sats) is less than required fee ({actual_fee} sats)"
+        )));
+    }
+
+    let (pst, utxos) = build_merge_pset(entries, fee_entry.as_ref(), script_pubkey, merge.asset, actual_fee)?;
+    let tx = sign_p2pk_inputs(pst.extract_tx()?, &utxos, wallet, config.network(), 0)?;
+
+    config.esplora().broadcast_transaction(&tx)?;
+    println!("Merged {} {} UTXOs: {}", entries.len(), assets.label(&merge.asset), tx.txid());
+
+    wallet.store().insert_transaction(&tx, HashMap::default()).await?;
+
+    Ok(())
+}
@@ -0,0 +1,277 @@
+use crate::assets::AssetRegistry;
+use crate::cli::Cli;
+use crate::cli::interactive::{current_timestamp, extract_entries_from_results, format_utc, parse_time_bound};
+use crate::config::Config;
+use crate::error::Error;
+use crate::ledger::{ExportFormat, LedgerRow, wallet_flows, write_ledger};
+use crate::metadata::{ContractMetadata, HistoryEntry};
+use crate::price_fetcher::AssetPair;
+use crate::price_oracle::PriceOracle;
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Write;
+use std::path::Path;
+
+use coin_store::{UtxoFilter, UtxoStore};
+use contracts::option_offer::{OPTION_OFFER_SOURCE, OptionOfferArguments, get_option_offer_address};
+use contracts::options::{OPTION_SOURCE, OptionsArguments, get_options_address};
+use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
+use simplicityhl::elements::{Address, AssetId, OutPoint, Txid};
+use simplicityhl_core::SimplicityNetwork;
+
+/// A contract whose history may mention wallet transactions.
+struct ExportContract {
+    contract_type: &'static str,
+    address: Address,
+    metadata: ContractMetadata,
+}
+
+impl Cli {
+    #[allow(clippy::too_many_lines)]
+    pub(crate) async fn run_export(
+        &self,
+        config: Config,
+        format: ExportFormat,
+        output: Option<&Path>,
+        fiat: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<(), Error> {
+        let assets = AssetRegistry::load(&config.database_path()).await;
+        let fiat = fiat
+            .map(|value| {
+                assets.resolve(value).ok_or_else(|| {
+                    Error::Config(format!("Unknown fiat asset '{value}'; use a registered ticker or an asset id"))
+                })
+            })
+            .transpose()?;
+        let from = from.map(parse_time_bound).transpose()?;
+        let to = to.map(parse_time_bound).transpose()?;
+
+        let wallet = self.get_wallet(&config).await?;
+        let store = wallet.store();
+        let user_script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
+        let own_author = self.get_nostr_keys()?.public_key().to_hex();
+
+        let mut contracts = Vec::new();
+        for (source, contract_type) in [(OPTION_SOURCE, "Option"), (OPTION_OFFER_SOURCE, "OptionOffer")] {
+            for (args_bytes, tpg_str, metadata_bytes) in
+                <_ as UtxoStore>::list_contracts_by_source_with_metadata(store, source).await?
+            {
+                let Some(metadata) = metadata_bytes.and_then(|bytes| ContractMetadata::from_bytes(&bytes).ok()) else {
+                    continue;
+                };
+                let Some(address) = contract_address(source, &args_bytes, &tpg_str, config.network()) else {
+                    continue;
+                };
+                contracts.push(ExportContract {
+                    contract_type,
+                    address,
+                    metadata,
+                });
+            }
+        }
+
+        let mut actions: BTreeMap<Txid, Vec<(&ExportContract, &HistoryEntry)>> = BTreeMap::new();
+        for contract in &contracts {
+            for entry in &contract.metadata.history {
+                if let Some(txid) = entry.txid.as_deref().and_then(|txid| txid.parse::<Txid>().ok()) {
+                    actions.entry(txid).or_default().push((contract, entry));
+                }
+            }
+        }
+
+        // Transactions that paid us, the ones that spent those outputs, and every contract action.
+        let filter = UtxoFilter::new().script_pubkey(user_script_pubkey.clone()).include_spent().include_frozen();
+        let outputs = extract_entries_from_results(<_ as UtxoStore>::query_utxos(store, &[filter]).await?);
+        let own_outpoints: HashSet<OutPoint> = outputs.iter().map(|entry| *entry.outpoint()).collect();
+        let mut txids: BTreeSet<Txid> = own_outpoints.iter().map(|outpoint| outpoint.txid).collect();
+
+        let esplora = config.esplora();
+        for txid in txids.clone() {
+            let outspends = match esplora.fetch_outspends(txid) {
+                Ok(outspends) => outspends,
+                Err(e) => {
+                    tracing::debug!("Could not fetch outspends of {txid}: {e}");
+                    continue;
+                }
+            };
+            for (vout, status) in (0u32..).zip(outspends) {
+                if own_outpoints.contains(&OutPoint::new(txid, vout))
+                    && let Some(spender) = status.txid.as_deref().and_then(|txid| txid.parse::<Txid>().ok())
+                {
+                    txids.insert(spender);
+                }
+            }
+        }
+        txids.extend(actions.keys());
+
+        let now = current_timestamp();
+        let mut rows = Vec::new();
+        let mut touched: HashSet<String> = HashSet::new();
+        let mut unavailable = 0;
+
+        for txid in txids {
+            let tx = match esplora.fetch_transaction(txid) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    tracing::debug!("Could not fetch {txid}: {e}");
+                    unavailable += 1;
+                    continue;
+                }
+            };
+
+            let (mut net, fee) = wallet_flows(store, &tx, &user_script_pubkey).await?;
+            if let Some((fee_asset, fee)) = fee {
+                *net.entry(fee_asset).or_default() += i128::from(fee);
+            }
+            net.retain(|_, amount| *amount != 0);
+            if net.is_empty() && fee.is_none() {
+                continue;
+            }
+
+            let tx_actions = actions.get(&txid).map(Vec::as_slice).unwrap_or_default();
+            let timestamp = tx_actions.iter().map(|(_, entry)| entry.timestamp).min().or_else(|| {
+                esplora
+                    .fetch_transaction_status(txid)
+                    .inspect_err(|e| tracing::debug!("Could not fetch status of {txid}: {e}"))
+                    .ok()
+                    .map(|status| status.block_time.unwrap_or(now))
+            });
+
+            let base = LedgerRow {
+                timestamp,
+                date: timestamp.map(format_utc),
+                txid: Some(txid.to_string()),
+                ..action_columns(tx_actions, &own_author)
+            };
+            touched.extend(tx_actions.iter().map(|(contract, _)| contract.address.to_string()));
+
+            let movements = net.into_iter().map(|(asset, amount)| ("movement", asset, amount));
+            let fee_row = fee.map(|(asset, fee)| ("fee", asset, -i128::from(fee)));
+            for (kind, asset, amount) in movements.chain(fee_row) {
+                rows.push(LedgerRow {
+                    kind: kind.to_string(),
+                    asset_id: Some(asset.to_string()),
+                    asset: Some(assets.label(&asset)),
+                    amount: Some(assets.format_signed_units(amount, &asset)),
+                    amount_base_units: i64::try_from(amount).ok(),
+                    ..base.clone()
+                });
+            }
+        }
+
+        // Actions without a transaction, on contracts we traded.
+        for contract in contracts.iter().filter(|contract| touched.contains(&contract.address.to_string())) {
+            for entry in contract.metadata.history.iter().filter(|entry| entry.txid.is_none()) {
+                rows.push(LedgerRow {
+                    timestamp: Some(entry.timestamp),
+                    date: Some(format_utc(entry.timestamp)),
+                    ..action_columns(&[(contract, entry)], &own_author)
+                });
+            }
+        }
+
+        rows.retain(|row| {
+            (from.is_none() && to.is_none())
+                || row.timestamp.is_some_and(|timestamp| {
+                    from.is_none_or(|from| timestamp >= from) && to.is_none_or(|to| timestamp <= to)
+                })
+        });
+        rows.sort_by_key(|row| row.timestamp.unwrap_or(i64::MAX));
+
+        if let Some(fiat) = fiat {
+            value_in_fiat(&assets, &mut rows, fiat, PriceOracle::from_config(&config, &assets)).await;
+        }
+
+        match output {
+            Some(path) => {
+                let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+                write_ledger(&rows, format, &mut file)?;
+                file.flush()?;
+                println!("Exported {} ledger row(s) to {}", rows.len(), path.display());
+            }
+            None => write_ledger(&rows, format, &mut std::io::stdout().lock())?,
+        }
+
+        if unavailable > 0 {
+            eprintln!("Warning: {unavailable} transaction(s) could not be fetched and are left out");
+        }
+
+        Ok(())
+    }
+}
+
+/// Contract columns of a row, from the actions recorded for its transaction.
+fn action_columns(actions: &[(&ExportContract, &HistoryEntry)], own_author: &str) -> LedgerRow {
+    let Some((contract, first)) = actions.first() else {
+        return LedgerRow {
+            kind: "movement".to_string(),
+            ..LedgerRow::default()
+        };
+    };
+
+    let names: Vec<&str> = actions.iter().map(|(_, entry)| entry.action.as_str()).collect();
+
+    LedgerRow {
+        kind: "action".to_string(),
+        contract_type: Some(contract.contract_type.to_string()),
+        contract: Some(contract.address.to_string()),
+        action: Some(names.join("; ")),
+        counterparty: contract.metadata.nostr_author.clone().filter(|author| author != own_author),
+        nostr_event_id: first.nostr_event_id.clone().or_else(|| contract.metadata.nostr_event_id.clone()),
+        ..LedgerRow::default()
+    }
+}
+
+/// Fill in the fiat columns of rows with an amount and a timestamp, at the price on that day.
+#[allow(clippy::cast_precision_loss)]
+async fn value_in_fiat(assets: &AssetRegistry, rows: &mut [LedgerRow], fiat: AssetId, oracle: PriceOracle) {
+    let priced = |row: &LedgerRow| -> Option<(AssetId, u64, i64)> {
+        let asset = row.asset_id.as_deref()?.parse().ok()?;
+        Some((asset, u64::try_from(row.timestamp?).ok()?, row.amount_base_units?))
+    };
+
+    let requests: BTreeSet<(AssetPair, u64)> = rows
+        .iter()
+        .filter_map(priced)
+        .filter(|(asset, _, _)| *asset != fiat)
+        .map(|(asset, timestamp, _)| (AssetPair::new(asset, fiat), timestamp))
+        .collect();
+    let prices = oracle.prices_at(requests).await;
+
+    for row in rows.iter_mut() {
+        row.fiat = Some(assets.label(&fiat));
+
+        let Some((asset, timestamp, amount)) = priced(&*row) else {
+            continue;
+        };
+        let price = if asset == fiat {
+            Some(1.0)
+        } else {
+            prices.get(&(AssetPair::new(asset, fiat), timestamp)).copied()
+        };
+
+        if let Some(price) = price {
+            let whole_units = amount as f64 / 10f64.powi(i32::from(assets.precision(&asset)));
+            row.fiat_price = Some(price);
+            row.fiat_value = Some(whole_units * price);
+        }
+    }
+}
+
+pub(crate) fn contract_address(source: &str, args_bytes: &[u8], tpg_str: &str, network: SimplicityNetwork) -> Option<Address> {
+    let (args, _) =
+        bincode::serde::decode_from_slice::<simplicityhl::Arguments, _>(args_bytes, bincode::config::standard())
+            .ok()?;
+
+    let tpg = if source == OPTION_SOURCE {
+        let args = OptionsArguments::from_arguments(&args).ok()?;
+        TaprootPubkeyGen::build_from_str(tpg_str, &args, network, &get_options_address).ok()?
+    } else {
+        let args = OptionOfferArguments::from_arguments(&args).ok()?;
+        TaprootPubkeyGen::build_from_str(tpg_str, &args, network, &get_option_offer_address).ok()?
+    };
+
+    Some(tpg.address)
+}
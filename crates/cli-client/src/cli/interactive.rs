@@ -1,15 +1,15 @@
+use crate::assets::{AssetAmount, AssetRegistry};
 use crate::cli::tables::display_token_table;
 use crate::error::Error;
 
 use std::io::{self, Write};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use coin_store::{UtxoEntry, UtxoFilter, UtxoQueryResult, UtxoStore};
 
 use contracts::options::OptionsArguments;
 
 use simplicityhl::elements::Script;
-use simplicityhl::elements::hex::ToHex;
 use simplicityhl_core::LIQUID_TESTNET_BITCOIN_ASSET;
 
 pub const OPTION_TOKEN_TAG: &str = "option_token";
@@ -145,6 +145,31 @@ pub fn prompt_amount(prompt: &str) -> io::Result<u64> {
     )
 }
 
+/// Prompt for an amount of `asset_id`: base units, or a decimal followed by the asset's ticker.
+pub fn prompt_asset_amount(
+    assets: &AssetRegistry,
+    prompt: &str,
+    asset_id: &simplicityhl::elements::AssetId,
+) -> io::Result<u64> {
+    print!("{prompt} ({}): ", assets.label(asset_id));
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    let amount = input
+        .parse::<AssetAmount>()
+        .map_err(Error::Config)
+        .and_then(|amount| amount.to_base_units(assets, *asset_id));
+
+    amount.or_else(|e| {
+        println!("Invalid amount: {e}");
+        prompt_asset_amount(assets, prompt, asset_id)
+    })
+}
+
 #[must_use]
 pub fn truncate_with_ellipsis(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
@@ -183,6 +208,33 @@ pub fn parse_expiry(expiry: &str) -> Result<i64, Error> {
     )))
 }
 
+/// Parse a time bound: a Unix timestamp, or a time relative to now in the past (-30d) or the future (+1d).
+pub fn parse_time_bound(value: &str) -> Result<i64, Error> {
+    if value.parse::<i64>().is_err()
+        && let Some(duration_str) = value.strip_prefix('-')
+    {
+        let duration: Duration = duration_str
+            .parse::<humantime::Duration>()
+            .map_err(|err| Error::HumantimeParse {
+                str: duration_str.to_string(),
+                err,
+            })?
+            .into();
+        let secs = i64::try_from(duration.as_secs()).map_err(|_| Error::Config("Duration too large".to_string()))?;
+
+        return Ok(current_timestamp() - secs);
+    }
+
+    parse_expiry(value)
+}
+
+/// Format a Unix timestamp as RFC 3339 in UTC.
+#[must_use]
+pub fn format_utc(timestamp: i64) -> String {
+    let secs = u64::try_from(timestamp).unwrap_or(0);
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(secs)).to_string()
+}
+
 #[allow(clippy::cast_possible_wrap)]
 pub fn current_timestamp() -> i64 {
     SystemTime::now()
@@ -212,15 +264,20 @@ pub fn extract_entries_from_results(results: Vec<UtxoQueryResult>) -> Vec<UtxoEn
 ///
 /// This function queries UTXOs with the "`grantor_token`" tag, which automatically
 /// joins with the `contract_tokens` and `simplicity_contracts` tables to provide
-/// full contract context.
+/// full contract context. Frozen tokens are left out unless `include_frozen` is set
+/// for reporting.
 pub async fn get_grantor_tokens_from_wallet(
     wallet: &crate::wallet::Wallet,
     _source: &str,
     user_script_pubkey: &Script,
+    include_frozen: bool,
 ) -> Result<Vec<EnrichedTokenEntry>, Error> {
-    let filter = UtxoFilter::new()
+    let mut filter = UtxoFilter::new()
         .token_tag(GRANTOR_TOKEN_TAG)
         .script_pubkey(user_script_pubkey.clone());
+    if include_frozen {
+        filter = filter.include_frozen();
+    }
 
     let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[filter]).await?;
     let entries = extract_entries_from_results(results);
@@ -256,15 +313,20 @@ pub struct EnrichedTokenEntry {
 ///
 /// This function queries UTXOs with the "`option_token`" tag, which automatically
 /// joins with the `contract_tokens` and `simplicity_contracts` tables to provide
-/// full contract context.
+/// full contract context. Frozen tokens are left out unless `include_frozen` is set
+/// for reporting.
 pub async fn get_option_tokens_from_wallet(
     wallet: &crate::wallet::Wallet,
     _source: &str,
     user_script_pubkey: &Script,
+    include_frozen: bool,
 ) -> Result<Vec<EnrichedTokenEntry>, Error> {
-    let filter = UtxoFilter::new()
+    let mut filter = UtxoFilter::new()
         .token_tag(OPTION_TOKEN_TAG)
         .script_pubkey(user_script_pubkey.clone());
+    if include_frozen {
+        filter = filter.include_frozen();
+    }
 
     let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[filter]).await?;
     let entries = extract_entries_from_results(results);
@@ -292,6 +354,7 @@ pub async fn get_option_tokens_from_wallet(
 /// Select from enriched token entries that include contract arguments.
 /// This shows settlement and expiry information from the contract.
 pub fn select_enriched_token_interactive<'a>(
+    assets: &AssetRegistry,
     entries: &'a [EnrichedTokenEntry],
     prompt: &str,
 ) -> Result<&'a EnrichedTokenEntry, Error> {
@@ -312,11 +375,7 @@ pub fn select_enriched_token_interactive<'a>(
             TokenDisplay {
                 index: idx + 1,
                 collateral: format!("{} tokens", enriched.entry.value().unwrap_or(0)),
-                settlement: format!(
-                    "{} {}",
-                    settlement_per_contract,
-                    format_settlement_asset(&settlement_asset)
-                ),
+                settlement: format!("{settlement_per_contract} {}", assets.label(&settlement_asset)),
                 expires: format_relative_time(i64::from(expiry_time)),
                 status: contract_addr,
             }
@@ -337,15 +396,6 @@ pub fn select_enriched_token_interactive<'a>(
     Ok(&entries[selection])
 }
 
-pub fn format_settlement_asset(asset_id: &simplicityhl::elements::AssetId) -> String {
-    if *asset_id == *LIQUID_TESTNET_BITCOIN_ASSET {
-        "LBTC".to_string()
-    } else {
-        let hex = asset_id.to_hex();
-        format!("({})...", &hex[..hex.len().min(8)])
-    }
-}
-
 /// Look up a human-readable tag for an asset from the `contract_tokens` table.
 ///
 /// Returns `Some(tag)` if the asset is registered (e.g., "`option_token`", "`grantor_token`"),
@@ -360,30 +410,30 @@ pub async fn lookup_asset_tag(store: &coin_store::Store, asset_id: &simplicityhl
 
 /// Format an asset ID with tag lookup, falling back to hex if no tag found.
 ///
-/// Returns "LBTC" for native asset, the tag if registered, or truncated hex otherwise.
-pub async fn format_asset_with_tag(store: &coin_store::Store, asset_id: &simplicityhl::elements::AssetId) -> String {
-    if *asset_id == *LIQUID_TESTNET_BITCOIN_ASSET {
-        return "LBTC".to_string();
-    }
-
+/// Returns the contract token tag if registered, or the asset registry label (ticker or truncated hex) otherwise.
+pub async fn format_asset_with_tag(
+    store: &coin_store::Store,
+    assets: &AssetRegistry,
+    asset_id: &simplicityhl::elements::AssetId,
+) -> String {
     if let Some(tag) = lookup_asset_tag(store, asset_id).await {
         return tag;
     }
 
-    let hex = asset_id.to_hex();
-    format!("({})...", &hex[..hex.len().min(8)])
+    assets.label(asset_id)
 }
 
 /// Format an asset value with tag lookup, showing "value tag" or "value (hex)...".
 pub async fn format_asset_value_with_tag(
     store: &coin_store::Store,
+    assets: &AssetRegistry,
     value: Option<u64>,
     asset_id: Option<simplicityhl::elements::AssetId>,
 ) -> String {
     match (value, asset_id) {
         (Some(v), Some(a)) => {
-            let asset_str = format_asset_with_tag(store, &a).await;
-            format!("{v} {asset_str}")
+            let asset_str = format_asset_with_tag(store, assets, &a).await;
+            format!("{} {asset_str}", assets.format_units(v, &a))
         }
         (Some(v), None) => format!("{v} (unknown)"),
         _ => "Confidential".to_string(),
@@ -397,22 +447,27 @@ pub struct WalletAssetDisplay {
     pub asset_id: simplicityhl::elements::AssetId,
     pub asset_name: String,
     pub balance: u64,
+    /// Balance in whole units of the asset.
+    pub balance_units: String,
     /// Tag for contract tokens (e.g., "`option_token`", "`grantor_token`"), None for regular assets
     pub tag: Option<String>,
 }
 
 /// Get wallet assets grouped by asset ID with total balances.
 ///
-/// Queries all UTXOs belonging to the user's script pubkey and groups them by asset,
-/// summing up the balances. For contract tokens (option/grantor), displays the tag
+/// Queries all UTXOs belonging to the user's script pubkey, frozen ones included, and groups
+/// them by asset, summing up the balances. For contract tokens (option/grantor), displays the tag
 /// with a truncated contract address prefix.
 pub async fn get_wallet_assets(
     wallet: &crate::wallet::Wallet,
+    assets: &AssetRegistry,
     user_script_pubkey: &Script,
 ) -> Result<Vec<WalletAssetDisplay>, Error> {
     use std::collections::HashMap;
 
-    let filter = UtxoFilter::new().script_pubkey(user_script_pubkey.clone());
+    let filter = UtxoFilter::new()
+        .script_pubkey(user_script_pubkey.clone())
+        .include_frozen();
 
     let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[filter]).await?;
     let entries = extract_entries_from_results(results);
@@ -428,12 +483,13 @@ pub async fn get_wallet_assets(
     let mut displays: Vec<WalletAssetDisplay> = Vec::with_capacity(asset_balances.len());
 
     for (asset_id, balance) in asset_balances {
-        let (asset_name, tag) = format_asset_name_with_contract_info(wallet.store(), &asset_id).await;
+        let (asset_name, tag) = format_asset_name_with_contract_info(wallet.store(), assets, &asset_id).await;
         displays.push(WalletAssetDisplay {
             index: 0, // Will be set after sorting
             asset_id,
             asset_name,
             balance,
+            balance_units: assets.format_units(balance, &asset_id),
             tag,
         });
     }
@@ -458,19 +514,13 @@ pub async fn get_wallet_assets(
 /// Format an asset name with contract info lookup.
 ///
 /// Returns (`display_name`, tag) where:
-/// - For LBTC: ("LBTC", None)
 /// - For contract tokens: ("tag (`contract_addr`)", Some(tag))
-/// - For unknown assets: ("(`hex_prefix`)...", None)
+/// - For other assets: (ticker or "(`hex_prefix`)...", None)
 async fn format_asset_name_with_contract_info(
     store: &coin_store::Store,
+    assets: &AssetRegistry,
     asset_id: &simplicityhl::elements::AssetId,
 ) -> (String, Option<String>) {
-    use simplicityhl::elements::hex::ToHex;
-
-    if *asset_id == *LIQUID_TESTNET_BITCOIN_ASSET {
-        return ("LBTC".to_string(), None);
-    }
-
     if let Ok(Some((taproot_pubkey_gen, tag))) = <_ as UtxoStore>::get_contract_by_token(store, *asset_id).await {
         let contract_addr = taproot_pubkey_gen
             .split(':')
@@ -481,10 +531,7 @@ async fn format_asset_name_with_contract_info(
         return (display_name, Some(tag));
     }
 
-    // Fallback to truncated hex
-    let hex = asset_id.to_hex();
-    let display_name = format!("({})...", &hex[..hex.len().min(8)]);
-    (display_name, None)
+    (assets.label(asset_id), None)
 }
 
 /// Filter wallet assets to exclude option and grantor tokens.
@@ -532,6 +579,7 @@ pub fn select_asset_interactive<'a>(
             asset_id: a.asset_id,
             asset_name: a.asset_name.clone(),
             balance: a.balance,
+            balance_units: a.balance_units.clone(),
             tag: a.tag.clone(),
         })
         .collect();
@@ -0,0 +1,196 @@
+use crate::cli::export::contract_address;
+use crate::cli::interactive::truncate_with_ellipsis;
+use crate::cli::tables::display_label_table;
+use crate::cli::{Cli, LabelCommand, LabelTarget};
+use crate::config::Config;
+use crate::error::Error;
+use crate::labels::{Bip329Record, parse_bip329, write_bip329};
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use coin_store::{LabelRef, LabelStore, Store, UtxoStore};
+use contracts::option_offer::OPTION_OFFER_SOURCE;
+use contracts::options::OPTION_SOURCE;
+use simplicityhl::elements::{Address, AddressParams, OutPoint, Script};
+use simplicityhl_core::SimplicityNetwork;
+
+/// Display struct for labels
+#[derive(Debug, Clone)]
+pub struct LabelDisplay {
+    pub kind: String,
+    pub reference: String,
+    pub label: String,
+}
+
+impl Cli {
+    pub(crate) async fn run_label(&self, config: Config, command: &LabelCommand) -> Result<(), Error> {
+        let wallet = self.get_wallet(&config).await?;
+        let store = wallet.store();
+        let params = wallet.signer().p2pk_address(config.network())?.params;
+        let contracts = contract_addresses(store, config.network()).await?;
+
+        match command {
+            LabelCommand::Set { target, label } => {
+                let target = resolve_target(target, &contracts)?;
+                store.set_label(&target, Some(label)).await?;
+
+                println!("Labelled {} {}", target.kind(), describe(&target, &contracts, params));
+                Ok(())
+            }
+            LabelCommand::Remove { target } => {
+                let target = resolve_target(target, &contracts)?;
+                let description = describe(&target, &contracts, params);
+
+                if store.get_label(&target).await?.is_some() {
+                    store.set_label(&target, None).await?;
+                    println!("Removed the label of {} {description}", target.kind());
+                } else {
+                    println!("{} {description} has no label", target.kind());
+                }
+                Ok(())
+            }
+            LabelCommand::List => {
+                let displays: Vec<LabelDisplay> = store
+                    .list_labels()
+                    .await?
+                    .into_iter()
+                    .map(|(target, label)| LabelDisplay {
+                        kind: target.kind().to_string(),
+                        reference: describe(&target, &contracts, params),
+                        label,
+                    })
+                    .collect();
+
+                display_label_table(&displays);
+                Ok(())
+            }
+            LabelCommand::Export { output } => {
+                let mut records = Vec::new();
+                let mut skipped = 0;
+                for (target, label) in store.list_labels().await? {
+                    match bip329_reference(&target, &contracts, params) {
+                        Some((kind, reference)) => records.push(Bip329Record::new(kind, reference, label)),
+                        None => skipped += 1,
+                    }
+                }
+
+                match output {
+                    Some(path) => {
+                        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+                        write_bip329(&records, &mut file)?;
+                        file.flush()?;
+                        println!("Exported {} label(s) to {}", records.len(), path.display());
+                    }
+                    None => write_bip329(&records, &mut std::io::stdout().lock())?,
+                }
+
+                if skipped > 0 {
+                    eprintln!("Warning: {skipped} label(s) have no address on this network and are left out");
+                }
+                Ok(())
+            }
+            LabelCommand::Import { input } => {
+                let records = parse_bip329(&std::fs::read_to_string(input)?)?;
+                let contract_scripts: HashMap<Script, &String> = contracts
+                    .iter()
+                    .map(|(tpg, address)| (address.script_pubkey(), tpg))
+                    .collect();
+
+                let mut imported = 0;
+                for record in &records {
+                    let (Some(label), Some(target)) = (&record.label, label_target(record, &contract_scripts)) else {
+                        continue;
+                    };
+                    store.set_label(&target, Some(label)).await?;
+                    imported += 1;
+                }
+
+                println!("Imported {imported} label(s) from {}", input.display());
+                if imported < records.len() {
+                    println!(
+                        "Skipped {} record(s) without a label or of an unsupported type (pubkey, input, xpub)",
+                        records.len() - imported
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Addresses of the stored contracts, by taproot pubkey gen.
+async fn contract_addresses(store: &Store, network: SimplicityNetwork) -> Result<HashMap<String, Address>, Error> {
+    let mut addresses = HashMap::new();
+
+    for source in [OPTION_SOURCE, OPTION_OFFER_SOURCE] {
+        for (args_bytes, tpg_str, _) in <_ as UtxoStore>::list_contracts_by_source_with_metadata(store, source).await? {
+            if let Some(address) = contract_address(source, &args_bytes, &tpg_str, network) {
+                addresses.insert(tpg_str, address);
+            }
+        }
+    }
+
+    Ok(addresses)
+}
+
+fn resolve_target(target: &LabelTarget, contracts: &HashMap<String, Address>) -> Result<LabelRef, Error> {
+    if let Some(txid) = target.tx {
+        return Ok(LabelRef::Tx(txid));
+    }
+    if let Some(outpoint) = target.output {
+        return Ok(LabelRef::Output(outpoint));
+    }
+    if let Some(address) = &target.address {
+        return Ok(LabelRef::Script(address.script_pubkey()));
+    }
+    if let Some(address) = &target.contract {
+        let script_pubkey = address.script_pubkey();
+        return contracts
+            .iter()
+            .find(|(_, contract)| contract.script_pubkey() == script_pubkey)
+            .map(|(tpg, _)| LabelRef::Contract(tpg.clone()))
+            .ok_or_else(|| Error::Config(format!("No known contract at {address}; run `sync nostr` first")));
+    }
+
+    Err(Error::Config("Specify one of --tx, --output, --address or --contract".to_string()))
+}
+
+/// BIP-329 type and reference of a label. Contracts are exported as their address.
+fn bip329_reference(
+    target: &LabelRef,
+    contracts: &HashMap<String, Address>,
+    params: &'static AddressParams,
+) -> Option<(&'static str, String)> {
+    match target {
+        LabelRef::Tx(txid) => Some(("tx", txid.to_string())),
+        LabelRef::Output(outpoint) => Some(("output", format!("{}:{}", outpoint.txid, outpoint.vout))),
+        LabelRef::Script(script) => Some(("addr", Address::from_script(script, None, params)?.to_string())),
+        LabelRef::Contract(tpg) => Some(("addr", contracts.get(tpg)?.to_string())),
+    }
+}
+
+/// What a BIP-329 record labels, if it is a type we keep. Addresses of known contracts label the contract.
+fn label_target(record: &Bip329Record, contract_scripts: &HashMap<Script, &String>) -> Option<LabelRef> {
+    match record.kind.as_str() {
+        "tx" => record.reference.parse().ok().map(LabelRef::Tx),
+        "output" => record.reference.parse::<OutPoint>().ok().map(LabelRef::Output),
+        "addr" => {
+            let script_pubkey = record.reference.parse::<Address>().ok()?.script_pubkey();
+            Some(contract_scripts.get(&script_pubkey).map_or_else(
+                || LabelRef::Script(script_pubkey),
+                |tpg| LabelRef::Contract((*tpg).clone()),
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn describe(target: &LabelRef, contracts: &HashMap<String, Address>, params: &'static AddressParams) -> String {
+    match (target, bip329_reference(target, contracts, params)) {
+        (_, Some((_, reference))) => reference,
+        (LabelRef::Script(script), None) => hex::encode(script.as_bytes()),
+        (LabelRef::Contract(tpg), None) => truncate_with_ellipsis(tpg, 24),
+        (LabelRef::Tx(_) | LabelRef::Output(_), None) => String::new(),
+    }
+}
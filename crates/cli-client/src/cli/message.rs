@@ -0,0 +1,169 @@
+use crate::cli::interactive::truncate_with_ellipsis;
+use crate::cli::tables::display_negotiation_messages_table;
+use crate::cli::{Cli, MessageCommand};
+use crate::config::Config;
+use crate::error::Error;
+
+use std::str::FromStr;
+
+use nostr::{EventId, Filter, PublicKey};
+use options_relay::{
+    NegotiationMessage, NegotiationPayload, OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY, PublishingClient,
+    ReceivedNegotiationMessage,
+};
+use simplicityhl::elements::pset::PartiallySignedTransaction;
+
+/// Display struct for received negotiation messages
+#[derive(Debug, Clone)]
+pub struct NegotiationMessageDisplay {
+    pub index: usize,
+    pub received: String,
+    pub from: String,
+    pub offer: String,
+    pub kind: String,
+    pub details: String,
+}
+
+impl Cli {
+    pub(crate) async fn run_message(&self, config: Config, command: &MessageCommand) -> Result<(), Error> {
+        let client = self.get_publishing_client(&config).await?;
+
+        let result = match command {
+            MessageCommand::Quote { offer, amount } => {
+                let payload = NegotiationPayload::QuoteRequest {
+                    collateral_amount: *amount,
+                };
+                send_message(&client, offer, None, payload).await
+            }
+            MessageCommand::Counter {
+                offer,
+                amount,
+                price,
+                to,
+            } => {
+                let payload = NegotiationPayload::CounterPrice {
+                    collateral_amount: *amount,
+                    price: *price,
+                };
+                send_message(&client, offer, to.as_deref(), payload).await
+            }
+            MessageCommand::Accept {
+                offer,
+                amount,
+                price,
+                to,
+            } => {
+                let payload = NegotiationPayload::Accept {
+                    collateral_amount: *amount,
+                    price: *price,
+                };
+                send_message(&client, offer, to.as_deref(), payload).await
+            }
+            MessageCommand::Pset { offer, pset, to } => {
+                PartiallySignedTransaction::from_str(pset)
+                    .map_err(|e| Error::Config(format!("Invalid base64 PSET: {e}")))?;
+
+                let payload = NegotiationPayload::PsetAttachment { pset: pset.clone() };
+                send_message(&client, offer, to.as_deref(), payload).await
+            }
+            MessageCommand::Inbox { offer, show_pset } => {
+                let offer_filter = offer.as_deref().map(parse_event_id).transpose()?;
+
+                let messages: Vec<ReceivedNegotiationMessage> = client
+                    .fetch_negotiation_messages()
+                    .await?
+                    .into_iter()
+                    .filter(|m| offer_filter.is_none_or(|id| m.message.offer_event_id == id))
+                    .collect();
+
+                let displays: Vec<NegotiationMessageDisplay> = messages
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, m)| build_message_display(idx, m))
+                    .collect();
+
+                println!("Negotiation messages:");
+                println!();
+                display_negotiation_messages_table(&displays);
+
+                if *show_pset {
+                    for (idx, m) in messages.iter().enumerate() {
+                        if let NegotiationPayload::PsetAttachment { pset } = &m.message.payload {
+                            println!();
+                            println!("PSET from message #{}:", idx + 1);
+                            println!("{pset}");
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+        };
+
+        client.disconnect().await;
+
+        result
+    }
+}
+
+/// Send `payload` about `offer` to `to`, or to the offer's maker if no recipient is given.
+async fn send_message(
+    client: &PublishingClient,
+    offer: &str,
+    to: Option<&str>,
+    payload: NegotiationPayload,
+) -> Result<(), Error> {
+    let offer_event_id = parse_event_id(offer)?;
+
+    let receiver = match to {
+        Some(to) => PublicKey::parse(to).map_err(|e| Error::Config(format!("Invalid recipient public key: {e}")))?,
+        None => resolve_offer_maker(client, offer_event_id).await?,
+    };
+
+    let kind = payload.as_str();
+    let message = NegotiationMessage::new(offer_event_id, payload);
+    let event_id = client.send_negotiation_message(receiver, &message).await?;
+
+    println!("Sent {kind} to {}", receiver.to_hex());
+    println!("Gift wrap event: {event_id}");
+
+    Ok(())
+}
+
+async fn resolve_offer_maker(client: &PublishingClient, offer_event_id: EventId) -> Result<PublicKey, Error> {
+    let events = client.fetch_events(Filter::new().id(offer_event_id)).await?;
+
+    events
+        .iter()
+        .find(|e| e.kind == OPTION_OFFER_CREATED || e.kind == OPTION_OFFER_CREATED_LEGACY)
+        .map(|e| e.pubkey)
+        .ok_or_else(|| Error::Config(format!("Option offer event not found on relays: {offer_event_id}")))
+}
+
+fn parse_event_id(value: &str) -> Result<EventId, Error> {
+    EventId::parse(value).map_err(|e| Error::Config(format!("Invalid event id '{value}': {e}")))
+}
+
+fn build_message_display(idx: usize, received: &ReceivedNegotiationMessage) -> NegotiationMessageDisplay {
+    let details = match &received.message.payload {
+        NegotiationPayload::QuoteRequest { collateral_amount } => format!("amount {collateral_amount}"),
+        NegotiationPayload::CounterPrice {
+            collateral_amount,
+            price,
+        }
+        | NegotiationPayload::Accept {
+            collateral_amount,
+            price,
+        } => format!("amount {collateral_amount} @ {price}"),
+        NegotiationPayload::PsetAttachment { pset } => format!("{} bytes (base64)", pset.len()),
+    };
+
+    NegotiationMessageDisplay {
+        index: idx + 1,
+        received: received.created_at.to_human_datetime(),
+        from: truncate_with_ellipsis(&received.sender.to_hex(), 16),
+        offer: truncate_with_ellipsis(&received.message.offer_event_id.to_hex(), 16),
+        kind: received.message.payload.as_str().to_string(),
+        details,
+    }
+}
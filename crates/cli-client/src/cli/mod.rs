@@ -1,9 +1,18 @@
+mod asset;
+mod batch_transfer;
 mod browse;
 mod commands;
+mod consolidate;
+mod export;
 mod interactive;
+mod label;
+mod message;
 mod option;
 mod option_offer;
 mod positions;
+mod relay;
+mod report;
+mod reputation;
 mod sync;
 mod tables;
 mod tx;
@@ -21,7 +30,10 @@ use std::path::PathBuf;
 
 use signer::Signer;
 
-pub use commands::{Command, OptionCommand, OptionOfferCommand, SyncCommand, TxCommand, WalletCommand};
+pub use commands::{
+    AssetCommand, CoinControlArgs, Command, LabelCommand, LabelTarget, MessageCommand, OptionCommand, OptionOfferCommand,
+    PricingArgs, RelayCommand, ReportCommand, ReputationCommand, SyncCommand, TxCommand, WalletCommand,
+};
 pub use interactive::{GRANTOR_TOKEN_TAG, OPTION_TOKEN_TAG};
 pub use option_offer::OPTION_OFFER_COLLATERAL_TAG;
 
@@ -78,13 +90,18 @@ impl Cli {
         Ok(client)
     }
 
-    async fn get_publishing_client(&self, config: &Config) -> Result<PublishingClient, Error> {
+    fn get_nostr_keys(&self) -> Result<nostr::Keys, Error> {
         let seed = self.parse_seed()?;
-        let relay_config = config.relay.get_nostr_relay_config();
 
         let secret_key =
             SecretKey::from_slice(&seed).map_err(|e| Error::Config(format!("Invalid seed for NOSTR key: {e}")))?;
-        let keys = nostr::Keys::new(secret_key);
+
+        Ok(nostr::Keys::new(secret_key))
+    }
+
+    async fn get_publishing_client(&self, config: &Config) -> Result<PublishingClient, Error> {
+        let relay_config = config.relay.get_nostr_relay_config();
+        let keys = self.get_nostr_keys()?;
 
         let client = PublishingClient::connect(relay_config, keys).await?;
 
@@ -99,9 +116,50 @@ impl Cli {
             Command::Tx { command } => self.run_tx(config, command).await,
             Command::Option { command } => Box::pin(self.run_option(config, command)).await,
             Command::OptionOffer { command } => Box::pin(self.run_option_offer(config, command)).await,
-            Command::Browse => self.run_browse(config).await,
-            Command::Positions => self.run_positions(config).await,
+            Command::Browse {
+                show_invalid,
+                no_verify,
+                market,
+                max_expiry,
+                sort,
+                pricing,
+            } => {
+                self.run_browse(
+                    config,
+                    *show_invalid,
+                    *no_verify,
+                    market.as_deref(),
+                    max_expiry.as_deref(),
+                    *sort,
+                    *pricing,
+                )
+                .await
+            }
+            Command::Positions { pricing } => self.run_positions(config, *pricing).await,
             Command::Sync { command } => self.run_sync(config, command).await,
+            Command::Relay { command } => self.run_relay(config, command).await,
+            Command::Message { command } => self.run_message(config, command).await,
+            Command::Reputation { command } => self.run_reputation(config, command).await,
+            Command::Asset { command } => self.run_asset(config, command).await,
+            Command::Export {
+                format,
+                output,
+                fiat,
+                from,
+                to,
+            } => {
+                self.run_export(
+                    config,
+                    *format,
+                    output.as_deref(),
+                    fiat.as_deref(),
+                    from.as_deref(),
+                    to.as_deref(),
+                )
+                .await
+            }
+            Command::Report { command } => self.run_report(config, command).await,
+            Command::Label { command } => self.run_label(config, command).await,
             Command::Config => {
                 println!("{config:#?}");
                 Ok(())
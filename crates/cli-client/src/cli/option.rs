@@ -1,11 +1,13 @@
 use std::collections::HashMap;
 
+use crate::assets::AssetRegistry;
 use crate::cli::interactive::{
     GRANTOR_TOKEN_TAG, OPTION_TOKEN_TAG, current_timestamp, extract_entries_from_result, extract_entries_from_results,
     format_relative_time, get_grantor_tokens_from_wallet, get_option_tokens_from_wallet, parse_expiry, prompt_amount,
     select_enriched_token_interactive,
 };
 use crate::cli::{Cli, OptionCommand};
+use crate::coin_control::CoinControl;
 use crate::config::Config;
 use crate::error::Error;
 use crate::fee::{PLACEHOLDER_FEE, estimate_fee_signed};
@@ -28,6 +30,7 @@ impl Cli {
     #[allow(clippy::too_many_lines)]
     pub(crate) async fn run_option(&self, config: Config, command: &OptionCommand) -> Result<(), Error> {
         let wallet = self.get_wallet(&config).await?;
+        let assets = AssetRegistry::load(&config.database_path()).await;
 
         match command {
             OptionCommand::Create {
@@ -39,31 +42,37 @@ impl Cli {
                 expiry,
                 fee,
                 broadcast,
+                coin_control,
             } => {
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
+
                 println!("Creating option contract...");
 
+                let total_collateral = total_collateral.to_base_units(&assets, *collateral_asset)?;
+                let total_strike = total_strike.to_base_units(&assets, *settlement_asset)?;
+
                 if *num_contracts == 0 {
                     return Err(Error::Config("num-contracts must be greater than 0".to_string()));
                 }
-                if *total_collateral % *num_contracts != 0 {
+                if total_collateral % *num_contracts != 0 {
                     return Err(Error::Config(format!(
                         "total-collateral ({total_collateral}) must be divisible by num-contracts ({num_contracts})"
                     )));
                 }
-                if *total_strike % *num_contracts != 0 {
+                if total_strike % *num_contracts != 0 {
                     return Err(Error::Config(format!(
                         "total-strike ({total_strike}) must be divisible by num-contracts ({num_contracts})"
                     )));
                 }
 
-                let collateral_per_contract = *total_collateral / *num_contracts;
-                let settlement_per_contract = *total_strike / *num_contracts;
+                let collateral_per_contract = total_collateral / *num_contracts;
+                let settlement_per_contract = total_strike / *num_contracts;
 
                 let expiry_time = parse_expiry(expiry)?;
                 let start_time = current_timestamp();
 
-                println!("  Total collateral: {total_collateral} of {collateral_asset}");
-                println!("  Total strike: {total_strike} of {settlement_asset}");
+                println!("  Total collateral: {}", assets.format_amount(total_collateral, collateral_asset));
+                println!("  Total strike: {}", assets.format_amount(total_strike, settlement_asset));
                 println!("  Number of contracts: {num_contracts}");
                 println!("  Per-contract collateral: {collateral_per_contract}");
                 println!("  Per-contract strike: {settlement_per_contract}");
@@ -75,16 +84,18 @@ impl Cli {
                 let initial_fee = fee.unwrap_or(PLACEHOLDER_FEE);
 
                 let lbtc_required = if is_lbtc_collateral {
-                    initial_fee * 3 + *total_collateral
+                    initial_fee * 3 + total_collateral
                 } else {
                     initial_fee * 3
                 };
 
-                let lbtc_fee_filter = UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(script_pubkey.clone())
-                    .required_value(lbtc_required)
-                    .limit(3);
+                let lbtc_fee_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(script_pubkey.clone())
+                        .required_value(lbtc_required)
+                        .limit(3),
+                );
 
                 let lbtc_results = <_ as UtxoStore>::query_utxos(wallet.store(), &[lbtc_fee_filter]).await?;
                 let lbtc_entries = extract_entries_from_results(lbtc_results);
@@ -100,10 +111,12 @@ impl Cli {
                 let (collateral_outpoint, collateral_txout, funding_fee_utxo) = if is_lbtc_collateral {
                     (*lbtc_entries[2].outpoint(), lbtc_entries[2].txout().clone(), None)
                 } else {
-                    let collateral_filter = UtxoFilter::new()
-                        .asset_id(*collateral_asset)
-                        .script_pubkey(script_pubkey.clone())
-                        .required_value(*total_collateral);
+                    let collateral_filter = coin_control.filter(
+                        UtxoFilter::new()
+                            .asset_id(*collateral_asset)
+                            .script_pubkey(script_pubkey.clone())
+                            .required_value(total_collateral),
+                    );
                     coll_query_results = <_ as UtxoStore>::query_utxos(wallet.store(), &[collateral_filter]).await?;
 
                     let coll_entries = extract_entries_from_results(coll_query_results);
@@ -136,7 +149,11 @@ impl Cli {
                     (second_fee_utxo.0, second_fee_utxo.1.value.is_confidential()),
                 );
 
-                let blinding_keypair = derive_public_blinder_key();
+                // The creation outputs stay with the wallet until the funding spends them, so they are blinded
+                // to the wallet's own key. The funded contract outputs use the public blinder key so that
+                // anyone syncing the contract can unblind them.
+                let blinding_keypair = wallet.change_blinding_keypair()?;
+                let contract_blinder = derive_public_blinder_key();
 
                 let creation_fee = estimate_fee_signed(
                     fee.as_ref(),
@@ -153,7 +170,10 @@ impl Cli {
                         )?;
                         Ok((pst, vec![first_fee_utxo.1.clone(), second_fee_utxo.1.clone()]))
                     },
-                    |tx, utxos| sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0),
+                    |tx, utxos| {
+                        let tx = coin_control.redirect_change(tx, utxos);
+                        sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0)
+                    },
                 )?;
 
                 println!("  Creation fee: {creation_fee} sats");
@@ -177,6 +197,7 @@ impl Cli {
                     .unblind(SECP256K1, blinding_keypair.secret_key())
                     .map_err(|e| Error::Config(format!("Failed to unblind grantor token output: {e}")))?;
                 let creation_utxos = vec![first_fee_utxo.1.clone(), second_fee_utxo.1.clone()];
+                let creation_tx = coin_control.redirect_change(creation_tx, &creation_utxos);
 
                 let creation_tx = sign_p2pk_inputs(creation_tx, &creation_utxos, &wallet, config.network(), 0)?;
 
@@ -198,13 +219,13 @@ impl Cli {
                     *f
                 } else {
                     let (pst, branch) = contracts::sdk::build_option_funding(
-                        &blinding_keypair,
+                        &contract_blinder,
                         option_token_utxo.clone(),
                         grantor_token_utxo.clone(),
                         collateral_utxo.clone(),
                         funding_fee_utxo.as_ref(),
                         &args,
-                        *total_collateral,
+                        total_collateral,
                         PLACEHOLDER_FEE,
                     )?;
                     let mut tx = pst.extract_tx()?;
@@ -216,6 +237,7 @@ impl Cli {
                     if let Some((_, fee_txout)) = &funding_fee_utxo {
                         utxos.push(fee_txout.clone());
                     }
+                    tx = coin_control.redirect_change(tx, &utxos);
                     let options_program = get_options_program(&args)?;
                     for i in 0..2 {
                         tx = finalize_options_transaction(
@@ -242,13 +264,13 @@ impl Cli {
                 println!("  Funding fee: {funding_fee} sats");
 
                 let (funding_pst, option_branch) = contracts::sdk::build_option_funding(
-                    &blinding_keypair,
+                    &contract_blinder,
                     option_token_utxo.clone(),
                     grantor_token_utxo.clone(),
                     collateral_utxo.clone(),
                     funding_fee_utxo.as_ref(),
                     &args,
-                    *total_collateral,
+                    total_collateral,
                     funding_fee,
                 )?;
 
@@ -262,6 +284,7 @@ impl Cli {
                     funding_utxos.push(fee_txout.clone());
                 }
 
+                funding_tx = coin_control.redirect_change(funding_tx, &funding_utxos);
                 let options_program = get_options_program(&args)?;
                 for i in 0..2 {
                     funding_tx = finalize_options_transaction(
@@ -279,10 +302,10 @@ impl Cli {
                 let funding_tx = sign_p2pk_inputs(funding_tx, &funding_utxos, &wallet, config.network(), 2)?;
 
                 if *broadcast {
-                    cli_helper::explorer::broadcast_tx(&creation_tx).await?;
+                    config.esplora().broadcast_transaction(&creation_tx)?;
                     println!("Creation tx: {}", creation_tx.txid());
 
-                    cli_helper::explorer::broadcast_tx(&funding_tx).await?;
+                    config.esplora().broadcast_transaction(&funding_tx)?;
                     println!("Funding tx: {}", funding_tx.txid());
 
                     let publishing_client = self.get_publishing_client(&config).await?;
@@ -330,13 +353,9 @@ impl Cli {
                         )
                         .await?;
 
-                    let mut blinder_keys = HashMap::new();
-                    blinder_keys.insert(0, blinding_keypair);
-                    wallet
-                        .store()
-                        .insert_transaction(&creation_tx, blinder_keys.clone())
-                        .await?;
-                    blinder_keys.insert(1, blinding_keypair);
+                    let blinder_keys = HashMap::from([(0, blinding_keypair)]);
+                    wallet.store().insert_transaction(&creation_tx, blinder_keys).await?;
+                    let blinder_keys = HashMap::from([(0, contract_blinder), (1, contract_blinder)]);
                     wallet.store().insert_transaction(&funding_tx, blinder_keys).await?;
 
                     let (option_token_id, _) = args.get_option_token_ids();
@@ -367,11 +386,15 @@ impl Cli {
                 option_token,
                 fee,
                 broadcast,
+                coin_control,
             } => {
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
+
                 println!("Exercising option...");
 
                 let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
-                let option_entries = get_option_tokens_from_wallet(&wallet, OPTION_SOURCE, &script_pubkey).await?;
+                let option_entries =
+                    get_option_tokens_from_wallet(&wallet, OPTION_SOURCE, &script_pubkey, false).await?;
                 if option_entries.is_empty() {
                     return Err(Error::Config("No option contract tokens found".to_string()));
                 }
@@ -434,6 +457,7 @@ impl Cli {
                 } else {
                     println!("  (Showing one entry per contract with collateral available)");
                     select_enriched_token_interactive(
+                        &assets,
                         &entries_with_collateral,
                         "Select contract to exercise options from",
                     )?
@@ -469,10 +493,12 @@ impl Cli {
                 let settlement_is_lbtc = settlement_asset_id == *LIQUID_TESTNET_BITCOIN_ASSET;
 
                 let (settlement_input, fee_input) = if settlement_is_lbtc {
-                    let combined_filter = UtxoFilter::new()
-                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                        .script_pubkey(script_pubkey.clone())
-                        .required_value(settlement_required + initial_fee);
+                    let combined_filter = coin_control.filter(
+                        UtxoFilter::new()
+                            .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                            .script_pubkey(script_pubkey.clone())
+                            .required_value(settlement_required + initial_fee),
+                    );
 
                     let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[combined_filter]).await?;
                     let entries = extract_entries_from_result(&results[0]);
@@ -488,15 +514,19 @@ impl Cli {
                     ((*utxo.outpoint(), utxo.txout().clone()), None)
                 } else {
                     // Separate queries for different assets
-                    let fee_filter = UtxoFilter::new()
-                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                        .script_pubkey(script_pubkey.clone())
-                        .required_value(initial_fee);
+                    let fee_filter = coin_control.filter(
+                        UtxoFilter::new()
+                            .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                            .script_pubkey(script_pubkey.clone())
+                            .required_value(initial_fee),
+                    );
 
-                    let settlement_filter = UtxoFilter::new()
-                        .asset_id(settlement_asset_id)
-                        .script_pubkey(script_pubkey.clone())
-                        .required_value(settlement_required);
+                    let settlement_filter = coin_control.filter(
+                        UtxoFilter::new()
+                            .asset_id(settlement_asset_id)
+                            .script_pubkey(script_pubkey.clone())
+                            .required_value(settlement_required),
+                    );
 
                     let results =
                         <_ as UtxoStore>::query_utxos(wallet.store(), &[fee_filter, settlement_filter]).await?;
@@ -558,6 +588,7 @@ impl Cli {
                     if let Some(ref fi) = fee_input {
                         utxos.push(fi.1.clone());
                     }
+                    tx = coin_control.redirect_change(tx, &utxos);
                     let options_program = get_options_program(&option_arguments)?;
                     tx = finalize_options_transaction(
                         tx,
@@ -597,6 +628,7 @@ impl Cli {
                     utxos.push(fi.1);
                 }
 
+                tx = coin_control.redirect_change(tx, &utxos);
                 let options_program = get_options_program(&option_arguments)?;
                 tx = finalize_options_transaction(
                     tx,
@@ -612,7 +644,7 @@ impl Cli {
                 let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 1)?;
 
                 if *broadcast {
-                    cli_helper::explorer::broadcast_tx(&tx).await?;
+                    config.esplora().broadcast_transaction(&tx)?;
                     println!("Broadcasted: {}", tx.txid());
 
                     if let Some(metadata) =
@@ -652,11 +684,15 @@ impl Cli {
                 grantor_token,
                 fee,
                 broadcast,
+                coin_control,
             } => {
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
+
                 println!("Expiring option...");
 
                 let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
-                let grantor_entries = get_grantor_tokens_from_wallet(&wallet, OPTION_SOURCE, &script_pubkey).await?;
+                let grantor_entries =
+                    get_grantor_tokens_from_wallet(&wallet, OPTION_SOURCE, &script_pubkey, false).await?;
                 if grantor_entries.is_empty() {
                     return Err(Error::Config("No grantor tokens found".to_string()));
                 }
@@ -721,6 +757,7 @@ impl Cli {
                 } else {
                     println!("  (Showing one entry per contract with collateral available)");
                     select_enriched_token_interactive(
+                        &assets,
                         &entries_with_collateral,
                         "Select contract to expire options from",
                     )?
@@ -749,10 +786,12 @@ impl Cli {
                 println!("  Burning: {amount_to_burn} grantor tokens");
 
                 let initial_fee = fee.unwrap_or(PLACEHOLDER_FEE);
-                let fee_filter = UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(script_pubkey.clone())
-                    .required_value(initial_fee);
+                let fee_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(script_pubkey.clone())
+                        .required_value(initial_fee),
+                );
 
                 let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[fee_filter]).await?;
                 let fee_entries = extract_entries_from_result(&results[0]);
@@ -798,6 +837,7 @@ impl Cli {
                     )?;
                     let mut tx = pst.extract_tx()?;
                     let utxos = vec![collateral_input.1.clone(), grantor_input.1.clone(), fee_input.1.clone()];
+                    tx = coin_control.redirect_change(tx, &utxos);
                     let options_program = get_options_program(&option_arguments)?;
                     tx = finalize_options_transaction(
                         tx,
@@ -833,6 +873,7 @@ impl Cli {
                 let mut tx = pst.extract_tx()?;
                 let utxos = vec![collateral_input.1, grantor_input.1, fee_input.1];
 
+                tx = coin_control.redirect_change(tx, &utxos);
                 let options_program = get_options_program(&option_arguments)?;
                 tx = finalize_options_transaction(
                     tx,
@@ -848,7 +889,7 @@ impl Cli {
                 let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 1)?;
 
                 if *broadcast {
-                    cli_helper::explorer::broadcast_tx(&tx).await?;
+                    config.esplora().broadcast_transaction(&tx)?;
                     println!("Broadcasted: {}", tx.txid());
 
                     if let Some(metadata) =
@@ -885,11 +926,15 @@ impl Cli {
                 grantor_token,
                 fee,
                 broadcast,
+                coin_control,
             } => {
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
+
                 println!("Claiming settlement...");
 
                 let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
-                let grantor_entries = get_grantor_tokens_from_wallet(&wallet, OPTION_SOURCE, &script_pubkey).await?;
+                let grantor_entries =
+                    get_grantor_tokens_from_wallet(&wallet, OPTION_SOURCE, &script_pubkey, false).await?;
                 if grantor_entries.is_empty() {
                     return Err(Error::Config("No grantor tokens found".to_string()));
                 }
@@ -954,6 +999,7 @@ impl Cli {
                 } else {
                     println!("  (Showing one entry per contract with settlement available)");
                     select_enriched_token_interactive(
+                        &assets,
                         &entries_with_settlement,
                         "Select contract to claim settlement from",
                     )?
@@ -983,10 +1029,12 @@ impl Cli {
                 println!("  Burning: {amount_to_burn} grantor tokens");
 
                 let initial_fee = fee.unwrap_or(PLACEHOLDER_FEE);
-                let fee_filter = UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(script_pubkey.clone())
-                    .required_value(initial_fee);
+                let fee_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(script_pubkey.clone())
+                        .required_value(initial_fee),
+                );
 
                 let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[fee_filter]).await?;
                 let fee_entries = extract_entries_from_result(&results[0]);
@@ -1044,6 +1092,7 @@ impl Cli {
                     )?;
                     let mut tx = pst.extract_tx()?;
                     let utxos = vec![settlement_input.1.clone(), grantor_input.1.clone(), fee_input.1.clone()];
+                    tx = coin_control.redirect_change(tx, &utxos);
                     let options_program = get_options_program(&option_arguments)?;
                     tx = finalize_options_transaction(
                         tx,
@@ -1079,6 +1128,7 @@ impl Cli {
                 let mut tx = pst.extract_tx()?;
                 let utxos = vec![settlement_input.1, grantor_input.1, fee_input.1];
 
+                tx = coin_control.redirect_change(tx, &utxos);
                 let options_program = get_options_program(&option_arguments)?;
                 tx = finalize_options_transaction(
                     tx,
@@ -1094,7 +1144,7 @@ impl Cli {
                 let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 1)?;
 
                 if *broadcast {
-                    cli_helper::explorer::broadcast_tx(&tx).await?;
+                    config.esplora().broadcast_transaction(&tx)?;
                     println!("Broadcasted: {}", tx.txid());
 
                     if let Some(metadata) =
@@ -1134,11 +1184,15 @@ impl Cli {
                 option_token,
                 fee,
                 broadcast,
+                coin_control,
             } => {
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
+
                 println!("Cancelling option...");
 
                 let user_script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
-                let token_entries = get_option_tokens_from_wallet(&wallet, OPTION_SOURCE, &user_script_pubkey).await?;
+                let token_entries =
+                    get_option_tokens_from_wallet(&wallet, OPTION_SOURCE, &user_script_pubkey, false).await?;
                 if token_entries.is_empty() {
                     return Err(Error::Config("No option tokens found".to_string()));
                 }
@@ -1148,7 +1202,9 @@ impl Cli {
                         .iter()
                         .find(|e| e.entry.outpoint() == outpoint)
                         .ok_or_else(|| Error::Config("Option token not found in wallet".to_string()))?,
-                    None => select_enriched_token_interactive(&token_entries, "Select option token to cancel")?,
+                    None => {
+                        select_enriched_token_interactive(&assets, &token_entries, "Select option token to cancel")?
+                    }
                 };
 
                 let option_entry = &enriched_entry.entry;
@@ -1163,9 +1219,11 @@ impl Cli {
                 let (_option_token_id, _) = option_arguments.get_option_token_ids();
                 let (grantor_token_id, _) = option_arguments.get_grantor_token_ids();
 
-                let grantor_filter = UtxoFilter::new()
-                    .asset_id(grantor_token_id)
-                    .script_pubkey(user_script_pubkey.clone());
+                let grantor_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(grantor_token_id)
+                        .script_pubkey(user_script_pubkey.clone()),
+                );
 
                 let grantor_results = <_ as UtxoStore>::query_utxos(wallet.store(), &[grantor_filter]).await?;
                 let grantor_entries = extract_entries_from_result(&grantor_results[0]);
@@ -1198,10 +1256,12 @@ impl Cli {
                 let initial_fee = fee.unwrap_or(PLACEHOLDER_FEE);
 
                 let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
-                let fee_filter = UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(script_pubkey.clone())
-                    .required_value(initial_fee);
+                let fee_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(script_pubkey.clone())
+                        .required_value(initial_fee),
+                );
 
                 let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[fee_filter]).await?;
                 let fee_entries = extract_entries_from_result(&results[0]);
@@ -1249,6 +1309,7 @@ impl Cli {
                         grantor_input.1.clone(),
                         fee_input.1.clone(),
                     ];
+                    tx = coin_control.redirect_change(tx, &utxos);
                     let options_program = get_options_program(&option_arguments)?;
                     tx = finalize_options_transaction(
                         tx,
@@ -1285,6 +1346,7 @@ impl Cli {
                 let mut tx = pst.extract_tx()?;
                 let utxos = vec![collateral_input.1, option_input.1, grantor_input.1, fee_input.1];
 
+                tx = coin_control.redirect_change(tx, &utxos);
                 let options_program = get_options_program(&option_arguments)?;
                 tx = finalize_options_transaction(
                     tx,
@@ -1300,7 +1362,7 @@ impl Cli {
                 let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 1)?;
 
                 if *broadcast {
-                    cli_helper::explorer::broadcast_tx(&tx).await?;
+                    config.esplora().broadcast_transaction(&tx)?;
                     println!("Broadcasted: {}", tx.txid());
 
                     if let Some(metadata) =
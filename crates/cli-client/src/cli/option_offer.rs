@@ -1,14 +1,19 @@
+use crate::assets::AssetRegistry;
 use crate::cli::interactive::{
-    current_timestamp, extract_entries_from_result, format_relative_time, format_settlement_asset, get_wallet_assets,
-    parse_expiry, prompt_amount, select_asset_interactive, truncate_with_ellipsis,
+    current_timestamp, extract_entries_from_result, format_relative_time, get_wallet_assets, parse_expiry,
+    prompt_asset_amount, select_asset_interactive, truncate_with_ellipsis,
 };
+use crate::cli::reputation::ReputationBook;
 use crate::cli::tables::{
     display_active_option_offers_table, display_cancellable_option_offers_table,
     display_withdrawable_option_offers_table,
 };
 use crate::cli::{Cli, OptionOfferCommand};
+use crate::coin_control::CoinControl;
 use crate::config::Config;
 use crate::error::Error;
+use crate::event_cache::{load_cached_actions, load_cached_option_offers, refresh_event_cache};
+use crate::explorer::EsploraBackend;
 use crate::fee::{PLACEHOLDER_FEE, estimate_fee_signed};
 use crate::metadata::{ContractMetadata, HistoryEntry};
 use crate::signing::sign_p2pk_inputs;
@@ -19,7 +24,8 @@ use coin_store::{UtxoFilter, UtxoQueryResult, UtxoStore};
 use contracts::option_offer::{
     OPTION_OFFER_SOURCE, OptionOfferArguments, finalize_option_offer_transaction, get_option_offer_program,
 };
-use options_relay::{ActionCompletedEvent, ActionType, OptionOfferCreatedEvent};
+use options_relay::events::option_offer_coordinate;
+use options_relay::{ActionCompletedEvent, ActionType, ListingValidator, OfferDepth, OptionOfferCreatedEvent};
 use simplicityhl::elements::pset::serialize::Serialize;
 use simplicityhl::simplicity::hex::DisplayHex;
 use simplicityhl::tracker::TrackerLogLevel;
@@ -33,6 +39,56 @@ pub struct LocalOptionOfferData {
     pub(crate) metadata: ContractMetadata,
     pub(crate) current_outpoint: simplicityhl::elements::OutPoint,
     pub(crate) current_value: u64,
+    /// Depth from the actions published on NOSTR, if the offer was published there.
+    pub(crate) nostr_depth: Option<OfferDepth>,
+}
+
+impl LocalOptionOfferData {
+    /// Author and event of the offer on NOSTR, if it was published there.
+    fn nostr_offer(&self) -> Option<(nostr::PublicKey, nostr::EventId)> {
+        let author = nostr::PublicKey::from_hex(self.metadata.nostr_author.as_deref()?).ok()?;
+        let event_id = nostr::EventId::from_hex(self.metadata.nostr_event_id.as_deref()?).ok()?;
+        Some((author, event_id))
+    }
+
+    /// Follow the offer on chain from the UTXO we know about, counting only confirmed fills.
+    fn refresh_depth(&mut self, esplora: &EsploraBackend, actions: &[ActionCompletedEvent]) {
+        let Some((author, event_id)) = self.nostr_offer() else {
+            return;
+        };
+
+        let mut event = OptionOfferCreatedEvent::new(
+            self.option_offer_args.clone(),
+            self.current_outpoint,
+            self.taproot_pubkey_gen.clone(),
+        )
+        .with_collateral_amount(self.current_value);
+        event.event_id = event_id;
+        event.pubkey = author;
+
+        match ListingValidator::new(esplora.clone()).offer_depth(&event, actions) {
+            Ok(depth) => self.nostr_depth = Some(depth),
+            Err(e) => tracing::debug!("Could not follow option offer {event_id} on chain: {e}"),
+        }
+    }
+
+    /// Whether a confirmed fill spent the offer UTXO we know about.
+    fn is_stale(&self) -> bool {
+        self.nostr_depth
+            .and_then(|depth| depth.latest_outpoint)
+            .is_some_and(|outpoint| outpoint != self.current_outpoint)
+    }
+
+    /// Remaining collateral, preferring confirmed fills newer than the last sync.
+    fn live_remaining(&self) -> u64 {
+        if self.is_stale() {
+            self.nostr_depth
+                .and_then(|depth| depth.remaining)
+                .unwrap_or(self.current_value)
+        } else {
+            self.current_value
+        }
+    }
 }
 
 pub struct LocalCancellableOptionOffer {
@@ -79,6 +135,7 @@ impl Cli {
     #[allow(clippy::too_many_lines)]
     pub(crate) async fn run_option_offer(&self, config: Config, command: &OptionOfferCommand) -> Result<(), Error> {
         let wallet = self.get_wallet(&config).await?;
+        let assets = AssetRegistry::load(&config.database_path()).await;
 
         match command {
             OptionOfferCommand::Create {
@@ -91,12 +148,15 @@ impl Cli {
                 expiry,
                 fee,
                 broadcast,
+                coin_control,
             } => {
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
+
                 println!("Creating option offer...");
 
                 let user_script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
 
-                let wallet_assets = get_wallet_assets(&wallet, &user_script_pubkey).await?;
+                let wallet_assets = get_wallet_assets(&wallet, &assets, &user_script_pubkey).await?;
 
                 let collateral_asset_id = if let Some(asset) = collateral_asset {
                     *asset
@@ -106,9 +166,9 @@ impl Cli {
                 };
 
                 let collateral_amt = if let Some(amt) = collateral_amount {
-                    *amt
+                    amt.to_base_units(&assets, collateral_asset_id)?
                 } else {
-                    prompt_amount("Enter collateral amount").map_err(Error::Io)?
+                    prompt_asset_amount(&assets, "Enter collateral amount", &collateral_asset_id).map_err(Error::Io)?
                 };
 
                 if collateral_amt == 0 {
@@ -123,9 +183,9 @@ impl Cli {
                 };
 
                 let total_premium = if let Some(amt) = premium_amount {
-                    *amt
+                    amt.to_base_units(&assets, premium_asset_id)?
                 } else {
-                    prompt_amount("Enter total premium amount").map_err(Error::Io)?
+                    prompt_asset_amount(&assets, "Enter total premium amount", &premium_asset_id).map_err(Error::Io)?
                 };
 
                 let premium_per_collateral = if total_premium == 0 {
@@ -149,9 +209,10 @@ impl Cli {
                 };
 
                 let settlement_amt = if let Some(amt) = settlement_amount {
-                    *amt
+                    amt.to_base_units(&assets, settlement_asset_id)?
                 } else {
-                    prompt_amount("Enter total settlement amount expected").map_err(Error::Io)?
+                    prompt_asset_amount(&assets, "Enter total settlement amount expected", &settlement_asset_id)
+                        .map_err(Error::Io)?
                 };
 
                 let collateral_per_contract = if settlement_amt == 0 {
@@ -172,17 +233,16 @@ impl Cli {
 
                 println!();
                 println!(
-                    "  Collateral: {collateral_amt} of {}",
-                    format_settlement_asset(&collateral_asset_id)
+                    "  Collateral: {}",
+                    assets.format_amount(collateral_amt, &collateral_asset_id)
                 );
                 println!(
-                    "  Premium: {total_premium} of {} (rate: {premium_per_collateral} per collateral)",
-                    format_settlement_asset(&premium_asset_id)
+                    "  Premium: {} (rate: {premium_per_collateral} per collateral)",
+                    assets.format_amount(total_premium, &premium_asset_id)
                 );
                 println!(
-                    "  Settlement: {} of {} (rate: {collateral_per_contract} per collateral)",
-                    settlement_amt,
-                    format_settlement_asset(&settlement_asset_id)
+                    "  Settlement: {} (rate: {collateral_per_contract} per collateral)",
+                    assets.format_amount(settlement_amt, &settlement_asset_id)
                 );
                 println!("  Expiry: {}", format_relative_time(i64::from(offer_expiry)));
 
@@ -196,20 +256,26 @@ impl Cli {
                     wallet.signer().public_key().serialize(),
                 );
 
-                let collateral_filter = UtxoFilter::new()
-                    .asset_id(collateral_asset_id)
-                    .script_pubkey(user_script_pubkey.clone())
-                    .required_value(collateral_amt);
+                let collateral_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(collateral_asset_id)
+                        .script_pubkey(user_script_pubkey.clone())
+                        .required_value(collateral_amt),
+                );
 
-                let premium_filter = UtxoFilter::new()
-                    .asset_id(premium_asset_id)
-                    .script_pubkey(user_script_pubkey.clone())
-                    .required_value(total_premium);
+                let premium_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(premium_asset_id)
+                        .script_pubkey(user_script_pubkey.clone())
+                        .required_value(total_premium),
+                );
 
-                let fee_filter = UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(user_script_pubkey.clone())
-                    .required_value(fee.unwrap_or(PLACEHOLDER_FEE));
+                let fee_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(user_script_pubkey.clone())
+                        .required_value(fee.unwrap_or(PLACEHOLDER_FEE)),
+                );
 
                 let results =
                     <_ as UtxoStore>::query_utxos(wallet.store(), &[collateral_filter, premium_filter, fee_filter])
@@ -222,13 +288,13 @@ impl Cli {
                 if collateral_entries.is_empty() {
                     return Err(Error::Config(format!(
                         "No collateral UTXOs found for asset {}",
-                        format_settlement_asset(&collateral_asset_id)
+                        assets.label(&collateral_asset_id)
                     )));
                 }
                 if premium_entries.is_empty() {
                     return Err(Error::Config(format!(
                         "No premium UTXOs found for asset {}. Need {total_premium}",
-                        format_settlement_asset(&premium_asset_id)
+                        assets.label(&premium_asset_id)
                     )));
                 }
                 if fee_entries.is_empty() {
@@ -261,7 +327,10 @@ impl Cli {
                             vec![collateral_input.1.clone(), premium_input.1.clone(), fee_input.1.clone()],
                         ))
                     },
-                    |tx, utxos| sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0),
+                    |tx, utxos| {
+                        let tx = coin_control.redirect_change(tx, utxos);
+                        sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0)
+                    },
                 )?;
 
                 println!("  Fee: {actual_fee} sats");
@@ -276,13 +345,13 @@ impl Cli {
                     config.network(),
                 )?;
 
-                let tx = pst.extract_tx()?;
                 let utxos = vec![collateral_input.1.clone(), premium_input.1, fee_input.1];
+                let tx = coin_control.redirect_change(pst.extract_tx()?, &utxos);
 
                 let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 0)?;
 
                 if *broadcast {
-                    cli_helper::explorer::broadcast_tx(&tx).await?;
+                    config.esplora().broadcast_transaction(&tx)?;
                     println!("Broadcasted: {}", tx.txid());
 
                     let offer_outpoint = simplicityhl::elements::OutPoint::new(tx.txid(), 0);
@@ -293,7 +362,8 @@ impl Cli {
                         option_offer_args.clone(),
                         offer_outpoint,
                         taproot_pubkey_gen.clone(),
-                    );
+                    )
+                    .with_collateral_amount(collateral_amt);
 
                     let event_id = publishing_client.publish_option_offer_created(&offer_event).await?;
                     println!("Published to NOSTR: {event_id}");
@@ -342,13 +412,29 @@ impl Cli {
                 offer_event,
                 fee,
                 broadcast,
+                coin_control,
             } => {
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
+
                 println!("Taking option offer...");
 
                 let offer_contracts =
                     <_ as UtxoStore>::list_contracts_by_source_with_metadata(wallet.store(), OPTION_OFFER_SOURCE)
                         .await?;
 
+                // Pick up fills published since the last sync; the relay being unreachable is not fatal.
+                match self.get_read_only_client(&config).await {
+                    Ok(client) => {
+                        if let Err(e) = refresh_event_cache(wallet.store(), &client).await {
+                            tracing::debug!("Could not refresh NOSTR event cache: {e}");
+                        }
+                        client.disconnect().await;
+                    }
+                    Err(e) => tracing::debug!("Could not connect to NOSTR relays: {e}"),
+                }
+                let offer_actions = load_cached_actions(wallet.store()).await?;
+                let reputation = ReputationBook::load(wallet.store()).await?;
+
                 let mut active_offers: Vec<LocalOptionOfferData> = Vec::new();
                 for (args_bytes, tpg_str, metadata_bytes) in offer_contracts {
                     let Ok((arguments, _)): Result<(simplicityhl::Arguments, usize), _> =
@@ -389,25 +475,56 @@ impl Cli {
                             UtxoQueryResult::Empty => None,
                         }
                     {
-                        active_offers.push(LocalOptionOfferData {
+                        let mut offer = LocalOptionOfferData {
                             option_offer_args,
                             taproot_pubkey_gen,
                             metadata,
                             current_outpoint: outpoint,
                             current_value: value,
+                            nostr_depth: None,
+                        };
+
+                        // Only the maker can cancel; fills are checked on chain once an offer is picked.
+                        offer.nostr_depth = offer.nostr_offer().map(|(author, id)| {
+                            let coordinate = option_offer_coordinate(author, &offer.taproot_pubkey_gen);
+                            OfferDepth::from_actions(id, &coordinate, outpoint, None, &offer_actions, &HashMap::new())
                         });
+                        if offer.nostr_depth.is_some_and(|depth| depth.cancelled) {
+                            continue;
+                        }
+
+                        active_offers.push(offer);
                     }
                 }
 
-                let selected_offer = if let Some(event_id_str) = offer_event {
+                // Offers from blocked counterparties can only be taken after unblocking them.
+                let (blocked_offers, active_offers): (Vec<LocalOptionOfferData>, Vec<LocalOptionOfferData>) =
+                    active_offers.into_iter().partition(|offer| {
+                        offer
+                            .metadata
+                            .nostr_author
+                            .as_deref()
+                            .is_some_and(|author| reputation.is_blocked(author))
+                    });
+
+                let mut selected_offer = if let Some(event_id_str) = offer_event {
+                    let matches_event = |s: &LocalOptionOfferData| {
+                        s.metadata
+                            .nostr_event_id
+                            .as_ref()
+                            .is_some_and(|id| id.starts_with(event_id_str))
+                    };
+
+                    if blocked_offers.iter().any(matches_event) {
+                        return Err(Error::Config(format!(
+                            "Option offer {event_id_str} is from a blocked counterparty. \
+                             Run `reputation forget <pubkey>` to take it anyway."
+                        )));
+                    }
+
                     active_offers
                         .into_iter()
-                        .find(|s| {
-                            s.metadata
-                                .nostr_event_id
-                                .as_ref()
-                                .is_some_and(|id| id.starts_with(event_id_str))
-                        })
+                        .find(matches_event)
                         .ok_or_else(|| {
                             Error::Config(format!("Option offer event not found or fully taken: {event_id_str}"))
                         })?
@@ -420,8 +537,12 @@ impl Cli {
                         ));
                     }
 
-                    let active_offer_displays = build_active_option_offers_displays(&active_offers);
+                    let active_offer_displays =
+                        build_active_option_offers_displays(&assets, &active_offers, &reputation);
                     display_active_option_offers_table(&active_offer_displays);
+                    if !blocked_offers.is_empty() {
+                        println!("({} offer(s) from blocked counterparties hidden)", blocked_offers.len());
+                    }
                     println!();
 
                     let selection =
@@ -435,21 +556,40 @@ impl Cli {
                         .ok_or_else(|| Error::Config("Invalid selection".to_string()))?
                 };
 
+                selected_offer.refresh_depth(&config.esplora(), &offer_actions);
+                if selected_offer.is_stale() {
+                    return Err(Error::Config(format!(
+                        "Option offer was taken since the last sync ({} collateral remaining). \
+                         Run `sync nostr` and `sync spent` first.",
+                        selected_offer.live_remaining()
+                    )));
+                }
+
                 let args = &selected_offer.option_offer_args;
                 let current_offer_outpoint = selected_offer.current_outpoint;
                 let actual_collateral = selected_offer.current_value;
 
                 let event_id_display = selected_offer.metadata.nostr_event_id.as_deref().unwrap_or("local");
                 println!("  Offer event: {event_id_display}");
-                println!("  Collateral available: {actual_collateral}");
+                if let Some(author) = selected_offer.metadata.nostr_author.as_deref() {
+                    println!("  Seller: {}", reputation.seller(author));
+                }
+                println!(
+                    "  Collateral available: {}",
+                    assets.format_amount(actual_collateral, &args.get_collateral_asset_id())
+                );
                 println!(
                     "  Price: {} (settlement per collateral)",
                     args.collateral_per_contract()
                 );
                 println!("  Expiry: {}", format_relative_time(i64::from(args.expiry_time())));
 
-                let collateral_amount_to_receive =
-                    prompt_amount("Amount of collateral to receive").map_err(Error::Io)?;
+                let collateral_amount_to_receive = prompt_asset_amount(
+                    &assets,
+                    "Amount of collateral to receive",
+                    &args.get_collateral_asset_id(),
+                )
+                .map_err(Error::Io)?;
 
                 if collateral_amount_to_receive > actual_collateral {
                     return Err(Error::Config(format!(
@@ -466,15 +606,19 @@ impl Cli {
                 let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
                 let settlement_asset = args.get_settlement_asset_id();
 
-                let settlement_filter = UtxoFilter::new()
-                    .asset_id(settlement_asset)
-                    .script_pubkey(script_pubkey.clone())
-                    .required_value(settlement_required);
+                let settlement_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(settlement_asset)
+                        .script_pubkey(script_pubkey.clone())
+                        .required_value(settlement_required),
+                );
 
-                let fee_filter = UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(script_pubkey.clone())
-                    .required_value(fee.unwrap_or(PLACEHOLDER_FEE));
+                let fee_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(script_pubkey.clone())
+                        .required_value(fee.unwrap_or(PLACEHOLDER_FEE)),
+                );
 
                 let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[settlement_filter, fee_filter]).await?;
 
@@ -514,11 +658,11 @@ impl Cli {
                     &fee_entries[0]
                 };
 
-                let collateral_txout = cli_helper::explorer::fetch_utxo(current_offer_outpoint).await?;
+                let collateral_txout = config.esplora().fetch_output(current_offer_outpoint)?;
 
                 let premium_outpoint =
                     simplicityhl::elements::OutPoint::new(current_offer_outpoint.txid, current_offer_outpoint.vout + 1);
-                let premium_txout = cli_helper::explorer::fetch_utxo(premium_outpoint).await?;
+                let premium_txout = config.esplora().fetch_output(premium_outpoint)?;
 
                 let collateral_input = (current_offer_outpoint, collateral_txout.clone());
                 let premium_input = (premium_outpoint, premium_txout.clone());
@@ -545,6 +689,7 @@ impl Cli {
                         settlement_input.1.clone(),
                         fee_input.1.clone(),
                     ];
+                    tx = coin_control.redirect_change(tx, &utxos);
                     let offer_program = get_option_offer_program(args)?;
                     tx = finalize_option_offer_transaction(
                         tx,
@@ -597,6 +742,7 @@ impl Cli {
                     fee_input.1.clone(),
                 ];
 
+                tx = coin_control.redirect_change(tx, &utxos);
                 let offer_program = get_option_offer_program(args)?;
                 tx = finalize_option_offer_transaction(
                     tx,
@@ -623,19 +769,23 @@ impl Cli {
                 let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 2)?;
 
                 if *broadcast {
-                    cli_helper::explorer::broadcast_tx(&tx).await?;
+                    config.esplora().broadcast_transaction(&tx)?;
                     println!("Broadcasted: {}", tx.txid());
 
-                    if let Some(ref nostr_event_id) = selected_offer.metadata.nostr_event_id
-                        && let Ok(event_id) = nostr::EventId::from_hex(nostr_event_id)
-                    {
+                    // The maker republishes or deletes the offer on their next `option-offer republish`.
+                    if let Some((author, event_id)) = selected_offer.nostr_offer() {
                         let publishing_client = self.get_publishing_client(&config).await?;
 
                         let action_event = ActionCompletedEvent::new(
                             event_id,
                             ActionType::OptionOfferExercised,
                             simplicityhl::elements::OutPoint::new(tx.txid(), 0),
-                        );
+                        )
+                        .with_fill(
+                            collateral_amount_to_receive,
+                            actual_collateral - collateral_amount_to_receive,
+                        )
+                        .with_offer_coordinate(option_offer_coordinate(author, &selected_offer.taproot_pubkey_gen));
 
                         let published_id = publishing_client.publish_action_completed(&action_event).await?;
                         println!("Published action to NOSTR: {published_id}");
@@ -661,7 +811,10 @@ impl Cli {
                 offer_event,
                 fee,
                 broadcast,
+                coin_control,
             } => {
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
+
                 println!("Cancelling option offer (reclaiming collateral + premium after expiry)...");
 
                 let offer_contracts =
@@ -737,7 +890,7 @@ impl Cli {
                     ));
                 }
 
-                let cancellable_offer_displays = build_cancellable_option_offers_displays(&cancellable_offers);
+                let cancellable_offer_displays = build_cancellable_option_offers_displays(&assets, &cancellable_offers);
                 display_cancellable_option_offers_table(&cancellable_offer_displays);
                 println!();
 
@@ -775,10 +928,12 @@ impl Cli {
                 let initial_fee = fee.unwrap_or(PLACEHOLDER_FEE);
 
                 let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
-                let fee_filter = UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(script_pubkey.clone())
-                    .required_value(initial_fee);
+                let fee_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(script_pubkey.clone())
+                        .required_value(initial_fee),
+                );
 
                 let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[fee_filter]).await?;
                 let fee_entries = extract_entries_from_result(&results[0]);
@@ -814,7 +969,7 @@ impl Cli {
 
                 let premium_outpoint =
                     simplicityhl::elements::OutPoint::new(current_outpoint.txid, current_outpoint.vout + 1);
-                let premium_txout = cli_helper::explorer::fetch_utxo(premium_outpoint).await?;
+                let premium_txout = config.esplora().fetch_output(premium_outpoint)?;
 
                 let collateral_input = (current_outpoint, collateral_txout.clone());
                 let premium_input = (premium_outpoint, premium_txout.clone());
@@ -832,6 +987,7 @@ impl Cli {
                     )?;
                     let mut tx = pst.extract_tx()?;
                     let utxos = vec![collateral_txout.clone(), premium_txout.clone(), fee_input.1.clone()];
+                    tx = coin_control.redirect_change(tx, &utxos);
                     let offer_program = get_option_offer_program(args)?;
                     let signature = wallet.signer().sign_contract(
                         &tx,
@@ -898,6 +1054,7 @@ impl Cli {
 
                 let mut tx = pst.extract_tx()?;
                 let utxos = vec![collateral_txout.clone(), premium_txout.clone(), fee_input.1.clone()];
+                tx = coin_control.redirect_change(tx, &utxos);
                 let offer_program = get_option_offer_program(args)?;
 
                 let signature = wallet.signer().sign_contract(
@@ -951,7 +1108,7 @@ impl Cli {
                 let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 2)?;
 
                 if *broadcast {
-                    cli_helper::explorer::broadcast_tx(&tx).await?;
+                    config.esplora().broadcast_transaction(&tx)?;
                     println!("Broadcasted: {}", tx.txid());
 
                     if let Some(ref nostr_event_id) = selected.metadata.nostr_event_id
@@ -959,15 +1116,25 @@ impl Cli {
                     {
                         let publishing_client = self.get_publishing_client(&config).await?;
 
+                        let maker = publishing_client.public_key().await?;
                         let action_event = ActionCompletedEvent::new(
                             event_id,
                             ActionType::OptionOfferCancelled,
                             simplicityhl::elements::OutPoint::new(tx.txid(), 0),
-                        );
+                        )
+                        .with_offer_coordinate(option_offer_coordinate(maker, taproot_pubkey_gen));
 
                         let published_id = publishing_client.publish_action_completed(&action_event).await?;
                         println!("Published cancellation to NOSTR: {published_id}");
 
+                        match publishing_client
+                            .delete_option_offer(event_id, &taproot_pubkey_gen.to_string(), "option offer cancelled")
+                            .await
+                        {
+                            Ok(deletion_id) => println!("Requested deletion of offer event: {deletion_id}"),
+                            Err(e) => tracing::warn!("Failed to request deletion of offer event {event_id}: {e}"),
+                        }
+
                         publishing_client.disconnect().await;
                     }
 
@@ -985,11 +1152,106 @@ impl Cli {
 
                 Ok(())
             }
+            OptionOfferCommand::Republish { offer_event } => {
+                println!("Checking your published option offers for fills...");
+
+                let publishing_client = self.get_publishing_client(&config).await?;
+                let maker = publishing_client.public_key().await?;
+
+                if let Err(e) = refresh_event_cache(wallet.store(), publishing_client.as_reader()).await {
+                    tracing::debug!("Could not refresh NOSTR event cache: {e}");
+                }
+                let actions = load_cached_actions(wallet.store()).await?;
+                let offers: Vec<OptionOfferCreatedEvent> = load_cached_option_offers(wallet.store(), config.network())
+                    .await?
+                    .into_iter()
+                    .filter(|offer| offer.pubkey == maker)
+                    .filter(|offer| {
+                        offer_event
+                            .as_ref()
+                            .is_none_or(|prefix| offer.event_id.to_hex().starts_with(prefix.as_str()))
+                    })
+                    .collect();
+
+                if let Some(event_id_str) = offer_event
+                    && offers.is_empty()
+                {
+                    return Err(Error::Config(format!(
+                        "Option offer {event_id_str} not found among your published offers"
+                    )));
+                }
+
+                let esplora = config.esplora();
+                let validator = ListingValidator::new(esplora.clone());
+                let (mut republished, mut deleted) = (0, 0);
+
+                for offer in &offers {
+                    let depth = validator.offer_depth(offer, &actions)?;
+                    if depth.cancelled {
+                        continue;
+                    }
+
+                    if depth.fill_count > 0 && depth.remaining == Some(0) {
+                        publishing_client
+                            .delete_option_offer(
+                                offer.event_id,
+                                &offer.taproot_pubkey_gen.to_string(),
+                                "option offer fully taken",
+                            )
+                            .await?;
+                        println!("  {}: fully taken, deleted", offer.event_id);
+                        deleted += 1;
+                        continue;
+                    }
+
+                    // Nothing to update until a confirmed fill moved the offer to a new output.
+                    let Some(outpoint) = depth.latest_outpoint else {
+                        continue;
+                    };
+
+                    // Same taproot pubkey gen, so relays replace the previous version.
+                    let mut update = OptionOfferCreatedEvent::new(
+                        offer.option_offer_args.clone(),
+                        outpoint,
+                        offer.taproot_pubkey_gen.clone(),
+                    );
+                    if let Some(remaining) = depth.remaining {
+                        update = update.with_collateral_amount(remaining);
+                    }
+
+                    update.event_id = publishing_client.publish_option_offer_created(&update).await?;
+                    update.pubkey = maker;
+                    crate::sync::track_republished_offer(wallet.store(), &esplora, &update).await?;
+
+                    let collateral_asset = offer.option_offer_args.get_collateral_asset_id();
+                    let remaining = depth.remaining.map_or_else(
+                        || "unknown".to_string(),
+                        |remaining| assets.format_amount(remaining, &collateral_asset),
+                    );
+                    println!(
+                        "  {}: {remaining} remaining after {} fill(s), republished as {}",
+                        offer.event_id, depth.fill_count, update.event_id
+                    );
+                    republished += 1;
+                }
+
+                publishing_client.disconnect().await;
+
+                println!(
+                    "Checked {} offer(s): {republished} republished, {deleted} deleted.",
+                    offers.len()
+                );
+
+                Ok(())
+            }
             OptionOfferCommand::Withdraw {
                 offer_event,
                 fee,
                 broadcast,
+                coin_control,
             } => {
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
+
                 println!("Withdrawing settlement from option offer (claiming payment after offer was taken)...");
 
                 let offer_contracts =
@@ -1062,7 +1324,8 @@ impl Cli {
                     ));
                 }
 
-                let withdrawable_offer_displays = build_withdrawable_option_offers_displays(&withdrawable_offers);
+                let withdrawable_offer_displays =
+                    build_withdrawable_option_offers_displays(&assets, &withdrawable_offers);
                 display_withdrawable_option_offers_table(&withdrawable_offer_displays);
                 println!();
 
@@ -1100,10 +1363,12 @@ impl Cli {
                 let initial_fee = fee.unwrap_or(PLACEHOLDER_FEE);
 
                 let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
-                let fee_filter = UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(script_pubkey.clone())
-                    .required_value(initial_fee);
+                let fee_filter = coin_control.filter(
+                    UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(script_pubkey.clone())
+                        .required_value(initial_fee),
+                );
 
                 let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[fee_filter]).await?;
                 let fee_entries = extract_entries_from_result(&results[0]);
@@ -1152,6 +1417,7 @@ impl Cli {
                     )?;
                     let mut tx = pst.extract_tx()?;
                     let utxos = vec![offer_txout.clone(), fee_input.1.clone()];
+                    tx = coin_control.redirect_change(tx, &utxos);
                     let offer_program = get_option_offer_program(args)?;
                     let signature = wallet.signer().sign_contract(
                         &tx,
@@ -1196,6 +1462,7 @@ impl Cli {
 
                 let mut tx = pst.extract_tx()?;
                 let utxos = vec![offer_txout.clone(), fee_input.1.clone()];
+                tx = coin_control.redirect_change(tx, &utxos);
                 let offer_program = get_option_offer_program(args)?;
 
                 let signature = wallet.signer().sign_contract(
@@ -1225,7 +1492,7 @@ impl Cli {
                 let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 1)?;
 
                 if *broadcast {
-                    cli_helper::explorer::broadcast_tx(&tx).await?;
+                    config.esplora().broadcast_transaction(&tx)?;
                     println!("Broadcasted: {}", tx.txid());
 
                     if let Some(ref nostr_event_id) = selected.metadata.nostr_event_id
@@ -1233,11 +1500,13 @@ impl Cli {
                     {
                         let publishing_client = self.get_publishing_client(&config).await?;
 
+                        let maker = publishing_client.public_key().await?;
                         let action_event = ActionCompletedEvent::new(
                             event_id,
                             ActionType::SettlementClaimed,
                             simplicityhl::elements::OutPoint::new(tx.txid(), 0),
-                        );
+                        )
+                        .with_offer_coordinate(option_offer_coordinate(maker, taproot_pubkey_gen));
 
                         let published_id = publishing_client.publish_action_completed(&action_event).await?;
                         println!("Published withdrawal to NOSTR: {published_id}");
@@ -1260,26 +1529,35 @@ impl Cli {
     }
 }
 
-fn build_active_option_offers_displays(active_offers: &[LocalOptionOfferData]) -> Vec<ActiveOptionOfferDisplay> {
+fn build_active_option_offers_displays(
+    assets: &AssetRegistry,
+    active_offers: &[LocalOptionOfferData],
+    reputation: &ReputationBook,
+) -> Vec<ActiveOptionOfferDisplay> {
     active_offers
         .iter()
         .enumerate()
         .map(|(idx, offer)| {
-            let seller = offer.metadata.nostr_author.as_deref().unwrap_or("unknown");
+            let seller = offer
+                .metadata
+                .nostr_author
+                .as_deref()
+                .map_or_else(|| "unknown".to_string(), |author| reputation.seller(author));
             let price = offer.option_offer_args.collateral_per_contract();
             ActiveOptionOfferDisplay {
                 index: idx + 1,
-                offering: offer.current_value.to_string(),
+                offering: offer.live_remaining().to_string(),
                 price: price.to_string(),
-                wants: format_settlement_asset(&offer.option_offer_args.get_settlement_asset_id()),
+                wants: assets.label(&offer.option_offer_args.get_settlement_asset_id()),
                 expires: format_relative_time(i64::from(offer.option_offer_args.expiry_time())),
-                seller: truncate_with_ellipsis(seller, 12),
+                seller,
             }
         })
         .collect()
 }
 
 fn build_cancellable_option_offers_displays(
+    assets: &AssetRegistry,
     cancellable_offers: &[LocalCancellableOptionOffer],
 ) -> Vec<CancellableOptionOfferDisplay> {
     cancellable_offers
@@ -1295,7 +1573,7 @@ fn build_cancellable_option_offers_displays(
                 format!(
                     "{} {}",
                     cs.premium_amount,
-                    format_settlement_asset(&cs.option_offer_args.get_premium_asset_id())
+                    assets.label(&cs.option_offer_args.get_premium_asset_id())
                 )
             } else {
                 "0".to_string()
@@ -1304,7 +1582,7 @@ fn build_cancellable_option_offers_displays(
                 index: idx + 1,
                 collateral: cs.collateral_amount.to_string(),
                 premium: premium_display,
-                asset: format_settlement_asset(&cs.option_offer_args.get_collateral_asset_id()),
+                asset: assets.label(&cs.option_offer_args.get_collateral_asset_id()),
                 expired: format!("expired ({expiry_time})"),
                 contract: contract_short,
             }
@@ -1313,6 +1591,7 @@ fn build_cancellable_option_offers_displays(
 }
 
 fn build_withdrawable_option_offers_displays(
+    assets: &AssetRegistry,
     withdrawable_offers: &[LocalWithdrawableOptionOffer],
 ) -> Vec<WithdrawableOptionOfferDisplay> {
     withdrawable_offers
@@ -1326,7 +1605,7 @@ fn build_withdrawable_option_offers_displays(
             WithdrawableOptionOfferDisplay {
                 index: idx + 1,
                 settlement: ws.settlement_amount.to_string(),
-                asset: format_settlement_asset(&ws.option_offer_args.get_settlement_asset_id()),
+                asset: assets.label(&ws.option_offer_args.get_settlement_asset_id()),
                 contract: contract_short,
             }
         })
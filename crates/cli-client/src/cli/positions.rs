@@ -1,79 +1,110 @@
-use crate::cli::Cli;
+use crate::assets::AssetRegistry;
+use crate::cli::{Cli, PricingArgs};
 use crate::cli::interactive::{
     EnrichedTokenEntry, GRANTOR_TOKEN_TAG, OPTION_TOKEN_TAG, TokenDisplay, format_asset_value_with_tag,
-    format_asset_with_tag, format_relative_time, format_settlement_asset, format_time_ago,
-    get_grantor_tokens_from_wallet, get_option_tokens_from_wallet, truncate_with_ellipsis,
+    format_asset_with_tag, format_relative_time, format_time_ago, get_grantor_tokens_from_wallet,
+    get_option_tokens_from_wallet, truncate_with_ellipsis,
+};
+use crate::cli::tables::{
+    display_collateral_table, display_mark_to_market_table, display_token_table, display_user_token_table,
 };
-use crate::cli::tables::{display_collateral_table, display_token_table, display_user_token_table};
 use crate::config::Config;
 use crate::error::Error;
 use crate::metadata::ContractMetadata;
 
-use crate::price_fetcher::{CoingeckoPriceFetcher, PriceFetcherError, fetch_btc_usd_price};
-use coin_store::{Store, UtxoEntry, UtxoFilter, UtxoQueryResult, UtxoStore};
+use crate::price_fetcher::AssetPair;
+use crate::price_oracle::{PriceQuote, spot_prices};
+use crate::pricing::{PricingParams, black_scholes_call, contract_size, option_strike, years_to_expiry};
+use coin_store::{LabelRef, LabelStore, Store, UtxoEntry, UtxoFilter, UtxoQueryResult, UtxoStore};
 use contracts::option_offer::{OPTION_OFFER_SOURCE, OptionOfferArguments, get_option_offer_address};
 use contracts::options::{OPTION_SOURCE, OptionsArguments, get_options_address};
 use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
+use nostr::Timestamp;
 use simplicityhl::elements::Address;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 /// Result type for contract info queries: (metadata, arguments, `taproot_pubkey_gen`)
 type ContractInfoResult = Result<Option<(Vec<u8>, Vec<u8>, String)>, coin_store::StoreError>;
 
 impl Cli {
     #[allow(clippy::too_many_lines)]
-    pub(crate) async fn run_positions(&self, config: Config) -> Result<(), Error> {
+    pub(crate) async fn run_positions(&self, config: Config, pricing: PricingArgs) -> Result<(), Error> {
         let wallet = self.get_wallet(&config).await?;
+        let assets = AssetRegistry::load(&config.database_path()).await;
+        let labels: HashMap<LabelRef, String> = wallet.store().list_labels().await?.into_iter().collect();
 
         println!("Your Positions:");
         println!("===============");
         println!();
 
-        let fetcher = CoingeckoPriceFetcher;
-        let btc_result = tokio::task::spawn_blocking(move || fetch_btc_usd_price(&fetcher))
-            .await
-            .unwrap_or_else(|e| Err(PriceFetcherError::Internal(e.to_string())));
-
-        let btc_price = match btc_result {
-            Ok(price) => format!("${price:.2}"),
-            Err(PriceFetcherError::RateLimit) => "Rate limit exceeded".to_string(),
-            Err(e) => {
-                eprintln!("Fetcher error: {e}");
-                "Price fetcher service unavailable".to_string()
-            }
-        };
-
-        println!("Current btc price: {btc_price}");
-        println!("-----------------------------");
-        println!();
-
         let user_script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
 
-        let options_filter = UtxoFilter::new().source(OPTION_SOURCE);
+        let options_filter = UtxoFilter::new().source(OPTION_SOURCE).include_frozen();
         let options_results = <_ as UtxoStore>::query_utxos(wallet.store(), &[options_filter]).await?;
         let option_entries = extract_entries(options_results);
 
-        let collateral_displays = build_collateral_displays(&wallet, &option_entries, config.network()).await;
+        let collateral_displays =
+            build_collateral_displays(&wallet, &assets, &labels, &option_entries, config.network()).await;
 
         println!("Option Contract Locked Assets:");
         println!("------------------------------");
         display_collateral_table(&collateral_displays);
         println!();
 
-        let option_tokens = get_option_tokens_from_wallet(&wallet, OPTION_SOURCE, &user_script_pubkey).await?;
-        let grantor_tokens = get_grantor_tokens_from_wallet(&wallet, OPTION_SOURCE, &user_script_pubkey).await?;
+        let option_tokens = get_option_tokens_from_wallet(&wallet, OPTION_SOURCE, &user_script_pubkey, true).await?;
+        let grantor_tokens = get_grantor_tokens_from_wallet(&wallet, OPTION_SOURCE, &user_script_pubkey, true).await?;
 
-        let user_token_displays = build_user_token_displays(&option_tokens, &grantor_tokens, config.network());
+        let user_token_displays = build_user_token_displays(&assets, &option_tokens, &grantor_tokens, config.network());
 
         println!("Your Option/Grantor Tokens:");
         println!("---------------------------");
         display_user_token_table(&user_token_displays);
         println!();
 
-        let option_offer_filter = UtxoFilter::new().source(OPTION_OFFER_SOURCE);
+        let pairs: BTreeSet<AssetPair> = option_tokens
+            .iter()
+            .chain(&grantor_tokens)
+            .map(|entry| {
+                let args = &entry.option_arguments;
+                AssetPair::new(args.get_collateral_asset_id(), args.get_settlement_asset_id())
+            })
+            .collect();
+        let spots = spot_prices(&config, &assets, pricing.spot, pairs.clone()).await;
+
+        println!("Spot Prices:");
+        println!("------------");
+        if pairs.is_empty() {
+            println!("  (No positions to price)");
+        }
+        for pair in &pairs {
+            let label = pair.label(&assets);
+            match spots.get(pair) {
+                Some(quote) => println!("  {label}: {quote}"),
+                None => println!("  {label}: unavailable (use --spot or [oracle] overrides)"),
+            }
+        }
+        println!();
+
+        let params = config.pricing.params(pricing.volatility)?;
+        let mark_to_market_displays =
+            build_mark_to_market_displays(&assets, &option_tokens, &grantor_tokens, &spots, params);
+
+        println!(
+            "Mark-to-Market (Black-Scholes, volatility {:.0}%, rate {:.1}%):",
+            params.volatility * 100.0,
+            params.risk_free_rate * 100.0
+        );
+        println!("-----------------------------------------------------------");
+        display_mark_to_market_table(&mark_to_market_displays);
+        println!("  (Values in the settlement asset; grantor tokens are valued as collateral that sold the call)");
+        println!();
+
+        let option_offer_filter = UtxoFilter::new().source(OPTION_OFFER_SOURCE).include_frozen();
         let option_offer_results = <_ as UtxoStore>::query_utxos(wallet.store(), &[option_offer_filter]).await?;
         let option_offer_entries = extract_entries(option_offer_results);
 
-        let option_offer_displays = build_option_offer_displays_with_args(&wallet, &option_offer_entries).await;
+        let option_offer_displays =
+            build_option_offer_displays_with_args(&wallet, &assets, &option_offer_entries).await;
 
         println!("Pending Option Offers:");
         println!("----------------------");
@@ -88,7 +119,7 @@ impl Cli {
         let option_offer_contracts =
             <_ as UtxoStore>::list_contracts_by_source_with_metadata(wallet.store(), OPTION_OFFER_SOURCE).await?;
 
-        let mut contracts_with_history: Vec<(&str, Address, ContractMetadata, i64)> = Vec::new();
+        let mut contracts_with_history: Vec<(&str, Address, ContractMetadata, i64, Option<String>)> = Vec::new();
 
         for (args_bytes, tpg_str, metadata_bytes) in &option_contracts {
             if let Some(bytes) = metadata_bytes
@@ -110,7 +141,8 @@ impl Cli {
                     continue;
                 };
                 let most_recent = metadata.history.iter().map(|h| h.timestamp).max().unwrap_or(0);
-                contracts_with_history.push(("Option", tpg.address, metadata, most_recent));
+                let label = labels.get(&LabelRef::Contract(tpg_str.clone())).cloned();
+                contracts_with_history.push(("Option", tpg.address, metadata, most_recent, label));
             }
         }
 
@@ -137,15 +169,17 @@ impl Cli {
                     continue;
                 };
                 let most_recent = metadata.history.iter().map(|h| h.timestamp).max().unwrap_or(0);
-                contracts_with_history.push(("OptionOffer", tpg.address, metadata, most_recent));
+                let label = labels.get(&LabelRef::Contract(tpg_str.clone())).cloned();
+                contracts_with_history.push(("OptionOffer", tpg.address, metadata, most_recent, label));
             }
         }
 
         contracts_with_history.sort_by(|a, b| b.3.cmp(&a.3));
 
-        for (contract_type, address, metadata, _) in &contracts_with_history {
+        for (contract_type, address, metadata, _, label) in &contracts_with_history {
             let short_addr = format_contract_address(address);
-            println!("\n  {contract_type} Contract {short_addr}:");
+            let label = label.as_deref().map_or_else(String::new, |label| format!(" [{label}]"));
+            println!("\n  {contract_type} Contract {short_addr}{label}:");
             for entry in &metadata.history {
                 let time_str = format_time_ago(entry.timestamp);
                 let txid_str = entry.txid.as_deref().map_or("N/A", |t| &t[..t.len().min(12)]);
@@ -188,9 +222,26 @@ pub struct UserTokenDisplay {
     pub contract: String,
 }
 
+/// Display struct for the fair value and Greeks of held option/grantor tokens
+#[derive(Debug, Clone)]
+pub struct MarkToMarketDisplay {
+    pub index: usize,
+    pub token_type: String,
+    pub amount: String,
+    pub spot: String,
+    pub fair_per_token: String,
+    pub value: String,
+    pub delta: String,
+    pub gamma: String,
+    pub theta: String,
+    pub vega: String,
+}
+
 /// Build locked asset displays, filtering to only show collateral or settlement assets (not reissuance tokens)
 async fn build_collateral_displays(
     wallet: &crate::wallet::Wallet,
+    assets: &AssetRegistry,
+    labels: &HashMap<LabelRef, String>,
     entries: &[UtxoEntry],
     network: simplicityhl_core::SimplicityNetwork,
 ) -> Vec<CollateralDisplay> {
@@ -202,7 +253,8 @@ async fn build_collateral_displays(
         let contract_info = <_ as UtxoStore>::get_contract_by_script_pubkey(wallet.store(), &script_pubkey).await;
 
         // Try to get option arguments to check if this is collateral
-        let Some(info) = extract_collateral_info(wallet.store(), contract_info, entry, network).await else {
+        let Some(info) = extract_collateral_info(wallet.store(), assets, labels, contract_info, entry, network).await
+        else {
             continue;
         };
 
@@ -222,6 +274,8 @@ async fn build_collateral_displays(
 /// Extract contract asset info, returning None if this UTXO is not a collateral or settlement asset (e.g., reissuance token)
 async fn extract_collateral_info(
     store: &Store,
+    assets: &AssetRegistry,
+    labels: &HashMap<LabelRef, String>,
     contract_info: ContractInfoResult,
     entry: &UtxoEntry,
     network: simplicityhl_core::SimplicityNetwork,
@@ -243,16 +297,20 @@ async fn extract_collateral_info(
 
     let tpg = TaprootPubkeyGen::build_from_str(&tpg_str, &opt_args, network, &get_options_address).ok()?;
 
-    let locked_str = format_asset_value_with_tag(store, entry.value(), entry.asset()).await;
-    let settlement_str = format_asset_with_tag(store, &opt_args.get_settlement_asset_id()).await;
+    let locked_str = format_asset_value_with_tag(store, assets, entry.value(), entry.asset()).await;
+    let settlement_str = format_asset_with_tag(store, assets, &opt_args.get_settlement_asset_id()).await;
     let expiry_str = format_relative_time(i64::from(opt_args.expiry_time()));
-    let contract_str = format_contract_address(&tpg.address);
+    let contract_str = match labels.get(&LabelRef::Contract(tpg_str)) {
+        Some(label) => format!("{} [{label}]", format_contract_address(&tpg.address)),
+        None => format_contract_address(&tpg.address),
+    };
 
     Some((locked_str, settlement_str, expiry_str, contract_str))
 }
 
 /// Build user token displays from option and grantor tokens
 fn build_user_token_displays(
+    assets: &AssetRegistry,
     option_tokens: &[EnrichedTokenEntry],
     grantor_tokens: &[EnrichedTokenEntry],
     network: simplicityhl_core::SimplicityNetwork,
@@ -279,11 +337,7 @@ fn build_user_token_displays(
             index: idx,
             token_type: OPTION_TOKEN_TAG.to_string(),
             amount: entry.entry.value().unwrap_or(0).to_string(),
-            strike: format!(
-                "{} {}",
-                settlement_per_contract,
-                format_settlement_asset(&settlement_asset)
-            ),
+            strike: format!("{} {}", settlement_per_contract, assets.label(&settlement_asset)),
             expires: format_relative_time(i64::from(expiry_time)),
             contract: contract_addr,
         });
@@ -308,11 +362,7 @@ fn build_user_token_displays(
             index: idx,
             token_type: GRANTOR_TOKEN_TAG.to_string(),
             amount: entry.entry.value().unwrap_or(0).to_string(),
-            strike: format!(
-                "{} {}",
-                settlement_per_contract,
-                format_settlement_asset(&settlement_asset)
-            ),
+            strike: format!("{} {}", settlement_per_contract, assets.label(&settlement_asset)),
             expires: format_relative_time(i64::from(expiry_time)),
             contract: contract_addr,
         });
@@ -321,8 +371,70 @@ fn build_user_token_displays(
     displays
 }
 
+/// Value option and grantor tokens at their Black-Scholes fair value. Greeks are for the whole
+/// holding; tokens whose collateral has no known spot price are listed without a value.
+#[allow(clippy::cast_precision_loss)]
+fn build_mark_to_market_displays(
+    assets: &AssetRegistry,
+    option_tokens: &[EnrichedTokenEntry],
+    grantor_tokens: &[EnrichedTokenEntry],
+    spots: &BTreeMap<AssetPair, PriceQuote>,
+    params: PricingParams,
+) -> Vec<MarkToMarketDisplay> {
+    let now = Timestamp::now().as_u64();
+
+    let tokens = option_tokens
+        .iter()
+        .map(|entry| (OPTION_TOKEN_TAG, entry))
+        .chain(grantor_tokens.iter().map(|entry| (GRANTOR_TOKEN_TAG, entry)));
+
+    tokens
+        .enumerate()
+        .map(|(idx, (token_type, entry))| {
+            let args = &entry.option_arguments;
+            let amount = entry.entry.value().unwrap_or(0);
+            let settlement = assets.label(&args.get_settlement_asset_id());
+            let spot = spots
+                .get(&AssetPair::new(args.get_collateral_asset_id(), args.get_settlement_asset_id()))
+                .map(|quote| quote.price);
+
+            // One token is one contract.
+            let per_token = spot.map(|spot| {
+                let years = years_to_expiry(args.expiry_time(), now);
+                let call = black_scholes_call(spot, option_strike(assets, args), years, params);
+                let per_collateral = if token_type == GRANTOR_TOKEN_TAG { call.covered(spot) } else { call };
+                per_collateral.scale(contract_size(assets, args))
+            });
+            let holding = per_token.map(|valuation| valuation.scale(amount as f64));
+            let show = |value: Option<f64>, decimals: usize| {
+                value.map_or_else(|| "-".to_string(), |value| format!("{value:.decimals$}"))
+            };
+
+            MarkToMarketDisplay {
+                index: idx + 1,
+                token_type: token_type.to_string(),
+                amount: amount.to_string(),
+                spot: show(spot, 2),
+                fair_per_token: per_token.map_or_else(
+                    || "-".to_string(),
+                    |valuation| format!("{:.4} {settlement}", valuation.fair_value),
+                ),
+                value: holding.map_or_else(
+                    || "unknown spot (use --spot)".to_string(),
+                    |valuation| format!("{:.2} {settlement}", valuation.fair_value),
+                ),
+                delta: show(holding.map(|valuation| valuation.delta), 4),
+                gamma: show(holding.map(|valuation| valuation.gamma), 6),
+                theta: show(holding.map(|valuation| valuation.theta), 4),
+                vega: show(holding.map(|valuation| valuation.vega), 4),
+            }
+        })
+        .collect()
+}
+
 async fn build_option_offer_displays_with_args(
     wallet: &crate::wallet::Wallet,
+    assets: &AssetRegistry,
     entries: &[UtxoEntry],
 ) -> Vec<TokenDisplay> {
     let mut displays = Vec::new();
@@ -333,7 +445,7 @@ async fn build_option_offer_displays_with_args(
         let contract_info = <_ as UtxoStore>::get_contract_by_script_pubkey(wallet.store(), &script_pubkey).await;
 
         let Some((settlement, expires, is_collateral, price)) =
-            extract_option_offer_display_info_with_tags(wallet.store(), contract_info, entry).await
+            extract_option_offer_display_info_with_tags(wallet.store(), assets, contract_info, entry).await
         else {
             continue;
         };
@@ -342,7 +454,7 @@ async fn build_option_offer_displays_with_args(
             continue; // Skip settlement outputs
         }
 
-        let collateral = format_asset_value_with_tag(wallet.store(), entry.value(), entry.asset()).await;
+        let collateral = format_asset_value_with_tag(wallet.store(), assets, entry.value(), entry.asset()).await;
 
         display_idx += 1;
         displays.push(TokenDisplay {
@@ -360,6 +472,7 @@ async fn build_option_offer_displays_with_args(
 /// Returns (`settlement_display`, `expiry_display`, `is_collateral_asset`, price)
 async fn extract_option_offer_display_info_with_tags(
     store: &Store,
+    assets: &AssetRegistry,
     contract_info: ContractInfoResult,
     entry: &UtxoEntry,
 ) -> Option<(String, String, bool, u64)> {
@@ -371,7 +484,7 @@ async fn extract_option_offer_display_info_with_tags(
 
     let option_offer_args = OptionOfferArguments::from_arguments(&args).ok()?;
 
-    let settlement_str = format_asset_with_tag(store, &option_offer_args.get_settlement_asset_id()).await;
+    let settlement_str = format_asset_with_tag(store, assets, &option_offer_args.get_settlement_asset_id()).await;
     let expiry_str = format_relative_time(i64::from(option_offer_args.expiry_time()));
     let price = option_offer_args.collateral_per_contract();
 
@@ -0,0 +1,94 @@
+use crate::cli::tables::display_relay_status_table;
+use crate::cli::{Cli, RelayCommand};
+use crate::config::Config;
+use crate::error::Error;
+
+use std::time::Duration;
+
+use options_relay::{RelayHealth, check_relays, fetch_relay_information};
+
+/// Display struct for relay health checks
+#[derive(Debug, Clone)]
+pub struct RelayStatusDisplay {
+    pub url: String,
+    pub role: String,
+    pub connect: String,
+    pub fetch: String,
+    pub publish: String,
+    pub events: String,
+    pub software: String,
+}
+
+impl Cli {
+    pub(crate) async fn run_relay(&self, config: Config, command: &RelayCommand) -> Result<(), Error> {
+        match command {
+            RelayCommand::Status { skip_publish } => {
+                let relay_config = config.relay.get_nostr_relay_config();
+                let keys = self.get_nostr_keys()?;
+
+                println!("Checking {} relay(s)...", relay_config.all_relays().len());
+                println!();
+
+                let results = check_relays(&relay_config, &keys, !*skip_publish).await;
+
+                let mut displays = Vec::with_capacity(results.len());
+                for health in &results {
+                    let url = health.url.clone();
+                    let timeout = relay_config.timeout();
+                    let software = tokio::task::spawn_blocking(move || fetch_relay_information(&url, timeout))
+                        .await
+                        .ok()
+                        .and_then(Result::ok)
+                        .map_or_else(
+                            || "no NIP-11".to_string(),
+                            |doc| match (doc.software, doc.version) {
+                                (Some(software), Some(version)) => format!("{software} {version}"),
+                                (Some(software), None) => software,
+                                (None, _) => doc.name.unwrap_or_else(|| "unknown".to_string()),
+                            },
+                        );
+
+                    displays.push(build_relay_status_display(health, software));
+                }
+
+                display_relay_status_table(&displays);
+
+                for health in results.iter().filter(|h| h.error.is_some()) {
+                    println!("  {}: {}", health.url, health.error.as_deref().unwrap_or_default());
+                }
+
+                let reachable = results.iter().filter(|h| h.is_reachable()).count();
+                println!();
+                println!(
+                    "{reachable}/{} relay(s) reachable, {} ack(s) required for publishing",
+                    results.len(),
+                    relay_config.min_acks()
+                );
+
+                Ok(())
+            }
+        }
+    }
+}
+
+fn build_relay_status_display(health: &RelayHealth, software: String) -> RelayStatusDisplay {
+    RelayStatusDisplay {
+        url: health.url.clone(),
+        role: health.role.as_str().to_string(),
+        connect: format_latency(health.connect_latency),
+        fetch: format_latency(health.fetch_latency),
+        publish: match health.publish_ack {
+            Some(true) => "ok".to_string(),
+            Some(false) => "rejected".to_string(),
+            None => "-".to_string(),
+        },
+        events: health
+            .stored_events
+            .map_or_else(|| "-".to_string(), |count| count.to_string()),
+        software,
+    }
+}
+
+fn format_latency(latency: Option<Duration>) -> String {
+    latency.map_or_else(|| "failed".to_string(), |d| format!("{} ms", d.as_millis()))
+}
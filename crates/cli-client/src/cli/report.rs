@@ -0,0 +1,552 @@
+use crate::assets::AssetRegistry;
+use crate::cli::interactive::{
+    EnrichedTokenEntry, GRANTOR_TOKEN_TAG, OPTION_TOKEN_TAG, current_timestamp, extract_entries_from_results,
+    format_utc, get_grantor_tokens_from_wallet, get_option_tokens_from_wallet, parse_time_bound,
+    truncate_with_ellipsis,
+};
+use crate::cli::tables::{display_pnl_asset_table, display_pnl_contract_table};
+use crate::cli::{Cli, PricingArgs, ReportCommand};
+use crate::config::Config;
+use crate::error::Error;
+use crate::ledger::wallet_flows;
+use crate::metadata::{ContractMetadata, HistoryEntry};
+use crate::price_fetcher::AssetPair;
+use crate::price_oracle::spot_prices;
+use crate::pricing::{PricingParams, black_scholes_call, contract_size, option_strike, years_to_expiry};
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ops::AddAssign;
+
+use coin_store::{UtxoFilter, UtxoStore};
+use contracts::option_offer::{OPTION_OFFER_SOURCE, OptionOfferArguments, get_option_offer_address};
+use contracts::options::{OPTION_SOURCE, OptionsArguments, get_options_address};
+use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
+use simplicityhl::elements::{AssetId, Txid};
+
+/// Display struct for the P&L of one asset of one contract
+#[derive(Debug, Clone)]
+pub struct ContractPnlDisplay {
+    pub index: usize,
+    pub contract_type: String,
+    pub contract: String,
+    pub status: String,
+    pub asset: String,
+    pub premium: String,
+    pub collateral: String,
+    pub settlement: String,
+    pub fees: String,
+    pub realized: String,
+    pub unrealized: String,
+}
+
+/// Display struct for the P&L of one asset over every contract
+#[derive(Debug, Clone)]
+pub struct AssetPnlDisplay {
+    pub asset: String,
+    pub premium: String,
+    pub collateral: String,
+    pub settlement: String,
+    pub fees: String,
+    pub realized: String,
+    pub unrealized: String,
+}
+
+/// Our flows in one asset, in base units; negative when we paid.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct AssetFlows {
+    premium: i128,
+    collateral: i128,
+    settlement: i128,
+    fees: i128,
+}
+
+impl AssetFlows {
+    const fn net(self) -> i128 {
+        self.premium + self.collateral + self.settlement + self.fees
+    }
+}
+
+impl AddAssign for AssetFlows {
+    fn add_assign(&mut self, other: Self) {
+        self.premium += other.premium;
+        self.collateral += other.collateral;
+        self.settlement += other.settlement;
+        self.fees += other.fees;
+    }
+}
+
+/// What each asset moved by a contract's transactions stands for.
+#[derive(Debug, Clone)]
+struct ContractRoles {
+    collateral: AssetId,
+    settlement: AssetId,
+    /// Premium asset and premium per unit of collateral, for option offers.
+    premium: Option<(AssetId, u64)>,
+    /// The contract's own tokens, valued as open positions rather than as flows.
+    tokens: Vec<AssetId>,
+}
+
+impl ContractRoles {
+    fn for_option(args: &OptionsArguments) -> Self {
+        let (option_token, option_reissuance) = args.get_option_token_ids();
+        let (grantor_token, grantor_reissuance) = args.get_grantor_token_ids();
+
+        Self {
+            collateral: args.get_collateral_asset_id(),
+            settlement: args.get_settlement_asset_id(),
+            premium: None,
+            tokens: vec![option_token, option_reissuance, grantor_token, grantor_reissuance],
+        }
+    }
+
+    fn for_option_offer(args: &OptionOfferArguments) -> Self {
+        Self {
+            collateral: args.get_collateral_asset_id(),
+            settlement: args.get_settlement_asset_id(),
+            premium: Some((args.get_premium_asset_id(), args.premium_per_collateral())),
+            tokens: Vec::new(),
+        }
+    }
+
+    /// Split a flow of `amount` of `asset` by what it paid for. When the premium is paid in the
+    /// collateral asset, both move together and are told apart by the premium rate.
+    fn attribute(&self, asset: AssetId, amount: i128) -> AssetFlows {
+        if self.tokens.contains(&asset) {
+            return AssetFlows::default();
+        }
+
+        match self.premium {
+            Some((premium_asset, per_collateral)) if premium_asset == asset && asset == self.collateral => {
+                let premium = amount * i128::from(per_collateral) / (1 + i128::from(per_collateral));
+                AssetFlows {
+                    premium,
+                    collateral: amount - premium,
+                    ..AssetFlows::default()
+                }
+            }
+            Some((premium_asset, _)) if premium_asset == asset => AssetFlows {
+                premium: amount,
+                ..AssetFlows::default()
+            },
+            _ if asset == self.collateral => AssetFlows {
+                collateral: amount,
+                ..AssetFlows::default()
+            },
+            _ if asset == self.settlement => AssetFlows {
+                settlement: amount,
+                ..AssetFlows::default()
+            },
+            _ => AssetFlows::default(),
+        }
+    }
+
+    /// Attribute our net flows in one transaction. The fee is reported on its own, so it is
+    /// taken out of the flow of the asset it was paid in first.
+    fn attribute_transaction(
+        &self,
+        net: &BTreeMap<AssetId, i128>,
+        fee: Option<(AssetId, u64)>,
+    ) -> BTreeMap<AssetId, AssetFlows> {
+        let mut net = net.clone();
+        if let Some((fee_asset, fee)) = fee {
+            *net.entry(fee_asset).or_default() += i128::from(fee);
+        }
+
+        let mut flows: BTreeMap<AssetId, AssetFlows> = net
+            .into_iter()
+            .map(|(asset, amount)| (asset, self.attribute(asset, amount)))
+            .filter(|(_, flows)| *flows != AssetFlows::default())
+            .collect();
+
+        if let Some((fee_asset, fee)) = fee {
+            flows.entry(fee_asset).or_default().fees -= i128::from(fee);
+        }
+
+        flows
+    }
+}
+
+/// A contract we may have traded, with what it currently holds for us.
+struct ReportContract {
+    contract_type: &'static str,
+    address: String,
+    roles: ContractRoles,
+    history: Vec<HistoryEntry>,
+    /// Value of what we can still claim, in base units of each asset.
+    open_claims: BTreeMap<AssetId, i128>,
+    is_open: bool,
+}
+
+impl Cli {
+    pub(crate) async fn run_report(&self, config: Config, command: &ReportCommand) -> Result<(), Error> {
+        match command {
+            ReportCommand::Pnl { from, to, pricing } => {
+                self.run_pnl_report(config, from.as_deref(), to.as_deref(), *pricing).await
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn run_pnl_report(
+        &self,
+        config: Config,
+        from: Option<&str>,
+        to: Option<&str>,
+        pricing: PricingArgs,
+    ) -> Result<(), Error> {
+        let from = from.map(parse_time_bound).transpose()?;
+        let to = to.map(parse_time_bound).transpose()?;
+        if let (Some(from), Some(to)) = (from, to)
+            && from > to
+        {
+            return Err(Error::Config(format!("--from ({from}) is after --to ({to})")));
+        }
+
+        let wallet = self.get_wallet(&config).await?;
+        let assets = AssetRegistry::load(&config.database_path()).await;
+        let store = wallet.store();
+        let network = config.network();
+        let user_script_pubkey = wallet.signer().p2pk_address(network)?.script_pubkey();
+        let user_pubkey = wallet.signer().public_key().serialize();
+
+        let option_tokens = get_option_tokens_from_wallet(&wallet, OPTION_SOURCE, &user_script_pubkey, true).await?;
+        let grantor_tokens = get_grantor_tokens_from_wallet(&wallet, OPTION_SOURCE, &user_script_pubkey, true).await?;
+
+        let pairs: BTreeSet<AssetPair> = option_tokens
+            .iter()
+            .chain(&grantor_tokens)
+            .map(|entry| {
+                let args = &entry.option_arguments;
+                AssetPair::new(args.get_collateral_asset_id(), args.get_settlement_asset_id())
+            })
+            .collect();
+        let spots = spot_prices(&config, &assets, pricing.spot, pairs).await;
+        let params = config.pricing.params(pricing.volatility)?;
+
+        // Open contracts are valued at today's prices, which says nothing about a past period,
+        // so a bounded report only covers contracts that are closed.
+        let bounded = from.is_some() || to.is_some();
+        let mut contracts = Vec::new();
+        let mut unpriced = 0;
+        let mut open_skipped = 0;
+
+        for (args_bytes, tpg_str, metadata_bytes) in
+            <_ as UtxoStore>::list_contracts_by_source_with_metadata(store, OPTION_SOURCE).await?
+        {
+            let Some(metadata) = metadata_bytes.and_then(|bytes| ContractMetadata::from_bytes(&bytes).ok()) else {
+                continue;
+            };
+            let Some(args) = decode_arguments(&args_bytes).and_then(|a| OptionsArguments::from_arguments(&a).ok())
+            else {
+                continue;
+            };
+            let Ok(tpg) = TaprootPubkeyGen::build_from_str(&tpg_str, &args, network, &get_options_address) else {
+                continue;
+            };
+
+            let held: Vec<(&str, &EnrichedTokenEntry)> = option_tokens
+                .iter()
+                .map(|entry| (OPTION_TOKEN_TAG, entry))
+                .chain(grantor_tokens.iter().map(|entry| (GRANTOR_TOKEN_TAG, entry)))
+                .filter(|(_, entry)| entry.taproot_pubkey_gen_str == tpg_str)
+                .collect();
+            if bounded && !held.is_empty() {
+                open_skipped += 1;
+                continue;
+            }
+
+            let spot = spots
+                .get(&AssetPair::new(args.get_collateral_asset_id(), args.get_settlement_asset_id()))
+                .map(|quote| quote.price);
+            if spot.is_none() && !held.is_empty() {
+                unpriced += 1;
+            }
+
+            let mut open_claims = BTreeMap::new();
+            if let Some(spot) = spot {
+                let value: i128 = held
+                    .iter()
+                    .map(|(tag, entry)| token_value(&assets, tag, entry, spot, params))
+                    .sum();
+                open_claims.insert(args.get_settlement_asset_id(), value);
+            }
+
+            contracts.push(ReportContract {
+                contract_type: "Option",
+                address: truncate_with_ellipsis(&tpg.address.to_string(), 12),
+                roles: ContractRoles::for_option(&args),
+                history: metadata.history,
+                open_claims,
+                is_open: !held.is_empty(),
+            });
+        }
+
+        for (args_bytes, tpg_str, metadata_bytes) in
+            <_ as UtxoStore>::list_contracts_by_source_with_metadata(store, OPTION_OFFER_SOURCE).await?
+        {
+            let Some(metadata) = metadata_bytes.and_then(|bytes| ContractMetadata::from_bytes(&bytes).ok()) else {
+                continue;
+            };
+            let Some(args) =
+                decode_arguments(&args_bytes).and_then(|a| OptionOfferArguments::from_arguments(&a).ok())
+            else {
+                continue;
+            };
+            let Ok(tpg) = TaprootPubkeyGen::build_from_str(&tpg_str, &args, network, &get_option_offer_address)
+            else {
+                continue;
+            };
+            let address = truncate_with_ellipsis(&tpg.address.to_string(), 12);
+
+            // Only the maker can withdraw what is still locked in an offer.
+            let mut open_claims: BTreeMap<AssetId, i128> = BTreeMap::new();
+            if args.user_pubkey() == user_pubkey {
+                let filter = UtxoFilter::new().taproot_pubkey_gen(tpg).include_frozen();
+                let results = <_ as UtxoStore>::query_utxos(store, &[filter]).await?;
+                for entry in extract_entries_from_results(results) {
+                    if let (Some(asset), Some(value)) = (entry.asset(), entry.value()) {
+                        *open_claims.entry(asset).or_default() += i128::from(value);
+                    }
+                }
+            }
+            if bounded && !open_claims.is_empty() {
+                open_skipped += 1;
+                continue;
+            }
+
+            contracts.push(ReportContract {
+                contract_type: "OptionOffer",
+                address,
+                roles: ContractRoles::for_option_offer(&args),
+                history: metadata.history,
+                is_open: !open_claims.is_empty(),
+                open_claims,
+            });
+        }
+
+        // A transaction can appear in the history of several contracts; it is counted once,
+        // against the contract whose action happened first.
+        let mut actions: Vec<(i64, usize, Txid)> = contracts
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, contract)| {
+                contract.history.iter().filter_map(move |entry| {
+                    let txid = entry.txid.as_deref()?.parse::<Txid>().ok()?;
+                    Some((entry.timestamp, idx, txid))
+                })
+            })
+            .filter(|(timestamp, _, _)| {
+                from.is_none_or(|from| *timestamp >= from) && to.is_none_or(|to| *timestamp <= to)
+            })
+            .collect();
+        actions.sort_unstable();
+
+        let mut seen = HashSet::new();
+        let mut flows: Vec<BTreeMap<AssetId, AssetFlows>> = vec![BTreeMap::new(); contracts.len()];
+        let mut unavailable = 0;
+
+        let esplora = config.esplora();
+        for (_, idx, txid) in actions {
+            if !seen.insert(txid) {
+                continue;
+            }
+
+            let tx = match esplora.fetch_transaction(txid) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    tracing::debug!("Could not fetch {txid}: {e}");
+                    unavailable += 1;
+                    continue;
+                }
+            };
+
+            let (net, fee) = wallet_flows(store, &tx, &user_script_pubkey).await?;
+            for (asset, asset_flows) in contracts[idx].roles.attribute_transaction(&net, fee) {
+                *flows[idx].entry(asset).or_default() += asset_flows;
+            }
+        }
+
+        let mut contract_displays = Vec::new();
+        let mut totals: BTreeMap<AssetId, (AssetFlows, i128, i128)> = BTreeMap::new();
+
+        for (contract, contract_flows) in contracts.iter().zip(&flows) {
+            let contract_assets: BTreeSet<AssetId> =
+                contract_flows.keys().chain(contract.open_claims.keys()).copied().collect();
+
+            for asset in contract_assets {
+                let asset_flows = contract_flows.get(&asset).copied().unwrap_or_default();
+                let claims = contract.open_claims.get(&asset).copied().unwrap_or_default();
+                if asset_flows == AssetFlows::default() && claims == 0 {
+                    continue;
+                }
+
+                let (realized, unrealized) = if contract.is_open {
+                    (0, asset_flows.net() + claims)
+                } else {
+                    (asset_flows.net(), 0)
+                };
+
+                let total = totals.entry(asset).or_default();
+                total.0 += asset_flows;
+                total.1 += realized;
+                total.2 += unrealized;
+
+                contract_displays.push(ContractPnlDisplay {
+                    index: contract_displays.len() + 1,
+                    contract_type: contract.contract_type.to_string(),
+                    contract: contract.address.clone(),
+                    status: if contract.is_open { "Open" } else { "Closed" }.to_string(),
+                    asset: assets.label(&asset),
+                    premium: format_signed(&assets, asset_flows.premium, &asset),
+                    collateral: format_signed(&assets, asset_flows.collateral, &asset),
+                    settlement: format_signed(&assets, asset_flows.settlement, &asset),
+                    fees: format_signed(&assets, asset_flows.fees, &asset),
+                    realized: if contract.is_open {
+                        "-".to_string()
+                    } else {
+                        format_signed(&assets, realized, &asset)
+                    },
+                    unrealized: if contract.is_open {
+                        format_signed(&assets, unrealized, &asset)
+                    } else {
+                        "-".to_string()
+                    },
+                });
+            }
+        }
+
+        let asset_displays: Vec<AssetPnlDisplay> = totals
+            .iter()
+            .map(|(asset, (asset_flows, realized, unrealized))| AssetPnlDisplay {
+                asset: assets.label(asset),
+                premium: format_signed(&assets, asset_flows.premium, asset),
+                collateral: format_signed(&assets, asset_flows.collateral, asset),
+                settlement: format_signed(&assets, asset_flows.settlement, asset),
+                fees: format_signed(&assets, asset_flows.fees, asset),
+                realized: format_signed(&assets, *realized, asset),
+                unrealized: format_signed(&assets, *unrealized, asset),
+            })
+            .collect();
+
+        println!("Profit and Loss:");
+        println!("================");
+        println!(
+            "  Period: {} to {}",
+            from.map_or_else(|| "start".to_string(), format_utc),
+            to.map_or_else(|| "now".to_string(), format_utc)
+        );
+        println!(
+            "  Open positions valued with Black-Scholes (volatility {:.0}%, rate {:.1}%)",
+            params.volatility * 100.0,
+            params.risk_free_rate * 100.0
+        );
+        println!();
+
+        println!("P&L by Contract:");
+        println!("----------------");
+        display_pnl_contract_table(&contract_displays);
+        println!();
+
+        println!("P&L by Asset:");
+        println!("-------------");
+        display_pnl_asset_table(&asset_displays);
+        println!("  (Closed contracts are realized; open ones add what we can still claim at today's value)");
+
+        if unpriced > 0 {
+            println!("  Warning: {unpriced} contract(s) have no spot price; their tokens are left out (use --spot)");
+        }
+        if unavailable > 0 {
+            println!("  Warning: {unavailable} transaction(s) could not be fetched and are left out");
+        }
+        if open_skipped > 0 {
+            println!("  Note: {open_skipped} open contract(s) left out; date bounds only cover closed contracts");
+        }
+
+        Ok(())
+    }
+}
+
+fn decode_arguments(bytes: &[u8]) -> Option<simplicityhl::Arguments> {
+    bincode::serde::decode_from_slice::<simplicityhl::Arguments, _>(bytes, bincode::config::standard())
+        .ok()
+        .map(|(args, _)| args)
+}
+
+/// Fair value of a held option or grantor token entry, in settlement base units.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn token_value(
+    assets: &AssetRegistry,
+    token_tag: &str,
+    entry: &EnrichedTokenEntry,
+    spot: f64,
+    params: PricingParams,
+) -> i128 {
+    let args = &entry.option_arguments;
+    let years = years_to_expiry(args.expiry_time(), u64::try_from(current_timestamp()).unwrap_or(0));
+    let call = black_scholes_call(spot, option_strike(assets, args), years, params);
+    let per_collateral = if token_tag == GRANTOR_TOKEN_TAG { call.covered(spot) } else { call };
+
+    let whole_units =
+        per_collateral.scale(contract_size(assets, args)).fair_value * entry.entry.value().unwrap_or(0) as f64;
+    let precision = assets.precision(&args.get_settlement_asset_id());
+
+    (whole_units * 10f64.powi(i32::from(precision))).round() as i128
+}
+
+/// Format a signed base-unit amount in whole units of its asset, e.g. "+1.5" or "-0.25".
+pub(crate) fn format_signed(assets: &AssetRegistry, amount: i128, asset: &AssetId) -> String {
+    let units = assets.format_signed_units(amount, asset);
+    if amount > 0 { format!("+{units}") } else { units }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(byte: u8) -> AssetId {
+        AssetId::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn roles(premium: Option<(AssetId, u64)>) -> ContractRoles {
+        ContractRoles {
+            collateral: asset(1),
+            settlement: asset(2),
+            premium,
+            tokens: vec![asset(9)],
+        }
+    }
+
+    #[test]
+    fn test_attribute_transaction() {
+        // Taking an offer: pay 4000 settlement plus a 500 fee in it, receive 400 collateral and
+        // 800 premium, and get 20 change back in an unrelated asset.
+        let net = BTreeMap::from([(asset(1), 400), (asset(2), -4500), (asset(3), 800), (asset(7), 20)]);
+        let flows = roles(Some((asset(3), 2))).attribute_transaction(&net, Some((asset(2), 500)));
+
+        assert_eq!(flows.len(), 3);
+        assert_eq!(flows[&asset(1)].collateral, 400);
+        assert_eq!(flows[&asset(3)].premium, 800);
+        assert_eq!(
+            flows[&asset(2)],
+            AssetFlows {
+                settlement: -4000,
+                fees: -500,
+                ..AssetFlows::default()
+            }
+        );
+        assert_eq!(flows[&asset(2)].net(), -4500);
+
+        // Premium paid in the collateral asset is split off by its rate; tokens are skipped.
+        let net = BTreeMap::from([(asset(1), -3000), (asset(9), 10)]);
+        let flows = roles(Some((asset(1), 2))).attribute_transaction(&net, None);
+        assert_eq!(
+            flows[&asset(1)],
+            AssetFlows {
+                premium: -2000,
+                collateral: -1000,
+                ..AssetFlows::default()
+            }
+        );
+        assert!(!flows.contains_key(&asset(9)));
+    }
+}
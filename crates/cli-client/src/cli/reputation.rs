@@ -0,0 +1,201 @@
+use crate::cli::interactive::truncate_with_ellipsis;
+use crate::cli::tables::display_counterparty_table;
+use crate::cli::{Cli, ReputationCommand};
+use crate::config::Config;
+use crate::error::Error;
+use crate::event_cache::{load_cached_actions, load_cached_offer_history, refresh_event_cache};
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use coin_store::{CounterpartyList, CounterpartyStatsEntry, ReputationStore, Store};
+use nostr::PublicKey;
+use options_relay::{CounterpartyStats, ReputationCalculator};
+
+/// Display struct for counterparty reputation
+#[derive(Debug, Clone)]
+pub struct CounterpartyDisplay {
+    pub pubkey: String,
+    pub list: String,
+    pub score: String,
+    pub offers: String,
+    pub honoured: String,
+    pub cancelled_early: String,
+    pub bogus: String,
+    pub average_fill: String,
+}
+
+/// Cached reputation and the user's trust / block lists, keyed by hex public key.
+#[derive(Debug, Default)]
+pub(crate) struct ReputationBook {
+    stats: HashMap<String, CounterpartyStats>,
+    lists: HashMap<String, CounterpartyList>,
+}
+
+impl ReputationBook {
+    pub(crate) async fn load(store: &Store) -> Result<Self, Error> {
+        let stats = store
+            .list_counterparty_stats()
+            .await?
+            .iter()
+            .filter_map(stats_from_entry)
+            .map(|stats| (stats.pubkey.to_hex(), stats))
+            .collect();
+        let lists = store.list_counterparty_lists().await?.into_iter().collect();
+
+        Ok(Self { stats, lists })
+    }
+
+    pub(crate) fn is_blocked(&self, pubkey: &str) -> bool {
+        self.lists.get(pubkey) == Some(&CounterpartyList::Blocked)
+    }
+
+    /// Short label for seller columns: the list membership if any, otherwise the score.
+    pub(crate) fn label(&self, pubkey: &str) -> String {
+        if let Some(list) = self.lists.get(pubkey) {
+            return list.to_string();
+        }
+
+        match self.stats.get(pubkey) {
+            Some(stats) => stats.score().map_or_else(|| "unrated".to_string(), |score| format!("{score}/100")),
+            None => "new".to_string(),
+        }
+    }
+
+    /// Seller column value: truncated public key followed by its label.
+    pub(crate) fn seller(&self, pubkey: &str) -> String {
+        format!("{} ({})", truncate_with_ellipsis(pubkey, 12), self.label(pubkey))
+    }
+}
+
+impl Cli {
+    pub(crate) async fn run_reputation(&self, config: Config, command: &ReputationCommand) -> Result<(), Error> {
+        let wallet = self.get_wallet(&config).await?;
+        let store = wallet.store();
+
+        match command {
+            ReputationCommand::Refresh => {
+                let client = self.get_read_only_client(&config).await?;
+                refresh_event_cache(store, &client).await?;
+                client.disconnect().await;
+
+                let offers = load_cached_offer_history(store, config.network()).await?;
+                let actions = load_cached_actions(store).await?;
+
+                println!(
+                    "Checking {} offer(s) and {} action(s) against the chain...",
+                    offers.len(),
+                    actions.len()
+                );
+
+                let stats = ReputationCalculator::new(config.esplora()).compute(&offers, &actions)?;
+
+                #[allow(clippy::cast_possible_wrap)]
+                let updated_at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or(0);
+                let entries: Vec<CounterpartyStatsEntry> =
+                    stats.values().map(|stats| stats_to_entry(stats, updated_at)).collect();
+
+                store.replace_counterparty_stats(&entries).await?;
+
+                println!("Updated reputation for {} counterpart(ies)", entries.len());
+                Ok(())
+            }
+            ReputationCommand::Show { pubkey } => {
+                let book = ReputationBook::load(store).await?;
+                let filter = pubkey.as_deref().map(parse_pubkey).transpose()?;
+
+                let mut pubkeys: Vec<&String> = book.stats.keys().chain(book.lists.keys()).collect();
+                pubkeys.sort();
+                pubkeys.dedup();
+
+                let displays: Vec<CounterpartyDisplay> = pubkeys
+                    .into_iter()
+                    .filter(|pk| filter.as_ref().is_none_or(|f| f == *pk))
+                    .map(|pk| build_counterparty_display(&book, pk))
+                    .collect();
+
+                display_counterparty_table(&displays);
+                Ok(())
+            }
+            ReputationCommand::Trust { pubkey } => {
+                let pubkey = parse_pubkey(pubkey)?;
+                store
+                    .set_counterparty_list(&pubkey, Some(CounterpartyList::Trusted))
+                    .await?;
+                println!("Added {pubkey} to the trust list");
+                Ok(())
+            }
+            ReputationCommand::Block { pubkey } => {
+                let pubkey = parse_pubkey(pubkey)?;
+                store
+                    .set_counterparty_list(&pubkey, Some(CounterpartyList::Blocked))
+                    .await?;
+                println!("Added {pubkey} to the block list; their listings will be hidden");
+                Ok(())
+            }
+            ReputationCommand::Forget { pubkey } => {
+                let pubkey = parse_pubkey(pubkey)?;
+                store.set_counterparty_list(&pubkey, None).await?;
+                println!("Removed {pubkey} from the trust and block lists");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Normalize a hex or bech32 public key to hex.
+fn parse_pubkey(value: &str) -> Result<String, Error> {
+    PublicKey::parse(value)
+        .map(|pk| pk.to_hex())
+        .map_err(|e| Error::Config(format!("Invalid public key '{value}': {e}")))
+}
+
+fn stats_to_entry(stats: &CounterpartyStats, updated_at: i64) -> CounterpartyStatsEntry {
+    CounterpartyStatsEntry {
+        pubkey: stats.pubkey.to_hex(),
+        offers_published: stats.offers_published,
+        offers_honoured: stats.offers_honoured,
+        offers_cancelled_early: stats.offers_cancelled_early,
+        offers_bogus: stats.offers_bogus,
+        fills: stats.fills,
+        total_filled: i64::try_from(stats.total_filled).unwrap_or(i64::MAX),
+        updated_at,
+    }
+}
+
+fn stats_from_entry(entry: &CounterpartyStatsEntry) -> Option<CounterpartyStats> {
+    let pubkey = PublicKey::from_hex(&entry.pubkey).ok()?;
+
+    Some(CounterpartyStats {
+        offers_published: entry.offers_published,
+        offers_honoured: entry.offers_honoured,
+        offers_cancelled_early: entry.offers_cancelled_early,
+        offers_bogus: entry.offers_bogus,
+        fills: entry.fills,
+        total_filled: u64::try_from(entry.total_filled).unwrap_or_default(),
+        ..CounterpartyStats::new(pubkey)
+    })
+}
+
+fn build_counterparty_display(book: &ReputationBook, pubkey: &str) -> CounterpartyDisplay {
+    let stats = book.stats.get(pubkey);
+    let count = |f: fn(&CounterpartyStats) -> u32| stats.map_or_else(|| "-".to_string(), |s| f(s).to_string());
+
+    CounterpartyDisplay {
+        pubkey: truncate_with_ellipsis(pubkey, 16),
+        list: book.lists.get(pubkey).map_or_else(|| "-".to_string(), ToString::to_string),
+        score: stats
+            .and_then(CounterpartyStats::score)
+            .map_or_else(|| "-".to_string(), |score| format!("{score}/100")),
+        offers: count(|s| s.offers_published),
+        honoured: count(|s| s.offers_honoured),
+        cancelled_early: count(|s| s.offers_cancelled_early),
+        bogus: count(|s| s.offers_bogus),
+        average_fill: stats
+            .and_then(CounterpartyStats::average_fill)
+            .map_or_else(|| "-".to_string(), |avg| avg.to_string()),
+    }
+}
@@ -1,10 +1,10 @@
 use std::collections::{HashMap, HashSet};
 
-use coin_store::UtxoStore;
+use coin_store::{TransactionStore, UtxoStore};
 use contracts::option_offer::OPTION_OFFER_SOURCE;
 use contracts::options::OPTION_SOURCE;
-use options_relay::{OptionCreatedEvent, OptionOfferCreatedEvent};
 use simplicityhl::elements::hex::ToHex;
+use simplicityhl::elements::secp256k1_zkp::{Keypair, SECP256K1};
 use simplicityhl::elements::{OutPoint, Txid};
 use simplicityhl_core::derive_public_blinder_key;
 
@@ -12,10 +12,11 @@ use crate::cli::Cli;
 use crate::cli::SyncCommand;
 use crate::config::Config;
 use crate::error::Error;
-use crate::explorer::{
-    esplora_utxo_to_outpoint, fetch_address_utxos, fetch_outspends, fetch_scripthash_utxos, fetch_tip_height,
-    fetch_transaction,
+use crate::event_cache::{
+    actions_by_event, load_cached_actions, load_cached_option_offers, load_cached_options, offer_actions,
+    refresh_event_cache,
 };
+use crate::explorer::{EsploraBackend, esplora_utxo_to_outpoint};
 use crate::sync::{sync_option_event, sync_option_offer_event};
 use options_relay::ReadOnlyClient;
 
@@ -25,6 +26,7 @@ struct SyncStats {
     utxos_marked_spent: usize,
     new_utxos_discovered: usize,
     new_utxos_imported: usize,
+    transactions_backfilled: usize,
     nostr_options_synced: usize,
     nostr_option_offers_synced: usize,
     history_contracts_checked: usize,
@@ -40,6 +42,7 @@ impl SyncStats {
         println!("UTXOs marked spent:   {}", self.utxos_marked_spent);
         println!("New UTXOs discovered: {}", self.new_utxos_discovered);
         println!("New UTXOs imported:   {}", self.new_utxos_imported);
+        println!("Transactions backfilled: {}", self.transactions_backfilled);
         println!("NOSTR options synced: {}", self.nostr_options_synced);
         println!("NOSTR option offers synced: {}", self.nostr_option_offers_synced);
         println!("History contracts checked: {}", self.history_contracts_checked);
@@ -69,7 +72,8 @@ impl Cli {
         }
     }
 
-    /// Full sync: mark spent UTXOs + discover new UTXOs + sync NOSTR events + sync history
+    /// Full sync: discover new UTXOs + backfill transaction history + sync NOSTR events + mark spent UTXOs
+    /// + sync history
     async fn run_sync_full(&self, config: Config) -> Result<(), Error> {
         println!("Starting full sync...");
         println!();
@@ -78,29 +82,39 @@ impl Cli {
 
         // Step 1: Discover new UTXOs
         println!();
-        println!("[1/4] Discovering new UTXOs via Esplora...");
+        println!("[1/5] Discovering new UTXOs via Esplora...");
         self.sync_discover_utxos(&config, &mut stats).await?;
 
+        // Step 2: Record transactions of outputs stored without one
+        println!();
+        println!("[2/5] Backfilling transaction history via Esplora...");
+        self.sync_backfill_transactions(&config, &mut stats).await?;
+
         let client = self.get_read_only_client(&config).await?;
 
-        // Step 2: Sync NOSTR events
+        // Step 3: Sync NOSTR events
         println!();
-        println!("[2/4] Syncing from NOSTR relay...");
+        println!("[3/5] Syncing from NOSTR relay...");
         self.sync_nostr_events_with_client(&config, &mut stats, &client).await?;
 
-        // Step 3: Mark spent UTXOs
-        println!("[3/4] Checking for spent UTXOs via Esplora...");
+        // Step 4: Mark spent UTXOs
+        println!("[4/5] Checking for spent UTXOs via Esplora...");
         self.sync_spent_utxos(&config, &mut stats).await?;
 
-        // Step 4: Sync action history for existing contracts
+        // Step 5: Sync action history for existing contracts
         println!();
-        println!("[4/4] Syncing action history from NOSTR...");
+        println!("[5/5] Syncing action history from NOSTR...");
         self.sync_history_with_client(&config, &mut stats, &client).await?;
 
         client.disconnect().await;
 
         stats.print_summary();
 
+        if config.consolidation.auto {
+            println!();
+            self.run_auto_consolidation(&config).await;
+        }
+
         Ok(())
     }
 
@@ -149,6 +163,12 @@ impl Cli {
         let wallet = self.get_wallet(&config).await?;
         let client = self.get_read_only_client(&config).await?;
 
+        refresh_event_cache(wallet.store(), &client).await?;
+        client.disconnect().await;
+
+        let cached_actions = load_cached_actions(wallet.store()).await?;
+        let grouped_actions = actions_by_event(&cached_actions);
+
         let mut actions_synced = 0;
         let mut contracts_checked = 0;
         let mut errors: Vec<String> = Vec::new();
@@ -209,8 +229,8 @@ impl Cli {
 
             contracts_checked += 1;
 
-            if let Ok(actions) = client.fetch_actions_for_event(event_id).await {
-                for action in actions.into_iter().flatten() {
+            if let Some(actions) = grouped_actions.get(&event_id) {
+                for action in actions {
                     #[allow(clippy::cast_possible_wrap)]
                     let timestamp = action.created_at.as_secs() as i64;
                     let entry = crate::metadata::HistoryEntry::with_txid_and_nostr(
@@ -275,29 +295,31 @@ impl Cli {
 
             contracts_checked += 1;
 
-            if let Ok(actions) = client.fetch_actions_for_event(event_id).await {
-                for action in actions.into_iter().flatten() {
-                    #[allow(clippy::cast_possible_wrap)]
-                    let timestamp = action.created_at.as_secs() as i64;
-                    let entry = crate::metadata::HistoryEntry::with_txid_and_nostr(
-                        action.action.as_str(),
-                        &action.outpoint.txid.to_string(),
-                        &action.event_id.to_hex(),
-                        timestamp,
-                    );
-
-                    if let Ok(added) =
-                        crate::sync::add_history_entry_if_new(wallet.store(), &taproot_pubkey_gen, entry).await
-                        && added
-                    {
-                        actions_synced += 1;
-                    }
+            let actions = offer_actions(
+                &cached_actions,
+                event_id,
+                metadata.nostr_author.as_deref(),
+                &taproot_pubkey_gen,
+            );
+            for action in actions {
+                #[allow(clippy::cast_possible_wrap)]
+                let timestamp = action.created_at.as_secs() as i64;
+                let entry = crate::metadata::HistoryEntry::with_txid_and_nostr(
+                    action.action.as_str(),
+                    &action.outpoint.txid.to_string(),
+                    &action.event_id.to_hex(),
+                    timestamp,
+                );
+
+                if let Ok(added) =
+                    crate::sync::add_history_entry_if_new(wallet.store(), &taproot_pubkey_gen, entry).await
+                    && added
+                {
+                    actions_synced += 1;
                 }
             }
         }
 
-        client.disconnect().await;
-
         println!();
         println!("=== History Sync Summary ===");
         println!("Contracts checked:    {contracts_checked}");
@@ -320,6 +342,7 @@ impl Cli {
     /// Check all unspent UTXOs in the store and mark any that have been spent on-chain.
     async fn sync_spent_utxos(&self, config: &Config, stats: &mut SyncStats) -> Result<(), Error> {
         let wallet = self.get_wallet(config).await?;
+        let esplora = config.esplora();
 
         let unspent_outpoints = wallet.store().list_unspent_outpoints().await?;
         stats.utxos_checked = unspent_outpoints.len();
@@ -340,7 +363,7 @@ impl Cli {
 
         let mut spent_count = 0;
         for (txid, vouts) in by_txid {
-            match fetch_outspends(txid) {
+            match esplora.fetch_outspends(txid) {
                 Ok(outspends) => {
                     for vout in vouts {
                         if let Some(status) = outspends.get(vout as usize)
@@ -381,21 +404,23 @@ impl Cli {
     /// Discover new UTXOs for the wallet address and all tracked contract script pubkeys.
     async fn sync_discover_utxos(&self, config: &Config, stats: &mut SyncStats) -> Result<(), Error> {
         let wallet = self.get_wallet(config).await?;
+        let esplora = config.esplora();
 
         let existing_outpoints: HashSet<OutPoint> =
             wallet.store().list_unspent_outpoints().await?.into_iter().collect();
 
         let mut imported_txids: HashSet<Txid> = HashSet::new();
 
-        match fetch_tip_height() {
+        match esplora.fetch_tip_height() {
             Ok(height) => println!("  Current block height: {height}"),
             Err(e) => stats.errors.push(format!("Failed to fetch tip height: {e}")),
         }
 
         println!("  Checking wallet address...");
         let wallet_address = wallet.signer().p2pk_address(config.network())?;
+        let receive_keys = wallet.blinding_keypairs().await?;
 
-        match fetch_address_utxos(&wallet_address) {
+        match esplora.fetch_address_utxos(&wallet_address) {
             Ok(utxos) => {
                 stats.new_utxos_discovered += utxos.len();
                 println!("    Found {} UTXOs for wallet address", utxos.len());
@@ -405,7 +430,12 @@ impl Cli {
                         Ok(outpoint) => {
                             if !existing_outpoints.contains(&outpoint) && !imported_txids.contains(&outpoint.txid) {
                                 match self
-                                    .import_transaction_from_esplora(wallet.store(), outpoint.txid)
+                                    .import_transaction_from_esplora(
+                                        wallet.store(),
+                                        &esplora,
+                                        outpoint.txid,
+                                        &receive_keys,
+                                    )
                                     .await
                                 {
                                     Ok(true) => {
@@ -440,7 +470,7 @@ impl Cli {
         println!("    Found {} tracked contracts", script_pubkeys.len());
 
         for script in &script_pubkeys {
-            match fetch_scripthash_utxos(script) {
+            match esplora.fetch_scripthash_utxos(script) {
                 Ok(utxos) => {
                     stats.new_utxos_discovered += utxos.len();
 
@@ -449,7 +479,12 @@ impl Cli {
                             Ok(outpoint) => {
                                 if !existing_outpoints.contains(&outpoint) && !imported_txids.contains(&outpoint.txid) {
                                     match self
-                                        .import_transaction_from_esplora(wallet.store(), outpoint.txid)
+                                        .import_transaction_from_esplora(
+                                            wallet.store(),
+                                            &esplora,
+                                            outpoint.txid,
+                                            &receive_keys,
+                                        )
                                         .await
                                     {
                                         Ok(true) => {
@@ -485,8 +520,47 @@ impl Cli {
         Ok(())
     }
 
-    async fn import_transaction_from_esplora(&self, store: &coin_store::Store, txid: Txid) -> Result<bool, Error> {
-        let tx = fetch_transaction(txid)?;
+    /// Record the transactions of stored wallet outputs that have no transaction row, such as outputs
+    /// imported by hand or stored before transaction history was kept.
+    async fn sync_backfill_transactions(&self, config: &Config, stats: &mut SyncStats) -> Result<(), Error> {
+        let wallet = self.get_wallet(config).await?;
+        let esplora = config.esplora();
+
+        let txids = wallet.store().list_unrecorded_txids().await?;
+        println!("  Found {} transactions missing from history", txids.len());
+
+        if txids.is_empty() {
+            return Ok(());
+        }
+
+        let receive_keys = wallet.blinding_keypairs().await?;
+
+        for txid in txids {
+            match self
+                .import_transaction_from_esplora(wallet.store(), &esplora, txid, &receive_keys)
+                .await
+            {
+                Ok(_) => {
+                    stats.transactions_backfilled += 1;
+                    tracing::debug!("Backfilled transaction: {txid}");
+                }
+                Err(e) => stats.errors.push(format!("Failed to backfill tx {txid}: {e}")),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Import a transaction, unblinding outputs to our confidential receive addresses and our blinded
+    /// change with `receive_keys` and other confidential outputs with the public blinder key.
+    async fn import_transaction_from_esplora(
+        &self,
+        store: &coin_store::Store,
+        esplora: &EsploraBackend,
+        txid: Txid,
+        receive_keys: &[Keypair],
+    ) -> Result<bool, Error> {
+        let tx = esplora.fetch_transaction(txid)?;
 
         let blinder_keypair = derive_public_blinder_key();
         let blinder_keys: HashMap<usize, _> = tx
@@ -495,7 +569,14 @@ impl Cli {
             .enumerate()
             .filter(|(_, out)| !out.is_fee())
             .filter(|(_, out)| out.asset.is_confidential())
-            .map(|(i, _)| (i, blinder_keypair))
+            .map(|(i, out)| {
+                let keypair = receive_keys
+                    .iter()
+                    .find(|keypair| out.unblind(SECP256K1, keypair.secret_key()).is_ok())
+                    .copied()
+                    .unwrap_or(blinder_keypair);
+                (i, keypair)
+            })
             .collect();
 
         match store.insert_transaction(&tx, blinder_keys).await {
@@ -525,17 +606,20 @@ impl Cli {
         client: &ReadOnlyClient,
     ) -> Result<(), Error> {
         let wallet = self.get_wallet(config).await?;
+        let esplora = config.esplora();
+
+        println!("  Fetching new events from NOSTR...");
+        let new_events = refresh_event_cache(wallet.store(), client).await?;
+        println!("    Cached {new_events} new events");
 
-        println!("  Fetching options from NOSTR...");
-        let options_results = client.fetch_options(config.network()).await?;
-        let valid_options: Vec<OptionCreatedEvent> = options_results.into_iter().filter_map(Result::ok).collect();
+        let valid_options = load_cached_options(wallet.store(), config.network()).await?;
 
         println!("    Found {} valid options", valid_options.len());
 
         let mut options_already_synced = 0;
         for event in &valid_options {
             let arguments = event.options_args.build_option_arguments();
-            match sync_option_event(wallet.store(), event, OPTION_SOURCE, arguments).await {
+            match sync_option_event(wallet.store(), &esplora, event, OPTION_SOURCE, arguments).await {
                 Ok(()) => {
                     stats.nostr_options_synced += 1;
                 }
@@ -554,9 +638,8 @@ impl Cli {
             println!("    ({options_already_synced} options already synced)");
         }
 
-        println!("  Fetching option offers from NOSTR...");
-        let offers_results = client.fetch_option_offers(config.network()).await?;
-        let valid_offers: Vec<OptionOfferCreatedEvent> = offers_results.into_iter().filter_map(Result::ok).collect();
+        let valid_offers = load_cached_option_offers(wallet.store(), config.network()).await?;
+        let cached_actions = load_cached_actions(wallet.store()).await?;
 
         println!("    Found {} valid option offers", valid_offers.len());
 
@@ -565,7 +648,7 @@ impl Cli {
         for offer in &valid_offers {
             // First sync the option offer contract itself
             let arguments = offer.option_offer_args.build_arguments();
-            match sync_option_offer_event(wallet.store(), offer, OPTION_OFFER_SOURCE, arguments, None).await {
+            match sync_option_offer_event(wallet.store(), &esplora, offer, OPTION_OFFER_SOURCE, arguments, None).await {
                 Ok(()) => {
                     stats.nostr_option_offers_synced += 1;
                 }
@@ -573,6 +656,14 @@ impl Cli {
                     // Ignore duplicate errors (already synced)
                     if e.to_string().contains("UNIQUE constraint") {
                         offers_already_synced += 1;
+
+                        // Offers are addressable; a republished version replaces the one we synced.
+                        if let Err(e) = crate::sync::track_republished_offer(wallet.store(), &esplora, offer).await {
+                            tracing::debug!(
+                                "Could not track republished offer {}: {e} (soft failure)",
+                                offer.event_id
+                            );
+                        }
                     } else {
                         stats
                             .errors
@@ -581,27 +672,32 @@ impl Cli {
                 }
             }
 
-            if let Ok(actions) = client.fetch_actions_for_event(offer.event_id).await {
-                for action in actions.into_iter().flatten() {
-                    #[allow(clippy::cast_possible_wrap)]
-                    let timestamp = action.created_at.as_secs() as i64;
-                    let entry = crate::metadata::HistoryEntry::with_txid_and_nostr(
-                        action.action.as_str(),
-                        &action.outpoint.txid.to_string(),
-                        &action.event_id.to_hex(),
-                        timestamp,
-                    );
-
-                    if let Ok(added) =
-                        crate::sync::add_history_entry_if_new(wallet.store(), &offer.taproot_pubkey_gen, entry).await
-                        && added
-                    {
-                        actions_synced += 1;
-                    }
+            // Fills may name an earlier version of the offer; the coordinate covers all of them.
+            let coordinate = offer.coordinate(offer.pubkey);
+            for action in cached_actions
+                .iter()
+                .filter(|action| action.references_offer(offer.event_id, &coordinate))
+            {
+                #[allow(clippy::cast_possible_wrap)]
+                let timestamp = action.created_at.as_secs() as i64;
+                let entry = crate::metadata::HistoryEntry::with_txid_and_nostr(
+                    action.action.as_str(),
+                    &action.outpoint.txid.to_string(),
+                    &action.event_id.to_hex(),
+                    timestamp,
+                );
+
+                if let Ok(added) =
+                    crate::sync::add_history_entry_if_new(wallet.store(), &offer.taproot_pubkey_gen, entry).await
+                    && added
+                {
+                    actions_synced += 1;
+                }
 
-                    if let Err(e) = crate::sync::sync_utxo_with_public_blinder(wallet.store(), action.outpoint).await {
-                        tracing::debug!("Could not sync action UTXO {}: {} (soft failure)", action.outpoint, e);
-                    }
+                if let Err(e) =
+                    crate::sync::sync_utxo_with_public_blinder(wallet.store(), &esplora, action.outpoint).await
+                {
+                    tracing::debug!("Could not sync action UTXO {}: {} (soft failure)", action.outpoint, e);
                 }
             }
         }
@@ -637,6 +733,10 @@ impl Cli {
     ) -> Result<(), Error> {
         let wallet = self.get_wallet(config).await?;
 
+        refresh_event_cache(wallet.store(), client).await?;
+        let cached_actions = load_cached_actions(wallet.store()).await?;
+        let grouped_actions = actions_by_event(&cached_actions);
+
         let option_contracts =
             <_ as UtxoStore>::list_contracts_by_source_with_metadata(wallet.store(), OPTION_SOURCE).await?;
         let option_offer_contracts =
@@ -693,8 +793,8 @@ impl Cli {
 
             stats.history_contracts_checked += 1;
 
-            if let Ok(actions) = client.fetch_actions_for_event(event_id).await {
-                for action in actions.into_iter().flatten() {
+            if let Some(actions) = grouped_actions.get(&event_id) {
+                for action in actions {
                     #[allow(clippy::cast_possible_wrap)]
                     let timestamp = action.created_at.as_secs() as i64;
                     let entry = crate::metadata::HistoryEntry::with_txid_and_nostr(
@@ -759,23 +859,27 @@ impl Cli {
 
             stats.history_contracts_checked += 1;
 
-            if let Ok(actions) = client.fetch_actions_for_event(event_id).await {
-                for action in actions.into_iter().flatten() {
-                    #[allow(clippy::cast_possible_wrap)]
-                    let timestamp = action.created_at.as_secs() as i64;
-                    let entry = crate::metadata::HistoryEntry::with_txid_and_nostr(
-                        action.action.as_str(),
-                        &action.outpoint.txid.to_string(),
-                        &action.event_id.to_hex(),
-                        timestamp,
-                    );
-
-                    if let Ok(added) =
-                        crate::sync::add_history_entry_if_new(wallet.store(), &taproot_pubkey_gen, entry).await
-                        && added
-                    {
-                        stats.history_actions_synced += 1;
-                    }
+            let actions = offer_actions(
+                &cached_actions,
+                event_id,
+                metadata.nostr_author.as_deref(),
+                &taproot_pubkey_gen,
+            );
+            for action in actions {
+                #[allow(clippy::cast_possible_wrap)]
+                let timestamp = action.created_at.as_secs() as i64;
+                let entry = crate::metadata::HistoryEntry::with_txid_and_nostr(
+                    action.action.as_str(),
+                    &action.outpoint.txid.to_string(),
+                    &action.event_id.to_hex(),
+                    timestamp,
+                );
+
+                if let Ok(added) =
+                    crate::sync::add_history_entry_if_new(wallet.store(), &taproot_pubkey_gen, entry).await
+                    && added
+                {
+                    stats.history_actions_synced += 1;
                 }
             }
         }
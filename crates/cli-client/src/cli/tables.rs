@@ -1,8 +1,15 @@
+use crate::cli::asset::AssetRegistryDisplay;
+use crate::cli::browse::OrderBookDisplay;
 use crate::cli::interactive::{TokenDisplay, WalletAssetDisplay};
+use crate::cli::label::LabelDisplay;
+use crate::cli::message::NegotiationMessageDisplay;
 use crate::cli::option_offer::{
     ActiveOptionOfferDisplay, CancellableOptionOfferDisplay, WithdrawableOptionOfferDisplay,
 };
-use crate::cli::positions::{CollateralDisplay, UserTokenDisplay};
+use crate::cli::positions::{CollateralDisplay, MarkToMarketDisplay, UserTokenDisplay};
+use crate::cli::relay::RelayStatusDisplay;
+use crate::cli::report::{AssetPnlDisplay, ContractPnlDisplay};
+use crate::cli::reputation::CounterpartyDisplay;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{Attribute, Cell, Table};
 
@@ -66,6 +73,29 @@ impl TableData for UserTokenDisplay {
     }
 }
 
+impl TableData for MarkToMarketDisplay {
+    fn get_header() -> Vec<String> {
+        vec!["#", "Type", "Amount", "Spot", "Fair/Token", "Value", "Delta", "Gamma", "Theta/Day", "Vega/1%"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.index.to_string(),
+            self.token_type.clone(),
+            self.amount.clone(),
+            self.spot.clone(),
+            self.fair_per_token.clone(),
+            self.value.clone(),
+            self.delta.clone(),
+            self.gamma.clone(),
+            self.theta.clone(),
+            self.vega.clone(),
+        ]
+    }
+}
+
 impl TableData for ActiveOptionOfferDisplay {
     fn get_header() -> Vec<String> {
         vec!["#", "Offering", "Price", "Wants", "Expires", "Seller"]
@@ -85,6 +115,40 @@ impl TableData for ActiveOptionOfferDisplay {
     }
 }
 
+impl TableData for OrderBookDisplay {
+    fn get_header() -> Vec<String> {
+        vec![
+            "#",
+            "Offering",
+            "Price",
+            "Effective",
+            "Premium",
+            "Fair value",
+            "Premium/Fair",
+            "Depth",
+            "Expires",
+            "Seller",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.index.to_string(),
+            self.offering.clone(),
+            self.price.clone(),
+            self.effective.clone(),
+            self.premium.clone(),
+            self.fair.clone(),
+            self.premium_vs_fair.clone(),
+            self.depth.clone(),
+            self.expires.clone(),
+            self.seller.clone(),
+        ]
+    }
+}
+
 impl TableData for CancellableOptionOfferDisplay {
     fn get_header() -> Vec<String> {
         vec!["#", "Collateral", "Premium", "Asset", "Expired", "Contract"]
@@ -129,7 +193,170 @@ impl TableData for WalletAssetDisplay {
         vec![
             self.index.to_string(),
             self.asset_name.clone(),
-            self.balance.to_string(),
+            self.balance_units.clone(),
+        ]
+    }
+}
+
+impl TableData for RelayStatusDisplay {
+    fn get_header() -> Vec<String> {
+        vec!["Relay", "Role", "Connect", "Fetch", "Publish", "Our Events", "Software"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.url.clone(),
+            self.role.clone(),
+            self.connect.clone(),
+            self.fetch.clone(),
+            self.publish.clone(),
+            self.events.clone(),
+            self.software.clone(),
+        ]
+    }
+}
+
+impl TableData for NegotiationMessageDisplay {
+    fn get_header() -> Vec<String> {
+        vec!["#", "Received", "From", "Offer", "Type", "Details"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.index.to_string(),
+            self.received.clone(),
+            self.from.clone(),
+            self.offer.clone(),
+            self.kind.clone(),
+            self.details.clone(),
+        ]
+    }
+}
+
+impl TableData for AssetRegistryDisplay {
+    fn get_header() -> Vec<String> {
+        vec!["Ticker", "Name", "Precision", "Issuer", "Asset ID"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.ticker.clone(),
+            self.name.clone(),
+            self.precision.clone(),
+            self.issuer.clone(),
+            self.asset_id.clone(),
+        ]
+    }
+}
+
+impl TableData for LabelDisplay {
+    fn get_header() -> Vec<String> {
+        vec!["Type", "Reference", "Label"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![self.kind.clone(), self.reference.clone(), self.label.clone()]
+    }
+}
+
+impl TableData for CounterpartyDisplay {
+    fn get_header() -> Vec<String> {
+        vec![
+            "Public Key",
+            "List",
+            "Score",
+            "Offers",
+            "Honoured",
+            "Cancelled Early",
+            "Bogus",
+            "Avg Fill",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.pubkey.clone(),
+            self.list.clone(),
+            self.score.clone(),
+            self.offers.clone(),
+            self.honoured.clone(),
+            self.cancelled_early.clone(),
+            self.bogus.clone(),
+            self.average_fill.clone(),
+        ]
+    }
+}
+
+impl TableData for ContractPnlDisplay {
+    fn get_header() -> Vec<String> {
+        vec![
+            "#",
+            "Type",
+            "Contract",
+            "Status",
+            "Asset",
+            "Premium",
+            "Collateral",
+            "Settlement",
+            "Fees",
+            "Realized",
+            "Unrealized",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.index.to_string(),
+            self.contract_type.clone(),
+            self.contract.clone(),
+            self.status.clone(),
+            self.asset.clone(),
+            self.premium.clone(),
+            self.collateral.clone(),
+            self.settlement.clone(),
+            self.fees.clone(),
+            self.realized.clone(),
+            self.unrealized.clone(),
+        ]
+    }
+}
+
+impl TableData for AssetPnlDisplay {
+    fn get_header() -> Vec<String> {
+        vec![
+            "Asset",
+            "Premium",
+            "Collateral",
+            "Settlement",
+            "Fees",
+            "Realized",
+            "Unrealized",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.asset.clone(),
+            self.premium.clone(),
+            self.collateral.clone(),
+            self.settlement.clone(),
+            self.fees.clone(),
+            self.realized.clone(),
+            self.unrealized.clone(),
         ]
     }
 }
@@ -138,17 +365,103 @@ pub struct UtxoDisplay {
     pub outpoint: String,
     pub asset: String,
     pub value: String,
+    pub label: String,
+    pub frozen: String,
 }
 
 impl TableData for UtxoDisplay {
     fn get_header() -> Vec<String> {
-        vec!["Outpoint", "Asset", "Value"]
+        vec!["Outpoint", "Asset", "Value", "Label", "Frozen"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.outpoint.clone(),
+            self.asset.clone(),
+            self.value.clone(),
+            self.label.clone(),
+            self.frozen.clone(),
+        ]
+    }
+}
+
+pub struct TransactionHistoryDisplay {
+    pub date: String,
+    pub txid: String,
+    pub status: String,
+    pub changes: String,
+    pub fee: String,
+    pub contract: String,
+}
+
+impl TableData for TransactionHistoryDisplay {
+    fn get_header() -> Vec<String> {
+        vec!["Date", "Txid", "Status", "Net Change", "Fee Paid", "Contract"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.date.clone(),
+            self.txid.clone(),
+            self.status.clone(),
+            self.changes.clone(),
+            self.fee.clone(),
+            self.contract.clone(),
+        ]
+    }
+}
+
+pub struct BatchPaymentDisplay {
+    pub vout: String,
+    pub address: String,
+    pub amount: String,
+    pub blinding: String,
+}
+
+impl TableData for BatchPaymentDisplay {
+    fn get_header() -> Vec<String> {
+        vec!["Vout", "Address", "Amount", "Blinding"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+    fn to_row(&self) -> Vec<String> {
+        vec![
+            self.vout.clone(),
+            self.address.clone(),
+            self.amount.clone(),
+            self.blinding.clone(),
+        ]
+    }
+}
+
+pub struct ConsolidationDisplay {
+    pub asset: String,
+    pub inputs: String,
+    pub total: String,
+    pub fee: String,
+    pub dust: String,
+}
+
+impl TableData for ConsolidationDisplay {
+    fn get_header() -> Vec<String> {
+        vec!["Asset", "Inputs", "Total", "Est. Fee", "Dust Left"]
             .into_iter()
             .map(String::from)
             .collect()
     }
     fn to_row(&self) -> Vec<String> {
-        vec![self.outpoint.clone(), self.asset.clone(), self.value.clone()]
+        vec![
+            self.asset.clone(),
+            self.inputs.clone(),
+            self.total.clone(),
+            self.fee.clone(),
+            self.dust.clone(),
+        ]
     }
 }
 
@@ -189,10 +502,18 @@ pub fn display_user_token_table(displays: &[UserTokenDisplay]) {
     render_table(displays, "No option/grantor tokens found");
 }
 
+pub fn display_mark_to_market_table(displays: &[MarkToMarketDisplay]) {
+    render_table(displays, "No option/grantor tokens to value");
+}
+
 pub fn display_active_option_offers_table(active_offers: &[ActiveOptionOfferDisplay]) {
     render_table(active_offers, "No option offers found");
 }
 
+pub fn display_order_book_table(levels: &[OrderBookDisplay]) {
+    render_table(levels, "No option offers in this market");
+}
+
 pub fn display_cancellable_option_offers_table(cancellable_offers: &[CancellableOptionOfferDisplay]) {
     render_table(cancellable_offers, "No cancellable option offers found");
 }
@@ -205,6 +526,46 @@ pub fn display_utxo_table(utxos: &[UtxoDisplay]) {
     render_table(utxos, "No UTXOs found");
 }
 
+pub fn display_transaction_history_table(transactions: &[TransactionHistoryDisplay]) {
+    render_table(transactions, "No wallet transactions recorded");
+}
+
+pub fn display_batch_payment_table(payments: &[BatchPaymentDisplay]) {
+    render_table(payments, "No payments");
+}
+
+pub fn display_consolidation_table(merges: &[ConsolidationDisplay]) {
+    render_table(merges, "Nothing to consolidate");
+}
+
 pub fn display_wallet_assets_table(assets: &[WalletAssetDisplay]) {
     render_table(assets, "No assets found in wallet");
 }
+
+pub fn display_relay_status_table(relays: &[RelayStatusDisplay]) {
+    render_table(relays, "No relays configured");
+}
+
+pub fn display_negotiation_messages_table(messages: &[NegotiationMessageDisplay]) {
+    render_table(messages, "No negotiation messages");
+}
+
+pub fn display_asset_registry_table(assets: &[AssetRegistryDisplay]) {
+    render_table(assets, "No assets registered; use `asset add` or `asset import`");
+}
+
+pub fn display_label_table(labels: &[LabelDisplay]) {
+    render_table(labels, "No labels; use `label set` or `label import`");
+}
+
+pub fn display_counterparty_table(counterparties: &[CounterpartyDisplay]) {
+    render_table(counterparties, "No counterparty history; run `reputation refresh`");
+}
+
+pub fn display_pnl_contract_table(displays: &[ContractPnlDisplay]) {
+    render_table(displays, "No contract activity in this period");
+}
+
+pub fn display_pnl_asset_table(displays: &[AssetPnlDisplay]) {
+    render_table(displays, "No P&L in this period");
+}
@@ -1,4 +1,7 @@
+use crate::assets::AssetRegistry;
+use crate::cli::batch_transfer::batch_payments;
 use crate::cli::{Cli, TxCommand};
+use crate::coin_control::CoinControl;
 use crate::config::Config;
 use crate::error::Error;
 use crate::fee::{PLACEHOLDER_FEE, estimate_fee_signed};
@@ -6,9 +9,9 @@ use crate::signing::sign_p2pk_inputs;
 
 use std::collections::HashMap;
 
-use coin_store::{UtxoQueryResult, UtxoStore};
+use coin_store::{UtxoEntry, UtxoQueryResult, UtxoStore};
 
-use simplicityhl::elements::TxOut;
+use simplicityhl::elements::{AssetId, Script, TxOut};
 use simplicityhl::elements::hashes::Hash;
 use simplicityhl::elements::issuance::ContractHash;
 use simplicityhl::elements::pset::serialize::Serialize;
@@ -21,12 +24,26 @@ impl Cli {
     #[allow(clippy::too_many_lines)]
     pub(crate) async fn run_tx(&self, config: Config, command: &TxCommand) -> Result<(), Error> {
         match command {
-            TxCommand::SplitNative { count, fee, broadcast } => {
+            TxCommand::SplitNative {
+                count,
+                fee,
+                broadcast,
+                coin_control,
+            } => {
+                if coin_control.change_address.is_some() {
+                    return Err(Error::Config(
+                        "split-native pays everything back to the wallet; --change-address does not apply".to_string(),
+                    ));
+                }
+
                 let wallet = self.get_wallet(&config).await?;
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
 
-                let filter = coin_store::UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(wallet.signer().p2pk_address(config.network())?.script_pubkey());
+                let filter = coin_control.filter(
+                    coin_store::UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(wallet.signer().p2pk_address(config.network())?.script_pubkey()),
+                );
 
                 let results: Vec<UtxoQueryResult> = <_ as UtxoStore>::query_utxos(wallet.store(), &[filter]).await?;
 
@@ -66,7 +83,7 @@ impl Cli {
                         println!("{}", tx.serialize().to_lower_hex_string());
                     }
                     true => {
-                        cli_helper::explorer::broadcast_tx(&tx).await?;
+                        config.esplora().broadcast_transaction(&tx)?;
 
                         println!("Broadcasted: {}", tx.txid());
 
@@ -79,22 +96,31 @@ impl Cli {
                 count,
                 fee,
                 broadcast,
+                coin_control,
             } => {
                 if *count < 2 {
                     return Err(Error::Config("Need at least 2 UTXOs to merge".to_string()));
                 }
+                if coin_control.change_address.is_some() {
+                    return Err(Error::Config(
+                        "merge pays everything back to the wallet; --change-address does not apply".to_string(),
+                    ));
+                }
 
                 let wallet = self.get_wallet(&config).await?;
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
                 let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
 
                 let target_asset = asset_id.unwrap_or(*LIQUID_TESTNET_BITCOIN_ASSET);
                 let is_native = target_asset == *LIQUID_TESTNET_BITCOIN_ASSET;
 
                 #[allow(clippy::cast_possible_wrap)]
-                let asset_filter = coin_store::UtxoFilter::new()
-                    .asset_id(target_asset)
-                    .script_pubkey(script_pubkey.clone())
-                    .limit(*count as i64);
+                let asset_filter = coin_control.filter(
+                    coin_store::UtxoFilter::new()
+                        .asset_id(target_asset)
+                        .script_pubkey(script_pubkey.clone())
+                        .limit(*count as i64),
+                );
 
                 let results: Vec<UtxoQueryResult> =
                     <_ as UtxoStore>::query_utxos(wallet.store(), &[asset_filter]).await?;
@@ -121,70 +147,15 @@ impl Cli {
 
                 let total_asset_value: u64 = entries.iter().filter_map(coin_store::UtxoEntry::value).sum();
 
-                let build_merge_pset = |actual_fee: u64,
-                                        fee_entry: Option<&coin_store::UtxoEntry>|
-                 -> Result<(PartiallySignedTransaction, Vec<TxOut>), Error> {
-                    let mut pst = PartiallySignedTransaction::new_v2();
-                    let mut utxos: Vec<TxOut> = entries
-                        .iter()
-                        .map(|e| {
-                            let mut input = Input::from_prevout(*e.outpoint());
-                            input.witness_utxo = Some(e.txout().clone());
-                            pst.add_input(input);
-                            e.txout().clone()
-                        })
-                        .collect();
-
-                    if is_native {
-                        let output_value = total_asset_value
-                            .checked_sub(actual_fee)
-                            .ok_or_else(|| Error::Config("Fee exceeds total UTXO value".to_string()))?;
-                        pst.add_output(Output::new_explicit(
-                            script_pubkey.clone(),
-                            output_value,
-                            *LIQUID_TESTNET_BITCOIN_ASSET,
-                            None,
-                        ));
-                    } else if let Some(fee_e) = fee_entry {
-                        let Some(fee_input_value) = fee_e.value() else {
-                            return Err(Error::Config("Unexpected confidential value".to_string()));
-                        };
-                        let mut fee_input = Input::from_prevout(*fee_e.outpoint());
-                        fee_input.witness_utxo = Some(fee_e.txout().clone());
-                        pst.add_input(fee_input);
-                        utxos.push(fee_e.txout().clone());
-
-                        pst.add_output(Output::new_explicit(
-                            script_pubkey.clone(),
-                            total_asset_value,
-                            target_asset,
-                            None,
-                        ));
-
-                        if fee_input_value > actual_fee {
-                            pst.add_output(Output::new_explicit(
-                                script_pubkey.clone(),
-                                fee_input_value - actual_fee,
-                                *LIQUID_TESTNET_BITCOIN_ASSET,
-                                None,
-                            ));
-                        }
-                    }
-
-                    pst.add_output(Output::from_txout(TxOut::new_fee(
-                        actual_fee,
-                        *LIQUID_TESTNET_BITCOIN_ASSET,
-                    )));
-                    Ok((pst, utxos))
-                };
-
                 let fee_entry_opt = if is_native {
                     None
                 } else {
-                    let fee_filter = coin_store::UtxoFilter::new()
-                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                        .script_pubkey(script_pubkey.clone())
-                        .required_value(fee.unwrap_or(PLACEHOLDER_FEE));
+                    let fee_filter = coin_control.filter(
+                        coin_store::UtxoFilter::new()
+                            .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                            .script_pubkey(script_pubkey.clone())
+                            .required_value(fee.unwrap_or(PLACEHOLDER_FEE)),
+                    );
 
                     let fee_results: Vec<UtxoQueryResult> =
                         <_ as UtxoStore>::query_utxos(wallet.store(), &[fee_filter]).await?;
@@ -209,7 +180,7 @@ impl Cli {
                 let actual_fee = estimate_fee_signed(
                     fee.as_ref(),
                     config.get_fee_rate(),
-                    |f| build_merge_pset(f, fee_entry_opt.as_ref()),
+                    |f| build_merge_pset(&entries, fee_entry_opt.as_ref(), &script_pubkey, target_asset, f),
                     |tx, utxos| sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0),
                 )?;
 
@@ -224,7 +195,8 @@ impl Cli {
                     }
                 }
 
-                let (pst, utxos) = build_merge_pset(actual_fee, fee_entry_opt.as_ref())?;
+                let (pst, utxos) =
+                    build_merge_pset(&entries, fee_entry_opt.as_ref(), &script_pubkey, target_asset, actual_fee)?;
 
                 if is_native {
                     println!(
@@ -250,7 +222,7 @@ impl Cli {
                         println!("{}", tx.serialize().to_lower_hex_string());
                     }
                     true => {
-                        cli_helper::explorer::broadcast_tx(&tx).await?;
+                        config.esplora().broadcast_transaction(&tx)?;
 
                         println!("Broadcasted: {}", tx.txid());
 
@@ -262,205 +234,47 @@ impl Cli {
                 asset_id,
                 to,
                 amount,
+                explicit,
                 fee,
                 broadcast,
+                coin_control,
             } => {
-                let wallet = self.get_wallet(&config).await?;
-                let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
-
-                let target_asset = asset_id.unwrap_or(*LIQUID_TESTNET_BITCOIN_ASSET);
-                let is_native = target_asset == *LIQUID_TESTNET_BITCOIN_ASSET;
-
-                let required_amount = if is_native {
-                    *amount + fee.unwrap_or(PLACEHOLDER_FEE)
-                } else {
-                    *amount
-                };
-
-                let asset_filter = coin_store::UtxoFilter::new()
-                    .asset_id(target_asset)
-                    .script_pubkey(script_pubkey.clone())
-                    .required_value(required_amount);
-
-                let results: Vec<UtxoQueryResult> =
-                    <_ as UtxoStore>::query_utxos(wallet.store(), &[asset_filter]).await?;
-
-                let entries: Vec<_> = results
-                    .into_iter()
-                    .next()
-                    .and_then(|r| match r {
-                        UtxoQueryResult::Found(entries, _) => Some(entries),
-                        UtxoQueryResult::InsufficientValue(entries, _) => {
-                            let available: u64 = entries.iter().filter_map(coin_store::UtxoEntry::value).sum();
-                            eprintln!(
-                                "Insufficient funds: have {available} sats, need {required_amount} sats. Try using 'merge' command first."
-                            );
-                            None
-                        }
-                        UtxoQueryResult::Empty => None,
-                    })
-                    .ok_or_else(|| Error::Config(format!("No UTXOs found for asset {target_asset}")))?;
-
-                let total_asset_value: u64 = entries.iter().filter_map(coin_store::UtxoEntry::value).sum();
-
-                let build_transfer_pset = |actual_fee: u64,
-                                           fee_entry: Option<&coin_store::UtxoEntry>|
-                 -> Result<(PartiallySignedTransaction, Vec<TxOut>), Error> {
-                    let mut pst = PartiallySignedTransaction::new_v2();
-                    let mut utxos: Vec<TxOut> = entries
-                        .iter()
-                        .map(|e| {
-                            let mut input = Input::from_prevout(*e.outpoint());
-                            input.witness_utxo = Some(e.txout().clone());
-                            pst.add_input(input);
-                            e.txout().clone()
-                        })
-                        .collect();
-
-                    if is_native {
-                        pst.add_output(Output::new_explicit(
-                            to.script_pubkey(),
-                            *amount,
-                            *LIQUID_TESTNET_BITCOIN_ASSET,
-                            None,
-                        ));
-
-                        let change = total_asset_value
-                            .checked_sub(*amount + actual_fee)
-                            .ok_or_else(|| Error::Config("Fee + amount exceeds total UTXO value".to_string()))?;
-
-                        if change > 0 {
-                            pst.add_output(Output::new_explicit(
-                                script_pubkey.clone(),
-                                change,
-                                *LIQUID_TESTNET_BITCOIN_ASSET,
-                                None,
-                            ));
-                        }
-                    } else if let Some(fee_e) = fee_entry {
-                        let Some(fee_input_value) = fee_e.value() else {
-                            return Err(Error::Config("Unexpected confidential value".to_string()));
-                        };
-
-                        let mut fee_input = Input::from_prevout(*fee_e.outpoint());
-                        fee_input.witness_utxo = Some(fee_e.txout().clone());
-                        pst.add_input(fee_input);
-                        utxos.push(fee_e.txout().clone());
-
-                        pst.add_output(Output::new_explicit(to.script_pubkey(), *amount, target_asset, None));
-
-                        let asset_change = total_asset_value - *amount;
-                        if asset_change > 0 {
-                            pst.add_output(Output::new_explicit(
-                                script_pubkey.clone(),
-                                asset_change,
-                                target_asset,
-                                None,
-                            ));
-                        }
-
-                        if fee_input_value > actual_fee {
-                            pst.add_output(Output::new_explicit(
-                                script_pubkey.clone(),
-                                fee_input_value - actual_fee,
-                                *LIQUID_TESTNET_BITCOIN_ASSET,
-                                None,
-                            ));
-                        }
-                    }
-
-                    pst.add_output(Output::from_txout(TxOut::new_fee(
-                        actual_fee,
-                        *LIQUID_TESTNET_BITCOIN_ASSET,
-                    )));
-                    Ok((pst, utxos))
-                };
-
-                let fee_entry_opt = if is_native {
-                    None
+                let assets = AssetRegistry::load(&config.database_path()).await;
+                let target_asset = asset_id
+                    .or(amount.ticker_asset(&assets)?)
+                    .unwrap_or(*LIQUID_TESTNET_BITCOIN_ASSET);
+                let amount = amount.to_base_units(&assets, target_asset)?;
+
+                if target_asset == *LIQUID_TESTNET_BITCOIN_ASSET {
+                    println!("Transferring {} to {to}", assets.format_amount(amount, &target_asset));
                 } else {
-                    let fee_filter = coin_store::UtxoFilter::new()
-                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                        .script_pubkey(script_pubkey.clone())
-                        .required_value(fee.unwrap_or(PLACEHOLDER_FEE));
-
-                    let fee_results: Vec<UtxoQueryResult> =
-                        <_ as UtxoStore>::query_utxos(wallet.store(), &[fee_filter]).await?;
-
-                    Some(fee_results
-                        .into_iter()
-                        .next()
-                        .and_then(|r| match r {
-                            UtxoQueryResult::Found(entries, _) => entries.into_iter().next(),
-                            UtxoQueryResult::InsufficientValue(entries, _) => {
-                                let available: u64 = entries.iter().filter_map(coin_store::UtxoEntry::value).sum();
-                                eprintln!(
-                                    "Insufficient LBTC for fee: have {available} sats. Try using 'merge' command first."
-                                );
-                                None
-                            }
-                            UtxoQueryResult::Empty => None,
-                        })
-                        .ok_or_else(|| Error::Config("No LBTC UTXO found to pay fee".to_string()))?)
-                };
-
-                let actual_fee = estimate_fee_signed(
-                    fee.as_ref(),
-                    config.get_fee_rate(),
-                    |f| build_transfer_pset(f, fee_entry_opt.as_ref()),
-                    |tx, utxos| sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0),
-                )?;
-
-                if is_native && total_asset_value < *amount + actual_fee {
-                    return Err(Error::Config(format!(
-                        "Insufficient funds: have {total_asset_value} sats, need {} sats (amount + fee)",
-                        *amount + actual_fee
-                    )));
-                }
-
-                if !is_native && let Some(ref fee_e) = fee_entry_opt {
-                    let Some(fee_input_value) = fee_e.value() else {
-                        return Err(Error::Config("Unexpected confidential value".to_string()));
-                    };
-                    if fee_input_value < actual_fee {
-                        return Err(Error::Config(format!(
-                            "Fee UTXO value ({fee_input_value} sats) is less than required fee ({actual_fee} sats)"
-                        )));
-                    }
-                }
-
-                let (pst, utxos) = build_transfer_pset(actual_fee, fee_entry_opt.as_ref())?;
-
-                if is_native {
-                    println!("Transferring {amount} sats LBTC to {to}");
-                } else {
-                    println!("Transferring {amount} units of asset {target_asset} to {to}");
+                    println!(
+                        "Transferring {} (asset {target_asset}) to {to}",
+                        assets.format_amount(amount, &target_asset)
+                    );
                 }
 
-                let tx = pst.extract_tx()?;
-                let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 0)?;
-
-                match broadcast {
-                    false => {
-                        println!("{}", tx.serialize().to_lower_hex_string());
-                    }
-                    true => {
-                        cli_helper::explorer::broadcast_tx(&tx).await?;
-
-                        println!("Broadcasted: {}", tx.txid());
-
-                        wallet.store().insert_transaction(&tx, HashMap::default()).await?;
-                    }
-                }
+                let to = if *explicit { to.to_unconfidential() } else { to.clone() };
+                let payments = vec![(to, target_asset, amount)];
+                self.send_payments(&config, &assets, payments, fee.as_ref(), *broadcast, coin_control)
+                    .await?;
             }
-            TxCommand::IssueAsset { amount, fee, broadcast } => {
+            TxCommand::IssueAsset {
+                amount,
+                fee,
+                broadcast,
+                coin_control,
+            } => {
                 let wallet = self.get_wallet(&config).await?;
                 let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
 
-                let fee_filter = coin_store::UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(script_pubkey)
-                    .required_value(fee.unwrap_or(PLACEHOLDER_FEE));
+                let fee_filter = coin_control.filter(
+                    coin_store::UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(script_pubkey)
+                        .required_value(fee.unwrap_or(PLACEHOLDER_FEE)),
+                );
 
                 let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[fee_filter]).await?;
 
@@ -492,7 +306,10 @@ impl Cli {
                             contracts::sdk::issue_asset(&blinding_keypair.public_key(), fee_utxo.clone(), *amount, f)?;
                         Ok((pst, vec![fee_utxo.1.clone()]))
                     },
-                    |tx, utxos| sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0),
+                    |tx, utxos| {
+                        let tx = coin_control.redirect_change(tx, utxos);
+                        sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0)
+                    },
                 )?;
 
                 if let Some(fee_input_value) = fee_entry.value()
@@ -516,8 +333,8 @@ impl Cli {
                     contract_hash,
                 );
 
-                let tx = pst.extract_tx()?;
                 let utxos = vec![fee_utxo.1];
+                let tx = coin_control.redirect_change(pst.extract_tx()?, &utxos);
 
                 let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 0)?;
 
@@ -530,7 +347,7 @@ impl Cli {
                         println!("{}", tx.serialize().to_lower_hex_string());
                     }
                     true => {
-                        cli_helper::explorer::broadcast_tx(&tx).await?;
+                        config.esplora().broadcast_transaction(&tx)?;
 
                         println!("Broadcasted: {}", tx.txid());
 
@@ -545,9 +362,11 @@ impl Cli {
                 amount,
                 fee,
                 broadcast,
+                coin_control,
             } => {
                 let wallet = self.get_wallet(&config).await?;
                 let script_pubkey = wallet.signer().p2pk_address(config.network())?.script_pubkey();
+                let coin_control = CoinControl::new(coin_control, &wallet, config.network()).await?;
 
                 let asset_filter = coin_store::UtxoFilter::new()
                     .asset_id(*asset_id)
@@ -579,16 +398,20 @@ impl Cli {
                     .0
                     .ok_or_else(|| Error::Config("Missing entropy".to_string()))?;
 
-                let token_filter = coin_store::UtxoFilter::new()
-                    .asset_id(token_id)
-                    .script_pubkey(script_pubkey.clone())
-                    .limit(1);
+                let token_filter = coin_control.filter(
+                    coin_store::UtxoFilter::new()
+                        .asset_id(token_id)
+                        .script_pubkey(script_pubkey.clone())
+                        .limit(1),
+                );
 
-                let fee_filter = coin_store::UtxoFilter::new()
-                    .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
-                    .script_pubkey(script_pubkey)
-                    .required_value(fee.unwrap_or(PLACEHOLDER_FEE))
-                    .limit(1);
+                let fee_filter = coin_control.filter(
+                    coin_store::UtxoFilter::new()
+                        .asset_id(*LIQUID_TESTNET_BITCOIN_ASSET)
+                        .script_pubkey(script_pubkey)
+                        .required_value(fee.unwrap_or(PLACEHOLDER_FEE))
+                        .limit(1),
+                );
 
                 let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[token_filter, fee_filter]).await?;
 
@@ -634,7 +457,10 @@ impl Cli {
                         )?;
                         Ok((pst, vec![token_utxo.1.clone(), fee_utxo.1.clone()]))
                     },
-                    |tx, utxos| sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0),
+                    |tx, utxos| {
+                        let tx = coin_control.redirect_change(tx, utxos);
+                        sign_p2pk_inputs(tx, utxos, &wallet, config.network(), 0)
+                    },
                 )?;
 
                 if let Some(fee_input_value) = fee_entry.value()
@@ -655,8 +481,8 @@ impl Cli {
                     entropy,
                 )?;
 
-                let tx = pst.extract_tx()?;
                 let utxos = vec![token_utxo.1, fee_utxo.1];
+                let tx = coin_control.redirect_change(pst.extract_tx()?, &utxos);
 
                 let tx = sign_p2pk_inputs(tx, &utxos, &wallet, config.network(), 0)?;
 
@@ -667,7 +493,7 @@ impl Cli {
                         println!("{}", tx.serialize().to_lower_hex_string());
                     }
                     true => {
-                        cli_helper::explorer::broadcast_tx(&tx).await?;
+                        config.esplora().broadcast_transaction(&tx)?;
                         println!("Broadcasted: {}", tx.txid());
 
                         let mut blinder_keys = HashMap::new();
@@ -676,8 +502,82 @@ impl Cli {
                     }
                 }
             }
+            TxCommand::BatchTransfer {
+                recipients,
+                file,
+                explicit,
+                fee,
+                broadcast,
+                coin_control,
+            } => {
+                let assets = AssetRegistry::load(&config.database_path()).await;
+                let payments = batch_payments(&assets, recipients, file.as_deref(), *explicit)?;
+                self.send_payments(&config, &assets, payments, fee.as_ref(), *broadcast, coin_control)
+                    .await?;
+            }
+            TxCommand::Consolidate {
+                fee_rate,
+                min_utxos,
+                broadcast,
+            } => {
+                self.run_consolidate(&config, *fee_rate, *min_utxos, *broadcast).await?;
+            }
         }
 
         Ok(())
     }
 }
+
+/// Build a transaction merging `entries` of `asset` into one output to `script_pubkey`.
+///
+/// LBTC pays the fee out of the merged value; other assets need `fee_entry`, whose change returns to
+/// `script_pubkey`.
+pub(crate) fn build_merge_pset(
+    entries: &[UtxoEntry],
+    fee_entry: Option<&UtxoEntry>,
+    script_pubkey: &Script,
+    asset: AssetId,
+    fee: u64,
+) -> Result<(PartiallySignedTransaction, Vec<TxOut>), Error> {
+    let total_asset_value: u64 = entries.iter().filter_map(UtxoEntry::value).sum();
+
+    let mut pst = PartiallySignedTransaction::new_v2();
+    let mut utxos: Vec<TxOut> = entries
+        .iter()
+        .map(|e| {
+            let mut input = Input::from_prevout(*e.outpoint());
+            input.witness_utxo = Some(e.txout().clone());
+            pst.add_input(input);
+            e.txout().clone()
+        })
+        .collect();
+
+    if asset == *LIQUID_TESTNET_BITCOIN_ASSET {
+        let output_value = total_asset_value
+            .checked_sub(fee)
+            .ok_or_else(|| Error::Config("Fee exceeds total UTXO value".to_string()))?;
+        pst.add_output(Output::new_explicit(script_pubkey.clone(), output_value, asset, None));
+    } else if let Some(fee_e) = fee_entry {
+        let Some(fee_input_value) = fee_e.value() else {
+            return Err(Error::Config("Unexpected confidential value".to_string()));
+        };
+        let mut fee_input = Input::from_prevout(*fee_e.outpoint());
+        fee_input.witness_utxo = Some(fee_e.txout().clone());
+        pst.add_input(fee_input);
+        utxos.push(fee_e.txout().clone());
+
+        pst.add_output(Output::new_explicit(script_pubkey.clone(), total_asset_value, asset, None));
+
+        if fee_input_value > fee {
+            pst.add_output(Output::new_explicit(
+                script_pubkey.clone(),
+                fee_input_value - fee,
+                *LIQUID_TESTNET_BITCOIN_ASSET,
+                None,
+            ));
+        }
+    }
+
+    pst.add_output(Output::from_txout(TxOut::new_fee(fee, *LIQUID_TESTNET_BITCOIN_ASSET)));
+    Ok((pst, utxos))
+}
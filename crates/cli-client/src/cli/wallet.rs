@@ -1,11 +1,18 @@
-use crate::cli::tables::{UtxoDisplay, display_utxo_table};
+use crate::assets::AssetRegistry;
+use crate::cli::interactive::{format_utc, parse_time_bound, truncate_with_ellipsis};
+use crate::cli::report::format_signed;
+use crate::cli::tables::{TransactionHistoryDisplay, UtxoDisplay, display_transaction_history_table, display_utxo_table};
 use crate::cli::{Cli, WalletCommand};
 use crate::config::Config;
 use crate::error::Error;
 use crate::wallet::Wallet;
 
-use coin_store::UtxoStore;
+use coin_store::{BlindingKeyStore, FreezeStore, LabelRef, LabelStore, TransactionStore, UtxoStore};
 use simplicityhl::elements::bitcoin::secp256k1;
+use simplicityhl::elements::secp256k1_zkp::{Keypair, SECP256K1};
+use simplicityhl::elements::{OutPoint, TxOut};
+use simplicityhl_core::LIQUID_TESTNET_BITCOIN_ASSET;
+use std::collections::{HashMap, HashSet};
 
 impl Cli {
     pub(crate) async fn run_wallet(&self, config: Config, command: &WalletCommand) -> Result<(), Error> {
@@ -21,21 +28,35 @@ impl Cli {
 
                 Ok(())
             }
-            WalletCommand::Address => {
+            WalletCommand::Address { confidential } => {
                 let wallet = self.get_wallet(&config).await?;
 
                 wallet.signer().print_details()?;
 
+                if *confidential {
+                    let address = wallet.signer().p2pk_address(config.network())?;
+                    let index = wallet.store().next_receive_index(&address.script_pubkey()).await?;
+                    let blinding_key = wallet.signer().receive_blinding_key(&address.script_pubkey(), index)?;
+
+                    let address = address.to_confidential(blinding_key.public_key(SECP256K1));
+                    println!("Confidential Address #{index}: {address}");
+                }
+
                 Ok(())
             }
             WalletCommand::Balance => {
                 let wallet = self.get_wallet(&config).await?;
+                let assets = AssetRegistry::load(&config.database_path()).await;
 
                 let filter = coin_store::UtxoFilter::new()
-                    .script_pubkey(wallet.signer().p2pk_address(config.network())?.script_pubkey());
+                    .script_pubkey(wallet.signer().p2pk_address(config.network())?.script_pubkey())
+                    .include_frozen();
                 let results = <_ as UtxoStore>::query_utxos(wallet.store(), &[filter]).await?;
+                let frozen: HashSet<OutPoint> =
+                    wallet.store().list_frozen().await?.into_iter().map(|f| f.outpoint).collect();
 
-                let mut balances: std::collections::HashMap<simplicityhl::elements::AssetId, u64> =
+                // Total and frozen amount per asset
+                let mut balances: std::collections::HashMap<simplicityhl::elements::AssetId, (u64, u64)> =
                     std::collections::HashMap::new();
 
                 if let Some(coin_store::UtxoQueryResult::Found(entries, _)) = results.into_iter().next() {
@@ -44,37 +65,77 @@ impl Cli {
                             continue;
                         };
 
-                        *balances.entry(asset).or_insert(0) += value;
+                        let balance = balances.entry(asset).or_insert((0, 0));
+                        balance.0 += value;
+                        if frozen.contains(entry.outpoint()) {
+                            balance.1 += value;
+                        }
                     }
                 }
 
                 if balances.is_empty() {
                     println!("No UTXOs found");
                 } else {
-                    for (asset, value) in &balances {
-                        println!("{asset}: {value}");
+                    for (asset, (value, frozen_value)) in &balances {
+                        if *frozen_value > 0 {
+                            println!(
+                                "{asset}: {} ({} frozen)",
+                                assets.format_amount(*value, asset),
+                                assets.format_amount(*frozen_value, asset)
+                            );
+                        } else {
+                            println!("{asset}: {}", assets.format_amount(*value, asset));
+                        }
                     }
                 }
                 Ok(())
             }
             WalletCommand::Utxos => {
                 let wallet = self.get_wallet(&config).await?;
+                let assets = AssetRegistry::load(&config.database_path()).await;
 
-                let filter = coin_store::UtxoFilter::new();
+                let filter = coin_store::UtxoFilter::new().include_frozen();
                 let results = wallet.store().query_utxos(&[filter]).await?;
+                let labels: HashMap<LabelRef, String> = wallet.store().list_labels().await?.into_iter().collect();
+                let frozen: HashMap<OutPoint, Option<String>> = wallet
+                    .store()
+                    .list_frozen()
+                    .await?
+                    .into_iter()
+                    .map(|f| (f.outpoint, f.reason))
+                    .collect();
 
                 if let Some(coin_store::UtxoQueryResult::Found(entries, _)) = results.into_iter().next() {
                     let displays: Vec<UtxoDisplay> = entries
                         .iter()
                         .map(|entry| {
                             let (asset, value) = match (entry.asset(), entry.value()) {
-                                (Some(a), Some(v)) => (a.to_string(), v.to_string()),
+                                (Some(a), Some(v)) => (a.to_string(), assets.format_amount(v, &a)),
                                 _ => ("Confidential".to_string(), "Confidential".to_string()),
                             };
+                            // An output's own label wins over the labels of its transaction and address.
+                            let outpoint = *entry.outpoint();
+                            let label = [
+                                LabelRef::Output(outpoint),
+                                LabelRef::Tx(outpoint.txid),
+                                LabelRef::Script(entry.txout().script_pubkey.clone()),
+                            ]
+                            .iter()
+                            .find_map(|target| labels.get(target).cloned())
+                            .unwrap_or_default();
+
+                            let frozen = match frozen.get(&outpoint) {
+                                Some(Some(reason)) => reason.clone(),
+                                Some(None) => "yes".to_string(),
+                                None => String::new(),
+                            };
+
                             UtxoDisplay {
-                                outpoint: entry.outpoint().to_string(),
+                                outpoint: outpoint.to_string(),
                                 asset,
                                 value,
+                                label,
+                                frozen,
                             }
                         })
                         .collect();
@@ -89,20 +150,31 @@ impl Cli {
             WalletCommand::Import { outpoint, blinding_key } => {
                 let wallet = self.get_wallet(&config).await?;
 
-                let txout = cli_helper::explorer::fetch_utxo(*outpoint).await?;
+                let tx = config.esplora().fetch_transaction(outpoint.txid)?;
+                let vout = outpoint.vout as usize;
 
-                let blinder = match blinding_key {
-                    Some(key_hex) => {
-                        let bytes: [u8; secp256k1::constants::SECRET_KEY_SIZE] = hex::decode(key_hex)
-                            .map_err(|e| Error::Config(format!("Invalid blinding key hex: {e}")))?
-                            .try_into()
-                            .map_err(|_| Error::Config("Blinding key must be 32 bytes".to_string()))?;
-                        Some(bytes)
-                    }
-                    None => None,
-                };
+                if tx.output.get(vout).is_none_or(TxOut::is_fee) {
+                    return Err(Error::Config(format!("Output {outpoint} not found")));
+                }
+
+                let mut blinder_keys = HashMap::new();
+                if let Some(key_hex) = blinding_key {
+                    let bytes: [u8; secp256k1::constants::SECRET_KEY_SIZE] = hex::decode(key_hex)
+                        .map_err(|e| Error::Config(format!("Invalid blinding key hex: {e}")))?
+                        .try_into()
+                        .map_err(|_| Error::Config("Blinding key must be 32 bytes".to_string()))?;
+                    let keypair = Keypair::from_seckey_slice(SECP256K1, &bytes)
+                        .map_err(|e| Error::Config(format!("Invalid blinding key: {e}")))?;
+                    blinder_keys.insert(vout, keypair);
+                }
 
-                wallet.store().insert(*outpoint, txout, blinder).await?;
+                wallet.store().insert_transaction(&tx, blinder_keys).await?;
+
+                if wallet.store().get_output(*outpoint).await?.is_none() {
+                    return Err(Error::Config(format!(
+                        "Could not import {outpoint}: output is confidential and the blinding key does not unblind it"
+                    )));
+                }
 
                 println!("Imported {outpoint}");
 
@@ -117,6 +189,109 @@ impl Cli {
 
                 Ok(())
             }
+            WalletCommand::Freeze { outpoint, reason } => {
+                let wallet = self.get_wallet(&config).await?;
+
+                wallet.store().freeze(*outpoint, reason.as_deref()).await?;
+
+                println!("Froze {outpoint}; it will only be spent when passed with --utxo");
+
+                Ok(())
+            }
+            WalletCommand::Unfreeze { outpoint } => {
+                let wallet = self.get_wallet(&config).await?;
+
+                if wallet.store().unfreeze(*outpoint).await? {
+                    println!("Unfroze {outpoint}");
+                } else {
+                    println!("{outpoint} is not frozen");
+                }
+
+                Ok(())
+            }
+            WalletCommand::History { asset, from, to } => {
+                self.run_wallet_history(config, asset.as_deref(), from.as_deref(), to.as_deref())
+                    .await
+            }
+        }
+    }
+
+    async fn run_wallet_history(
+        &self,
+        config: Config,
+        asset: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<(), Error> {
+        let assets = AssetRegistry::load(&config.database_path()).await;
+        let asset = asset
+            .map(|value| {
+                assets.resolve(value).ok_or_else(|| {
+                    Error::Config(format!("Unknown asset '{value}'; use a registered ticker or an asset id"))
+                })
+            })
+            .transpose()?;
+        let from = from.map(parse_time_bound).transpose()?;
+        let to = to.map(parse_time_bound).transpose()?;
+
+        let wallet = self.get_wallet(&config).await?;
+        let store = wallet.store();
+
+        // Confirmation moves a transaction to its block time, so refresh before filtering by date.
+        let esplora = config.esplora();
+        for entry in store.list_transactions(asset, None, None).await? {
+            if entry.block_height.is_some() {
+                continue;
+            }
+
+            match esplora.fetch_transaction_status(entry.txid) {
+                Ok(status) => {
+                    if let (true, Some(height), Some(time)) = (status.confirmed, status.block_height, status.block_time)
+                        && let Ok(height) = u32::try_from(height)
+                    {
+                        store.set_transaction_status(entry.txid, height, time).await?;
+                    }
+                }
+                Err(e) => tracing::debug!("Could not fetch status of {}: {e}", entry.txid),
+            }
         }
+
+        let entries = store.list_transactions(asset, from, to).await?;
+
+        let displays: Vec<TransactionHistoryDisplay> = entries
+            .iter()
+            .map(|entry| TransactionHistoryDisplay {
+                date: format_utc(entry.timestamp),
+                txid: entry.txid.to_string(),
+                status: entry
+                    .block_height
+                    .map_or_else(|| "Unconfirmed".to_string(), |height| format!("Block {height}")),
+                changes: entry
+                    .deltas
+                    .iter()
+                    .filter(|(id, _)| asset.is_none_or(|asset| asset == **id))
+                    .map(|(id, delta)| {
+                        format!(
+                            "{} {}",
+                            format_signed(&assets, i128::from(*delta), id),
+                            assets.label(id)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                fee: entry.fee.map_or_else(String::new, |fee| {
+                    assets.format_amount(fee, &LIQUID_TESTNET_BITCOIN_ASSET)
+                }),
+                contract: entry
+                    .taproot_pubkey_gen
+                    .as_deref()
+                    .map_or_else(String::new, |tpg| truncate_with_ellipsis(tpg, 20)),
+            })
+            .collect();
+
+        display_transaction_history_table(&displays);
+        println!("Total: {} transaction(s)", entries.len());
+
+        Ok(())
     }
 }
@@ -0,0 +1,298 @@
+use crate::cli::CoinControlArgs;
+use crate::error::Error;
+use crate::wallet::Wallet;
+
+use std::collections::{BTreeMap, HashSet};
+
+use coin_store::{UtxoFilter, UtxoQueryResult, UtxoStore};
+use simplicityhl::elements::confidential::{Asset, Value};
+use simplicityhl::elements::{AssetId, OutPoint, Script, Transaction, TxOut};
+use simplicityhl_core::SimplicityNetwork;
+
+/// Coin selection overrides of a spending command, checked against the store.
+#[derive(Debug, Clone, Default)]
+pub struct CoinControl {
+    include: Vec<OutPoint>,
+    exclude: Vec<OutPoint>,
+    wallet_script: Script,
+    change_script: Option<Script>,
+}
+
+impl CoinControl {
+    /// Check `--utxo`, `--exclude-utxo` and `--change-address` against the wallet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a given output is unknown, not the wallet's or already spent,
+    /// or if the change address is confidential or for another network.
+    pub async fn new(args: &CoinControlArgs, wallet: &Wallet, network: SimplicityNetwork) -> Result<Self, Error> {
+        let wallet_address = wallet.signer().p2pk_address(network)?;
+        let wallet_script = wallet_address.script_pubkey();
+        let store = wallet.store();
+
+        if let Some(outpoint) = args.utxos.iter().find(|outpoint| args.exclude_utxos.contains(outpoint)) {
+            return Err(Error::Config(format!("{outpoint} is given to both --utxo and --exclude-utxo")));
+        }
+
+        if !args.utxos.is_empty() {
+            let filter = UtxoFilter::new()
+                .outpoints(args.utxos.clone())
+                .script_pubkey(wallet_script.clone())
+                .include_frozen();
+            let spendable: HashSet<OutPoint> = match <_ as UtxoStore>::query_utxos(store, &[filter]).await?.pop() {
+                Some(UtxoQueryResult::Found(entries, _) | UtxoQueryResult::InsufficientValue(entries, _)) => {
+                    entries.iter().map(|entry| *entry.outpoint()).collect()
+                }
+                Some(UtxoQueryResult::Empty) | None => HashSet::new(),
+            };
+
+            for outpoint in args.utxos.iter().filter(|outpoint| !spendable.contains(outpoint)) {
+                let reason = match store.get_output(*outpoint).await? {
+                    None => "is unknown; run `sync utxos` if it is new",
+                    Some((script, _, _)) if script != wallet_script => "is not a wallet output",
+                    Some(_) => "is already spent",
+                };
+                return Err(Error::Config(format!("--utxo {outpoint} {reason}")));
+            }
+        }
+
+        for outpoint in &args.exclude_utxos {
+            if store.get_output(*outpoint).await?.is_none() {
+                return Err(Error::Config(format!(
+                    "--exclude-utxo {outpoint} is unknown; run `sync utxos` if it is new"
+                )));
+            }
+        }
+
+        let change_script = match &args.change_address {
+            Some(address) if address.blinding_pubkey.is_some() => {
+                return Err(Error::Config(
+                    "Confidential change addresses are not supported; pass the unconfidential address".to_string(),
+                ));
+            }
+            Some(address) if address.params != wallet_address.params => {
+                return Err(Error::Config(format!("Change address {address} is for another network")));
+            }
+            Some(address) => Some(address.script_pubkey()),
+            None => None,
+        };
+
+        Ok(Self {
+            include: args.utxos.clone(),
+            exclude: args.exclude_utxos.clone(),
+            wallet_script,
+            change_script,
+        })
+    }
+
+    /// Restrict a filter selecting wallet funds to the allowed outputs. Outputs given with `--utxo`
+    /// may be spent even when frozen.
+    ///
+    /// Only for coins the command picks itself: contract outputs and tokens chosen by their own
+    /// flags are not subject to coin control.
+    #[must_use]
+    pub fn filter(&self, filter: UtxoFilter) -> UtxoFilter {
+        let filter = filter.exclude_outpoints(self.exclude.iter().copied());
+
+        if self.include.is_empty() {
+            filter
+        } else {
+            filter.outpoints(self.include.clone()).include_frozen()
+        }
+    }
+
+    /// Send the change of `tx` built by a contract builder to the change address, if one was given.
+    ///
+    /// These builders do not report their change, so it is taken to be the explicit outputs back to
+    /// the wallet in an asset the wallet's own inputs spend, up to what those inputs have left after
+    /// the fee and the payments that contract inputs do not cover. Outputs are taken from the last,
+    /// where the builders put change, so payouts from contract inputs stay with the wallet.
+    #[must_use]
+    pub fn redirect_change(&self, tx: Transaction, utxos: &[TxOut]) -> Transaction {
+        if self.change_script.is_none() {
+            return tx;
+        }
+
+        let outputs = self.change_outputs(&tx, utxos);
+        self.redirect_outputs(tx, &outputs)
+    }
+
+    /// Send the change outputs of `tx` at `outputs`, as reported by the builder, to the change
+    /// address, if one was given. Blinded change stays with the wallet.
+    #[must_use]
+    pub fn redirect_outputs(&self, mut tx: Transaction, outputs: &[usize]) -> Transaction {
+        let Some(change_script) = &self.change_script else {
+            return tx;
+        };
+
+        for index in outputs {
+            if let Some(output) = tx.output.get_mut(*index)
+                && output.script_pubkey == self.wallet_script
+                && output.asset.is_explicit()
+                && output.value.is_explicit()
+            {
+                output.script_pubkey = change_script.clone();
+            }
+        }
+
+        tx
+    }
+
+    /// Indices of the outputs of `tx` that return the wallet's own inputs as change.
+    fn change_outputs(&self, tx: &Transaction, utxos: &[TxOut]) -> Vec<usize> {
+        let mut wallet_in: BTreeMap<AssetId, u64> = BTreeMap::new();
+        let mut contract_in: BTreeMap<AssetId, u64> = BTreeMap::new();
+        for utxo in utxos {
+            if let (Asset::Explicit(asset), Value::Explicit(value)) = (utxo.asset, utxo.value) {
+                let inputs = if utxo.script_pubkey == self.wallet_script {
+                    &mut wallet_in
+                } else {
+                    &mut contract_in
+                };
+                *inputs.entry(asset).or_default() += value;
+            }
+        }
+
+        let mut fees: BTreeMap<AssetId, u64> = BTreeMap::new();
+        let mut payments: BTreeMap<AssetId, u64> = BTreeMap::new();
+        for output in &tx.output {
+            if let (Asset::Explicit(asset), Value::Explicit(value)) = (output.asset, output.value) {
+                if output.is_fee() {
+                    *fees.entry(asset).or_default() += value;
+                } else if output.script_pubkey != self.wallet_script {
+                    *payments.entry(asset).or_default() += value;
+                }
+            }
+        }
+
+        // The fee is paid from the wallet's inputs; other payments from contract inputs first.
+        let mut budget: BTreeMap<AssetId, u64> = wallet_in
+            .into_iter()
+            .map(|(asset, value)| {
+                let fee = fees.get(&asset).copied().unwrap_or_default();
+                let paid = payments.get(&asset).copied().unwrap_or_default();
+                let covered = contract_in.get(&asset).copied().unwrap_or_default();
+                let spent = fee.saturating_add(paid.saturating_sub(covered));
+                (asset, value.saturating_sub(spent))
+            })
+            .collect();
+
+        let mut outputs = Vec::new();
+        for (index, output) in tx.output.iter().enumerate().rev() {
+            let (Asset::Explicit(asset), Value::Explicit(value)) = (output.asset, output.value) else {
+                continue;
+            };
+            if output.script_pubkey != self.wallet_script {
+                continue;
+            }
+            if let Some(remaining) = budget.get_mut(&asset)
+                && *remaining >= value
+            {
+                *remaining -= value;
+                outputs.push(index);
+            }
+        }
+
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use simplicityhl::elements::confidential::Nonce;
+    use simplicityhl::elements::{LockTime, TxOutWitness};
+
+    fn txout(asset: AssetId, value: u64, script_pubkey: &Script) -> TxOut {
+        TxOut {
+            asset: Asset::Explicit(asset),
+            value: Value::Explicit(value),
+            nonce: Nonce::Null,
+            script_pubkey: script_pubkey.clone(),
+            witness: TxOutWitness::default(),
+        }
+    }
+
+    #[test]
+    fn test_redirect_change() {
+        let lbtc = AssetId::from_slice(&[1; 32]).unwrap();
+        let token = AssetId::from_slice(&[2; 32]).unwrap();
+        let wallet_script = Script::from(vec![0x51; 34]);
+        let contract_script = Script::from(vec![0x52; 34]);
+        let change_script = Script::from(vec![0x53; 22]);
+
+        let coin_control = CoinControl {
+            wallet_script: wallet_script.clone(),
+            change_script: Some(change_script.clone()),
+            ..CoinControl::default()
+        };
+
+        // A contract pays 5000 LBTC out; the wallet funds the 100 sat fee.
+        let utxos = [txout(lbtc, 5000, &contract_script), txout(lbtc, 1000, &wallet_script)];
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: Vec::new(),
+            output: vec![
+                txout(lbtc, 5000, &wallet_script),
+                txout(token, 10, &wallet_script),
+                txout(lbtc, 900, &wallet_script),
+                TxOut::new_fee(100, lbtc),
+            ],
+        };
+
+        let tx = coin_control.redirect_change(tx, &utxos);
+        assert_eq!(tx.output[0].script_pubkey, wallet_script);
+        assert_eq!(tx.output[1].script_pubkey, wallet_script);
+        assert_eq!(tx.output[2].script_pubkey, change_script);
+        assert!(tx.output[3].is_fee());
+
+        let without_change = CoinControl {
+            wallet_script: wallet_script.clone(),
+            ..CoinControl::default()
+        };
+        let tx = without_change.redirect_change(tx, &utxos);
+        assert_eq!(tx.output[2].script_pubkey, change_script);
+    }
+
+    #[test]
+    fn test_redirect_change_smaller_payout() {
+        let lbtc = AssetId::from_slice(&[1; 32]).unwrap();
+        let wallet_script = Script::from(vec![0x51; 34]);
+        let contract_script = Script::from(vec![0x52; 34]);
+        let change_script = Script::from(vec![0x53; 22]);
+
+        let coin_control = CoinControl {
+            wallet_script: wallet_script.clone(),
+            change_script: Some(change_script.clone()),
+            ..CoinControl::default()
+        };
+
+        // A contract pays 50 LBTC out, less than the 100 sat fee funded by the wallet's 1000.
+        let utxos = [txout(lbtc, 50, &contract_script), txout(lbtc, 1000, &wallet_script)];
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: Vec::new(),
+            output: vec![
+                txout(lbtc, 50, &wallet_script),
+                txout(lbtc, 900, &wallet_script),
+                TxOut::new_fee(100, lbtc),
+            ],
+        };
+
+        let tx = coin_control.redirect_change(tx, &utxos);
+        assert_eq!(tx.output[0].script_pubkey, wallet_script);
+        assert_eq!(tx.output[1].script_pubkey, change_script);
+
+        // Builders that report their change have exactly those outputs redirected.
+        let tx = Transaction {
+            output: vec![txout(lbtc, 50, &wallet_script), txout(lbtc, 900, &wallet_script)],
+            ..tx
+        };
+        let tx = coin_control.redirect_outputs(tx, &[0]);
+        assert_eq!(tx.output[0].script_pubkey, change_script);
+        assert_eq!(tx.output[1].script_pubkey, wallet_script);
+    }
+}
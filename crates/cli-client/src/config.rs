@@ -1,9 +1,12 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use crate::error::Error;
-use crate::explorer;
+use crate::explorer::{self, EsploraBackend};
 use crate::fee::DEFAULT_FEE_RATE;
+use crate::price_fetcher::PriceSourceKind;
+use crate::pricing::PricingParams;
 use options_relay::NostrRelayConfig;
 use serde::{Deserialize, Serialize};
 use simplicityhl_core::SimplicityNetwork;
@@ -11,8 +14,16 @@ use simplicityhl_core::SimplicityNetwork;
 const DEFAULT_CONFIG_PATH: &str = "config.toml";
 const DEFAULT_DATA_DIR: &str = ".data";
 const DEFAULT_DATABASE_FILENAME: &str = "coins.db";
+const DEFAULT_PRICE_CACHE_FILENAME: &str = "price_cache.json";
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
 const DEFAULT_RELAY: &str = "wss://relay.damus.io";
+const DEFAULT_RETRY_COUNT: u32 = 3;
+const DEFAULT_MIN_ACKS: usize = 1;
+const DEFAULT_VOLATILITY: f64 = 0.6;
+const DEFAULT_PRICE_CACHE_TTL_SECS: u64 = 300;
+const DEFAULT_CONSOLIDATION_MIN_UTXOS: usize = 10;
+const DEFAULT_CONSOLIDATION_MAX_INPUTS: usize = 50;
+const DEFAULT_CONSOLIDATION_MAX_FEE_RATE: f32 = 10.0;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -24,6 +35,14 @@ pub struct Config {
     pub storage: StorageConfig,
     #[serde(default)]
     pub fee: FeeConfig,
+    #[serde(default)]
+    pub explorer: ExplorerConfig,
+    #[serde(default)]
+    pub pricing: PricingConfig,
+    #[serde(default)]
+    pub oracle: OracleConfig,
+    #[serde(default)]
+    pub consolidation: ConsolidationConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +75,12 @@ pub struct RelayConfig {
     pub urls: Vec<String>,
     #[serde(default = "default_timeout")]
     pub timeout_secs: u64,
+    /// How many times to retry publishing to relays that failed.
+    #[serde(default = "default_retry_count")]
+    pub retry_count: u32,
+    /// Minimum number of relays that must accept an event, otherwise the command fails.
+    #[serde(default = "default_min_acks")]
+    pub min_acks: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +103,66 @@ pub struct FeeConfig {
     pub fallback_rate: f32,
 }
 
+/// Esplora instance used for chain queries and broadcasts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplorerConfig {
+    /// Base URL of the Esplora HTTP API, without a trailing slash.
+    #[serde(default = "default_esplora_url")]
+    pub url: String,
+}
+
+/// Assumptions used to value options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingConfig {
+    /// Annualized volatility of the collateral price, e.g. 0.6 for 60%.
+    #[serde(default = "default_volatility")]
+    pub volatility: f64,
+    /// Annualized risk-free rate of the settlement asset.
+    #[serde(default)]
+    pub risk_free_rate: f64,
+}
+
+/// Spot price sources.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleConfig {
+    /// Sources queried for live prices; the median of those that answer is used.
+    #[serde(default = "default_price_sources")]
+    pub sources: Vec<PriceSourceKind>,
+    /// JSON file of `"BASE/QUOTE": price` entries, read by the `file` source.
+    #[serde(default)]
+    pub prices_file: Option<PathBuf>,
+    /// Fixed `"BASE/QUOTE" = price` entries that take precedence over every source.
+    #[serde(default)]
+    pub overrides: BTreeMap<String, f64>,
+    /// How long a fetched price is reused before the sources are queried again.
+    #[serde(default = "default_price_cache_ttl")]
+    pub cache_ttl_secs: u64,
+    /// Never query the network; rely on overrides, the prices file and cached prices.
+    #[serde(default)]
+    pub offline: bool,
+}
+
+/// When and how to merge small wallet outputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsolidationConfig {
+    /// Merge an asset once the wallet holds at least this many spendable outputs of it.
+    #[serde(default = "default_consolidation_min_utxos")]
+    pub min_utxos: usize,
+    /// Per-asset overrides of `min_utxos`, keyed by ticker or asset id.
+    #[serde(default)]
+    pub thresholds: BTreeMap<String, usize>,
+    /// Most outputs merged by one transaction.
+    #[serde(default = "default_consolidation_max_inputs")]
+    pub max_inputs: usize,
+    /// Consolidate at the end of `sync full` when the estimated fee rate is at most `max_fee_rate`.
+    /// Skipped when there is no live estimate.
+    #[serde(default)]
+    pub auto: bool,
+    /// Highest estimated fee rate in sats/kvb at which `sync full` consolidates.
+    #[serde(default = "default_consolidation_max_fee_rate")]
+    pub max_fee_rate: f32,
+}
+
 impl Config {
     pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
         let content = std::fs::read_to_string(path)?;
@@ -94,18 +179,40 @@ impl Config {
         self.storage.data_dir.join(DEFAULT_DATABASE_FILENAME)
     }
 
+    #[must_use]
+    pub fn price_cache_path(&self) -> PathBuf {
+        self.storage.data_dir.join(DEFAULT_PRICE_CACHE_FILENAME)
+    }
+
     #[must_use]
     pub const fn network(&self) -> SimplicityNetwork {
         self.network.name.network()
     }
 
+    /// Esplora client for the configured `[explorer]` URL.
+    #[must_use]
+    pub fn esplora(&self) -> EsploraBackend {
+        EsploraBackend::new(&self.explorer.url)
+    }
+
     /// Get fee rate from config or Esplora.
     /// Returns fee rate in sats/kvb.
     pub fn get_fee_rate(&self) -> f32 {
+        self.live_fee_rate().unwrap_or(self.fee.fallback_rate)
+    }
+
+    /// Fee rate estimated by Esplora in sats/kvb, or `None` if estimation is off or fails.
+    pub fn live_fee_rate(&self) -> Option<f32> {
         if self.fee.confirmation_target == 0 {
-            self.fee.fallback_rate
-        } else {
-            explorer::get_fee_rate(self.fee.confirmation_target).unwrap_or(self.fee.fallback_rate)
+            return None;
+        }
+
+        match self.esplora().get_fee_rate(self.fee.confirmation_target) {
+            Ok(rate) => Some(rate),
+            Err(e) => {
+                tracing::debug!("Fee estimation failed: {e}");
+                None
+            }
         }
     }
 }
@@ -119,6 +226,8 @@ impl RelayConfig {
         NostrRelayConfig::new(primary)
             .add_backup_relays(urls.map(String::as_str))
             .with_timeout(Duration::from_secs(self.timeout_secs))
+            .with_retry_count(self.retry_count)
+            .with_min_acks(self.min_acks)
     }
 }
 
@@ -135,6 +244,8 @@ impl Default for RelayConfig {
         Self {
             urls: default_relays(),
             timeout_secs: default_timeout(),
+            retry_count: default_retry_count(),
+            min_acks: default_min_acks(),
         }
     }
 }
@@ -147,6 +258,70 @@ impl Default for StorageConfig {
     }
 }
 
+impl Default for ExplorerConfig {
+    fn default() -> Self {
+        Self {
+            url: default_esplora_url(),
+        }
+    }
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            volatility: default_volatility(),
+            risk_free_rate: 0.0,
+        }
+    }
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        Self {
+            sources: default_price_sources(),
+            prices_file: None,
+            overrides: BTreeMap::new(),
+            cache_ttl_secs: default_price_cache_ttl(),
+            offline: false,
+        }
+    }
+}
+
+impl Default for ConsolidationConfig {
+    fn default() -> Self {
+        Self {
+            min_utxos: default_consolidation_min_utxos(),
+            thresholds: BTreeMap::new(),
+            max_inputs: default_consolidation_max_inputs(),
+            auto: false,
+            max_fee_rate: default_consolidation_max_fee_rate(),
+        }
+    }
+}
+
+impl PricingConfig {
+    /// Pricing parameters, with the volatility overridden from the command line if given.
+    ///
+    /// # Errors
+    /// Returns an error if the volatility is negative or not a finite number.
+    pub fn params(&self, volatility: Option<f64>) -> Result<PricingParams, Error> {
+        let (volatility, source) = match volatility {
+            Some(volatility) => (volatility, "--volatility"),
+            None => (self.volatility, "[pricing] volatility"),
+        };
+        if !volatility.is_finite() || volatility < 0.0 {
+            return Err(Error::Config(format!(
+                "Invalid {source} {volatility}: expected a non-negative number, e.g. 0.6 for 60%"
+            )));
+        }
+
+        Ok(PricingParams {
+            volatility,
+            risk_free_rate: self.risk_free_rate,
+        })
+    }
+}
+
 impl Default for FeeConfig {
     fn default() -> Self {
         Self {
@@ -168,10 +343,46 @@ const fn default_timeout() -> u64 {
     DEFAULT_TIMEOUT_SECS
 }
 
+const fn default_retry_count() -> u32 {
+    DEFAULT_RETRY_COUNT
+}
+
+const fn default_min_acks() -> usize {
+    DEFAULT_MIN_ACKS
+}
+
 const fn default_fallback_rate() -> f32 {
     DEFAULT_FEE_RATE
 }
 
+const fn default_volatility() -> f64 {
+    DEFAULT_VOLATILITY
+}
+
+fn default_price_sources() -> Vec<PriceSourceKind> {
+    vec![PriceSourceKind::Coingecko, PriceSourceKind::Kraken, PriceSourceKind::Bitstamp]
+}
+
+const fn default_price_cache_ttl() -> u64 {
+    DEFAULT_PRICE_CACHE_TTL_SECS
+}
+
+const fn default_consolidation_max_fee_rate() -> f32 {
+    DEFAULT_CONSOLIDATION_MAX_FEE_RATE
+}
+
+const fn default_consolidation_min_utxos() -> usize {
+    DEFAULT_CONSOLIDATION_MIN_UTXOS
+}
+
+const fn default_consolidation_max_inputs() -> usize {
+    DEFAULT_CONSOLIDATION_MAX_INPUTS
+}
+
+fn default_esplora_url() -> String {
+    explorer::DEFAULT_ESPLORA_URL.to_string()
+}
+
 fn default_data_dir() -> PathBuf {
     PathBuf::from(DEFAULT_DATA_DIR)
 }
@@ -0,0 +1,177 @@
+use crate::fee::calculate_fee;
+
+use std::collections::{BTreeMap, HashMap};
+
+use simplicityhl::elements::{AssetId, OutPoint};
+use simplicityhl_core::LIQUID_TESTNET_BITCOIN_ASSET;
+
+/// Approximate weight of a signed wallet input, including its Simplicity witness.
+const INPUT_WEIGHT: usize = 450;
+/// Weight of an explicit output to a wallet address.
+const OUTPUT_WEIGHT: usize = 320;
+/// Weight of the explicit fee output.
+const FEE_OUTPUT_WEIGHT: usize = 180;
+/// Version, lock time and counts.
+const TX_OVERHEAD_WEIGHT: usize = 50;
+
+/// What to consolidate and at which fee rate.
+#[derive(Debug, Clone)]
+pub struct ConsolidationPolicy {
+    /// Fee rate in sats/kvb the merges are planned at.
+    pub fee_rate: f32,
+    /// Merge an asset once there are at least this many spendable outputs of it.
+    pub min_utxos: usize,
+    /// Per-asset overrides of `min_utxos`.
+    pub thresholds: HashMap<AssetId, usize>,
+    /// Most outputs merged by one transaction.
+    pub max_inputs: usize,
+}
+
+/// One merge transaction of the plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedMerge {
+    pub asset: AssetId,
+    /// Outputs to merge, smallest first.
+    pub inputs: Vec<OutPoint>,
+    pub total: u64,
+    /// Estimated fee in LBTC.
+    pub fee: u64,
+    /// LBTC outputs worth less than the fee of spending them, left unspent.
+    pub dust: usize,
+}
+
+impl ConsolidationPolicy {
+    /// Fee of spending one more wallet input at this rate.
+    #[must_use]
+    pub fn input_cost(&self) -> u64 {
+        calculate_fee(INPUT_WEIGHT, self.fee_rate)
+    }
+
+    fn threshold(&self, asset: &AssetId) -> usize {
+        self.thresholds.get(asset).copied().unwrap_or(self.min_utxos).max(2)
+    }
+
+    /// Merges of other assets also spend an LBTC input for the fee and return its change.
+    fn merge_fee(&self, inputs: usize, is_lbtc: bool) -> u64 {
+        let weight = if is_lbtc {
+            TX_OVERHEAD_WEIGHT + inputs * INPUT_WEIGHT + OUTPUT_WEIGHT + FEE_OUTPUT_WEIGHT
+        } else {
+            TX_OVERHEAD_WEIGHT + (inputs + 1) * INPUT_WEIGHT + 2 * OUTPUT_WEIGHT + FEE_OUTPUT_WEIGHT
+        };
+        calculate_fee(weight, self.fee_rate)
+    }
+}
+
+/// Plan a merge for every asset holding at least its threshold of spendable outputs.
+///
+/// `utxos` are the wallet's explicit spendable outputs. Up to `max_inputs` of the smallest outputs
+/// of an asset are merged into one. LBTC outputs that would cost more to spend than they hold are
+/// dust and stay where they are. When other assets are merged, the largest LBTC output is kept out
+/// of the LBTC merge to pay their fees. The LBTC merge comes last.
+#[must_use]
+pub fn plan_consolidation(utxos: &[(OutPoint, AssetId, u64)], policy: &ConsolidationPolicy) -> Vec<PlannedMerge> {
+    let lbtc = *LIQUID_TESTNET_BITCOIN_ASSET;
+
+    let mut by_asset: BTreeMap<AssetId, Vec<(OutPoint, u64)>> = BTreeMap::new();
+    for (outpoint, asset, value) in utxos {
+        by_asset.entry(*asset).or_default().push((*outpoint, *value));
+    }
+    for outputs in by_asset.values_mut() {
+        outputs.sort_by_key(|(outpoint, value)| (*value, *outpoint));
+    }
+
+    let mut plan: Vec<PlannedMerge> = by_asset
+        .iter()
+        .filter(|(asset, _)| **asset != lbtc)
+        .filter_map(|(asset, outputs)| plan_merge(*asset, outputs, 0, policy))
+        .collect();
+
+    if let Some(outputs) = by_asset.get(&lbtc) {
+        let input_cost = policy.input_cost();
+        let (spendable, dust): (Vec<_>, Vec<_>) = outputs.iter().copied().partition(|(_, value)| *value > input_cost);
+        let reserved = usize::from(!plan.is_empty());
+        let spendable = &spendable[..spendable.len().saturating_sub(reserved)];
+        plan.extend(plan_merge(lbtc, spendable, dust.len(), policy));
+    }
+
+    plan
+}
+
+fn plan_merge(
+    asset: AssetId,
+    outputs: &[(OutPoint, u64)],
+    dust: usize,
+    policy: &ConsolidationPolicy,
+) -> Option<PlannedMerge> {
+    if outputs.len() < policy.threshold(&asset) {
+        return None;
+    }
+
+    let is_lbtc = asset == *LIQUID_TESTNET_BITCOIN_ASSET;
+    let inputs = &outputs[..outputs.len().min(policy.max_inputs.max(2))];
+    let total = inputs.iter().map(|(_, value)| value).sum();
+    let fee = policy.merge_fee(inputs.len(), is_lbtc);
+    if is_lbtc && total <= fee {
+        return None;
+    }
+
+    Some(PlannedMerge {
+        asset,
+        inputs: inputs.iter().map(|(outpoint, _)| *outpoint).collect(),
+        total,
+        fee,
+        dust,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use simplicityhl::elements::Txid;
+    use simplicityhl::elements::hashes::Hash;
+
+    fn utxos(asset: AssetId, values: &[u64], seed: u8) -> Vec<(OutPoint, AssetId, u64)> {
+        (0u32..)
+            .zip(values)
+            .map(|(vout, value)| (OutPoint::new(Txid::from_byte_array([seed; 32]), vout), asset, *value))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_consolidation() {
+        let lbtc = *LIQUID_TESTNET_BITCOIN_ASSET;
+        let token = AssetId::from_slice(&[7; 32]).unwrap();
+        let mut policy = ConsolidationPolicy {
+            fee_rate: 1000.0,
+            min_utxos: 4,
+            thresholds: HashMap::new(),
+            max_inputs: 3,
+        };
+        assert_eq!(policy.input_cost(), 113);
+
+        // Two LBTC outputs are dust; of the other four the three smallest are merged.
+        let mut wallet = utxos(lbtc, &[50, 100, 5000, 2000, 3000, 4000], 1);
+        let plan = plan_consolidation(&wallet, &policy);
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].asset, lbtc);
+        assert_eq!(plan[0].inputs, vec![wallet[3].0, wallet[4].0, wallet[5].0]);
+        assert_eq!(plan[0].total, 9000);
+        assert_eq!(plan[0].dust, 2);
+        assert!(plan[0].fee > 0);
+
+        // Tokens are never dust. Merging them keeps the largest LBTC output back, so too few are left.
+        wallet.extend(utxos(token, &[1, 1], 2));
+        assert_eq!(plan_consolidation(&wallet, &policy).len(), 1);
+        policy.thresholds.insert(token, 2);
+        let plan = plan_consolidation(&wallet, &policy);
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].asset, token);
+        assert_eq!(plan[0].total, 2);
+        assert_eq!(plan[0].dust, 0);
+
+        policy.fee_rate = 100.0;
+        policy.thresholds.clear();
+        assert!(plan_consolidation(&wallet, &policy).iter().all(|merge| merge.asset == lbtc && merge.dust == 0));
+    }
+}
@@ -17,9 +17,6 @@ pub enum Error {
     #[error("Store error: {0}")]
     Store(#[from] coin_store::StoreError),
 
-    #[error("Explorer error: {0}")]
-    Explorer(#[from] cli_helper::explorer::ExplorerError),
-
     #[error("Fetch transaction error: {0}")]
     FetchTransaction(#[from] crate::explorer::FetchTransactionError),
 
@@ -32,6 +29,9 @@ pub enum Error {
     #[error("PSET error: {0}")]
     Pset(#[from] simplicityhl::elements::pset::Error),
 
+    #[error("Blinding error: {0}")]
+    Blind(#[from] simplicityhl::elements::pset::PsetBlindError),
+
     #[error("Hex error: {0}")]
     Hex(#[from] hex::FromHexError),
 
@@ -53,6 +53,12 @@ pub enum Error {
     #[error("Event parse error: {0}")]
     EventParse(#[from] options_relay::ParseError),
 
+    #[error("Event cache error: {0}")]
+    EventCache(String),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
     #[error("Taproot pubkey generation error: {0}")]
     TaprootPubkeyGen(#[from] contracts::error::TaprootPubkeyGenError),
 
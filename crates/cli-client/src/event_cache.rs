@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use coin_store::{EventStore, NostrEventEntry, Store};
+use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
+use nostr::secp256k1::schnorr::Signature;
+use nostr::{Event, EventId, Kind, PublicKey, Tag, Timestamp};
+use options_relay::events::{filters, option_offer_coordinate};
+use options_relay::{
+    ACTION_COMPLETED, ActionCompletedEvent, OPTION_CREATED, OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY,
+    OptionCreatedEvent, OptionOfferCreatedEvent, ParseError, ReadOnlyClient, parse_option_offers, parse_options,
+};
+use simplicityhl_core::SimplicityNetwork;
+
+use crate::error::Error;
+
+/// Kinds stored in the local event cache.
+pub const CACHED_KINDS: [Kind; 5] = [
+    OPTION_CREATED,
+    OPTION_OFFER_CREATED,
+    OPTION_OFFER_CREATED_LEGACY,
+    ACTION_COMPLETED,
+    Kind::EventDeletion,
+];
+
+/// Offer kinds plus the deletion requests that can hide them.
+const OFFER_KINDS: [Kind; 3] = [OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY, Kind::EventDeletion];
+
+/// How far before the start of a fetch the next fetch from the same relay begins, so events
+/// that reach a relay late or are dated slightly in the past are still picked up.
+pub const CURSOR_SAFETY_WINDOW_SECS: u64 = 15 * 60;
+
+/// Fetch new events from every configured relay and store the verified ones.
+///
+/// Each relay keeps its own cursor, derived from when it was last fetched rather than from
+/// event timestamps, so a future-dated event cannot stall the cache. Relays that cannot be
+/// reached are skipped and keep their cursor.
+///
+/// Returns the number of newly cached events.
+pub async fn refresh_event_cache(store: &Store, client: &ReadOnlyClient) -> Result<usize, Error> {
+    let mut inserted = 0;
+    let mut reached = 0;
+
+    for relay_url in client.config().all_relays() {
+        match refresh_from_relay(store, client, relay_url).await {
+            Ok(count) => {
+                inserted += count;
+                reached += 1;
+            }
+            Err(e) => tracing::warn!("Could not refresh the event cache from {relay_url}: {e}"),
+        }
+    }
+
+    if reached == 0 {
+        return Err(Error::EventCache("No relay could be reached".to_string()));
+    }
+
+    Ok(inserted)
+}
+
+async fn refresh_from_relay(store: &Store, client: &ReadOnlyClient, relay_url: &str) -> Result<usize, Error> {
+    let fetched_at = Timestamp::now().as_secs();
+    let since = store
+        .relay_cursor(relay_url)
+        .await?
+        .map(|since| clamp_cursor(since, fetched_at));
+
+    // `since` is inclusive and overlaps the previous fetch; events already cached are deduplicated by id.
+    let (filter, deletions_filter) = match since {
+        Some(since) => (
+            filters::all_option_events_since(since),
+            filters::option_offer_deletions().since(since),
+        ),
+        None => (filters::all_option_events(), filters::option_offer_deletions()),
+    };
+
+    let mut events: Vec<Event> = client.fetch_events_from(relay_url, filter).await?.into_iter().collect();
+    events.extend(client.fetch_events_from(relay_url, deletions_filter).await?);
+
+    let mut entries = Vec::with_capacity(events.len());
+    for event in &events {
+        if let Err(e) = event.verify() {
+            tracing::debug!(event_id = %event.id, error = %e, "Skipping unverifiable event");
+            continue;
+        }
+
+        entries.push(event_to_entry(event)?);
+    }
+
+    let inserted = store.insert_events(&entries).await?;
+
+    let cursor = clamp_cursor(i64::MAX, fetched_at).as_secs();
+    store
+        .set_relay_cursor(relay_url, i64::try_from(cursor).unwrap_or(i64::MAX))
+        .await?;
+
+    tracing::debug!(
+        relay_url,
+        ?since,
+        fetched = entries.len(),
+        inserted,
+        "Refreshed NOSTR event cache"
+    );
+
+    Ok(inserted)
+}
+
+/// `since` bound for a fetch starting at `now`: the stored cursor, but never later than the
+/// safety window before `now`, so a cursor stored by a clock running ahead cannot skip events.
+fn clamp_cursor(since: i64, now: u64) -> Timestamp {
+    let latest = now.saturating_sub(CURSOR_SAFETY_WINDOW_SECS);
+    Timestamp::from_secs(u64::try_from(since).unwrap_or(0).min(latest))
+}
+
+/// Load cached events of the given kinds, oldest first.
+///
+/// Rows that can no longer be decoded are skipped.
+pub async fn load_cached_events(store: &Store, kinds: &[Kind]) -> Result<Vec<Event>, Error> {
+    let entries = store.list_events(&kinds_as_u16(kinds)).await?;
+
+    Ok(entries
+        .iter()
+        .filter_map(|entry| match entry_to_event(entry) {
+            Ok(event) => Some(event),
+            Err(e) => {
+                tracing::debug!(event_id = %entry.event_id, error = %e, "Skipping undecodable cached event");
+                None
+            }
+        })
+        .collect())
+}
+
+/// Load active, well-formed option events from the cache.
+pub async fn load_cached_options(store: &Store, network: SimplicityNetwork) -> Result<Vec<OptionCreatedEvent>, Error> {
+    let events = load_cached_events(store, &[OPTION_CREATED]).await?;
+
+    Ok(keep_parsed(parse_options(&events, network)))
+}
+
+/// Load active, well-formed option offer events from the cache.
+pub async fn load_cached_option_offers(
+    store: &Store,
+    network: SimplicityNetwork,
+) -> Result<Vec<OptionOfferCreatedEvent>, Error> {
+    let events = load_cached_events(store, &OFFER_KINDS).await?;
+
+    Ok(keep_parsed(parse_option_offers(&events, network)))
+}
+
+/// Load every well-formed option offer version from the cache, including expired and deleted ones.
+pub async fn load_cached_offer_history(
+    store: &Store,
+    network: SimplicityNetwork,
+) -> Result<Vec<OptionOfferCreatedEvent>, Error> {
+    let events = load_cached_events(store, &[OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY]).await?;
+
+    Ok(events
+        .iter()
+        .filter_map(|e| OptionOfferCreatedEvent::from_event(e, network).ok())
+        .collect())
+}
+
+/// Load well-formed cached action events, oldest first.
+pub async fn load_cached_actions(store: &Store) -> Result<Vec<ActionCompletedEvent>, Error> {
+    let events = load_cached_events(store, &[ACTION_COMPLETED]).await?;
+
+    Ok(events
+        .iter()
+        .filter_map(|e| ActionCompletedEvent::from_event(e).ok())
+        .collect())
+}
+
+/// Group actions by the event they reference.
+pub fn actions_by_event(actions: &[ActionCompletedEvent]) -> HashMap<EventId, Vec<&ActionCompletedEvent>> {
+    let mut grouped: HashMap<EventId, Vec<&ActionCompletedEvent>> = HashMap::new();
+    for action in actions {
+        grouped.entry(action.original_event_id).or_default().push(action);
+    }
+
+    grouped
+}
+
+/// Actions against an offer contract: those naming the offer version `event_id` and, when the
+/// maker is known, those naming any version of the offer by its coordinate.
+pub fn offer_actions<'a>(
+    actions: &'a [ActionCompletedEvent],
+    event_id: EventId,
+    maker: Option<&str>,
+    taproot_pubkey_gen: &TaprootPubkeyGen,
+) -> Vec<&'a ActionCompletedEvent> {
+    let coordinate = maker
+        .and_then(|maker| PublicKey::from_hex(maker).ok())
+        .map(|maker| option_offer_coordinate(maker, taproot_pubkey_gen));
+
+    actions
+        .iter()
+        .filter(|action| match &coordinate {
+            Some(coordinate) => action.references_offer(event_id, coordinate),
+            None => action.original_event_id == event_id,
+        })
+        .collect()
+}
+
+pub fn event_to_entry(event: &Event) -> Result<NostrEventEntry, Error> {
+    #[allow(clippy::cast_possible_wrap)]
+    let created_at = event.created_at.as_secs() as i64;
+
+    Ok(NostrEventEntry {
+        event_id: event.id.to_hex(),
+        kind: event.kind.as_u16(),
+        pubkey: event.pubkey.to_hex(),
+        created_at,
+        tags: serde_json::to_string(&event.tags)?,
+        content: event.content.clone(),
+        sig: event.sig.to_string(),
+    })
+}
+
+pub fn entry_to_event(entry: &NostrEventEntry) -> Result<Event, Error> {
+    let id = EventId::from_hex(&entry.event_id).map_err(|e| Error::EventCache(e.to_string()))?;
+    let pubkey = PublicKey::from_hex(&entry.pubkey).map_err(|e| Error::EventCache(e.to_string()))?;
+    let sig = Signature::from_str(&entry.sig).map_err(|e| Error::EventCache(e.to_string()))?;
+    let tags: Vec<Tag> = serde_json::from_str(&entry.tags)?;
+
+    #[allow(clippy::cast_sign_loss)]
+    let created_at = Timestamp::from_secs(entry.created_at.max(0) as u64);
+
+    Ok(Event::new(
+        id,
+        pubkey,
+        created_at,
+        Kind::from(entry.kind),
+        tags,
+        entry.content.clone(),
+        sig,
+    ))
+}
+
+/// Drop events that failed to parse, warning if some were written by a newer schema.
+fn keep_parsed<T>(results: Vec<Result<T, ParseError>>) -> Vec<T> {
+    let mut unsupported = 0;
+    let parsed = results
+        .into_iter()
+        .filter_map(|result| {
+            result
+                .inspect_err(|e| {
+                    if matches!(e, ParseError::UnsupportedVersion(_)) {
+                        unsupported += 1;
+                    }
+                })
+                .ok()
+        })
+        .collect();
+
+    if unsupported > 0 {
+        tracing::warn!("Skipped {unsupported} event(s) with an unsupported schema version; consider upgrading");
+    }
+
+    parsed
+}
+
+fn kinds_as_u16(kinds: &[Kind]) -> Vec<u16> {
+    kinds.iter().map(Kind::as_u16).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::time::Duration;
+
+    use nostr::{EventBuilder, Keys};
+    use options_relay::NostrRelayConfig;
+    use options_relay::test_support::MockRelay;
+
+    #[test]
+    fn test_event_entry_roundtrip() -> anyhow::Result<()> {
+        let keys = Keys::generate();
+        let event = EventBuilder::new(OPTION_CREATED, "content")
+            .tag(Tag::expiration(Timestamp::from_secs(1_700_000_000)))
+            .sign_with_keys(&keys)?;
+
+        let entry = event_to_entry(&event)?;
+        let restored = entry_to_event(&entry)?;
+
+        restored.verify()?;
+        assert_eq!(restored, event);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_clamp_cursor() {
+        let now = 1_700_000_000;
+        let latest = now - CURSOR_SAFETY_WINDOW_SECS;
+
+        assert_eq!(clamp_cursor(1_600_000_000, now).as_secs(), 1_600_000_000);
+        assert_eq!(clamp_cursor(i64::MAX, now).as_secs(), latest);
+        assert_eq!(clamp_cursor(-5, now).as_secs(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_right_after_connect() -> anyhow::Result<()> {
+        let path = "/tmp/test_coin_store_event_cache.db";
+        let _ = fs::remove_file(path);
+        let store = Store::create(path).await?;
+
+        let relay = MockRelay::run().await?;
+        let event = EventBuilder::new(OPTION_CREATED, "content").sign_with_keys(&Keys::generate())?;
+        relay.insert_event(event.clone());
+
+        let config = NostrRelayConfig::new(relay.url()).with_timeout(Duration::from_secs(5));
+        let client = ReadOnlyClient::connect(config).await?;
+
+        assert_eq!(refresh_event_cache(&store, &client).await?, 1);
+        assert_eq!(load_cached_events(&store, &[OPTION_CREATED]).await?, vec![event]);
+
+        client.disconnect().await;
+        let _ = fs::remove_file(path);
+        Ok(())
+    }
+}
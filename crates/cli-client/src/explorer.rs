@@ -1,16 +1,14 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
+use options_relay::{ChainBackend, Spend};
 use serde::Deserialize;
 use simplicityhl::elements::encode;
 use simplicityhl::elements::hashes::{Hash, sha256};
 use simplicityhl::elements::hex::ToHex;
-use simplicityhl::elements::{Address, OutPoint, Script, Transaction, Txid};
+use simplicityhl::elements::{Address, OutPoint, Script, Transaction, TxOut, Txid};
 
-#[allow(unused_imports)]
-pub use cli_helper::explorer::{ExplorerError, broadcast_tx, fetch_utxo};
-
-const ESPLORA_URL: &str = "https://blockstream.info/liquidtestnet/api";
+pub const DEFAULT_ESPLORA_URL: &str = "https://blockstream.info/liquidtestnet/api";
 
 /// Fee estimates response from Esplora.
 /// Key: confirmation target (in blocks as string), Value: fee rate (sat/vB).
@@ -27,6 +25,12 @@ pub enum EsploraError {
 
     #[error("Invalid txid format: {0}")]
     InvalidTxid(String),
+
+    #[error("Output {0} does not exist")]
+    MissingOutput(OutPoint),
+
+    #[error("Transaction {0} not found")]
+    NotFound(Txid),
 }
 
 pub type FetchTransactionError = EsploraError;
@@ -40,6 +44,9 @@ pub struct OutspendStatus {
     pub txid: Option<String>,
     #[serde(default)]
     pub vin: Option<u32>,
+    /// Confirmation status of the spending transaction.
+    #[serde(default)]
+    pub status: Option<TxStatus>,
 }
 
 /// UTXO status from Esplora.
@@ -53,6 +60,16 @@ pub struct UtxoStatus {
     pub block_hash: Option<String>,
 }
 
+/// Confirmation status of a transaction from Esplora.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TxStatus {
+    pub confirmed: bool,
+    #[serde(default)]
+    pub block_height: Option<u64>,
+    #[serde(default)]
+    pub block_time: Option<i64>,
+}
+
 /// UTXO entry from Esplora address/scripthash endpoint.
 #[derive(Debug, Clone, Deserialize)]
 #[allow(dead_code)]
@@ -70,214 +87,328 @@ pub struct EsploraUtxo {
     pub status: UtxoStatus,
 }
 
-/// Fetch full transaction from Esplora by txid.
+/// Client for an Esplora HTTP API, built from the `[explorer]` config.
 ///
-/// Uses the `GET /tx/:txid/raw` endpoint which returns the raw transaction
-/// as binary data.
-///
-/// # Errors
-///
-/// Returns an error if the HTTP request fails or if the response cannot
-/// be deserialized into a valid transaction.
-pub fn fetch_transaction(txid: Txid) -> Result<Transaction, EsploraError> {
-    let url = format!("{ESPLORA_URL}/tx/{}/raw", txid.to_hex());
-    let response = minreq::get(&url)
-        .send()
-        .map_err(|e| EsploraError::Request(e.to_string()))?;
-
-    if response.status_code != 200 {
-        return Err(EsploraError::Request(format!(
-            "HTTP {}: {}",
-            response.status_code, response.reason_phrase
-        )));
+/// Also the chain backend used to validate NOSTR listings.
+#[derive(Debug, Clone)]
+pub struct EsploraBackend {
+    url: String,
+}
+
+impl Default for EsploraBackend {
+    fn default() -> Self {
+        Self::new(DEFAULT_ESPLORA_URL)
     }
+}
 
-    let bytes = response.as_bytes();
-    let tx: Transaction = encode::deserialize(bytes).map_err(|e| EsploraError::Deserialize(e.to_string()))?;
+impl EsploraBackend {
+    #[must_use]
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+        }
+    }
 
-    Ok(tx)
-}
+    /// Fetch full transaction from Esplora by txid.
+    ///
+    /// Uses the `GET /tx/:txid/raw` endpoint which returns the raw transaction
+    /// as binary data.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotFound` if Esplora does not know the transaction, and an error if the HTTP
+    /// request fails or if the response cannot be deserialized into a valid transaction.
+    pub fn fetch_transaction(&self, txid: Txid) -> Result<Transaction, EsploraError> {
+        let url = format!("{}/tx/{}/raw", self.url, txid.to_hex());
+        let response = minreq::get(&url)
+            .send()
+            .map_err(|e| EsploraError::Request(e.to_string()))?;
+
+        if response.status_code == 404 {
+            return Err(EsploraError::NotFound(txid));
+        }
 
-/// Check spending status of all outputs in a transaction.
-///
-/// Uses the `GET /tx/:txid/outspends` endpoint. More efficient than
-/// calling `fetch_outspend` for each output individually.
-pub fn fetch_outspends(txid: Txid) -> Result<Vec<OutspendStatus>, EsploraError> {
-    let url = format!("{ESPLORA_URL}/tx/{}/outspends", txid.to_hex());
-    let response = minreq::get(&url)
-        .send()
-        .map_err(|e| EsploraError::Request(e.to_string()))?;
-
-    if response.status_code != 200 {
-        return Err(EsploraError::Request(format!(
-            "HTTP {}: {}",
-            response.status_code, response.reason_phrase
-        )));
+        if response.status_code != 200 {
+            return Err(EsploraError::Request(format!(
+                "HTTP {}: {}",
+                response.status_code, response.reason_phrase
+            )));
+        }
+
+        let bytes = response.as_bytes();
+        let tx: Transaction = encode::deserialize(bytes).map_err(|e| EsploraError::Deserialize(e.to_string()))?;
+
+        Ok(tx)
     }
 
-    let statuses: Vec<OutspendStatus> = response.json().map_err(|e| EsploraError::Deserialize(e.to_string()))?;
+    /// Fetch a single transaction output.
+    ///
+    /// Fails with `NotFound` if the transaction is unknown and with `MissingOutput` if it
+    /// has no output at `outpoint.vout`.
+    pub fn fetch_output(&self, outpoint: OutPoint) -> Result<TxOut, EsploraError> {
+        let tx = self.fetch_transaction(outpoint.txid)?;
+
+        tx.output
+            .get(outpoint.vout as usize)
+            .cloned()
+            .ok_or(EsploraError::MissingOutput(outpoint))
+    }
 
-    Ok(statuses)
-}
+    /// Broadcast a transaction.
+    ///
+    /// Uses the `POST /tx` endpoint with the hex-encoded transaction as body,
+    /// and returns the txid reported by Esplora.
+    pub fn broadcast_transaction(&self, tx: &Transaction) -> Result<Txid, EsploraError> {
+        let url = format!("{}/tx", self.url);
+        let response = minreq::post(&url)
+            .with_body(encode::serialize_hex(tx))
+            .send()
+            .map_err(|e| EsploraError::Request(e.to_string()))?;
+
+        if response.status_code != 200 {
+            return Err(EsploraError::Request(format!(
+                "HTTP {}: {}",
+                response.status_code,
+                response.as_str().unwrap_or(&response.reason_phrase)
+            )));
+        }
 
-/// Fetch UTXOs for an address.
-///
-/// Uses the `GET /address/:address/utxo` endpoint.
-pub fn fetch_address_utxos(address: &Address) -> Result<Vec<EsploraUtxo>, EsploraError> {
-    let url = format!("{ESPLORA_URL}/address/{address}/utxo");
-    let response = minreq::get(&url)
-        .send()
-        .map_err(|e| EsploraError::Request(e.to_string()))?;
-
-    if response.status_code != 200 {
-        return Err(EsploraError::Request(format!(
-            "HTTP {}: {}",
-            response.status_code, response.reason_phrase
-        )));
+        let txid = response
+            .as_str()
+            .map_err(|e| EsploraError::Deserialize(e.to_string()))?;
+
+        parse_txid(txid.trim())
     }
 
-    let utxos: Vec<EsploraUtxo> = response.json().map_err(|e| EsploraError::Deserialize(e.to_string()))?;
+    /// Fetch the confirmation status of a transaction.
+    ///
+    /// Uses the `GET /tx/:txid/status` endpoint. Unconfirmed transactions have no block time.
+    pub fn fetch_transaction_status(&self, txid: Txid) -> Result<TxStatus, EsploraError> {
+        let url = format!("{}/tx/{}/status", self.url, txid.to_hex());
+        let response = minreq::get(&url)
+            .send()
+            .map_err(|e| EsploraError::Request(e.to_string()))?;
+
+        if response.status_code != 200 {
+            return Err(EsploraError::Request(format!(
+                "HTTP {}: {}",
+                response.status_code, response.reason_phrase
+            )));
+        }
 
-    Ok(utxos)
-}
+        response.json().map_err(|e| EsploraError::Deserialize(e.to_string()))
+    }
 
-/// Fetch UTXOs by scripthash.
-///
-/// Uses the `GET /scripthash/:hash/utxo` endpoint.
-/// The scripthash is SHA256 of the scriptPubKey (reversed for display).
-pub fn fetch_scripthash_utxos(script: &Script) -> Result<Vec<EsploraUtxo>, EsploraError> {
-    let hash = sha256::Hash::hash(script.as_bytes());
-    let hash_bytes = hash.to_byte_array();
-    let scripthash = hex::encode(hash_bytes);
-
-    let url = format!("{ESPLORA_URL}/scripthash/{scripthash}/utxo");
-    let response = minreq::get(&url)
-        .send()
-        .map_err(|e| EsploraError::Request(e.to_string()))?;
-
-    if response.status_code != 200 {
-        return Err(EsploraError::Request(format!(
-            "HTTP {}: {}",
-            response.status_code, response.reason_phrase
-        )));
+    /// Check spending status of all outputs in a transaction.
+    ///
+    /// Uses the `GET /tx/:txid/outspends` endpoint. More efficient than
+    /// calling `fetch_outspend` for each output individually.
+    pub fn fetch_outspends(&self, txid: Txid) -> Result<Vec<OutspendStatus>, EsploraError> {
+        let url = format!("{}/tx/{}/outspends", self.url, txid.to_hex());
+        let response = minreq::get(&url)
+            .send()
+            .map_err(|e| EsploraError::Request(e.to_string()))?;
+
+        if response.status_code != 200 {
+            return Err(EsploraError::Request(format!(
+                "HTTP {}: {}",
+                response.status_code, response.reason_phrase
+            )));
+        }
+
+        let statuses: Vec<OutspendStatus> = response.json().map_err(|e| EsploraError::Deserialize(e.to_string()))?;
+
+        Ok(statuses)
     }
 
-    let utxos: Vec<EsploraUtxo> = response.json().map_err(|e| EsploraError::Deserialize(e.to_string()))?;
+    /// Fetch UTXOs for an address.
+    ///
+    /// Uses the `GET /address/:address/utxo` endpoint.
+    pub fn fetch_address_utxos(&self, address: &Address) -> Result<Vec<EsploraUtxo>, EsploraError> {
+        let url = format!("{}/address/{address}/utxo", self.url);
+        let response = minreq::get(&url)
+            .send()
+            .map_err(|e| EsploraError::Request(e.to_string()))?;
+
+        if response.status_code != 200 {
+            return Err(EsploraError::Request(format!(
+                "HTTP {}: {}",
+                response.status_code, response.reason_phrase
+            )));
+        }
 
-    Ok(utxos)
-}
+        let utxos: Vec<EsploraUtxo> = response.json().map_err(|e| EsploraError::Deserialize(e.to_string()))?;
 
-/// Fetch current blockchain tip height.
-///
-/// Uses the `GET /blocks/tip/height` endpoint.
-pub fn fetch_tip_height() -> Result<u64, EsploraError> {
-    let url = format!("{ESPLORA_URL}/blocks/tip/height");
-    let response = minreq::get(&url)
-        .send()
-        .map_err(|e| EsploraError::Request(e.to_string()))?;
-
-    if response.status_code != 200 {
-        return Err(EsploraError::Request(format!(
-            "HTTP {}: {}",
-            response.status_code, response.reason_phrase
-        )));
+        Ok(utxos)
     }
 
-    let height_str = response
-        .as_str()
-        .map_err(|e| EsploraError::Deserialize(e.to_string()))?;
-    let height: u64 = height_str
-        .trim()
-        .parse()
-        .map_err(|e: std::num::ParseIntError| EsploraError::Deserialize(e.to_string()))?;
+    /// Fetch UTXOs by scripthash.
+    ///
+    /// Uses the `GET /scripthash/:hash/utxo` endpoint.
+    /// The scripthash is SHA256 of the scriptPubKey (reversed for display).
+    pub fn fetch_scripthash_utxos(&self, script: &Script) -> Result<Vec<EsploraUtxo>, EsploraError> {
+        let hash = sha256::Hash::hash(script.as_bytes());
+        let hash_bytes = hash.to_byte_array();
+        let scripthash = hex::encode(hash_bytes);
+
+        let url = format!("{}/scripthash/{scripthash}/utxo", self.url);
+        let response = minreq::get(&url)
+            .send()
+            .map_err(|e| EsploraError::Request(e.to_string()))?;
+
+        if response.status_code != 200 {
+            return Err(EsploraError::Request(format!(
+                "HTTP {}: {}",
+                response.status_code, response.reason_phrase
+            )));
+        }
 
-    Ok(height)
-}
+        let utxos: Vec<EsploraUtxo> = response.json().map_err(|e| EsploraError::Deserialize(e.to_string()))?;
 
-/// Parse a txid string into a Txid.
-pub fn parse_txid(txid_str: &str) -> Result<Txid, EsploraError> {
-    Txid::from_str(txid_str).map_err(|e| EsploraError::InvalidTxid(e.to_string()))
-}
+        Ok(utxos)
+    }
 
-/// Convert an `EsploraUtxo` to an `OutPoint`.
-pub fn esplora_utxo_to_outpoint(utxo: &EsploraUtxo) -> Result<OutPoint, EsploraError> {
-    let txid = parse_txid(&utxo.txid)?;
-    Ok(OutPoint::new(txid, utxo.vout))
-}
+    /// Fetch current blockchain tip height.
+    ///
+    /// Uses the `GET /blocks/tip/height` endpoint.
+    pub fn fetch_tip_height(&self) -> Result<u64, EsploraError> {
+        let url = format!("{}/blocks/tip/height", self.url);
+        let response = minreq::get(&url)
+            .send()
+            .map_err(|e| EsploraError::Request(e.to_string()))?;
+
+        if response.status_code != 200 {
+            return Err(EsploraError::Request(format!(
+                "HTTP {}: {}",
+                response.status_code, response.reason_phrase
+            )));
+        }
 
-/// Fetch fee estimates for various confirmation targets.
-///
-/// Uses the `GET /fee-estimates` endpoint.
-/// Note: Liquid testnet typically returns empty results, so callers should
-/// use a fallback rate (see `config.fee.fallback_rate`).
-///
-/// Returns a map where key is confirmation target (blocks) and value is fee rate (sat/vB).
-///
-/// Example response: `{ "1": 87.882, "2": 87.882, ..., "144": 1.027, "1008": 1.027 }`
-pub fn fetch_fee_estimates() -> Result<FeeEstimates, EsploraError> {
-    let url = format!("{ESPLORA_URL}/fee-estimates");
-    let response = minreq::get(&url)
-        .send()
-        .map_err(|e| EsploraError::Request(e.to_string()))?;
-
-    if response.status_code != 200 {
-        return Err(EsploraError::Request(format!(
-            "HTTP {}: {}",
-            response.status_code, response.reason_phrase
-        )));
+        let height_str = response
+            .as_str()
+            .map_err(|e| EsploraError::Deserialize(e.to_string()))?;
+        let height: u64 = height_str
+            .trim()
+            .parse()
+            .map_err(|e: std::num::ParseIntError| EsploraError::Deserialize(e.to_string()))?;
+
+        Ok(height)
     }
 
-    let estimates: FeeEstimates = response.json().map_err(|e| EsploraError::Deserialize(e.to_string()))?;
+    /// Fetch fee estimates for various confirmation targets.
+    ///
+    /// Uses the `GET /fee-estimates` endpoint.
+    /// Note: Liquid testnet typically returns empty results, so callers should
+    /// use a fallback rate (see `config.fee.fallback_rate`).
+    ///
+    /// Returns a map where key is confirmation target (blocks) and value is fee rate (sat/vB).
+    ///
+    /// Example response: `{ "1": 87.882, "2": 87.882, ..., "144": 1.027, "1008": 1.027 }`
+    pub fn fetch_fee_estimates(&self) -> Result<FeeEstimates, EsploraError> {
+        let url = format!("{}/fee-estimates", self.url);
+        let response = minreq::get(&url)
+            .send()
+            .map_err(|e| EsploraError::Request(e.to_string()))?;
+
+        if response.status_code != 200 {
+            return Err(EsploraError::Request(format!(
+                "HTTP {}: {}",
+                response.status_code, response.reason_phrase
+            )));
+        }
 
-    Ok(estimates)
-}
+        let estimates: FeeEstimates = response.json().map_err(|e| EsploraError::Deserialize(e.to_string()))?;
 
-/// Get fee rate for a specific confirmation target.
-///
-/// Fetches fee estimates from Esplora and returns the rate for the given target.
-/// If the exact target is not available, falls back to higher targets.
-///
-/// # Arguments
-///
-/// * `target_blocks` - Desired confirmation target in blocks (1-25, 144, 504, 1008)
-///
-/// # Returns
-///
-/// Fee rate in sats/kvb (satoshis per 1000 virtual bytes).
-/// Multiply Esplora's sat/vB value by 1000.
-///
-/// # Errors
-///
-/// Returns an error if the HTTP request fails or no suitable fee rate is found.
-#[allow(clippy::cast_possible_truncation)]
-pub fn get_fee_rate(target_blocks: u32) -> Result<f32, EsploraError> {
-    let estimates = fetch_fee_estimates()?;
-
-    let target_str = target_blocks.to_string();
-    if let Some(&rate) = estimates.get(&target_str) {
-        return Ok((rate * 1000.0) as f32); // Convert sat/vB to sats/kvb
+        Ok(estimates)
     }
 
-    // Fall back to higher targets (lower fee rates)
-    // Available targets: 1-25, 144, 504, 1008
-    let fallback_targets = [
-        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 144, 504, 1008,
-    ];
+    /// Get fee rate for a specific confirmation target.
+    ///
+    /// Fetches fee estimates from Esplora and returns the rate for the given target.
+    /// If the exact target is not available, falls back to higher targets.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_blocks` - Desired confirmation target in blocks (1-25, 144, 504, 1008)
+    ///
+    /// # Returns
+    ///
+    /// Fee rate in sats/kvb (satoshis per 1000 virtual bytes).
+    /// Multiply Esplora's sat/vB value by 1000.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request fails or no suitable fee rate is found.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn get_fee_rate(&self, target_blocks: u32) -> Result<f32, EsploraError> {
+        let estimates = self.fetch_fee_estimates()?;
+
+        let target_str = target_blocks.to_string();
+        if let Some(&rate) = estimates.get(&target_str) {
+            return Ok((rate * 1000.0) as f32); // Convert sat/vB to sats/kvb
+        }
+
+        // Fall back to higher targets (lower fee rates)
+        // Available targets: 1-25, 144, 504, 1008
+        let fallback_targets = [
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 144, 504, 1008,
+        ];
+
+        for &target in fallback_targets.iter().filter(|&&t| t >= target_blocks) {
+            let key = target.to_string();
+            if let Some(&rate) = estimates.get(&key) {
+                return Ok((rate * 1000.0) as f32);
+            }
+        }
 
-    for &target in fallback_targets.iter().filter(|&&t| t >= target_blocks) {
-        let key = target.to_string();
-        if let Some(&rate) = estimates.get(&key) {
-            return Ok((rate * 1000.0) as f32);
+        // If no higher target found, try any available rate (use lowest target = highest rate)
+        for &target in &fallback_targets {
+            let key = target.to_string();
+            if let Some(&rate) = estimates.get(&key) {
+                return Ok((rate * 1000.0) as f32);
+            }
         }
+
+        Err(EsploraError::Request("No fee estimates available".to_string()))
     }
+}
+
+impl ChainBackend for EsploraBackend {
+    type Error = EsploraError;
 
-    // If no higher target found, try any available rate (use lowest target = highest rate)
-    for &target in &fallback_targets {
-        let key = target.to_string();
-        if let Some(&rate) = estimates.get(&key) {
-            return Ok((rate * 1000.0) as f32);
+    fn get_output(&self, outpoint: OutPoint) -> Result<Option<TxOut>, Self::Error> {
+        match self.fetch_transaction(outpoint.txid) {
+            Ok(tx) => Ok(tx.output.get(outpoint.vout as usize).cloned()),
+            Err(EsploraError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
         }
     }
 
-    Err(EsploraError::Request("No fee estimates available".to_string()))
+    fn get_spend(&self, outpoint: OutPoint) -> Result<Option<Spend>, Self::Error> {
+        let statuses = self.fetch_outspends(outpoint.txid)?;
+        let Some(status) = statuses.into_iter().nth(outpoint.vout as usize).filter(|status| status.spent) else {
+            return Ok(None);
+        };
+
+        let txid = status
+            .txid
+            .as_deref()
+            .ok_or_else(|| EsploraError::Deserialize(format!("Spend of {outpoint} has no txid")))?;
+
+        Ok(Some(Spend {
+            txid: parse_txid(txid)?,
+            confirmed: status.status.is_some_and(|status| status.confirmed),
+        }))
+    }
+}
+
+/// Parse a txid string into a Txid.
+pub fn parse_txid(txid_str: &str) -> Result<Txid, EsploraError> {
+    Txid::from_str(txid_str).map_err(|e| EsploraError::InvalidTxid(e.to_string()))
+}
+
+/// Convert an `EsploraUtxo` to an `OutPoint`.
+pub fn esplora_utxo_to_outpoint(utxo: &EsploraUtxo) -> Result<OutPoint, EsploraError> {
+    let txid = parse_txid(&utxo.txid)?;
+    Ok(OutPoint::new(txid, utxo.vout))
 }
@@ -0,0 +1,96 @@
+use crate::error::Error;
+
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+/// One line of a BIP-329 wallet label export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bip329Record {
+    /// `tx`, `addr`, `pubkey`, `input`, `output` or `xpub`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Txid, address, `txid:vout` or key the label refers to
+    #[serde(rename = "ref")]
+    pub reference: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Key origin of the wallet the record came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    /// For outputs: whether the wallet may spend it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spendable: Option<bool>,
+}
+
+impl Bip329Record {
+    #[must_use]
+    pub fn new(kind: &str, reference: String, label: String) -> Self {
+        Self {
+            kind: kind.to_string(),
+            reference,
+            label: Some(label),
+            origin: None,
+            spendable: None,
+        }
+    }
+}
+
+/// Write `records` as JSON lines.
+pub fn write_bip329(records: &[Bip329Record], out: &mut impl Write) -> Result<(), Error> {
+    for record in records {
+        serde_json::to_writer(&mut *out, record)?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Parse JSON lines, skipping blank ones.
+pub fn parse_bip329(input: &str) -> Result<Vec<Bip329Record>, Error> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| Error::Config(format!("Invalid BIP-329 record on line {}: {e}", i + 1)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bip329_round_trip() {
+        let txid = "f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd";
+        let input = format!(
+            r#"
+{{"type":"tx","ref":"{txid}","label":"Transaction"}}
+
+{{"type":"output","ref":"{txid}:1","label":"Change","spendable":false}}
+{{"type":"addr","ref":"tex1qkc0xl4qmz6ctyg6elavw7zd3lkcu8hs4dq3eu5","origin":"wpkh([d34db33f/84'/1'/0'])"}}
+"#
+        );
+
+        let records = parse_bip329(&input).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].kind, "tx");
+        assert_eq!(records[1].label.as_deref(), Some("Change"));
+        assert_eq!(records[1].spendable, Some(false));
+        assert_eq!(records[2].label, None);
+        assert_eq!(records[2].origin.as_deref(), Some("wpkh([d34db33f/84'/1'/0'])"));
+
+        let mut out = Vec::new();
+        write_bip329(&records, &mut out).unwrap();
+        let written = String::from_utf8(out).unwrap();
+        assert_eq!(written.lines().count(), 3);
+        assert!(written.starts_with(&format!(r#"{{"type":"tx","ref":"{txid}""#)));
+        assert!(!written.lines().next().unwrap().contains("spendable"));
+        assert_eq!(parse_bip329(&written).unwrap(), records);
+
+        assert!(parse_bip329("{\"type\":\"tx\"}").is_err());
+    }
+}
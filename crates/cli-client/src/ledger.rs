@@ -0,0 +1,229 @@
+use crate::error::Error;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use coin_store::{Store, UtxoStore};
+use serde::Serialize;
+use simplicityhl::elements::{AssetId, OutPoint, Script, Transaction};
+
+/// Output format of exported ledgers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            other => Err(format!("Unknown export format '{other}', expected csv or json")),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        })
+    }
+}
+
+/// One line of the accounting ledger: a movement of one asset in or out of the wallet, a fee,
+/// or a contract action that moved nothing. Columns are stable; new ones are only appended.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LedgerRow {
+    /// Unix timestamp of the action, or of the block that confirmed the transaction
+    pub timestamp: Option<i64>,
+    /// The timestamp as RFC 3339
+    pub date: Option<String>,
+    /// `movement`, `fee` or `action`
+    pub kind: String,
+    pub txid: Option<String>,
+    pub contract_type: Option<String>,
+    pub contract: Option<String>,
+    /// Contract actions recorded for the transaction
+    pub action: Option<String>,
+    pub asset_id: Option<String>,
+    pub asset: Option<String>,
+    /// Signed amount in whole units; negative when it left the wallet
+    pub amount: Option<String>,
+    pub amount_base_units: Option<i64>,
+    /// NOSTR author of the contract, when it is not us
+    pub counterparty: Option<String>,
+    pub nostr_event_id: Option<String>,
+    /// Asset the fiat columns are quoted in
+    pub fiat: Option<String>,
+    /// Price of one whole unit of the asset in the fiat asset on the day of the row
+    pub fiat_price: Option<f64>,
+    pub fiat_value: Option<f64>,
+}
+
+impl LedgerRow {
+    pub const CSV_COLUMNS: [&'static str; 16] = [
+        "timestamp",
+        "date",
+        "kind",
+        "txid",
+        "contract_type",
+        "contract",
+        "action",
+        "asset_id",
+        "asset",
+        "amount",
+        "amount_base_units",
+        "counterparty",
+        "nostr_event_id",
+        "fiat",
+        "fiat_price",
+        "fiat_value",
+    ];
+
+    fn csv_fields(&self) -> [String; 16] {
+        let text = |value: Option<&str>| value.unwrap_or_default().to_string();
+        let number = |value: Option<String>| value.unwrap_or_default();
+
+        [
+            number(self.timestamp.map(|t| t.to_string())),
+            text(self.date.as_deref()),
+            self.kind.clone(),
+            text(self.txid.as_deref()),
+            text(self.contract_type.as_deref()),
+            text(self.contract.as_deref()),
+            text(self.action.as_deref()),
+            text(self.asset_id.as_deref()),
+            text(self.asset.as_deref()),
+            text(self.amount.as_deref()),
+            number(self.amount_base_units.map(|a| a.to_string())),
+            text(self.counterparty.as_deref()),
+            text(self.nostr_event_id.as_deref()),
+            text(self.fiat.as_deref()),
+            number(self.fiat_price.map(|p| p.to_string())),
+            number(self.fiat_value.map(|v| format!("{v:.2}"))),
+        ]
+    }
+}
+
+/// Write `rows` in `format`.
+pub fn write_ledger(rows: &[LedgerRow], format: ExportFormat, out: &mut impl Write) -> Result<(), Error> {
+    match format {
+        ExportFormat::Csv => {
+            writeln!(out, "{}", LedgerRow::CSV_COLUMNS.join(","))?;
+            for row in rows {
+                let fields: Vec<String> = row.csv_fields().iter().map(|field| csv_escape(field)).collect();
+                writeln!(out, "{}", fields.join(","))?;
+            }
+        }
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, rows)?;
+            writeln!(out)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Quote a CSV field if it holds a separator, quote or line break.
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Our net change per asset in `tx`, and its fee if we funded any of its inputs.
+///
+/// Confidential outputs are valued from the unblinded copy in the store; outputs the store
+/// has not seen count only if they are explicit.
+pub async fn wallet_flows(
+    store: &Store,
+    tx: &Transaction,
+    user_script_pubkey: &Script,
+) -> Result<(BTreeMap<AssetId, i128>, Option<(AssetId, u64)>), Error> {
+    let mut net: BTreeMap<AssetId, i128> = BTreeMap::new();
+    let mut funded = false;
+
+    for input in &tx.input {
+        if let Some((script, asset, value)) = <_ as UtxoStore>::get_output(store, input.previous_output).await?
+            && script == *user_script_pubkey
+        {
+            funded = true;
+            *net.entry(asset).or_default() -= i128::from(value);
+        }
+    }
+
+    let txid = tx.txid();
+    for (vout, output) in (0u32..).zip(&tx.output) {
+        if output.is_fee() || output.script_pubkey != *user_script_pubkey {
+            continue;
+        }
+
+        let known = <_ as UtxoStore>::get_output(store, OutPoint::new(txid, vout)).await?;
+        let asset_value = known
+            .map(|(_, asset, value)| (asset, value))
+            .or_else(|| Some((output.asset.explicit()?, output.value.explicit()?)));
+        if let Some((asset, value)) = asset_value {
+            *net.entry(asset).or_default() += i128::from(value);
+        }
+    }
+
+    let fee = tx
+        .output
+        .iter()
+        .find(|output| output.is_fee())
+        .filter(|_| funded)
+        .and_then(|output| Some((output.asset.explicit()?, output.value.explicit()?)));
+
+    Ok((net, fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_csv_ledger() {
+        let rows = vec![
+            LedgerRow {
+                timestamp: Some(1_700_000_000),
+                kind: "movement".to_string(),
+                asset: Some("USDt".to_string()),
+                amount: Some("-12.5".to_string()),
+                amount_base_units: Some(-1_250_000_000),
+                fiat_value: Some(-12.5),
+                ..LedgerRow::default()
+            },
+            LedgerRow {
+                kind: "action".to_string(),
+                action: Some("OptionOfferCreated, \"manual\"".to_string()),
+                ..LedgerRow::default()
+            },
+        ];
+
+        let mut out = Vec::new();
+        write_ledger(&rows, ExportFormat::Csv, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("timestamp,date,kind,txid,"));
+        assert_eq!(lines[1], "1700000000,,movement,,,,,,USDt,-12.5,-1250000000,,,,,-12.50");
+        assert_eq!(lines[2], ",,action,,,,\"OptionOfferCreated, \"\"manual\"\"\",,,,,,,,,");
+
+        let mut out = Vec::new();
+        write_ledger(&rows, ExportFormat::Json, &mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json[0]["amount_base_units"], -1_250_000_000);
+        assert!(json[1]["timestamp"].is_null());
+    }
+}
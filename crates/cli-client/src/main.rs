@@ -1,13 +1,22 @@
 #![warn(clippy::all, clippy::pedantic)]
 
+mod assets;
+mod batch;
 mod cli;
+mod coin_control;
 mod config;
+mod consolidation;
 mod error;
+mod event_cache;
 mod explorer;
 mod fee;
+mod labels;
+mod ledger;
 mod logging;
 mod metadata;
 mod price_fetcher;
+mod price_oracle;
+mod pricing;
 mod signing;
 mod sync;
 mod wallet;
@@ -1,6 +1,16 @@
-use serde::Deserialize;
+use crate::assets::AssetRegistry;
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use simplicityhl::elements::AssetId;
 use thiserror::Error;
 
+const USER_AGENT: &str = "simplicity-dex/1.0";
+const TIMEOUT_SECS: u64 = 5;
+
 #[derive(Error, Debug)]
 pub enum PriceFetcherError {
     #[error("Request error: {0}")]
@@ -11,51 +21,486 @@ pub enum PriceFetcherError {
     Status(i32),
     #[error("Parse error: {0}")]
     Parse(String),
-    #[error("Internal runtime error: {0}")]
-    Internal(String),
+    #[error("{0}")]
+    Unsupported(String),
+    #[error("No price available for {0}")]
+    Unavailable(String),
 }
 
-#[derive(Deserialize)]
-struct BitcoinResponse {
-    bitcoin: BitcoinPrice,
+/// Price of one whole unit of `base` in whole units of `quote`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssetPair {
+    pub base: AssetId,
+    pub quote: AssetId,
 }
 
-#[derive(Deserialize)]
-struct BitcoinPrice {
-    usd: f64,
+impl AssetPair {
+    #[must_use]
+    pub const fn new(base: AssetId, quote: AssetId) -> Self {
+        Self { base, quote }
+    }
+
+    #[must_use]
+    pub const fn inverse(self) -> Self {
+        Self {
+            base: self.quote,
+            quote: self.base,
+        }
+    }
+
+    /// Key independent of the asset registry, for files that outlive ticker changes.
+    #[must_use]
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.base, self.quote)
+    }
+
+    /// `BASE/QUOTE` with the registered tickers of both assets.
+    #[must_use]
+    pub fn label(&self, assets: &AssetRegistry) -> String {
+        format!("{}/{}", assets.label(&self.base), assets.label(&self.quote))
+    }
+
+    /// Parse `BASE/QUOTE`, where each side is a registered ticker or a full asset id.
+    pub fn parse(s: &str, assets: &AssetRegistry) -> Result<Self, String> {
+        let (base, quote) = s
+            .split_once('/')
+            .ok_or_else(|| format!("Invalid asset pair '{s}', expected BASE/QUOTE"))?;
+        let resolve = |side: &str| {
+            assets
+                .resolve(side.trim())
+                .ok_or_else(|| format!("Unknown asset '{side}' in pair '{s}'"))
+        };
+
+        Ok(Self::new(resolve(base)?, resolve(quote)?))
+    }
+}
+
+/// Configurable kinds of price source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceSourceKind {
+    Coingecko,
+    Kraken,
+    Bitstamp,
+    /// Prices read from the configured `prices_file`.
+    File,
 }
 
 pub trait PriceFetcher {
-    fn fetch_price(&self) -> Result<f64, PriceFetcherError>;
+    /// Short name shown next to prices.
+    fn name(&self) -> &'static str;
+
+    /// Whether the source needs the network; remote sources are skipped when offline.
+    fn is_remote(&self) -> bool {
+        true
+    }
+
+    /// Price of `pair`. Returns [`PriceFetcherError::Unsupported`] if the source has no such market.
+    fn fetch_price(&self, pair: &AssetPair) -> Result<f64, PriceFetcherError>;
+
+    /// Price of `pair` on the (UTC) day of `timestamp`. Sources without price history return
+    /// [`PriceFetcherError::Unsupported`].
+    fn fetch_price_at(&self, pair: &AssetPair, _timestamp: u64) -> Result<f64, PriceFetcherError> {
+        Err(PriceFetcherError::Unsupported(format!(
+            "{} has no price history for {}",
+            self.name(),
+            pair.key()
+        )))
+    }
+}
+
+/// Symbol of an asset on exchanges, from its registered ticker. Liquid Bitcoin trades as BTC.
+fn market_symbol(assets: &AssetRegistry, asset_id: &AssetId) -> Option<String> {
+    let ticker = assets.ticker(asset_id)?.to_ascii_uppercase();
+
+    Some(match ticker.as_str() {
+        "LBTC" | "L-BTC" => "BTC".to_string(),
+        _ => ticker,
+    })
 }
 
+fn market_symbols(
+    assets: &AssetRegistry,
+    source: &str,
+    pair: &AssetPair,
+) -> Result<(String, String), PriceFetcherError> {
+    market_symbol(assets, &pair.base)
+        .zip(market_symbol(assets, &pair.quote))
+        .ok_or_else(|| {
+            PriceFetcherError::Unsupported(format!(
+                "{source} cannot price unregistered assets of {}",
+                pair.label(assets)
+            ))
+        })
+}
+
+fn get(url: &str) -> Result<minreq::Response, PriceFetcherError> {
+    let resp = minreq::get(url)
+        .with_header("User-Agent", USER_AGENT)
+        .with_timeout(TIMEOUT_SECS)
+        .send()
+        .map_err(PriceFetcherError::from)?;
+
+    match resp.status_code {
+        200 => Ok(resp),
+        429 => Err(PriceFetcherError::RateLimit),
+        status => Err(PriceFetcherError::Status(status)),
+    }
+}
+
+/// Calendar date of a Unix timestamp as `YYYY-MM-DD`.
+fn utc_date(timestamp: u64) -> String {
+    let mut date = humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(timestamp)).to_string();
+    date.truncate(10);
+    date
+}
+
+fn parse_price(value: &str) -> Result<f64, PriceFetcherError> {
+    value
+        .parse::<f64>()
+        .map_err(|_| PriceFetcherError::Parse(format!("Invalid price '{value}'")))
+        .and_then(valid_price)
+}
+
+/// Reject prices that are zero, negative or not finite, so they can be inverted and averaged.
+pub(crate) fn valid_price(price: f64) -> Result<f64, PriceFetcherError> {
+    if price.is_finite() && price > 0.0 {
+        Ok(price)
+    } else {
+        Err(PriceFetcherError::Parse(format!("Invalid price '{price}'")))
+    }
+}
+
+/// Coingecko simple price API. Stablecoins are quoted against the currency they track.
 #[derive(Default)]
-pub struct CoingeckoPriceFetcher;
+pub struct CoingeckoPriceFetcher {
+    assets: AssetRegistry,
+}
+
+#[derive(Deserialize)]
+struct CoingeckoHistory {
+    #[serde(default)]
+    market_data: Option<CoingeckoMarketData>,
+}
+
+#[derive(Deserialize)]
+struct CoingeckoMarketData {
+    current_price: HashMap<String, f64>,
+}
 
 impl CoingeckoPriceFetcher {
-    const URL: &'static str = "https://api.coingecko.com/api/v3/simple/price?ids=bitcoin&vs_currencies=usd&precision=8";
-    const TIMEOUT_SECS: u64 = 5;
+    const URL: &'static str = "https://api.coingecko.com/api/v3/simple/price";
+    const HISTORY_URL: &'static str = "https://api.coingecko.com/api/v3/coins";
+
+    #[must_use]
+    pub const fn new(assets: AssetRegistry) -> Self {
+        Self { assets }
+    }
+
+    fn coin_and_currency(&self, pair: &AssetPair) -> Result<(&'static str, &'static str), PriceFetcherError> {
+        let (base, quote) = market_symbols(&self.assets, "coingecko", pair)?;
+        Self::coin_id(&base)
+            .zip(Self::vs_currency(&quote))
+            .ok_or_else(|| PriceFetcherError::Unsupported(format!("coingecko has no {base}/{quote} price")))
+    }
+
+    fn coin_id(symbol: &str) -> Option<&'static str> {
+        match symbol {
+            "BTC" => Some("bitcoin"),
+            "ETH" => Some("ethereum"),
+            "USDT" => Some("tether"),
+            "USDC" => Some("usd-coin"),
+            _ => None,
+        }
+    }
+
+    fn vs_currency(symbol: &str) -> Option<&'static str> {
+        match symbol {
+            "BTC" => Some("btc"),
+            "ETH" => Some("eth"),
+            "USD" | "USDT" | "USDC" => Some("usd"),
+            "EUR" | "EURX" => Some("eur"),
+            _ => None,
+        }
+    }
 }
 
 impl PriceFetcher for CoingeckoPriceFetcher {
-    fn fetch_price(&self) -> Result<f64, PriceFetcherError> {
-        let resp = minreq::get(Self::URL)
-            .with_header("User-Agent", "simplicity-dex/1.0")
-            .with_timeout(Self::TIMEOUT_SECS)
-            .send()
-            .map_err(PriceFetcherError::from)?;
-
-        match resp.status_code {
-            200 => resp
-                .json::<BitcoinResponse>()
-                .map(|data| data.bitcoin.usd)
-                .map_err(|e| PriceFetcherError::Parse(e.to_string())),
-            429 => Err(PriceFetcherError::RateLimit),
-            status => Err(PriceFetcherError::Status(status)),
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    fn fetch_price(&self, pair: &AssetPair) -> Result<f64, PriceFetcherError> {
+        let (id, vs) = self.coin_and_currency(pair)?;
+
+        let url = format!("{}?ids={id}&vs_currencies={vs}&precision=8", Self::URL);
+        let prices: HashMap<String, HashMap<String, f64>> =
+            get(&url)?.json().map_err(|e| PriceFetcherError::Parse(e.to_string()))?;
+
+        prices
+            .get(id)
+            .and_then(|by_currency| by_currency.get(vs))
+            .copied()
+            .ok_or_else(|| PriceFetcherError::Parse(format!("coingecko returned no {id}/{vs} price")))
+            .and_then(valid_price)
+    }
+
+    fn fetch_price_at(&self, pair: &AssetPair, timestamp: u64) -> Result<f64, PriceFetcherError> {
+        let (id, vs) = self.coin_and_currency(pair)?;
+
+        // Coingecko wants the date as dd-mm-yyyy.
+        let date = utc_date(timestamp);
+        let day_first = date.split('-').rev().collect::<Vec<_>>().join("-");
+
+        let url = format!("{}/{id}/history?date={day_first}&localization=false", Self::HISTORY_URL);
+        let history: CoingeckoHistory = get(&url)?.json().map_err(|e| PriceFetcherError::Parse(e.to_string()))?;
+
+        history
+            .market_data
+            .and_then(|data| data.current_price.get(vs).copied())
+            .ok_or_else(|| PriceFetcherError::Parse(format!("coingecko returned no {id}/{vs} price on {date}")))
+            .and_then(valid_price)
+    }
+}
+
+/// Kraken public ticker, using the last trade price.
+#[derive(Default)]
+pub struct KrakenPriceFetcher {
+    assets: AssetRegistry,
+}
+
+#[derive(Deserialize)]
+struct KrakenResponse {
+    error: Vec<String>,
+    #[serde(default)]
+    result: HashMap<String, KrakenTicker>,
+}
+
+#[derive(Deserialize)]
+struct KrakenTicker {
+    /// Last trade: price and volume.
+    c: Vec<String>,
+}
+
+impl KrakenPriceFetcher {
+    const URL: &'static str = "https://api.kraken.com/0/public/Ticker";
+
+    #[must_use]
+    pub const fn new(assets: AssetRegistry) -> Self {
+        Self { assets }
+    }
+}
+
+impl PriceFetcher for KrakenPriceFetcher {
+    fn name(&self) -> &'static str {
+        "kraken"
+    }
+
+    fn fetch_price(&self, pair: &AssetPair) -> Result<f64, PriceFetcherError> {
+        let (base, quote) = market_symbols(&self.assets, self.name(), pair)?;
+        let kraken_symbol = |symbol: String| if symbol == "BTC" { "XBT".to_string() } else { symbol };
+
+        let url = format!("{}?pair={}{}", Self::URL, kraken_symbol(base), kraken_symbol(quote));
+        let response: KrakenResponse = get(&url)?.json().map_err(|e| PriceFetcherError::Parse(e.to_string()))?;
+
+        if response.error.iter().any(|e| e.contains("Unknown asset pair")) {
+            return Err(PriceFetcherError::Unsupported(format!(
+                "kraken has no {} market",
+                pair.label(&self.assets)
+            )));
+        }
+        if let Some(error) = response.error.first() {
+            return Err(PriceFetcherError::Parse(error.clone()));
+        }
+
+        let last = response
+            .result
+            .values()
+            .next()
+            .and_then(|ticker| ticker.c.first())
+            .ok_or_else(|| PriceFetcherError::Parse("kraken returned no ticker".to_string()))?;
+        parse_price(last)
+    }
+}
+
+/// Bitstamp public ticker, using the last trade price.
+#[derive(Default)]
+pub struct BitstampPriceFetcher {
+    assets: AssetRegistry,
+}
+
+#[derive(Deserialize)]
+struct BitstampTicker {
+    last: String,
+}
+
+#[derive(Deserialize)]
+struct BitstampOhlcResponse {
+    data: BitstampOhlcData,
+}
+
+#[derive(Deserialize)]
+struct BitstampOhlcData {
+    ohlc: Vec<BitstampCandle>,
+}
+
+#[derive(Deserialize)]
+struct BitstampCandle {
+    close: String,
+}
+
+impl BitstampPriceFetcher {
+    const URL: &'static str = "https://www.bitstamp.net/api/v2/ticker";
+    const OHLC_URL: &'static str = "https://www.bitstamp.net/api/v2/ohlc";
+
+    #[must_use]
+    pub const fn new(assets: AssetRegistry) -> Self {
+        Self { assets }
+    }
+
+    fn market(&self, pair: &AssetPair) -> Result<String, PriceFetcherError> {
+        let (base, quote) = market_symbols(&self.assets, "bitstamp", pair)?;
+        Ok(format!("{}{}", base.to_ascii_lowercase(), quote.to_ascii_lowercase()))
+    }
+
+    fn get_market(&self, url: &str, pair: &AssetPair) -> Result<minreq::Response, PriceFetcherError> {
+        match get(url) {
+            Err(PriceFetcherError::Status(404)) => Err(PriceFetcherError::Unsupported(format!(
+                "bitstamp has no {} market",
+                pair.label(&self.assets)
+            ))),
+            result => result,
         }
     }
 }
 
-pub fn fetch_btc_usd_price<T: PriceFetcher>(fetcher: &T) -> Result<f64, PriceFetcherError> {
-    fetcher.fetch_price()
+impl PriceFetcher for BitstampPriceFetcher {
+    fn name(&self) -> &'static str {
+        "bitstamp"
+    }
+
+    fn fetch_price(&self, pair: &AssetPair) -> Result<f64, PriceFetcherError> {
+        let url = format!("{}/{}/", Self::URL, self.market(pair)?);
+        let ticker: BitstampTicker = self
+            .get_market(&url, pair)?
+            .json()
+            .map_err(|e| PriceFetcherError::Parse(e.to_string()))?;
+
+        parse_price(&ticker.last)
+    }
+
+    /// Close of the daily candle starting at or after the start of the day of `timestamp`.
+    fn fetch_price_at(&self, pair: &AssetPair, timestamp: u64) -> Result<f64, PriceFetcherError> {
+        let day_start = timestamp - timestamp % 86_400;
+        let url = format!("{}/{}/?step=86400&limit=1&start={day_start}", Self::OHLC_URL, self.market(pair)?);
+        let response: BitstampOhlcResponse = self
+            .get_market(&url, pair)?
+            .json()
+            .map_err(|e| PriceFetcherError::Parse(e.to_string()))?;
+
+        let candle = response.data.ohlc.first().ok_or_else(|| {
+            PriceFetcherError::Parse(format!("bitstamp returned no {} candle", pair.label(&self.assets)))
+        })?;
+        parse_price(&candle.close)
+    }
+}
+
+/// Fixed prices, e.g. from a JSON file of `"BASE/QUOTE": price` entries where each side is a
+/// ticker or an asset id. Serves manual prices and test fixtures.
+#[derive(Debug, Clone, Default)]
+pub struct StaticPriceFetcher {
+    prices: HashMap<AssetPair, f64>,
+    assets: AssetRegistry,
+}
+
+impl StaticPriceFetcher {
+    pub fn from_entries<'a>(
+        assets: &AssetRegistry,
+        entries: impl IntoIterator<Item = (&'a str, f64)>,
+    ) -> Result<Self, PriceFetcherError> {
+        let prices = entries
+            .into_iter()
+            .map(|(label, price)| {
+                let pair = AssetPair::parse(label, assets).map_err(PriceFetcherError::Parse)?;
+                if !price.is_finite() || price <= 0.0 {
+                    return Err(PriceFetcherError::Parse(format!("Invalid price {price} for {label}")));
+                }
+                Ok((pair, price))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            prices,
+            assets: assets.clone(),
+        })
+    }
+
+    pub fn from_json(assets: &AssetRegistry, json: &str) -> Result<Self, PriceFetcherError> {
+        let entries: BTreeMap<String, f64> =
+            serde_json::from_str(json).map_err(|e| PriceFetcherError::Parse(e.to_string()))?;
+
+        Self::from_entries(assets, entries.iter().map(|(pair, price)| (pair.as_str(), *price)))
+    }
+
+    pub fn from_file(assets: &AssetRegistry, path: &Path) -> Result<Self, PriceFetcherError> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| PriceFetcherError::Parse(format!("Cannot read {}: {e}", path.display())))?;
+
+        Self::from_json(assets, &json)
+    }
+
+    #[must_use]
+    pub fn into_prices(self) -> HashMap<AssetPair, f64> {
+        self.prices
+    }
+}
+
+impl PriceFetcher for StaticPriceFetcher {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn is_remote(&self) -> bool {
+        false
+    }
+
+    fn fetch_price(&self, pair: &AssetPair) -> Result<f64, PriceFetcherError> {
+        self.prices.get(pair).copied().ok_or_else(|| {
+            PriceFetcherError::Unsupported(format!("no {} price in the prices file", pair.label(&self.assets)))
+        })
+    }
+
+    /// Fixed prices hold on every day.
+    fn fetch_price_at(&self, pair: &AssetPair, _timestamp: u64) -> Result<f64, PriceFetcherError> {
+        self.fetch_price(pair)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use simplicityhl_core::LIQUID_TESTNET_BITCOIN_ASSET;
+
+    #[test]
+    fn test_static_prices_from_json() {
+        let other = AssetId::from_slice(&[7; 32]).unwrap();
+        let json = format!(r#"{{ "LBTC/{other}": 65000.5 }}"#);
+
+        let assets = AssetRegistry::default();
+        let fetcher = StaticPriceFetcher::from_json(&assets, &json).unwrap();
+        let pair = AssetPair::new(*LIQUID_TESTNET_BITCOIN_ASSET, other);
+
+        assert!((fetcher.fetch_price(&pair).unwrap() - 65000.5).abs() < f64::EPSILON);
+        assert!(matches!(
+            fetcher.fetch_price(&pair.inverse()),
+            Err(PriceFetcherError::Unsupported(_))
+        ));
+
+        assert!((fetcher.fetch_price_at(&pair, 1_700_000_000).unwrap() - 65000.5).abs() < f64::EPSILON);
+        assert_eq!(utc_date(1_700_000_000), "2023-11-14");
+
+        assert!(StaticPriceFetcher::from_json(&assets, r#"{ "LBTC": 1.0 }"#).is_err());
+        assert!(StaticPriceFetcher::from_json(&assets, &format!(r#"{{ "LBTC/{other}": -1.0 }}"#)).is_err());
+    }
 }
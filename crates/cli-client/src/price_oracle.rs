@@ -0,0 +1,506 @@
+use crate::assets::AssetRegistry;
+use crate::config::Config;
+use crate::price_fetcher::{
+    AssetPair, BitstampPriceFetcher, CoingeckoPriceFetcher, KrakenPriceFetcher, PriceFetcher, PriceFetcherError,
+    PriceSourceKind, StaticPriceFetcher, valid_price,
+};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Where a quoted price came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceOrigin {
+    /// Configured override or a price given on the command line.
+    Override,
+    /// Median of the sources that answered just now.
+    Live { sources: usize },
+    /// Fetched earlier; stale once older than the cache TTL.
+    Cached { age_secs: u64, stale: bool },
+}
+
+impl fmt::Display for PriceOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Override => f.write_str("override"),
+            Self::Live { sources } => write!(f, "live, median of {sources} source(s)"),
+            Self::Cached { age_secs, stale: false } => write!(f, "cached {}m ago", age_secs / 60),
+            Self::Cached { age_secs, stale: true } => write!(f, "STALE, fetched {}m ago", age_secs / 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceQuote {
+    pub price: f64,
+    pub origin: PriceOrigin,
+}
+
+impl fmt::Display for PriceQuote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2} ({})", self.price, self.origin)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct CachedPrice {
+    price: f64,
+    fetched_at: u64,
+}
+
+/// Fetched prices keyed by [`AssetPair::key`], kept between runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PriceCache {
+    prices: BTreeMap<String, CachedPrice>,
+    /// Daily prices keyed by `key@day`, with days counted from the Unix epoch. Only days that
+    /// are over are kept, so they never go stale.
+    #[serde(default)]
+    historical: BTreeMap<String, f64>,
+    /// Prices for the current day, reused within a run but never saved.
+    #[serde(skip)]
+    intraday: BTreeMap<String, f64>,
+}
+
+impl PriceCache {
+    /// A missing or unreadable cache starts empty.
+    fn load(path: &Path) -> Self {
+        let Ok(json) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&json)
+            .inspect_err(|e| tracing::debug!("Ignoring unreadable price cache {}: {e}", path.display()))
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Prices asset pairs from several sources.
+///
+/// Overrides win outright. Otherwise a cached price younger than the TTL is reused, then the
+/// sources are queried (each directly, or through the inverse pair) and the median of those that
+/// answer is cached. If none answer, the last cached price is returned marked as stale. Offline,
+/// only sources that need no network are queried.
+pub struct PriceOracle {
+    sources: Vec<Box<dyn PriceFetcher + Send>>,
+    overrides: HashMap<AssetPair, f64>,
+    cache: PriceCache,
+    cache_path: Option<PathBuf>,
+    cache_dirty: bool,
+    ttl_secs: u64,
+    offline: bool,
+    assets: AssetRegistry,
+}
+
+impl PriceOracle {
+    /// Oracle without overrides or persisted cache.
+    #[must_use]
+    pub fn new(sources: Vec<Box<dyn PriceFetcher + Send>>) -> Self {
+        Self {
+            sources,
+            overrides: HashMap::new(),
+            cache: PriceCache::default(),
+            cache_path: None,
+            cache_dirty: false,
+            ttl_secs: 0,
+            offline: false,
+            assets: AssetRegistry::default(),
+        }
+    }
+
+    /// Build the oracle from the `[oracle]` config. Misconfigured sources and overrides are
+    /// reported and skipped.
+    #[must_use]
+    pub fn from_config(config: &Config, assets: &AssetRegistry) -> Self {
+        let oracle = &config.oracle;
+
+        let sources = oracle
+            .sources
+            .iter()
+            .filter_map(|kind| -> Option<Box<dyn PriceFetcher + Send>> {
+                match kind {
+                    PriceSourceKind::Coingecko => Some(Box::new(CoingeckoPriceFetcher::new(assets.clone()))),
+                    PriceSourceKind::Kraken => Some(Box::new(KrakenPriceFetcher::new(assets.clone()))),
+                    PriceSourceKind::Bitstamp => Some(Box::new(BitstampPriceFetcher::new(assets.clone()))),
+                    PriceSourceKind::File => {
+                        let Some(path) = &oracle.prices_file else {
+                            tracing::warn!("The file price source needs `prices_file` in [oracle]");
+                            return None;
+                        };
+                        StaticPriceFetcher::from_file(assets, path)
+                            .inspect_err(|e| tracing::warn!("Ignoring prices file: {e}"))
+                            .ok()
+                            .map(|fetcher| Box::new(fetcher) as Box<dyn PriceFetcher + Send>)
+                    }
+                }
+            })
+            .collect();
+
+        let overrides =
+            StaticPriceFetcher::from_entries(assets, oracle.overrides.iter().map(|(k, v)| (k.as_str(), *v)))
+                .inspect_err(|e| tracing::warn!("Ignoring [oracle] overrides: {e}"))
+                .map(StaticPriceFetcher::into_prices)
+                .unwrap_or_default();
+
+        let cache_path = config.price_cache_path();
+
+        Self {
+            overrides,
+            cache: PriceCache::load(&cache_path),
+            cache_path: Some(cache_path),
+            ttl_secs: oracle.cache_ttl_secs,
+            offline: oracle.offline,
+            assets: assets.clone(),
+            ..Self::new(sources)
+        }
+    }
+
+    #[must_use]
+    pub fn with_override(mut self, pair: AssetPair, price: f64) -> Self {
+        self.overrides.insert(pair, price);
+        self
+    }
+
+    pub fn price(&mut self, pair: AssetPair, now: u64) -> Result<PriceQuote, PriceFetcherError> {
+        if let Some(price) = self.overrides.get(&pair) {
+            return Ok(PriceQuote {
+                price: *price,
+                origin: PriceOrigin::Override,
+            });
+        }
+
+        let ttl_secs = self.ttl_secs;
+        let cached = self.cache.prices.get(&pair.key()).copied();
+        let cached_quote = |cached: CachedPrice| {
+            let age_secs = now.saturating_sub(cached.fetched_at);
+            PriceQuote {
+                price: cached.price,
+                origin: PriceOrigin::Cached {
+                    age_secs,
+                    stale: age_secs >= ttl_secs,
+                },
+            }
+        };
+
+        if let Some(cached) = cached
+            && now.saturating_sub(cached.fetched_at) < ttl_secs
+        {
+            return Ok(cached_quote(cached));
+        }
+
+        let prices: Vec<f64> = self
+            .sources
+            .iter()
+            .filter(|source| !(self.offline && source.is_remote()))
+            .filter_map(|source| {
+                fetch_either_way(&pair, |pair| source.fetch_price(pair))
+                    .inspect_err(|e| {
+                        tracing::debug!("{} could not price {}: {e}", source.name(), pair.label(&self.assets));
+                    })
+                    .ok()
+            })
+            .collect();
+
+        let sources = prices.len();
+        if let Some(price) = median(prices) {
+            self.cache.prices.insert(pair.key(), CachedPrice { price, fetched_at: now });
+            self.cache_dirty = true;
+            return Ok(PriceQuote {
+                price,
+                origin: PriceOrigin::Live { sources },
+            });
+        }
+
+        cached
+            .map(cached_quote)
+            .ok_or_else(|| PriceFetcherError::Unavailable(pair.label(&self.assets)))
+    }
+
+    /// Price of `pair` on the day of `timestamp`: the median of the sources with price history.
+    /// Overrides hold on every day. The price of a day that is not over yet, as of `now`, is not
+    /// persisted.
+    pub fn price_at(&mut self, pair: AssetPair, timestamp: u64, now: u64) -> Result<f64, PriceFetcherError> {
+        if let Some(price) = self.overrides.get(&pair) {
+            return Ok(*price);
+        }
+
+        let day = timestamp / SECONDS_PER_DAY;
+        let key = format!("{}@{day}", pair.key());
+        let cached = self.cache.historical.get(&key).or_else(|| self.cache.intraday.get(&key));
+        if let Some(price) = cached.copied() {
+            return Ok(price);
+        }
+
+        let prices: Vec<f64> = self
+            .sources
+            .iter()
+            .filter(|source| !(self.offline && source.is_remote()))
+            .filter_map(|source| {
+                fetch_either_way(&pair, |pair| source.fetch_price_at(pair, timestamp))
+                    .inspect_err(|e| {
+                        let pair = pair.label(&self.assets);
+                        tracing::debug!("{} could not price {pair} at {timestamp}: {e}", source.name());
+                    })
+                    .ok()
+            })
+            .collect();
+
+        let price = median(prices)
+            .ok_or_else(|| PriceFetcherError::Unavailable(format!("{} at {timestamp}", pair.label(&self.assets))))?;
+        if day < now / SECONDS_PER_DAY {
+            self.cache.historical.insert(key, price);
+            self.cache_dirty = true;
+        } else {
+            self.cache.intraday.insert(key, price);
+        }
+
+        Ok(price)
+    }
+
+    /// Price every pair on a blocking thread and persist newly fetched prices.
+    /// Pairs that cannot be priced are left out.
+    pub async fn prices(mut self, pairs: BTreeSet<AssetPair>) -> BTreeMap<AssetPair, PriceQuote> {
+        tokio::task::spawn_blocking(move || {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+
+            let quotes = pairs
+                .into_iter()
+                .filter_map(|pair| {
+                    self.price(pair, now)
+                        .inspect_err(|e| tracing::debug!("{e}"))
+                        .ok()
+                        .map(|quote| (pair, quote))
+                })
+                .collect();
+
+            self.save_cache();
+            quotes
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    /// Like [`Self::prices`], for each pair on the day of its timestamp.
+    pub async fn prices_at(mut self, requests: BTreeSet<(AssetPair, u64)>) -> BTreeMap<(AssetPair, u64), f64> {
+        tokio::task::spawn_blocking(move || {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+
+            let prices = requests
+                .into_iter()
+                .filter_map(|(pair, timestamp)| {
+                    self.price_at(pair, timestamp, now)
+                        .inspect_err(|e| tracing::debug!("{e}"))
+                        .ok()
+                        .map(|price| ((pair, timestamp), price))
+                })
+                .collect();
+
+            self.save_cache();
+            prices
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    fn save_cache(&self) {
+        if self.cache_dirty
+            && let Some(path) = &self.cache_path
+            && let Err(e) = self.cache.save(path)
+        {
+            tracing::warn!("Could not save price cache to {}: {e}", path.display());
+        }
+    }
+}
+
+/// Spot prices for `pairs`: `spot` for every pair if it was given on the command line,
+/// otherwise the configured oracle's.
+pub async fn spot_prices(
+    config: &Config,
+    assets: &AssetRegistry,
+    spot: Option<f64>,
+    pairs: BTreeSet<AssetPair>,
+) -> BTreeMap<AssetPair, PriceQuote> {
+    let mut oracle = PriceOracle::from_config(config, assets);
+    if let Some(spot) = spot {
+        for pair in &pairs {
+            oracle = oracle.with_override(*pair, spot);
+        }
+    }
+
+    oracle.prices(pairs).await
+}
+
+/// Ask for the pair, or for its inverse if the source only quotes the other way round.
+/// Zero, negative and non-finite prices are rejected in either direction.
+fn fetch_either_way(
+    pair: &AssetPair,
+    fetch: impl Fn(&AssetPair) -> Result<f64, PriceFetcherError>,
+) -> Result<f64, PriceFetcherError> {
+    match fetch(pair) {
+        Err(PriceFetcherError::Unsupported(_)) => fetch(&pair.inverse())
+            .and_then(valid_price)
+            .map(|price| 1.0 / price)
+            .and_then(valid_price),
+        result => result.and_then(valid_price),
+    }
+}
+
+fn median(mut prices: Vec<f64>) -> Option<f64> {
+    prices.sort_by(f64::total_cmp);
+
+    let mid = prices.len() / 2;
+    match prices.len() {
+        0 => None,
+        len if len % 2 == 0 => Some(f64::midpoint(prices[mid - 1], prices[mid])),
+        _ => Some(prices[mid]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use simplicityhl::elements::AssetId;
+
+    struct FixedFetcher(Option<f64>);
+
+    impl PriceFetcher for FixedFetcher {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn fetch_price(&self, _pair: &AssetPair) -> Result<f64, PriceFetcherError> {
+            self.0.ok_or(PriceFetcherError::RateLimit)
+        }
+    }
+
+    /// Quotes the same price now and on every past day.
+    struct DailyFetcher(f64);
+
+    impl PriceFetcher for DailyFetcher {
+        fn name(&self) -> &'static str {
+            "daily"
+        }
+
+        fn fetch_price(&self, _pair: &AssetPair) -> Result<f64, PriceFetcherError> {
+            Ok(self.0)
+        }
+
+        fn fetch_price_at(&self, _pair: &AssetPair, _timestamp: u64) -> Result<f64, PriceFetcherError> {
+            Ok(self.0)
+        }
+    }
+
+    fn pair() -> AssetPair {
+        AssetPair::new(AssetId::from_slice(&[1; 32]).unwrap(), AssetId::from_slice(&[2; 32]).unwrap())
+    }
+
+    #[test]
+    fn test_median_and_cache_fallback() {
+        assert!(median(vec![]).is_none());
+        assert!(median(vec![3.0, 1.0, 2.0]).is_some_and(|m| (m - 2.0).abs() < f64::EPSILON));
+        assert!(median(vec![4.0, 1.0, 2.0, 100.0]).is_some_and(|m| (m - 3.0).abs() < f64::EPSILON));
+
+        let mut oracle = PriceOracle::new(vec![
+            Box::new(FixedFetcher(Some(10.0))),
+            Box::new(FixedFetcher(Some(12.0))),
+            Box::new(FixedFetcher(None)),
+        ]);
+        oracle.ttl_secs = 60;
+
+        let live = oracle.price(pair(), 1_000).unwrap();
+        assert_eq!(live.origin, PriceOrigin::Live { sources: 2 });
+        assert!((live.price - 11.0).abs() < f64::EPSILON);
+
+        let cached = oracle.price(pair(), 1_030).unwrap();
+        assert_eq!(
+            cached.origin,
+            PriceOrigin::Cached {
+                age_secs: 30,
+                stale: false
+            }
+        );
+
+        // Once every source fails, the expired price is still served, marked as stale.
+        oracle.sources = vec![Box::new(FixedFetcher(None))];
+        let stale = oracle.price(pair(), 2_000).unwrap();
+        assert!((stale.price - 11.0).abs() < f64::EPSILON);
+        assert_eq!(
+            stale.origin,
+            PriceOrigin::Cached {
+                age_secs: 1_000,
+                stale: true
+            }
+        );
+        assert!(oracle.price(pair().inverse(), 2_000).is_err());
+
+        // Fixed fetchers have no history; cached daily prices are kept regardless.
+        assert!(oracle.price_at(pair(), 2_000, 2_000).is_err());
+        oracle.cache.historical.insert(format!("{}@0", pair().key()), 9.0);
+        assert!(oracle.price_at(pair(), 2_000, 2_000).is_ok_and(|price| (price - 9.0).abs() < f64::EPSILON));
+
+        let overridden = oracle.with_override(pair(), 5.0).price(pair(), 2_000).unwrap();
+        assert_eq!(overridden.origin, PriceOrigin::Override);
+    }
+
+    #[test]
+    fn test_only_closed_days_are_persisted() {
+        let now = 5 * SECONDS_PER_DAY + 600;
+        let mut oracle = PriceOracle::new(vec![Box::new(DailyFetcher(3.0))]);
+
+        // Today's price may still move, so it is only reused within the run.
+        assert!(oracle.price_at(pair(), now - 60, now).is_ok_and(|price| (price - 3.0).abs() < f64::EPSILON));
+        assert!(oracle.cache.historical.is_empty());
+        assert!(!oracle.cache_dirty);
+        assert!(!serde_json::to_string(&oracle.cache).unwrap().contains("intraday"));
+
+        assert!(oracle.price_at(pair(), now - SECONDS_PER_DAY, now).is_ok());
+        assert!(oracle.cache.historical.contains_key(&format!("{}@4", pair().key())));
+        assert!(oracle.cache_dirty);
+    }
+
+    #[test]
+    fn test_invalid_prices_are_rejected() {
+        let inverse_only = |price: f64| {
+            move |requested: &AssetPair| {
+                if *requested == pair() {
+                    Err(PriceFetcherError::Unsupported(String::new()))
+                } else {
+                    Ok(price)
+                }
+            }
+        };
+
+        assert!(fetch_either_way(&pair(), |_| Ok(f64::NAN)).is_err());
+        assert!(fetch_either_way(&pair(), |_| Ok(-1.0)).is_err());
+        assert!(fetch_either_way(&pair(), inverse_only(0.0)).is_err());
+        assert!(fetch_either_way(&pair(), inverse_only(f64::INFINITY)).is_err());
+        assert!(fetch_either_way(&pair(), inverse_only(4.0)).is_ok_and(|price| (price - 0.25).abs() < f64::EPSILON));
+
+        let mut oracle = PriceOracle::new(vec![
+            Box::new(FixedFetcher(Some(0.0))),
+            Box::new(FixedFetcher(Some(7.0))),
+        ]);
+        let live = oracle.price(pair(), 1_000).unwrap();
+        assert_eq!(live.origin, PriceOrigin::Live { sources: 1 });
+        assert!((live.price - 7.0).abs() < f64::EPSILON);
+    }
+}
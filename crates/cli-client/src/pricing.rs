@@ -0,0 +1,197 @@
+use crate::assets::AssetRegistry;
+
+use std::f64::consts::{PI, SQRT_2};
+
+use contracts::options::OptionsArguments;
+use simplicityhl::elements::AssetId;
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 86_400.0;
+const DAYS_PER_YEAR: f64 = 365.25;
+
+/// Market assumptions shared by every valuation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PricingParams {
+    /// Annualized volatility of the collateral price, e.g. 0.6 for 60%.
+    pub volatility: f64,
+    /// Annualized continuously compounded risk-free rate of the settlement asset.
+    pub risk_free_rate: f64,
+}
+
+/// Fair value and Greeks of a position, in whole settlement units.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Valuation {
+    pub fair_value: f64,
+    /// Change in value per unit change in spot.
+    pub delta: f64,
+    /// Change in delta per unit change in spot.
+    pub gamma: f64,
+    /// Change in value per day that passes.
+    pub theta: f64,
+    /// Change in value per volatility point (1%).
+    pub vega: f64,
+}
+
+impl Valuation {
+    /// Valuation of `quantity` units of this position.
+    #[must_use]
+    pub fn scale(self, quantity: f64) -> Self {
+        Self {
+            fair_value: self.fair_value * quantity,
+            delta: self.delta * quantity,
+            gamma: self.gamma * quantity,
+            theta: self.theta * quantity,
+            vega: self.vega * quantity,
+        }
+    }
+
+    /// Turn a call valuation into that of its writer's collateral: one unit of collateral
+    /// that has sold the call, as held by grantor token holders.
+    #[must_use]
+    pub fn covered(self, spot: f64) -> Self {
+        Self {
+            fair_value: spot - self.fair_value,
+            delta: 1.0 - self.delta,
+            gamma: -self.gamma,
+            theta: -self.theta,
+            vega: -self.vega,
+        }
+    }
+}
+
+/// Black-Scholes value and Greeks of a call on one unit of collateral.
+///
+/// Contracts can be exercised at any time before expiry, but the collateral pays no yield,
+/// so early exercise is never optimal and the European price applies. Expired options,
+/// and options priced with no volatility, are worth their discounted intrinsic value.
+#[must_use]
+pub fn black_scholes_call(spot: f64, strike: f64, years: f64, params: PricingParams) -> Valuation {
+    let years = years.max(0.0);
+    let discount = (-params.risk_free_rate * years).exp();
+
+    if years <= 0.0 || params.volatility <= 0.0 || spot <= 0.0 || strike <= 0.0 {
+        let intrinsic = spot - strike * discount;
+        return Valuation {
+            fair_value: intrinsic.max(0.0),
+            delta: if intrinsic > 0.0 { 1.0 } else { 0.0 },
+            ..Valuation::default()
+        };
+    }
+
+    let vol_sqrt_t = params.volatility * years.sqrt();
+    let d_up = ((spot / strike).ln() + (params.risk_free_rate + params.volatility.powi(2) / 2.0) * years) / vol_sqrt_t;
+    let d_down = d_up - vol_sqrt_t;
+
+    let density = normal_pdf(d_up);
+    let annual_theta = -spot * density * params.volatility / (2.0 * years.sqrt())
+        - params.risk_free_rate * strike * discount * normal_cdf(d_down);
+
+    Valuation {
+        fair_value: spot * normal_cdf(d_up) - strike * discount * normal_cdf(d_down),
+        delta: normal_cdf(d_up),
+        gamma: density / (spot * vol_sqrt_t),
+        theta: annual_theta / DAYS_PER_YEAR,
+        vega: spot * density * years.sqrt() / 100.0,
+    }
+}
+
+/// Years from `now` until `expiry_time`, both Unix timestamps. Zero once expired.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn years_to_expiry(expiry_time: u32, now: u64) -> f64 {
+    u64::from(expiry_time).saturating_sub(now) as f64 / SECONDS_PER_YEAR
+}
+
+/// Convert a price in settlement base units per collateral base unit to whole units of each.
+#[must_use]
+pub fn whole_unit_price(assets: &AssetRegistry, base_price: f64, collateral: &AssetId, settlement: &AssetId) -> f64 {
+    base_price * 10f64.powi(i32::from(assets.precision(collateral)) - i32::from(assets.precision(settlement)))
+}
+
+/// Strike of an option contract, in whole settlement units per whole unit of collateral.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn option_strike(assets: &AssetRegistry, args: &OptionsArguments) -> f64 {
+    whole_unit_price(
+        assets,
+        args.settlement_per_contract() as f64 / args.collateral_per_contract().max(1) as f64,
+        &args.get_collateral_asset_id(),
+        &args.get_settlement_asset_id(),
+    )
+}
+
+/// Whole units of collateral locked by one option contract.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn contract_size(assets: &AssetRegistry, args: &OptionsArguments) -> f64 {
+    args.collateral_per_contract() as f64 / 10f64.powi(i32::from(assets.precision(&args.get_collateral_asset_id())))
+}
+
+fn normal_pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * PI).sqrt()
+}
+
+fn normal_cdf(x: f64) -> f64 {
+    erfc(-x / SQRT_2) / 2.0
+}
+
+/// Complementary error function, with a fractional error below 1.2e-7 (Numerical Recipes `erfcc`).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + z / 2.0);
+    let poly = -1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98 + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let result = t * (-z * z + poly).exp();
+
+    if x >= 0.0 { result } else { 2.0 - result }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: PricingParams = PricingParams {
+        volatility: 0.2,
+        risk_free_rate: 0.05,
+    };
+
+    /// Equal to within 0.1% of `expected`, so small Greeks such as theta and gamma are checked as
+    /// strictly as the fair value. Zero must match exactly.
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() <= expected.abs() * 1e-3, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn test_black_scholes_call() {
+        let call = black_scholes_call(100.0, 100.0, 1.0, PARAMS);
+
+        assert_close(call.fair_value, 10.4506);
+        assert_close(call.delta, 0.6368);
+        assert_close(call.gamma, 0.018_76);
+        assert_close(call.theta, -6.414 / DAYS_PER_YEAR);
+        assert_close(call.vega, 0.3752);
+
+        let covered = call.covered(100.0).scale(2.0);
+        assert_close(covered.fair_value, 2.0 * (100.0 - 10.4506));
+        assert_close(covered.delta, 2.0 * (1.0 - 0.6368));
+    }
+
+    #[test]
+    fn test_expired_call_is_intrinsic() {
+        let in_the_money = black_scholes_call(120.0, 100.0, 0.0, PARAMS);
+        assert_close(in_the_money.fair_value, 20.0);
+        assert_close(in_the_money.delta, 1.0);
+        assert_close(in_the_money.vega, 0.0);
+
+        let out_of_the_money = black_scholes_call(80.0, 100.0, 0.0, PARAMS);
+        assert_close(out_of_the_money.fair_value, 0.0);
+        assert_close(out_of_the_money.delta, 0.0);
+
+        assert_close(years_to_expiry(1_000, 2_000), 0.0);
+        assert_close(years_to_expiry(31_557_600, 0), 1.0);
+    }
+}
@@ -6,12 +6,13 @@ use simplicityhl_core::derive_public_blinder_key;
 
 use crate::cli::{GRANTOR_TOKEN_TAG, OPTION_OFFER_COLLATERAL_TAG, OPTION_TOKEN_TAG};
 use crate::error::Error;
-use crate::explorer::fetch_transaction;
+use crate::explorer::EsploraBackend;
 use crate::metadata::ContractMetadata;
 use crate::metadata::HistoryEntry;
 
 pub async fn sync_option_event(
     store: &Store,
+    esplora: &EsploraBackend,
     event: &OptionCreatedEvent,
     source: &str,
     arguments: simplicityhl::Arguments,
@@ -50,7 +51,7 @@ pub async fn sync_option_event(
         .insert_contract_token(&event.taproot_pubkey_gen, grantor_token_id, GRANTOR_TOKEN_TAG)
         .await?;
 
-    if let Err(e) = sync_utxo_with_public_blinder(store, event.utxo).await {
+    if let Err(e) = sync_utxo_with_public_blinder(store, esplora, event.utxo).await {
         tracing::debug!("Could not sync option UTXO {}: {} (soft failure)", event.utxo, e);
     }
 
@@ -67,9 +68,10 @@ pub async fn sync_option_event(
 /// - Handles asset issuance entropy
 pub async fn sync_utxo_with_public_blinder(
     store: &Store,
+    esplora: &EsploraBackend,
     outpoint: simplicityhl::elements::OutPoint,
 ) -> Result<(), Error> {
-    let tx = fetch_transaction(outpoint.txid)?;
+    let tx = esplora.fetch_transaction(outpoint.txid)?;
 
     let blinder_keypair = derive_public_blinder_key();
     let mut blinder_keys = HashMap::new();
@@ -82,6 +84,7 @@ pub async fn sync_utxo_with_public_blinder(
 
 pub async fn sync_option_offer_event(
     store: &Store,
+    esplora: &EsploraBackend,
     event: &OptionOfferCreatedEvent,
     source: &str,
     arguments: simplicityhl::Arguments,
@@ -134,13 +137,43 @@ pub async fn sync_option_offer_event(
         .insert_contract_token(&event.taproot_pubkey_gen, collateral_asset, OPTION_OFFER_COLLATERAL_TAG)
         .await?;
 
-    if let Err(e) = sync_utxo_with_public_blinder(store, event.utxo).await {
+    if let Err(e) = sync_utxo_with_public_blinder(store, esplora, event.utxo).await {
         tracing::debug!("Could not sync option offer UTXO {}: {} (soft failure)", event.utxo, e);
     }
 
     Ok(())
 }
 
+/// Point an offer contract synced earlier at the latest version of its offer event, so it keeps
+/// matching the offer relays serve after the maker republishes it.
+///
+/// Returns whether the contract metadata changed.
+pub async fn track_republished_offer(
+    store: &Store,
+    esplora: &EsploraBackend,
+    event: &OptionOfferCreatedEvent,
+) -> Result<bool, Error> {
+    let Some(mut metadata) = get_contract_metadata(store, &event.taproot_pubkey_gen).await? else {
+        return Ok(false);
+    };
+
+    let event_id = event.event_id.to_hex();
+    let republished = metadata.nostr_author.as_deref() == Some(event.pubkey.to_hex().as_str())
+        && metadata.nostr_event_id.as_deref() != Some(event_id.as_str());
+    if !republished {
+        return Ok(false);
+    }
+
+    metadata.nostr_event_id = Some(event_id);
+    update_contract_metadata(store, &event.taproot_pubkey_gen, &metadata).await?;
+
+    if let Err(e) = sync_utxo_with_public_blinder(store, esplora, event.utxo).await {
+        tracing::debug!("Could not sync option offer UTXO {}: {} (soft failure)", event.utxo, e);
+    }
+
+    Ok(true)
+}
+
 pub async fn get_contract_metadata(
     store: &Store,
     taproot_pubkey_gen: &contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen,
@@ -1,7 +1,8 @@
 use std::path::Path;
 
-use coin_store::Store;
+use coin_store::{BlindingKeyStore, Store, TransactionStore};
 use signer::Signer;
+use simplicityhl::elements::secp256k1_zkp::{Keypair, SECP256K1};
 use simplicityhl_core::SimplicityNetwork;
 
 use crate::error::Error;
@@ -20,6 +21,9 @@ impl Wallet {
     ) -> Result<Self, Error> {
         let signer = Signer::from_seed(seed)?;
         let store = Store::create(db_path).await?;
+        store
+            .add_wallet_script(&signer.p2pk_address(network)?.script_pubkey())
+            .await?;
 
         Ok(Self { signer, store, network })
     }
@@ -31,6 +35,9 @@ impl Wallet {
     ) -> Result<Self, Error> {
         let signer = Signer::from_seed(seed)?;
         let store = Store::connect(db_path).await?;
+        store
+            .add_wallet_script(&signer.p2pk_address(network)?.script_pubkey())
+            .await?;
 
         Ok(Self { signer, store, network })
     }
@@ -49,4 +56,27 @@ impl Wallet {
     pub const fn network(&self) -> SimplicityNetwork {
         self.network
     }
+
+    /// Key the wallet blinds its change to, derived from the seed.
+    pub fn change_blinding_keypair(&self) -> Result<Keypair, Error> {
+        let script_pubkey = self.signer.p2pk_address(self.network)?.script_pubkey();
+        let blinding_key = self.signer.blinding_key(&script_pubkey)?;
+
+        Ok(Keypair::from_secret_key(SECP256K1, &blinding_key))
+    }
+
+    /// Keys that unblind outputs to the wallet: those of its confidential receive addresses,
+    /// derived from the seed at the stored indices, followed by its change key.
+    pub async fn blinding_keypairs(&self) -> Result<Vec<Keypair>, Error> {
+        let script_pubkey = self.signer.p2pk_address(self.network)?.script_pubkey();
+
+        let mut keypairs = Vec::new();
+        for index in self.store.list_receive_indices(&script_pubkey).await? {
+            let blinding_key = self.signer.receive_blinding_key(&script_pubkey, index)?;
+            keypairs.push(Keypair::from_secret_key(SECP256K1, &blinding_key));
+        }
+        keypairs.push(self.change_blinding_keypair()?);
+
+        Ok(keypairs)
+    }
 }
@@ -0,0 +1,181 @@
+mod support;
+
+use std::str::FromStr;
+
+use nostr::EventId;
+use options_relay::test_support::MockRelay;
+use options_relay::{ACTION_COMPLETED, OPTION_CREATED, OPTION_OFFER_CREATED};
+use simplicityhl::elements::hashes::Hash;
+use simplicityhl::elements::{AssetId, OutPoint, Txid};
+use simplicityhl_core::LIQUID_TESTNET_BITCOIN_ASSET;
+use support::{Dex, FakeEsplora, value_after};
+
+fn test_asset(byte: u8) -> AssetId {
+    AssetId::from_byte_array([byte; 32])
+}
+
+fn txid_after(output: &str, label: &str) -> Txid {
+    Txid::from_str(value_after(output, label).expect("txid not printed")).expect("invalid txid")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn option_create_exercise_and_settle() -> anyhow::Result<()> {
+    let relay = MockRelay::run().await?;
+    let esplora = FakeEsplora::run()?;
+    let settlement_asset = test_asset(0xaa);
+
+    let dex = Dex::new("option-lifecycle", 1, &relay, &esplora)?;
+    dex.run(&["wallet", "init"]);
+
+    let address = dex.address();
+    esplora.fund(&address, *LIQUID_TESTNET_BITCOIN_ASSET, &[100_000; 4]);
+    esplora.fund(&address, settlement_asset, &[50_000]);
+    dex.run(&["sync", "utxos"]);
+
+    let created = dex.run(&[
+        "option",
+        "create",
+        "--collateral-asset",
+        &LIQUID_TESTNET_BITCOIN_ASSET.to_string(),
+        "--total-collateral",
+        "10000",
+        "--num-contracts",
+        "10",
+        "--settlement-asset",
+        &settlement_asset.to_string(),
+        "--total-strike",
+        "20000",
+        "--expiry",
+        "+30d",
+        "--fee",
+        "500",
+        "--broadcast",
+    ]);
+    let funding_txid = txid_after(&created, "Funding tx:");
+    assert_eq!(esplora.broadcasts(), vec![txid_after(&created, "Creation tx:"), funding_txid]);
+
+    let events = relay.stored_events();
+    assert_eq!(events.iter().filter(|e| e.kind == OPTION_CREATED).count(), 1);
+    assert_eq!(events.iter().filter(|e| e.kind == ACTION_COMPLETED).count(), 1);
+
+    // Select the only contract and exercise 2 of the 10 options.
+    dex.run_with_input(&["option", "exercise", "--fee", "500", "--broadcast"], "1\n2\n");
+    assert_eq!(esplora.broadcasts().len(), 3);
+    assert!(esplora.is_spent(OutPoint::new(funding_txid, 0)));
+
+    dex.run(&["sync", "full"]);
+
+    // Positions are valued against the price fixture rather than a live feed.
+    dex.set_prices(&format!(r#"{{ "LBTC/{settlement_asset}": 300000000.0 }}"#))?;
+    let positions = dex.run(&["positions", "--volatility", "0.5"]);
+    assert!(positions.contains("300000000.00 (live, median of 1 source(s))"));
+
+    // Wallet movements are exported with their value on the trade date.
+    let ledger = dex.run(&["export", "--format", "json", "--fiat", &settlement_asset.to_string()]);
+    assert!(ledger.contains(r#""kind": "fee""#));
+    assert!(ledger.contains(r#""fiat_price": 300000000.0"#));
+
+    // Broadcast transactions are recorded in the wallet history; the fixture mines each one at once.
+    let history = dex.run(&["wallet", "history"]);
+    assert!(history.contains(&funding_txid.to_string()));
+    assert!(history.contains("Block "));
+    assert!(!history.contains("Unconfirmed"));
+
+    // Claim the settlement paid for the exercised options with 2 grantor tokens.
+    dex.run_with_input(&["option", "settlement", "--fee", "500", "--broadcast"], "1\n2\n");
+    assert_eq!(esplora.broadcasts().len(), 4);
+    assert_eq!(relay.stored_events().iter().filter(|e| e.kind == ACTION_COMPLETED).count(), 3);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn option_offer_create_take_and_withdraw() -> anyhow::Result<()> {
+    let relay = MockRelay::run().await?;
+    let esplora = FakeEsplora::run()?;
+    let (collateral_asset, premium_asset) = (test_asset(0xbb), test_asset(0xcc));
+
+    let maker = Dex::new("offer-maker", 2, &relay, &esplora)?;
+    let taker = Dex::new("offer-taker", 3, &relay, &esplora)?;
+    maker.run(&["wallet", "init"]);
+    taker.run(&["wallet", "init"]);
+
+    let maker_address = maker.address();
+    esplora.fund(&maker_address, *LIQUID_TESTNET_BITCOIN_ASSET, &[100_000; 2]);
+    esplora.fund(&maker_address, collateral_asset, &[10_000]);
+    esplora.fund(&maker_address, premium_asset, &[10_000]);
+    maker.run(&["sync", "utxos"]);
+
+    esplora.fund(&taker.address(), *LIQUID_TESTNET_BITCOIN_ASSET, &[100_000; 3]);
+
+    // 1000 collateral for 10 LBTC each, with a premium of 2 per collateral.
+    let created = maker.run(&[
+        "option-offer",
+        "create",
+        "--collateral-asset",
+        &collateral_asset.to_string(),
+        "--collateral-amount",
+        "1000",
+        "--premium-asset",
+        &premium_asset.to_string(),
+        "--premium-amount",
+        "2000",
+        "--settlement-asset",
+        &LIQUID_TESTNET_BITCOIN_ASSET.to_string(),
+        "--settlement-amount",
+        "10000",
+        "--expiry",
+        "+30d",
+        "--fee",
+        "500",
+        "--broadcast",
+    ]);
+    let offer_txid = txid_after(&created, "Broadcasted:");
+    let offer_event = value_after(&created, "Published to NOSTR:").expect("offer event id not printed");
+    let offer_event_id = EventId::from_hex(offer_event)?;
+    assert!(
+        relay
+            .stored_events()
+            .iter()
+            .any(|e| e.id == offer_event_id && e.kind == OPTION_OFFER_CREATED)
+    );
+
+    taker.run(&["sync", "full"]);
+    taker.run_with_input(
+        &["option-offer", "take", "--offer-event", offer_event, "--fee", "500", "--broadcast"],
+        "400\n",
+    );
+    assert_eq!(esplora.broadcasts().len(), 2);
+    assert!(esplora.is_spent(OutPoint::new(offer_txid, 0)));
+    assert!(relay.stored_events().iter().any(|e| e.kind == ACTION_COMPLETED));
+
+    maker.run(&["sync", "full"]);
+    maker.run(&["option-offer", "withdraw", "--offer-event", offer_event, "--fee", "500", "--broadcast"]);
+    assert_eq!(esplora.broadcasts().len(), 3);
+
+    // The maker's deposit and withdrawal are fetched back from the chain and attributed.
+    let report = maker.run(&["report", "pnl", "--from", "-1d"]);
+    assert!(report.contains("P&L by Contract:") && report.contains("OptionOffer"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn transaction_label_shows_on_outputs_and_exports() -> anyhow::Result<()> {
+    let relay = MockRelay::run().await?;
+    let esplora = FakeEsplora::run()?;
+
+    let dex = Dex::new("transaction-label", 4, &relay, &esplora)?;
+    dex.run(&["wallet", "init"]);
+
+    let funding_txid = esplora.fund(&dex.address(), *LIQUID_TESTNET_BITCOIN_ASSET, &[100_000]);
+    dex.run(&["sync", "utxos"]);
+
+    // A transaction label shows on its outputs and is exported as BIP-329.
+    dex.run(&["label", "set", "--tx", &funding_txid.to_string(), "--label", "Funding"]);
+    assert!(dex.run(&["wallet", "utxos"]).contains("Funding"));
+    let labels = dex.run(&["label", "export"]);
+    assert!(labels.contains(&format!(r#"{{"type":"tx","ref":"{funding_txid}","label":"Funding"}}"#)));
+
+    Ok(())
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+use serde_json::{Value, json};
+use simplicityhl::elements::confidential::{Asset, Nonce, Value as ConfidentialValue};
+use simplicityhl::elements::encode;
+use simplicityhl::elements::hashes::{Hash, sha256};
+use simplicityhl::elements::{Address, AssetId, OutPoint, Script, Transaction, TxIn, TxOut, TxOutWitness, Txid};
+
+/// Time of block 0; each later block is mined a minute after the previous one.
+const GENESIS_TIME: u64 = 1_700_000_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Spend {
+    txid: Txid,
+    vin: usize,
+}
+
+#[derive(Debug, Default)]
+struct Chain {
+    transactions: HashMap<Txid, Transaction>,
+    /// Block height of each transaction; every transaction is mined in its own block.
+    heights: HashMap<Txid, u64>,
+    spends: HashMap<OutPoint, Spend>,
+    broadcasts: Vec<Txid>,
+    tip: u64,
+}
+
+impl Chain {
+    /// Confirm `tx` in a new block. Broadcasts must only spend known, unspent outputs.
+    fn add(&mut self, tx: Transaction, is_broadcast: bool) -> Result<Txid, String> {
+        let txid = tx.txid();
+        if self.transactions.contains_key(&txid) {
+            return Ok(txid);
+        }
+
+        if is_broadcast {
+            for input in &tx.input {
+                let exists = self
+                    .transactions
+                    .get(&input.previous_output.txid)
+                    .is_some_and(|prev| prev.output.len() > input.previous_output.vout as usize);
+                if !exists || self.spends.contains_key(&input.previous_output) {
+                    return Err(format!("bad-txns-inputs-missingorspent: {}", input.previous_output));
+                }
+            }
+
+            for (vin, input) in tx.input.iter().enumerate() {
+                self.spends.insert(input.previous_output, Spend { txid, vin });
+            }
+            self.broadcasts.push(txid);
+        }
+
+        self.tip += 1;
+        self.heights.insert(txid, self.tip);
+        self.transactions.insert(txid, tx);
+
+        Ok(txid)
+    }
+
+    fn unspent_outputs<'a>(&'a self, script: &'a Script) -> impl Iterator<Item = (OutPoint, &'a TxOut)> + 'a {
+        self.transactions.iter().flat_map(move |(txid, tx)| {
+            tx.output
+                .iter()
+                .enumerate()
+                .filter(move |(_, out)| out.script_pubkey == *script)
+                .map(move |(vout, out)| (OutPoint::new(*txid, u32::try_from(vout).unwrap()), out))
+                .filter(|(outpoint, _)| !self.spends.contains_key(outpoint))
+        })
+    }
+
+    /// Confirmation status of a known transaction, the same for every endpoint that reports it.
+    fn status_json(&self, txid: Txid) -> Value {
+        let height = self.heights.get(&txid).copied().unwrap_or_default();
+        json!({
+            "confirmed": true,
+            "block_height": height,
+            "block_time": GENESIS_TIME + height * 60,
+        })
+    }
+
+    fn utxo_json(&self, outpoint: OutPoint, out: &TxOut) -> Value {
+        let mut utxo = json!({
+            "txid": outpoint.txid.to_string(),
+            "vout": outpoint.vout,
+            "status": self.status_json(outpoint.txid),
+        });
+
+        match out.value {
+            ConfidentialValue::Explicit(value) => utxo["value"] = json!(value),
+            value => utxo["valuecommitment"] = json!(encode::serialize_hex(&value)),
+        }
+        match out.asset {
+            Asset::Explicit(asset) => utxo["asset"] = json!(asset.to_string()),
+            asset => utxo["assetcommitment"] = json!(encode::serialize_hex(&asset)),
+        }
+
+        utxo
+    }
+}
+
+/// In-memory Esplora HTTP API for tests.
+///
+/// Serves the endpoints the CLI uses from a UTXO set built from funding transactions and
+/// broadcasts. Broadcasts are checked for unknown and double-spent inputs but not for
+/// script validity, and each accepted transaction is confirmed immediately.
+pub struct FakeEsplora {
+    url: String,
+    chain: Arc<Mutex<Chain>>,
+}
+
+impl FakeEsplora {
+    /// Start the server on a random localhost port. The listener thread lives until the test process exits.
+    pub fn run() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let chain = Arc::new(Mutex::new(Chain::default()));
+
+        thread::spawn({
+            let chain = Arc::clone(&chain);
+            move || {
+                for stream in listener.incoming().flatten() {
+                    let chain = Arc::clone(&chain);
+                    thread::spawn(move || {
+                        let _ = handle_connection(stream, &chain);
+                    });
+                }
+            }
+        });
+
+        Ok(Self { url, chain })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Confirm a transaction paying explicit `amounts` of `asset` to `address`, one output each.
+    pub fn fund(&self, address: &Address, asset: AssetId, amounts: &[u64]) -> Txid {
+        let mut chain = lock(&self.chain);
+
+        // A unique, unknown previous output keeps funding txids distinct.
+        let marker = sha256::Hash::hash(&chain.tip.to_le_bytes());
+        let input = TxIn {
+            previous_output: OutPoint::new(Txid::from_byte_array(marker.to_byte_array()), 0),
+            ..TxIn::default()
+        };
+
+        let output = amounts
+            .iter()
+            .map(|amount| TxOut {
+                asset: Asset::Explicit(asset),
+                value: ConfidentialValue::Explicit(*amount),
+                nonce: Nonce::Null,
+                script_pubkey: address.script_pubkey(),
+                witness: TxOutWitness::default(),
+            })
+            .collect();
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: simplicityhl::elements::LockTime::ZERO,
+            input: vec![input],
+            output,
+        };
+
+        chain.add(tx, false).expect("funding transactions are always accepted")
+    }
+
+    /// Txids accepted through `POST /tx`, in broadcast order.
+    pub fn broadcasts(&self) -> Vec<Txid> {
+        lock(&self.chain).broadcasts.clone()
+    }
+
+    pub fn is_spent(&self, outpoint: OutPoint) -> bool {
+        lock(&self.chain).spends.contains_key(&outpoint)
+    }
+}
+
+fn lock(chain: &Mutex<Chain>) -> MutexGuard<'_, Chain> {
+    chain.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(value: &Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn text(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain",
+            body: body.into().into_bytes(),
+        }
+    }
+
+    fn not_found() -> Self {
+        Self::text(404, "Not Found")
+    }
+}
+
+fn handle_connection(stream: TcpStream, chain: &Mutex<Chain>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let response = route(method, path, &body, chain);
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        if response.status == 200 { "OK" } else { "Error" },
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+fn route(method: &str, path: &str, body: &[u8], chain: &Mutex<Chain>) -> Response {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let mut chain = lock(chain);
+
+    match (method, segments.as_slice()) {
+        ("POST", ["tx"]) => {
+            let tx = std::str::from_utf8(body)
+                .ok()
+                .and_then(|hex| hex::decode(hex.trim()).ok())
+                .and_then(|bytes| encode::deserialize::<Transaction>(&bytes).ok());
+            let Some(tx) = tx else {
+                return Response::text(400, "TX decode failed");
+            };
+
+            match chain.add(tx, true) {
+                Ok(txid) => Response::text(200, txid.to_string()),
+                Err(reason) => Response::text(400, reason),
+            }
+        }
+        ("GET", ["tx", txid, "raw"]) => match Txid::from_str(txid).ok().and_then(|t| chain.transactions.get(&t)) {
+            Some(tx) => Response {
+                status: 200,
+                content_type: "application/octet-stream",
+                body: encode::serialize(tx),
+            },
+            None => Response::not_found(),
+        },
+        ("GET", ["tx", txid, "status"]) => {
+            match Txid::from_str(txid).ok().filter(|t| chain.transactions.contains_key(t)) {
+                Some(txid) => Response::json(&chain.status_json(txid)),
+                None => Response::not_found(),
+            }
+        }
+        ("GET", ["tx", txid, "outspends"]) => {
+            let Some((txid, tx)) = Txid::from_str(txid)
+                .ok()
+                .and_then(|t| chain.transactions.get(&t).map(|tx| (t, tx)))
+            else {
+                return Response::not_found();
+            };
+
+            let outspends: Vec<Value> = (0..tx.output.len())
+                .map(|vout| match chain.spends.get(&OutPoint::new(txid, u32::try_from(vout).unwrap())) {
+                    Some(spend) => json!({
+                        "spent": true,
+                        "txid": spend.txid.to_string(),
+                        "vin": spend.vin,
+                        "status": chain.status_json(spend.txid),
+                    }),
+                    None => json!({ "spent": false }),
+                })
+                .collect();
+            Response::json(&Value::Array(outspends))
+        }
+        ("GET", ["address", address, "utxo"]) => {
+            let Ok(address) = Address::from_str(address) else {
+                return Response::text(400, "Invalid address");
+            };
+
+            let script = address.script_pubkey();
+            let utxos: Vec<Value> = chain
+                .unspent_outputs(&script)
+                .map(|(outpoint, out)| chain.utxo_json(outpoint, out))
+                .collect();
+            Response::json(&Value::Array(utxos))
+        }
+        ("GET", ["scripthash", scripthash, "utxo"]) => {
+            let scripts: Vec<Script> = chain
+                .transactions
+                .values()
+                .flat_map(|tx| tx.output.iter().map(|out| out.script_pubkey.clone()))
+                .filter(|script| hex::encode(sha256::Hash::hash(script.as_bytes()).to_byte_array()) == *scripthash)
+                .collect();
+
+            let utxos: Vec<Value> = scripts
+                .first()
+                .map(|script| {
+                    chain
+                        .unspent_outputs(script)
+                        .map(|(outpoint, out)| chain.utxo_json(outpoint, out))
+                        .collect()
+                })
+                .unwrap_or_default();
+            Response::json(&Value::Array(utxos))
+        }
+        ("GET", ["blocks", "tip", "height"]) => Response::text(200, chain.tip.to_string()),
+        ("GET", ["fee-estimates"]) => Response::json(&json!({})),
+        _ => Response::not_found(),
+    }
+}
@@ -0,0 +1,124 @@
+pub mod fake_esplora;
+
+pub use fake_esplora::FakeEsplora;
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use options_relay::test_support::MockRelay;
+use simplicityhl::elements::Address;
+
+/// One CLI user with its own seed, config and wallet database, talking to the fake backends.
+pub struct Dex {
+    dir: PathBuf,
+    seed: String,
+}
+
+impl Dex {
+    pub fn new(name: &str, seed: u8, relay: &MockRelay, esplora: &FakeEsplora) -> io::Result<Self> {
+        let dir = std::env::temp_dir().join(format!("simplicity-dex-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+
+        let config = format!(
+            r#"[network]
+name = "testnet"
+
+[relay]
+urls = ["{relay}"]
+timeout_secs = 5
+retry_count = 1
+min_acks = 1
+
+[storage]
+data_dir = "{data_dir}"
+
+[fee]
+confirmation_target = 0
+
+[explorer]
+url = "{esplora}"
+
+[oracle]
+sources = ["file"]
+prices_file = "{prices}"
+offline = true
+"#,
+            relay = relay.url(),
+            data_dir = dir.join("data").display(),
+            esplora = esplora.url(),
+            prices = dir.join("prices.json").display(),
+        );
+        fs::write(dir.join("config.toml"), config)?;
+        fs::write(dir.join("prices.json"), "{}")?;
+
+        Ok(Self {
+            dir,
+            seed: hex::encode([seed; 32]),
+        })
+    }
+
+    /// Run a command that must succeed and return its stdout.
+    pub fn run(&self, args: &[&str]) -> String {
+        self.run_with_input(args, "")
+    }
+
+    /// Run a command, answering its interactive prompts with `input`.
+    pub fn run_with_input(&self, args: &[&str], input: &str) -> String {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_simplicity-dex"))
+            .arg("--config")
+            .arg(self.dir.join("config.toml"))
+            .arg("--seed")
+            .arg(&self.seed)
+            .args(args)
+            .current_dir(&self.dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("failed to start simplicity-dex");
+
+        // The command may exit before reading every answer.
+        let _ = child.stdin.take().expect("stdin is piped").write_all(input.as_bytes());
+
+        let output = child.wait_with_output().expect("simplicity-dex did not exit");
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+
+        assert!(
+            output.status.success(),
+            "`simplicity-dex {}` failed\nstdout:\n{stdout}\nstderr:\n{}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        stdout
+    }
+
+    /// Replace the price fixture read by the offline oracle, as `"BASE/QUOTE": price` JSON.
+    pub fn set_prices(&self, prices: &str) -> io::Result<()> {
+        fs::write(self.dir.join("prices.json"), prices)
+    }
+
+    pub fn address(&self) -> Address {
+        let details = self.run(&["wallet", "address"]);
+        let address = value_after(&details, "P2PK Address:").expect("wallet address not printed");
+        Address::from_str(address).expect("invalid wallet address")
+    }
+}
+
+impl Drop for Dex {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Value printed after `label` on the first line containing it.
+pub fn value_after<'a>(output: &'a str, label: &str) -> Option<&'a str> {
+    output
+        .lines()
+        .find_map(|line| line.split_once(label))
+        .map(|(_, value)| value.trim())
+}
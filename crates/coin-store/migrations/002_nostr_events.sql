@@ -0,0 +1,15 @@
+CREATE TABLE nostr_events
+(
+    event_id   TEXT    NOT NULL,
+    kind       INTEGER NOT NULL,
+    pubkey     TEXT    NOT NULL,
+    created_at INTEGER NOT NULL,
+    tags       TEXT    NOT NULL,
+    content    TEXT    NOT NULL,
+    sig        TEXT    NOT NULL,
+
+    PRIMARY KEY (event_id)
+);
+
+CREATE INDEX idx_nostr_events_kind_created_at ON nostr_events (kind, created_at);
+CREATE INDEX idx_nostr_events_pubkey ON nostr_events (pubkey);
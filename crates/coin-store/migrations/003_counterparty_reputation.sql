@@ -0,0 +1,22 @@
+CREATE TABLE counterparty_stats
+(
+    pubkey                 TEXT    NOT NULL,
+    offers_published       INTEGER NOT NULL,
+    offers_honoured        INTEGER NOT NULL,
+    offers_cancelled_early INTEGER NOT NULL,
+    offers_bogus           INTEGER NOT NULL,
+    fills                  INTEGER NOT NULL,
+    total_filled           INTEGER NOT NULL,
+    updated_at             INTEGER NOT NULL,
+
+    PRIMARY KEY (pubkey)
+);
+
+CREATE TABLE counterparty_lists
+(
+    pubkey   TEXT    NOT NULL,
+    list     TEXT    NOT NULL CHECK (list IN ('trusted', 'blocked')),
+    added_at INTEGER NOT NULL,
+
+    PRIMARY KEY (pubkey)
+);
@@ -0,0 +1,13 @@
+CREATE TABLE asset_registry
+(
+    asset_id   TEXT    NOT NULL,
+    ticker     TEXT    NOT NULL COLLATE NOCASE,
+    name       TEXT    NOT NULL,
+    precision  INTEGER NOT NULL CHECK (precision BETWEEN 0 AND 8),
+    issuer     TEXT,
+    updated_at INTEGER NOT NULL,
+
+    PRIMARY KEY (asset_id)
+);
+
+CREATE UNIQUE INDEX idx_asset_registry_ticker ON asset_registry (ticker);
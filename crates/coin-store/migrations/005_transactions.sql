@@ -0,0 +1,31 @@
+CREATE TABLE wallet_scripts
+(
+    script_pubkey BLOB NOT NULL,
+
+    PRIMARY KEY (script_pubkey)
+);
+
+CREATE TABLE transactions
+(
+    txid               BLOB    NOT NULL,
+    raw                BLOB    NOT NULL,
+    block_height       INTEGER,
+    block_time         INTEGER,
+    first_seen         INTEGER NOT NULL,
+    fee                INTEGER,
+    taproot_pubkey_gen TEXT,
+
+    PRIMARY KEY (txid)
+);
+
+CREATE TABLE transaction_deltas
+(
+    txid     BLOB    NOT NULL,
+    asset_id TEXT    NOT NULL,
+    delta    INTEGER NOT NULL,
+
+    PRIMARY KEY (txid, asset_id),
+    FOREIGN KEY (txid) REFERENCES transactions (txid)
+);
+
+CREATE INDEX idx_transaction_deltas_asset_id ON transaction_deltas (asset_id);
@@ -0,0 +1,9 @@
+CREATE TABLE labels
+(
+    kind       TEXT    NOT NULL CHECK (kind IN ('tx', 'output', 'script', 'contract')),
+    reference  BLOB    NOT NULL,
+    label      TEXT    NOT NULL,
+    updated_at INTEGER NOT NULL,
+
+    PRIMARY KEY (kind, reference)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE frozen_utxos
+(
+    txid      BLOB    NOT NULL,
+    vout      INTEGER NOT NULL,
+    reason    TEXT,
+    frozen_at INTEGER NOT NULL,
+
+    PRIMARY KEY (txid, vout),
+    FOREIGN KEY (txid, vout) REFERENCES utxos (txid, vout)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE receive_blinding_keys
+(
+    script_pubkey BLOB    NOT NULL,
+    key_index     INTEGER NOT NULL,
+    created_at    INTEGER NOT NULL,
+    PRIMARY KEY (script_pubkey, key_index)
+);
@@ -0,0 +1,5 @@
+CREATE TABLE relay_cursors
+(
+    relay_url TEXT    NOT NULL PRIMARY KEY,
+    since     INTEGER NOT NULL
+);
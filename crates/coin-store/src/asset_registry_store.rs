@@ -0,0 +1,185 @@
+use crate::{Store, StoreError};
+
+use simplicityhl::elements::AssetId;
+use simplicityhl::elements::hex::ToHex;
+
+/// Largest supported number of decimal places, matching the Liquid asset registry.
+pub const MAX_ASSET_PRECISION: u8 = 8;
+
+/// Human-readable metadata for an asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetRegistryEntry {
+    pub asset_id: AssetId,
+    /// Short symbol, unique case-insensitively (e.g. "USDt").
+    pub ticker: String,
+    pub name: String,
+    /// Number of decimal places; one unit is `10^precision` base units.
+    pub precision: u8,
+    /// Issuer domain or other attribution, if known.
+    pub issuer: Option<String>,
+}
+
+type AssetRegistryRow = (String, String, String, i64, Option<String>);
+
+impl TryFrom<AssetRegistryRow> for AssetRegistryEntry {
+    type Error = StoreError;
+
+    fn try_from((asset_id, ticker, name, precision, issuer): AssetRegistryRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            asset_id: asset_id.parse().map_err(|_| StoreError::InvalidAssetId)?,
+            ticker,
+            name,
+            precision: u8::try_from(precision).map_err(|_| StoreError::ValueOverflow)?,
+            issuer,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+pub trait AssetRegistryStore {
+    type Error: std::error::Error;
+
+    /// Add an asset or replace its metadata. Fails if the ticker belongs to another asset.
+    async fn upsert_asset(&self, entry: &AssetRegistryEntry) -> Result<(), Self::Error>;
+
+    /// Remove an asset. Returns whether it was registered.
+    async fn remove_asset(&self, asset_id: AssetId) -> Result<bool, Self::Error>;
+
+    async fn get_asset(&self, asset_id: AssetId) -> Result<Option<AssetRegistryEntry>, Self::Error>;
+
+    /// Look up an asset by ticker, ignoring case.
+    async fn find_asset_by_ticker(&self, ticker: &str) -> Result<Option<AssetRegistryEntry>, Self::Error>;
+
+    async fn list_assets(&self) -> Result<Vec<AssetRegistryEntry>, Self::Error>;
+}
+
+#[async_trait::async_trait]
+impl AssetRegistryStore for Store {
+    type Error = StoreError;
+
+    async fn upsert_asset(&self, entry: &AssetRegistryEntry) -> Result<(), Self::Error> {
+        if entry.precision > MAX_ASSET_PRECISION {
+            return Err(StoreError::InvalidAssetPrecision(entry.precision));
+        }
+
+        if let Some(existing) = self.find_asset_by_ticker(&entry.ticker).await?
+            && existing.asset_id != entry.asset_id
+        {
+            return Err(StoreError::AssetTickerTaken(entry.ticker.clone()));
+        }
+
+        sqlx::query(
+            "INSERT INTO asset_registry (asset_id, ticker, name, precision, issuer, updated_at)
+             VALUES (?, ?, ?, ?, ?, CAST(strftime('%s', 'now') AS INTEGER))
+             ON CONFLICT (asset_id) DO UPDATE SET ticker = excluded.ticker, name = excluded.name,
+                 precision = excluded.precision, issuer = excluded.issuer, updated_at = excluded.updated_at",
+        )
+        .bind(entry.asset_id.to_hex())
+        .bind(&entry.ticker)
+        .bind(&entry.name)
+        .bind(i64::from(entry.precision))
+        .bind(&entry.issuer)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn remove_asset(&self, asset_id: AssetId) -> Result<bool, Self::Error> {
+        let result = sqlx::query("DELETE FROM asset_registry WHERE asset_id = ?")
+            .bind(asset_id.to_hex())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_asset(&self, asset_id: AssetId) -> Result<Option<AssetRegistryEntry>, Self::Error> {
+        let row: Option<AssetRegistryRow> =
+            sqlx::query_as("SELECT asset_id, ticker, name, precision, issuer FROM asset_registry WHERE asset_id = ?")
+                .bind(asset_id.to_hex())
+                .fetch_optional(&self.pool)
+                .await?;
+
+        row.map(AssetRegistryEntry::try_from).transpose()
+    }
+
+    async fn find_asset_by_ticker(&self, ticker: &str) -> Result<Option<AssetRegistryEntry>, Self::Error> {
+        let row: Option<AssetRegistryRow> =
+            sqlx::query_as("SELECT asset_id, ticker, name, precision, issuer FROM asset_registry WHERE ticker = ?")
+                .bind(ticker)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        row.map(AssetRegistryEntry::try_from).transpose()
+    }
+
+    async fn list_assets(&self) -> Result<Vec<AssetRegistryEntry>, Self::Error> {
+        let rows: Vec<AssetRegistryRow> =
+            sqlx::query_as("SELECT asset_id, ticker, name, precision, issuer FROM asset_registry ORDER BY ticker")
+                .fetch_all(&self.pool)
+                .await?;
+
+        rows.into_iter().map(AssetRegistryEntry::try_from).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    fn make_entry(byte: u8, ticker: &str, precision: u8) -> AssetRegistryEntry {
+        AssetRegistryEntry {
+            asset_id: AssetId::from_slice(&[byte; 32]).unwrap(),
+            ticker: ticker.to_string(),
+            name: format!("{ticker} token"),
+            precision,
+            issuer: Some("example.com".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_asset_registry() {
+        let path = "/tmp/test_coin_store_asset_registry.db";
+        let _ = fs::remove_file(path);
+
+        let store = Store::create(path).await.unwrap();
+
+        let usdt = make_entry(1, "USDt", 8);
+        store.upsert_asset(&usdt).await.unwrap();
+        store.upsert_asset(&make_entry(2, "EURx", 2)).await.unwrap();
+
+        assert_eq!(store.get_asset(usdt.asset_id).await.unwrap(), Some(usdt.clone()));
+        assert_eq!(store.find_asset_by_ticker("usdt").await.unwrap(), Some(usdt.clone()));
+
+        // Tickers are unique across assets, but an asset can be updated in place.
+        assert!(matches!(
+            store.upsert_asset(&make_entry(3, "USDT", 8)).await,
+            Err(StoreError::AssetTickerTaken(_))
+        ));
+        assert!(matches!(
+            store.upsert_asset(&make_entry(3, "BIG", 9)).await,
+            Err(StoreError::InvalidAssetPrecision(9))
+        ));
+
+        let renamed = make_entry(1, "USDT", 6);
+        store.upsert_asset(&renamed).await.unwrap();
+
+        let tickers: Vec<String> = store
+            .list_assets()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.ticker)
+            .collect();
+        assert_eq!(tickers, vec!["EURx", "USDT"]);
+
+        assert!(store.remove_asset(renamed.asset_id).await.unwrap());
+        assert!(!store.remove_asset(renamed.asset_id).await.unwrap());
+        assert_eq!(store.get_asset(renamed.asset_id).await.unwrap(), None);
+
+        let _ = fs::remove_file(path);
+    }
+}
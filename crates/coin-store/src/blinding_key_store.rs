@@ -0,0 +1,74 @@
+use crate::{Store, StoreError};
+
+use simplicityhl::elements::Script;
+
+#[async_trait::async_trait]
+pub trait BlindingKeyStore {
+    type Error: std::error::Error;
+
+    /// Allocate the next confidential receive address for `script_pubkey`. Only the index is kept;
+    /// the blinding key is derived from the seed.
+    async fn next_receive_index(&self, script_pubkey: &Script) -> Result<u32, Self::Error>;
+
+    /// Indices of the receive addresses for `script_pubkey`, oldest first.
+    async fn list_receive_indices(&self, script_pubkey: &Script) -> Result<Vec<u32>, Self::Error>;
+}
+
+#[async_trait::async_trait]
+impl BlindingKeyStore for Store {
+    type Error = StoreError;
+
+    async fn next_receive_index(&self, script_pubkey: &Script) -> Result<u32, Self::Error> {
+        let (index,): (i64,) = sqlx::query_as(
+            "INSERT INTO receive_blinding_keys (script_pubkey, key_index, created_at)
+             SELECT ?, COALESCE(MAX(key_index) + 1, 0), CAST(strftime('%s', 'now') AS INTEGER)
+             FROM receive_blinding_keys WHERE script_pubkey = ?
+             RETURNING key_index",
+        )
+        .bind(script_pubkey.as_bytes())
+        .bind(script_pubkey.as_bytes())
+        .fetch_one(&self.pool)
+        .await?;
+
+        u32::try_from(index).map_err(|_| StoreError::ValueOverflow)
+    }
+
+    async fn list_receive_indices(&self, script_pubkey: &Script) -> Result<Vec<u32>, Self::Error> {
+        let rows: Vec<(i64,)> =
+            sqlx::query_as("SELECT key_index FROM receive_blinding_keys WHERE script_pubkey = ? ORDER BY key_index")
+                .bind(script_pubkey.as_bytes())
+                .fetch_all(&self.pool)
+                .await?;
+
+        rows.into_iter()
+            .map(|(index,)| u32::try_from(index).map_err(|_| StoreError::ValueOverflow))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_receive_indices() {
+        let path = "/tmp/test_coin_store_blinding_keys.db";
+        let _ = fs::remove_file(path);
+
+        let store = Store::create(path).await.unwrap();
+
+        let script = Script::from(vec![0x51; 34]);
+        let other = Script::from(vec![0x52; 34]);
+
+        assert_eq!(store.next_receive_index(&script).await.unwrap(), 0);
+        assert_eq!(store.next_receive_index(&script).await.unwrap(), 1);
+        assert_eq!(store.next_receive_index(&other).await.unwrap(), 0);
+
+        assert_eq!(store.list_receive_indices(&script).await.unwrap(), vec![0, 1]);
+        assert_eq!(store.list_receive_indices(&other).await.unwrap(), vec![0]);
+
+        let _ = fs::remove_file(path);
+    }
+}
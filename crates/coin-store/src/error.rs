@@ -58,4 +58,16 @@ pub enum StoreError {
 
     #[error("Invalid asset ID")]
     InvalidAssetId,
+
+    #[error("Invalid counterparty list: {0}")]
+    InvalidCounterpartyList(String),
+
+    #[error("Asset precision must be at most 8, got {0}")]
+    InvalidAssetPrecision(u8),
+
+    #[error("Ticker already registered for another asset: {0}")]
+    AssetTickerTaken(String),
+
+    #[error("Invalid label kind: {0}")]
+    InvalidLabelKind(String),
 }
@@ -0,0 +1,164 @@
+use crate::{Store, StoreError};
+
+use sqlx::{QueryBuilder, Sqlite};
+
+/// A verified NOSTR event as stored in the local cache.
+///
+/// Fields are kept in their wire representation (hex ids, JSON tags) so the store
+/// does not depend on a NOSTR library; callers convert to and from their event type.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct NostrEventEntry {
+    pub event_id: String,
+    pub kind: u16,
+    pub pubkey: String,
+    pub created_at: i64,
+    /// JSON-encoded tag array.
+    pub tags: String,
+    pub content: String,
+    pub sig: String,
+}
+
+#[async_trait::async_trait]
+pub trait EventStore {
+    type Error: std::error::Error;
+
+    /// Insert events into the cache, ignoring ones that are already present.
+    /// Returns the number of newly inserted events.
+    async fn insert_events(&self, events: &[NostrEventEntry]) -> Result<usize, Self::Error>;
+
+    /// Get the `since` bound for the next incremental fetch from a relay, if it was fetched before.
+    async fn relay_cursor(&self, relay_url: &str) -> Result<Option<i64>, Self::Error>;
+
+    /// Store the `since` bound for the next incremental fetch from a relay.
+    async fn set_relay_cursor(&self, relay_url: &str, since: i64) -> Result<(), Self::Error>;
+
+    /// List cached events of the given kinds, oldest first.
+    async fn list_events(&self, kinds: &[u16]) -> Result<Vec<NostrEventEntry>, Self::Error>;
+}
+
+#[async_trait::async_trait]
+impl EventStore for Store {
+    type Error = StoreError;
+
+    async fn insert_events(&self, events: &[NostrEventEntry]) -> Result<usize, Self::Error> {
+        let mut db_tx = self.pool.begin().await?;
+        let mut inserted = 0;
+
+        for event in events {
+            let result = sqlx::query(
+                "INSERT OR IGNORE INTO nostr_events (event_id, kind, pubkey, created_at, tags, content, sig)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&event.event_id)
+            .bind(i64::from(event.kind))
+            .bind(&event.pubkey)
+            .bind(event.created_at)
+            .bind(&event.tags)
+            .bind(&event.content)
+            .bind(&event.sig)
+            .execute(&mut *db_tx)
+            .await?;
+
+            if result.rows_affected() > 0 {
+                inserted += 1;
+            }
+        }
+
+        db_tx.commit().await?;
+
+        Ok(inserted)
+    }
+
+    async fn relay_cursor(&self, relay_url: &str) -> Result<Option<i64>, Self::Error> {
+        let since: Option<(i64,)> = sqlx::query_as("SELECT since FROM relay_cursors WHERE relay_url = ?")
+            .bind(relay_url)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(since.map(|(since,)| since))
+    }
+
+    async fn set_relay_cursor(&self, relay_url: &str, since: i64) -> Result<(), Self::Error> {
+        sqlx::query(
+            "INSERT INTO relay_cursors (relay_url, since) VALUES (?, ?)
+             ON CONFLICT (relay_url) DO UPDATE SET since = excluded.since",
+        )
+        .bind(relay_url)
+        .bind(since)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_events(&self, kinds: &[u16]) -> Result<Vec<NostrEventEntry>, Self::Error> {
+        if kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut builder: QueryBuilder<Sqlite> =
+            QueryBuilder::new("SELECT event_id, kind, pubkey, created_at, tags, content, sig FROM nostr_events");
+        push_kinds_filter(&mut builder, kinds);
+        builder.push(" ORDER BY created_at ASC");
+
+        let rows: Vec<NostrEventEntry> = builder.build_query_as().fetch_all(&self.pool).await?;
+
+        Ok(rows)
+    }
+}
+
+fn push_kinds_filter(builder: &mut QueryBuilder<'_, Sqlite>, kinds: &[u16]) {
+    builder.push(" WHERE kind IN (");
+    let mut separated = builder.separated(", ");
+    for kind in kinds {
+        separated.push_bind(i64::from(*kind));
+    }
+    separated.push_unseparated(")");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    fn make_event(id: u8, kind: u16, created_at: i64) -> NostrEventEntry {
+        NostrEventEntry {
+            event_id: hex::encode([id; 32]),
+            kind,
+            pubkey: hex::encode([7; 32]),
+            created_at,
+            tags: "[]".to_string(),
+            content: String::new(),
+            sig: hex::encode([9; 64]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_cache_incremental() {
+        let path = "/tmp/test_coin_store_nostr_events.db";
+        let _ = fs::remove_file(path);
+
+        let store = Store::create(path).await.unwrap();
+
+        let events = vec![make_event(1, 9910, 100), make_event(2, 9911, 300), make_event(3, 9912, 500)];
+        assert_eq!(store.insert_events(&events).await.unwrap(), 3);
+
+        // Re-inserting the same events is a no-op
+        assert_eq!(store.insert_events(&events[..2]).await.unwrap(), 0);
+
+        let listed = store.list_events(&[9911, 9910]).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0], events[0]);
+        assert_eq!(listed[1], events[1]);
+
+        let relay = "wss://relay.example.com";
+        assert_eq!(store.relay_cursor(relay).await.unwrap(), None);
+        store.set_relay_cursor(relay, 200).await.unwrap();
+        store.set_relay_cursor(relay, 400).await.unwrap();
+        assert_eq!(store.relay_cursor(relay).await.unwrap(), Some(400));
+        assert_eq!(store.relay_cursor("wss://other.example.com").await.unwrap(), None);
+
+        let _ = fs::remove_file(path);
+    }
+}
@@ -88,6 +88,12 @@ pub trait UtxoStore {
     /// Returns a list of (txid, vout) tuples for UTXOs where `is_spent` = 0.
     async fn list_unspent_outpoints(&self) -> Result<Vec<OutPoint>, Self::Error>;
 
+    /// Get the script pubkey, asset and unblinded value of a known output, spent or not.
+    async fn get_output(
+        &self,
+        outpoint: OutPoint,
+    ) -> Result<Option<(simplicityhl::elements::Script, AssetId, u64)>, Self::Error>;
+
     /// List all tracked script pubkeys from contracts.
     /// Returns distinct script pubkeys from the `simplicity_contracts` table.
     async fn list_tracked_script_pubkeys(&self) -> Result<Vec<simplicityhl::elements::Script>, Self::Error>;
@@ -331,6 +337,8 @@ impl UtxoStore for Store {
             }
         }
 
+        Self::record_transaction_with_tx(&mut db_tx, tx).await?;
+
         db_tx.commit().await?;
 
         Ok(())
@@ -356,6 +364,28 @@ impl UtxoStore for Store {
         Ok(outpoints)
     }
 
+    async fn get_output(
+        &self,
+        outpoint: OutPoint,
+    ) -> Result<Option<(simplicityhl::elements::Script, AssetId, u64)>, Self::Error> {
+        let txid: &[u8] = outpoint.txid.as_ref();
+        let row: Option<(Vec<u8>, String, i64)> =
+            sqlx::query_as("SELECT script_pubkey, asset_id, value FROM utxos WHERE txid = ? AND vout = ?")
+                .bind(txid)
+                .bind(i64::from(outpoint.vout))
+                .fetch_optional(&self.pool)
+                .await?;
+
+        row.map(|(script, asset_id, value)| {
+            Ok((
+                simplicityhl::elements::Script::from(script),
+                asset_id.parse().map_err(|_| StoreError::InvalidAssetId)?,
+                u64::try_from(value).map_err(|_| StoreError::ValueOverflow)?,
+            ))
+        })
+        .transpose()
+    }
+
     async fn list_tracked_script_pubkeys(&self) -> Result<Vec<simplicityhl::elements::Script>, Self::Error> {
         let rows: Vec<(Vec<u8>,)> = sqlx::query_as("SELECT DISTINCT script_pubkey FROM simplicity_contracts")
             .fetch_all(&self.pool)
@@ -558,6 +588,10 @@ impl Store {
             builder.push(" AND u.is_spent = 0");
         }
 
+        if !filter.include_frozen {
+            builder.push(" AND NOT EXISTS (SELECT 1 FROM frozen_utxos f WHERE f.txid = u.txid AND f.vout = u.vout)");
+        }
+
         if let Some(ref asset_id) = filter.asset_id {
             builder.push(" AND u.asset_id = ");
             builder.push_bind(asset_id.to_hex());
@@ -588,6 +622,18 @@ impl Store {
             builder.push_bind(source_hash.to_vec());
         }
 
+        if let Some(ref outpoints) = filter.outpoints {
+            builder.push(" AND (0");
+            push_outpoints(&mut builder, outpoints);
+            builder.push(")");
+        }
+
+        if !filter.excluded_outpoints.is_empty() {
+            builder.push(" AND NOT (0");
+            push_outpoints(&mut builder, &filter.excluded_outpoints);
+            builder.push(")");
+        }
+
         builder.push(" ORDER BY u.value DESC");
 
         if let Some(limit) = limit {
@@ -634,6 +680,18 @@ impl Store {
     }
 }
 
+/// Push `OR (u.txid = ? AND u.vout = ?)` for each outpoint.
+fn push_outpoints(builder: &mut QueryBuilder<Sqlite>, outpoints: &[OutPoint]) {
+    for outpoint in outpoints {
+        let txid: &[u8] = outpoint.txid.as_ref();
+        builder.push(" OR (u.txid = ");
+        builder.push_bind(txid.to_vec());
+        builder.push(" AND u.vout = ");
+        builder.push_bind(i64::from(outpoint.vout));
+        builder.push(")");
+    }
+}
+
 #[derive(sqlx::FromRow)]
 pub struct UtxoRow {
     txid: Vec<u8>,
@@ -934,6 +992,32 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[tokio::test]
+    async fn test_get_output() {
+        let path = "/tmp/test_coin_store_get_output.db";
+        let _ = fs::remove_file(path);
+
+        let store = Store::create(path).await.unwrap();
+
+        let asset = test_asset_id();
+        let outpoint = OutPoint::new(Txid::from_byte_array([1; Txid::LEN]), 0);
+
+        store
+            .insert(outpoint, make_explicit_txout(asset, 1000), None)
+            .await
+            .unwrap();
+        assert_eq!(store.get_output(outpoint).await.unwrap(), Some((Script::new(), asset, 1000)));
+
+        // Spent outputs stay known, so past transactions can still be valued.
+        store.mark_as_spent(outpoint).await.unwrap();
+        assert_eq!(store.get_output(outpoint).await.unwrap(), Some((Script::new(), asset, 1000)));
+
+        let unknown = OutPoint::new(Txid::from_byte_array([9; Txid::LEN]), 0);
+        assert_eq!(store.get_output(unknown).await.unwrap(), None);
+
+        let _ = fs::remove_file(path);
+    }
+
     #[tokio::test]
     async fn test_query_empty() {
         let path = "/tmp/test_coin_store_empty.db";
@@ -989,6 +1073,49 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[tokio::test]
+    async fn test_query_by_outpoints() {
+        let path = "/tmp/test_coin_store_query_outpoints.db";
+        let _ = fs::remove_file(path);
+
+        let store = Store::create(path).await.unwrap();
+
+        let asset = test_asset_id();
+        let outpoints: Vec<OutPoint> = (1..=3)
+            .map(|i| OutPoint::new(Txid::from_byte_array([i; Txid::LEN]), u32::from(i)))
+            .collect();
+
+        for (outpoint, value) in outpoints.iter().zip([1000, 2000, 3000]) {
+            store.insert(*outpoint, make_explicit_txout(asset, value), None).await.unwrap();
+        }
+
+        let filter = UtxoFilter::new().asset_id(asset).outpoints(outpoints[..2].to_vec());
+        let results = store.query_utxos(&[filter]).await.unwrap();
+        match &results[0] {
+            UtxoQueryResult::Found(entries, _) => {
+                assert_eq!(entries.len(), 2);
+                assert_eq!(*entries[0].outpoint(), outpoints[1]);
+                assert_eq!(*entries[1].outpoint(), outpoints[0]);
+            }
+            _ => panic!("Expected Found result"),
+        }
+
+        let filter = UtxoFilter::new().asset_id(asset).exclude_outpoints([outpoints[2]]).required_value(2500);
+        let results = store.query_utxos(&[filter]).await.unwrap();
+        match &results[0] {
+            UtxoQueryResult::Found(entries, _) => {
+                assert!(entries.iter().all(|entry| *entry.outpoint() != outpoints[2]));
+            }
+            _ => panic!("Expected Found result"),
+        }
+
+        let filter = UtxoFilter::new().outpoints(vec![outpoints[0]]).exclude_outpoints([outpoints[0]]);
+        let results = store.query_utxos(&[filter]).await.unwrap();
+        assert!(matches!(&results[0], UtxoQueryResult::Empty));
+
+        let _ = fs::remove_file(path);
+    }
+
     #[tokio::test]
     async fn test_add_contract() {
         let path = "/tmp/test_coin_store_add_contract.db";
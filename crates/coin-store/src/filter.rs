@@ -1,7 +1,7 @@
 use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
 use simplicityhl::elements::hashes::{Hash, sha256};
 use simplicityhl::{
-    elements::{AssetId, Script},
+    elements::{AssetId, OutPoint, Script},
     simplicity::Cmr,
 };
 
@@ -12,11 +12,17 @@ pub struct UtxoFilter {
     pub required_value: Option<u64>,
     pub limit: Option<i64>,
     pub include_spent: bool,
+    /// Also match outputs frozen with `FreezeStore::freeze`.
+    pub include_frozen: bool,
     pub include_entropy: bool,
     pub cmr: Option<Cmr>,
     pub taproot_pubkey_gen: Option<TaprootPubkeyGen>,
     pub source_hash: Option<[u8; 32]>,
     pub token_tag: Option<String>,
+    /// Only these outputs may match.
+    pub outpoints: Option<Vec<OutPoint>>,
+    /// These outputs never match.
+    pub excluded_outpoints: Vec<OutPoint>,
 }
 
 impl UtxoFilter {
@@ -60,6 +66,12 @@ impl UtxoFilter {
         self
     }
 
+    #[must_use]
+    pub const fn include_frozen(mut self) -> Self {
+        self.include_frozen = true;
+        self
+    }
+
     #[must_use]
     pub const fn include_entropy(mut self) -> Self {
         self.include_entropy = true;
@@ -95,6 +107,18 @@ impl UtxoFilter {
         self
     }
 
+    #[must_use]
+    pub fn outpoints(mut self, outpoints: Vec<OutPoint>) -> Self {
+        self.outpoints = Some(outpoints);
+        self
+    }
+
+    #[must_use]
+    pub fn exclude_outpoints(mut self, outpoints: impl IntoIterator<Item = OutPoint>) -> Self {
+        self.excluded_outpoints.extend(outpoints);
+        self
+    }
+
     #[must_use]
     pub(crate) const fn is_contract_join(&self) -> bool {
         self.cmr.is_some()
@@ -0,0 +1,148 @@
+use crate::{Store, StoreError};
+
+use simplicityhl::elements::hashes::Hash;
+use simplicityhl::elements::{OutPoint, Txid};
+
+/// An output kept out of coin selection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrozenUtxo {
+    pub outpoint: OutPoint,
+    pub reason: Option<String>,
+    /// Unix timestamp of when the output was frozen.
+    pub frozen_at: i64,
+}
+
+#[async_trait::async_trait]
+pub trait FreezeStore {
+    type Error: std::error::Error;
+
+    /// Freeze a known output, so filters skip it unless they `include_frozen`.
+    /// Freezing it again replaces the reason.
+    async fn freeze(&self, outpoint: OutPoint, reason: Option<&str>) -> Result<(), Self::Error>;
+
+    /// Returns whether the output was frozen.
+    async fn unfreeze(&self, outpoint: OutPoint) -> Result<bool, Self::Error>;
+
+    /// List frozen outputs, spent ones included, oldest first.
+    async fn list_frozen(&self) -> Result<Vec<FrozenUtxo>, Self::Error>;
+}
+
+#[async_trait::async_trait]
+impl FreezeStore for Store {
+    type Error = StoreError;
+
+    async fn freeze(&self, outpoint: OutPoint, reason: Option<&str>) -> Result<(), Self::Error> {
+        let txid: &[u8] = outpoint.txid.as_ref();
+
+        let result = sqlx::query(
+            "INSERT INTO frozen_utxos (txid, vout, reason, frozen_at)
+             SELECT txid, vout, ?, CAST(strftime('%s', 'now') AS INTEGER) FROM utxos WHERE txid = ? AND vout = ?
+             ON CONFLICT (txid, vout) DO UPDATE SET reason = excluded.reason",
+        )
+        .bind(reason)
+        .bind(txid)
+        .bind(i64::from(outpoint.vout))
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StoreError::UtxoNotFound(outpoint));
+        }
+
+        Ok(())
+    }
+
+    async fn unfreeze(&self, outpoint: OutPoint) -> Result<bool, Self::Error> {
+        let txid: &[u8] = outpoint.txid.as_ref();
+
+        let result = sqlx::query("DELETE FROM frozen_utxos WHERE txid = ? AND vout = ?")
+            .bind(txid)
+            .bind(i64::from(outpoint.vout))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_frozen(&self) -> Result<Vec<FrozenUtxo>, Self::Error> {
+        let rows: Vec<(Vec<u8>, i64, Option<String>, i64)> =
+            sqlx::query_as("SELECT txid, vout, reason, frozen_at FROM frozen_utxos ORDER BY frozen_at, rowid")
+                .fetch_all(&self.pool)
+                .await?;
+
+        rows.into_iter()
+            .map(|(txid, vout, reason, frozen_at)| {
+                let vout = u32::try_from(vout).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+                Ok(FrozenUtxo {
+                    outpoint: OutPoint::new(Txid::from_slice(&txid)?, vout),
+                    reason,
+                    frozen_at,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{UtxoFilter, UtxoQueryResult, UtxoStore};
+
+    use std::fs;
+
+    use simplicityhl::elements::confidential::{Asset, Nonce, Value};
+    use simplicityhl::elements::{AssetId, Script, TxOut, TxOutWitness};
+
+    #[tokio::test]
+    async fn test_freeze_and_unfreeze() {
+        let path = "/tmp/test_coin_store_frozen.db";
+        let _ = fs::remove_file(path);
+
+        let store = Store::create(path).await.unwrap();
+
+        let asset = AssetId::from_slice(&[1; 32]).unwrap();
+        let outpoints = [1, 2].map(|i| OutPoint::new(Txid::from_byte_array([i; Txid::LEN]), 0));
+        for (outpoint, value) in outpoints.iter().zip([1000, 2000]) {
+            let txout = TxOut {
+                asset: Asset::Explicit(asset),
+                value: Value::Explicit(value),
+                nonce: Nonce::Null,
+                script_pubkey: Script::new(),
+                witness: TxOutWitness::default(),
+            };
+            store.insert(*outpoint, txout, None).await.unwrap();
+        }
+
+        store.freeze(outpoints[1], Some("client collateral")).await.unwrap();
+        let unknown = OutPoint::new(Txid::from_byte_array([9; Txid::LEN]), 0);
+        assert!(matches!(store.freeze(unknown, None).await, Err(StoreError::UtxoNotFound(_))));
+
+        let results = store.query_utxos(&[UtxoFilter::new().asset_id(asset)]).await.unwrap();
+        match &results[0] {
+            UtxoQueryResult::Found(entries, _) => {
+                assert_eq!(entries.len(), 1);
+                assert_eq!(*entries[0].outpoint(), outpoints[0]);
+            }
+            _ => panic!("Expected Found result"),
+        }
+
+        let results = store
+            .query_utxos(&[UtxoFilter::new().asset_id(asset).include_frozen()])
+            .await
+            .unwrap();
+        assert!(matches!(&results[0], UtxoQueryResult::Found(e, _) if e.len() == 2));
+
+        let frozen = store.list_frozen().await.unwrap();
+        assert_eq!(frozen.len(), 1);
+        assert_eq!(frozen[0].outpoint, outpoints[1]);
+        assert_eq!(frozen[0].reason.as_deref(), Some("client collateral"));
+
+        assert!(store.unfreeze(outpoints[1]).await.unwrap());
+        assert!(!store.unfreeze(outpoints[1]).await.unwrap());
+        assert!(store.list_frozen().await.unwrap().is_empty());
+
+        let _ = fs::remove_file(path);
+    }
+}
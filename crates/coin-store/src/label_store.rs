@@ -0,0 +1,157 @@
+use crate::{Store, StoreError};
+
+use simplicityhl::elements::hashes::Hash;
+use simplicityhl::elements::{OutPoint, Script, Txid, encode};
+
+/// What a label is attached to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LabelRef {
+    Tx(Txid),
+    Output(OutPoint),
+    /// A script pubkey, i.e. an address on any network.
+    Script(Script),
+    /// A contract, by its taproot pubkey gen.
+    Contract(String),
+}
+
+impl LabelRef {
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Tx(_) => "tx",
+            Self::Output(_) => "output",
+            Self::Script(_) => "script",
+            Self::Contract(_) => "contract",
+        }
+    }
+
+    fn reference(&self) -> Vec<u8> {
+        match self {
+            Self::Tx(txid) => txid.to_byte_array().to_vec(),
+            Self::Output(outpoint) => encode::serialize(outpoint),
+            Self::Script(script) => script.to_bytes(),
+            Self::Contract(taproot_pubkey_gen) => taproot_pubkey_gen.as_bytes().to_vec(),
+        }
+    }
+
+    fn from_row(kind: &str, reference: Vec<u8>) -> Result<Self, StoreError> {
+        match kind {
+            "tx" => Ok(Self::Tx(Txid::from_slice(&reference)?)),
+            "output" => Ok(Self::Output(encode::deserialize(&reference)?)),
+            "script" => Ok(Self::Script(Script::from(reference))),
+            "contract" => Ok(Self::Contract(
+                String::from_utf8(reference).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            )),
+            other => Err(StoreError::InvalidLabelKind(other.to_string())),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+pub trait LabelStore {
+    type Error: std::error::Error;
+
+    /// Attach a label, replacing any previous one. `None` removes the label.
+    async fn set_label(&self, target: &LabelRef, label: Option<&str>) -> Result<(), Self::Error>;
+
+    async fn get_label(&self, target: &LabelRef) -> Result<Option<String>, Self::Error>;
+
+    /// List all labels, grouped by kind.
+    async fn list_labels(&self) -> Result<Vec<(LabelRef, String)>, Self::Error>;
+}
+
+#[async_trait::async_trait]
+impl LabelStore for Store {
+    type Error = StoreError;
+
+    async fn set_label(&self, target: &LabelRef, label: Option<&str>) -> Result<(), Self::Error> {
+        match label {
+            Some(label) => {
+                sqlx::query(
+                    "INSERT INTO labels (kind, reference, label, updated_at)
+                     VALUES (?, ?, ?, CAST(strftime('%s', 'now') AS INTEGER))
+                     ON CONFLICT (kind, reference)
+                         DO UPDATE SET label = excluded.label, updated_at = excluded.updated_at",
+                )
+                .bind(target.kind())
+                .bind(target.reference())
+                .bind(label)
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM labels WHERE kind = ? AND reference = ?")
+                    .bind(target.kind())
+                    .bind(target.reference())
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_label(&self, target: &LabelRef) -> Result<Option<String>, Self::Error> {
+        let row: Option<(String,)> = sqlx::query_as("SELECT label FROM labels WHERE kind = ? AND reference = ?")
+            .bind(target.kind())
+            .bind(target.reference())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|(label,)| label))
+    }
+
+    async fn list_labels(&self) -> Result<Vec<(LabelRef, String)>, Self::Error> {
+        let rows: Vec<(String, Vec<u8>, String)> =
+            sqlx::query_as("SELECT kind, reference, label FROM labels ORDER BY kind, updated_at, rowid")
+                .fetch_all(&self.pool)
+                .await?;
+
+        rows.into_iter()
+            .map(|(kind, reference, label)| Ok((LabelRef::from_row(&kind, reference)?, label)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_set_and_list_labels() {
+        let path = "/tmp/test_coin_store_labels.db";
+        let _ = fs::remove_file(path);
+
+        let store = Store::create(path).await.unwrap();
+
+        let txid = Txid::from_byte_array([7; Txid::LEN]);
+        let targets = [
+            LabelRef::Tx(txid),
+            LabelRef::Output(OutPoint::new(txid, 3)),
+            LabelRef::Script(Script::from(vec![0x51; 34])),
+            LabelRef::Contract("taproot-pubkey-gen".to_string()),
+        ];
+
+        for (i, target) in targets.iter().enumerate() {
+            store.set_label(target, Some(&format!("label {i}"))).await.unwrap();
+        }
+        store.set_label(&targets[1], Some("cold storage")).await.unwrap();
+
+        assert_eq!(store.get_label(&targets[1]).await.unwrap().as_deref(), Some("cold storage"));
+        assert_eq!(store.get_label(&LabelRef::Output(OutPoint::new(txid, 4))).await.unwrap(), None);
+
+        let labels = store.list_labels().await.unwrap();
+        assert_eq!(labels.len(), 4);
+        for target in &targets {
+            assert!(labels.iter().any(|(labelled, _)| labelled == target));
+        }
+
+        store.set_label(&targets[0], None).await.unwrap();
+        assert_eq!(store.get_label(&targets[0]).await.unwrap(), None);
+        assert_eq!(store.list_labels().await.unwrap().len(), 3);
+
+        let _ = fs::remove_file(path);
+    }
+}
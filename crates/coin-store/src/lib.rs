@@ -1,16 +1,30 @@
 #![warn(clippy::all, clippy::pedantic)]
 #![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 
+pub mod asset_registry_store;
+pub mod blinding_key_store;
 pub mod entry;
 pub mod error;
+pub mod event_store;
 pub mod executor;
 pub mod filter;
+pub mod freeze_store;
+pub mod label_store;
+pub mod reputation_store;
 pub mod store;
+pub mod transaction_store;
 
 pub use error::StoreError;
 pub use simplicityhl::elements::AssetId;
 pub use store::Store;
 
+pub use asset_registry_store::{AssetRegistryEntry, AssetRegistryStore, MAX_ASSET_PRECISION};
+pub use blinding_key_store::BlindingKeyStore;
 pub use entry::{UtxoEntry, UtxoQueryResult};
+pub use event_store::{EventStore, NostrEventEntry};
 pub use executor::UtxoStore;
 pub use filter::UtxoFilter;
+pub use freeze_store::{FreezeStore, FrozenUtxo};
+pub use label_store::{LabelRef, LabelStore};
+pub use reputation_store::{CounterpartyList, CounterpartyStatsEntry, ReputationStore};
+pub use transaction_store::{TransactionEntry, TransactionStore};
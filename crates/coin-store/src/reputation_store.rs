@@ -0,0 +1,215 @@
+use crate::{Store, StoreError};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Cached counterparty statistics, keyed by the hex NOSTR public key.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct CounterpartyStatsEntry {
+    pub pubkey: String,
+    pub offers_published: u32,
+    pub offers_honoured: u32,
+    pub offers_cancelled_early: u32,
+    pub offers_bogus: u32,
+    pub fills: u32,
+    pub total_filled: i64,
+    /// Unix timestamp of the computation.
+    pub updated_at: i64,
+}
+
+/// User-maintained list a counterparty belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterpartyList {
+    Trusted,
+    Blocked,
+}
+
+impl CounterpartyList {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Trusted => "trusted",
+            Self::Blocked => "blocked",
+        }
+    }
+}
+
+impl fmt::Display for CounterpartyList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CounterpartyList {
+    type Err = StoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "trusted" => Ok(Self::Trusted),
+            "blocked" => Ok(Self::Blocked),
+            other => Err(StoreError::InvalidCounterpartyList(other.to_string())),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+pub trait ReputationStore {
+    type Error: std::error::Error;
+
+    /// Replace all cached statistics with a freshly computed set.
+    async fn replace_counterparty_stats(&self, stats: &[CounterpartyStatsEntry]) -> Result<(), Self::Error>;
+
+    async fn list_counterparty_stats(&self) -> Result<Vec<CounterpartyStatsEntry>, Self::Error>;
+
+    /// Put a counterparty on the trust or block list, or remove it from both with `None`.
+    async fn set_counterparty_list(&self, pubkey: &str, list: Option<CounterpartyList>) -> Result<(), Self::Error>;
+
+    async fn list_counterparty_lists(&self) -> Result<Vec<(String, CounterpartyList)>, Self::Error>;
+}
+
+#[async_trait::async_trait]
+impl ReputationStore for Store {
+    type Error = StoreError;
+
+    async fn replace_counterparty_stats(&self, stats: &[CounterpartyStatsEntry]) -> Result<(), Self::Error> {
+        let mut db_tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM counterparty_stats")
+            .execute(&mut *db_tx)
+            .await?;
+
+        for entry in stats {
+            sqlx::query(
+                "INSERT INTO counterparty_stats (pubkey, offers_published, offers_honoured, offers_cancelled_early,
+                                                 offers_bogus, fills, total_filled, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&entry.pubkey)
+            .bind(entry.offers_published)
+            .bind(entry.offers_honoured)
+            .bind(entry.offers_cancelled_early)
+            .bind(entry.offers_bogus)
+            .bind(entry.fills)
+            .bind(entry.total_filled)
+            .bind(entry.updated_at)
+            .execute(&mut *db_tx)
+            .await?;
+        }
+
+        db_tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn list_counterparty_stats(&self) -> Result<Vec<CounterpartyStatsEntry>, Self::Error> {
+        let rows: Vec<CounterpartyStatsEntry> = sqlx::query_as(
+            "SELECT pubkey, offers_published, offers_honoured, offers_cancelled_early, offers_bogus, fills,
+                    total_filled, updated_at
+             FROM counterparty_stats
+             ORDER BY pubkey",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn set_counterparty_list(&self, pubkey: &str, list: Option<CounterpartyList>) -> Result<(), Self::Error> {
+        match list {
+            Some(list) => {
+                sqlx::query(
+                    "INSERT INTO counterparty_lists (pubkey, list, added_at)
+                     VALUES (?, ?, CAST(strftime('%s', 'now') AS INTEGER))
+                     ON CONFLICT (pubkey) DO UPDATE SET list = excluded.list, added_at = excluded.added_at",
+                )
+                .bind(pubkey)
+                .bind(list.as_str())
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM counterparty_lists WHERE pubkey = ?")
+                    .bind(pubkey)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn list_counterparty_lists(&self) -> Result<Vec<(String, CounterpartyList)>, Self::Error> {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT pubkey, list FROM counterparty_lists ORDER BY pubkey")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|(pubkey, list)| Ok((pubkey, list.parse()?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    fn make_stats(pubkey: u8, honoured: u32) -> CounterpartyStatsEntry {
+        CounterpartyStatsEntry {
+            pubkey: hex::encode([pubkey; 32]),
+            offers_published: honoured + 1,
+            offers_honoured: honoured,
+            offers_cancelled_early: 1,
+            offers_bogus: 0,
+            fills: honoured,
+            total_filled: i64::from(honoured) * 100,
+            updated_at: 1_700_000_000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_counterparty_stats_and_lists() {
+        let path = "/tmp/test_coin_store_reputation.db";
+        let _ = fs::remove_file(path);
+
+        let store = Store::create(path).await.unwrap();
+
+        store
+            .replace_counterparty_stats(&[make_stats(1, 3), make_stats(2, 0)])
+            .await
+            .unwrap();
+        store.replace_counterparty_stats(&[make_stats(1, 5)]).await.unwrap();
+
+        let stats = store.list_counterparty_stats().await.unwrap();
+        assert_eq!(stats, vec![make_stats(1, 5)]);
+
+        let (alice, bob) = (hex::encode([1; 32]), hex::encode([2; 32]));
+        store
+            .set_counterparty_list(&alice, Some(CounterpartyList::Trusted))
+            .await
+            .unwrap();
+        store
+            .set_counterparty_list(&bob, Some(CounterpartyList::Trusted))
+            .await
+            .unwrap();
+        store
+            .set_counterparty_list(&bob, Some(CounterpartyList::Blocked))
+            .await
+            .unwrap();
+
+        let lists = store.list_counterparty_lists().await.unwrap();
+        assert_eq!(
+            lists,
+            vec![
+                (alice.clone(), CounterpartyList::Trusted),
+                (bob, CounterpartyList::Blocked)
+            ]
+        );
+
+        store.set_counterparty_list(&alice, None).await.unwrap();
+        assert_eq!(store.list_counterparty_lists().await.unwrap().len(), 1);
+
+        let _ = fs::remove_file(path);
+    }
+}
@@ -61,6 +61,9 @@ impl Store {
             return Err(StoreError::NotInitialized(path.to_path_buf()));
         }
 
+        // Apply migrations added after the database was created.
+        MIGRATOR.run(&pool).await?;
+
         Ok(Self { pool })
     }
 }
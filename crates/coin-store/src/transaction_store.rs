@@ -0,0 +1,448 @@
+use std::collections::BTreeMap;
+
+use crate::{Store, StoreError};
+
+use simplicityhl::elements::hashes::Hash;
+use simplicityhl::elements::hex::ToHex;
+use simplicityhl::elements::{AssetId, Script, Transaction, Txid, encode};
+
+use sqlx::{QueryBuilder, Sqlite};
+
+/// A transaction recorded by `insert_transaction`, with its effect on the wallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionEntry {
+    pub txid: Txid,
+    /// Consensus-encoded transaction.
+    pub raw: Vec<u8>,
+    /// Height of the confirming block, once known.
+    pub block_height: Option<u32>,
+    /// Block time once confirmed, otherwise when the store first saw the transaction.
+    pub timestamp: i64,
+    /// Net change per asset across the wallet scripts, fee included. Zero deltas are left out.
+    pub deltas: BTreeMap<AssetId, i64>,
+    /// Fee of the transaction, when the wallet funded any of its inputs.
+    pub fee: Option<u64>,
+    /// Contract whose script the transaction pays to or spends from, if any.
+    pub taproot_pubkey_gen: Option<String>,
+}
+
+/// Transaction columns followed by its deltas as comma-separated `asset_id:delta` pairs.
+type TransactionRow = (
+    Vec<u8>,
+    Vec<u8>,
+    Option<i64>,
+    i64,
+    Option<i64>,
+    Option<String>,
+    Option<String>,
+);
+
+#[async_trait::async_trait]
+pub trait TransactionStore {
+    type Error: std::error::Error;
+
+    /// Mark a script pubkey as the wallet's own, so transactions paying to or spending from it
+    /// count towards the wallet deltas. Only transactions inserted afterwards are affected.
+    async fn add_wallet_script(&self, script_pubkey: &Script) -> Result<(), Self::Error>;
+
+    /// Record the block that confirmed a transaction. Returns whether the transaction is known.
+    async fn set_transaction_status(
+        &self,
+        txid: Txid,
+        block_height: u32,
+        block_time: i64,
+    ) -> Result<bool, Self::Error>;
+
+    /// List transactions that moved wallet funds, oldest first.
+    ///
+    /// With `asset`, only those changing its balance; `from` and `to` bound the timestamp, inclusive.
+    async fn list_transactions(
+        &self,
+        asset: Option<AssetId>,
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> Result<Vec<TransactionEntry>, Self::Error>;
+
+    /// Transactions that created outputs to the wallet scripts but were never recorded, such as
+    /// those of outputs stored before transactions were, to be fetched and inserted again.
+    async fn list_unrecorded_txids(&self) -> Result<Vec<Txid>, Self::Error>;
+}
+
+#[async_trait::async_trait]
+impl TransactionStore for Store {
+    type Error = StoreError;
+
+    async fn add_wallet_script(&self, script_pubkey: &Script) -> Result<(), Self::Error> {
+        sqlx::query("INSERT OR IGNORE INTO wallet_scripts (script_pubkey) VALUES (?)")
+            .bind(script_pubkey.as_bytes())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_transaction_status(
+        &self,
+        txid: Txid,
+        block_height: u32,
+        block_time: i64,
+    ) -> Result<bool, Self::Error> {
+        let txid: &[u8] = txid.as_ref();
+
+        let result = sqlx::query("UPDATE transactions SET block_height = ?, block_time = ? WHERE txid = ?")
+            .bind(i64::from(block_height))
+            .bind(block_time)
+            .bind(txid)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_transactions(
+        &self,
+        asset: Option<AssetId>,
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> Result<Vec<TransactionEntry>, Self::Error> {
+        let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT t.txid, t.raw, t.block_height, COALESCE(t.block_time, t.first_seen), t.fee, t.taproot_pubkey_gen,
+                    GROUP_CONCAT(d.asset_id || ':' || d.delta)
+             FROM transactions t
+             LEFT JOIN transaction_deltas d ON d.txid = t.txid
+             WHERE (t.fee IS NOT NULL OR d.txid IS NOT NULL)",
+        );
+
+        if let Some(asset) = asset {
+            builder.push(" AND EXISTS (SELECT 1 FROM transaction_deltas a WHERE a.txid = t.txid AND a.asset_id = ");
+            builder.push_bind(asset.to_hex());
+            builder.push(")");
+        }
+        if let Some(from) = from {
+            builder.push(" AND COALESCE(t.block_time, t.first_seen) >= ");
+            builder.push_bind(from);
+        }
+        if let Some(to) = to {
+            builder.push(" AND COALESCE(t.block_time, t.first_seen) <= ");
+            builder.push_bind(to);
+        }
+        builder.push(" GROUP BY t.txid ORDER BY COALESCE(t.block_time, t.first_seen), t.rowid");
+
+        let rows: Vec<TransactionRow> = builder.build_query_as().fetch_all(&self.pool).await?;
+
+        let mut entries = Vec::with_capacity(rows.len());
+        for (txid, raw, block_height, timestamp, fee, taproot_pubkey_gen, deltas) in rows {
+            let deltas = deltas
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (asset_id, delta) = pair.split_once(':').ok_or(StoreError::InvalidAssetId)?;
+                    Ok((
+                        asset_id.parse().map_err(|_| StoreError::InvalidAssetId)?,
+                        delta.parse().map_err(|_| StoreError::ValueOverflow)?,
+                    ))
+                })
+                .collect::<Result<_, StoreError>>()?;
+
+            entries.push(TransactionEntry {
+                txid: Txid::from_slice(&txid)?,
+                raw,
+                block_height: block_height
+                    .map(|height| u32::try_from(height).map_err(|_| StoreError::ValueOverflow))
+                    .transpose()?,
+                timestamp,
+                deltas,
+                fee: fee
+                    .map(|fee| u64::try_from(fee).map_err(|_| StoreError::ValueOverflow))
+                    .transpose()?,
+                taproot_pubkey_gen,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    async fn list_unrecorded_txids(&self) -> Result<Vec<Txid>, Self::Error> {
+        let rows: Vec<(Vec<u8>,)> = sqlx::query_as(
+            "SELECT DISTINCT u.txid FROM utxos u
+             WHERE u.script_pubkey IN (SELECT script_pubkey FROM wallet_scripts)
+               AND NOT EXISTS (SELECT 1 FROM transactions t WHERE t.txid = u.txid)",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(|(txid,)| Ok(Txid::from_slice(&txid)?)).collect()
+    }
+}
+
+impl Store {
+    /// Record `tx` with the wallet's net change per asset, the fee it paid and the contract it touches.
+    ///
+    /// Must run after the outputs of `tx` are stored, so the unblinded values are known.
+    pub(crate) async fn record_transaction_with_tx(
+        db_tx: &mut sqlx::Transaction<'_, Sqlite>,
+        tx: &Transaction,
+    ) -> Result<(), StoreError> {
+        let txid = tx.txid();
+        let txid_bytes: &[u8] = txid.as_ref();
+
+        let mut deltas: BTreeMap<String, i64> = BTreeMap::new();
+        let mut funded = false;
+        let mut scripts: Vec<Vec<u8>> = tx
+            .output
+            .iter()
+            .filter(|txout| !txout.is_fee())
+            .map(|txout| txout.script_pubkey.to_bytes())
+            .collect();
+
+        for input in &tx.input {
+            let prev_txid: &[u8] = input.previous_output.txid.as_ref();
+
+            let prev: Option<(Vec<u8>, String, i64, bool)> = sqlx::query_as(
+                "SELECT u.script_pubkey, u.asset_id, u.value,
+                        EXISTS (SELECT 1 FROM wallet_scripts w WHERE w.script_pubkey = u.script_pubkey)
+                 FROM utxos u
+                 WHERE u.txid = ? AND u.vout = ?",
+            )
+            .bind(prev_txid)
+            .bind(i64::from(input.previous_output.vout))
+            .fetch_optional(&mut **db_tx)
+            .await?;
+
+            if let Some((script, asset_id, value, is_ours)) = prev {
+                if is_ours {
+                    funded = true;
+                    let delta = deltas.entry(asset_id).or_default();
+                    *delta = delta.checked_sub(value).ok_or(StoreError::ValueOverflow)?;
+                }
+                scripts.push(script);
+            }
+        }
+
+        let received: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT asset_id, value FROM utxos
+             WHERE txid = ? AND script_pubkey IN (SELECT script_pubkey FROM wallet_scripts)",
+        )
+        .bind(txid_bytes)
+        .fetch_all(&mut **db_tx)
+        .await?;
+
+        for (asset_id, value) in received {
+            let delta = deltas.entry(asset_id).or_default();
+            *delta = delta.checked_add(value).ok_or(StoreError::ValueOverflow)?;
+        }
+        deltas.retain(|_, delta| *delta != 0);
+
+        let fee = tx
+            .output
+            .iter()
+            .find(|txout| txout.is_fee())
+            .filter(|_| funded)
+            .and_then(|txout| txout.value.explicit())
+            .map(|fee| i64::try_from(fee).map_err(|_| StoreError::ValueOverflow))
+            .transpose()?;
+
+        let mut taproot_pubkey_gen = None;
+        for script in &scripts {
+            let contract: Option<(String,)> =
+                sqlx::query_as("SELECT taproot_pubkey_gen FROM simplicity_contracts WHERE script_pubkey = ? LIMIT 1")
+                    .bind(script.as_slice())
+                    .fetch_optional(&mut **db_tx)
+                    .await?;
+
+            if let Some((tpg,)) = contract {
+                taproot_pubkey_gen = Some(tpg);
+                break;
+            }
+        }
+
+        sqlx::query(
+            "INSERT INTO transactions (txid, raw, first_seen, fee, taproot_pubkey_gen)
+             VALUES (?, ?, CAST(strftime('%s', 'now') AS INTEGER), ?, ?)
+             ON CONFLICT (txid) DO UPDATE SET raw = excluded.raw, fee = COALESCE(excluded.fee, transactions.fee),
+                 taproot_pubkey_gen = COALESCE(excluded.taproot_pubkey_gen, transactions.taproot_pubkey_gen)",
+        )
+        .bind(txid_bytes)
+        .bind(encode::serialize(tx))
+        .bind(fee)
+        .bind(taproot_pubkey_gen)
+        .execute(&mut **db_tx)
+        .await?;
+
+        // Recomputed on every insert: inputs funded by transactions stored since may now be known.
+        sqlx::query("DELETE FROM transaction_deltas WHERE txid = ?")
+            .bind(txid_bytes)
+            .execute(&mut **db_tx)
+            .await?;
+
+        for (asset_id, delta) in deltas {
+            sqlx::query("INSERT INTO transaction_deltas (txid, asset_id, delta) VALUES (?, ?, ?)")
+                .bind(txid_bytes)
+                .bind(asset_id)
+                .bind(delta)
+                .execute(&mut **db_tx)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::fs;
+
+    use crate::UtxoStore;
+
+    use simplicityhl::elements::confidential::{Asset, Nonce, Value};
+    use simplicityhl::elements::{LockTime, OutPoint, Sequence, TxIn, TxOut, TxOutWitness};
+
+    fn make_txout(asset_id: AssetId, value: u64, script_pubkey: &Script) -> TxOut {
+        TxOut {
+            asset: Asset::Explicit(asset_id),
+            value: Value::Explicit(value),
+            nonce: Nonce::Null,
+            script_pubkey: script_pubkey.clone(),
+            witness: TxOutWitness::default(),
+        }
+    }
+
+    fn make_tx(inputs: &[OutPoint], output: Vec<TxOut>) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|outpoint| TxIn {
+                    previous_output: *outpoint,
+                    sequence: Sequence::MAX,
+                    ..TxIn::default()
+                })
+                .collect(),
+            output,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wallet_transaction_history() {
+        let path = "/tmp/test_coin_store_transactions.db";
+        let _ = fs::remove_file(path);
+
+        let store = Store::create(path).await.unwrap();
+
+        let asset = AssetId::from_slice(&[1; 32]).unwrap();
+        let other_asset = AssetId::from_slice(&[2; 32]).unwrap();
+        let ours = Script::from(vec![0x51; 34]);
+        let theirs = Script::from(vec![0x52; 34]);
+
+        store.add_wallet_script(&ours).await.unwrap();
+        store.add_wallet_script(&ours).await.unwrap();
+
+        let funding = make_tx(&[], vec![make_txout(asset, 1000, &ours)]);
+        store.insert_transaction(&funding, HashMap::new()).await.unwrap();
+
+        let spending = make_tx(
+            &[OutPoint::new(funding.txid(), 0)],
+            vec![
+                make_txout(asset, 600, &theirs),
+                make_txout(asset, 350, &ours),
+                TxOut::new_fee(50, asset),
+            ],
+        );
+        store.insert_transaction(&spending, HashMap::new()).await.unwrap();
+        store.insert_transaction(&spending, HashMap::new()).await.unwrap();
+
+        let unrelated = make_tx(&[], vec![make_txout(other_asset, 10, &theirs)]);
+        store.insert_transaction(&unrelated, HashMap::new()).await.unwrap();
+
+        let history = store.list_transactions(None, None, None).await.unwrap();
+        assert_eq!(history.len(), 2);
+
+        assert_eq!(history[0].txid, funding.txid());
+        assert_eq!(history[0].deltas, BTreeMap::from([(asset, 1000)]));
+        assert_eq!(history[0].fee, None);
+
+        assert_eq!(history[1].txid, spending.txid());
+        assert_eq!(history[1].deltas, BTreeMap::from([(asset, -650)]));
+        assert_eq!(history[1].fee, Some(50));
+        assert_eq!(history[1].raw, encode::serialize(&spending));
+        assert_eq!(history[1].block_height, None);
+
+        assert!(store.set_transaction_status(spending.txid(), 100, 1_000).await.unwrap());
+        assert!(
+            !store
+                .set_transaction_status(Txid::from_byte_array([9; Txid::LEN]), 100, 1_000)
+                .await
+                .unwrap()
+        );
+
+        let confirmed = store.list_transactions(None, None, Some(1_000)).await.unwrap();
+        assert_eq!(confirmed.len(), 1);
+        assert_eq!(confirmed[0].block_height, Some(100));
+        assert_eq!(confirmed[0].timestamp, 1_000);
+
+        assert_eq!(store.list_transactions(None, Some(1_001), None).await.unwrap().len(), 1);
+        assert_eq!(store.list_transactions(Some(asset), None, None).await.unwrap().len(), 2);
+        assert!(
+            store
+                .list_transactions(Some(other_asset), None, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_unrecorded_transactions() {
+        let path = "/tmp/test_coin_store_unrecorded_transactions.db";
+        let _ = fs::remove_file(path);
+
+        let store = Store::create(path).await.unwrap();
+
+        let asset = AssetId::from_slice(&[1; 32]).unwrap();
+        let other_asset = AssetId::from_slice(&[2; 32]).unwrap();
+        let ours = Script::from(vec![0x51; 34]);
+        let theirs = Script::from(vec![0x52; 34]);
+
+        store.add_wallet_script(&ours).await.unwrap();
+
+        let funding = make_tx(
+            &[],
+            vec![
+                make_txout(asset, 1000, &ours),
+                make_txout(other_asset, 20, &ours),
+                make_txout(asset, 5, &theirs),
+            ],
+        );
+        store
+            .insert(OutPoint::new(funding.txid(), 0), funding.output[0].clone(), None)
+            .await
+            .unwrap();
+
+        let foreign = make_tx(&[], vec![make_txout(asset, 7, &theirs)]);
+        store
+            .insert(OutPoint::new(foreign.txid(), 0), foreign.output[0].clone(), None)
+            .await
+            .unwrap();
+
+        assert!(store.list_transactions(None, None, None).await.unwrap().is_empty());
+        assert_eq!(store.list_unrecorded_txids().await.unwrap(), vec![funding.txid()]);
+
+        store.insert_transaction(&funding, HashMap::new()).await.unwrap();
+
+        assert!(store.list_unrecorded_txids().await.unwrap().is_empty());
+
+        let history = store.list_transactions(None, None, None).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].deltas, BTreeMap::from([(asset, 1000), (other_asset, 20)]));
+
+        let _ = fs::remove_file(path);
+    }
+}
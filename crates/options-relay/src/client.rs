@@ -1,5 +1,7 @@
 mod publishing;
 mod read_only;
+mod report;
 
 pub use publishing::PublishingClient;
-pub use read_only::ReadOnlyClient;
+pub use read_only::{ReadOnlyClient, parse_option_offers, parse_option_rfq_quotes, parse_option_rfqs, parse_options};
+pub use report::{PublishReport, RelayPublishStatus, RelayRole};
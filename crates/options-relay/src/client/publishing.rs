@@ -1,6 +1,10 @@
 use crate::config::NostrRelayConfig;
 use crate::error::{ParseError, RelayError};
-use crate::events::{ActionCompletedEvent, OptionCreatedEvent, OptionOfferCreatedEvent};
+use crate::events::kinds::OPTION_OFFER_CREATED;
+use crate::events::{
+    ActionCompletedEvent, NegotiationMessage, OptionCreatedEvent, OptionOfferCreatedEvent, OptionRfqEvent,
+    OptionRfqQuoteEvent, ReceivedNegotiationMessage, filters, option_offer_deletion,
+};
 
 use std::sync::Arc;
 
@@ -9,7 +13,7 @@ use nostr_sdk::prelude::Events;
 use simplicityhl_core::SimplicityNetwork;
 use tracing::instrument;
 
-use super::ReadOnlyClient;
+use super::{PublishReport, ReadOnlyClient};
 
 #[derive(Debug, Clone)]
 pub struct PublishingClient {
@@ -19,6 +23,8 @@ pub struct PublishingClient {
 impl PublishingClient {
     #[instrument(skip_all, level = "debug", err)]
     pub async fn connect(config: NostrRelayConfig, signer: impl IntoNostrSigner) -> Result<Self, RelayError> {
+        config.validate()?;
+
         let reader = ReadOnlyClient::connect(config).await?;
 
         reader.set_signer(signer).await;
@@ -38,34 +44,76 @@ impl PublishingClient {
 
     #[instrument(skip(self, event), level = "debug")]
     pub async fn publish_event(&self, event: &Event) -> Result<EventId, RelayError> {
+        Ok(self.publish_event_with_report(event).await?.event_id)
+    }
+
+    /// Publish an event to all configured relays, retrying the relays that failed.
+    ///
+    /// Failed relays are retried up to `retry_count` times, waiting [`NostrRelayConfig::retry_delay`]
+    /// before each retry. The publish fails with
+    /// [`RelayError::InsufficientAcks`] if fewer than `min_acks` relays accepted the event.
+    #[instrument(skip(self, event), level = "debug")]
+    pub async fn publish_event_with_report(&self, event: &Event) -> Result<PublishReport, RelayError> {
+        let client = self.reader.inner_client();
+        let config = self.reader.config();
+
         tracing::debug!(event_id = %event.id, "Publishing event to all relays");
 
-        let output = self.reader.inner_client().send_event(event).await?;
+        let mut report = PublishReport::new(event.id, config);
+
+        match client.send_event(event).await {
+            Ok(output) => report.record_output(&output),
+            Err(err) => report.record_failure_for_pending(&err.to_string()),
+        }
 
-        tracing::debug!(
-            event_id = %output.val,
-            success_count = output.success.len(),
-            failed_count = output.failed.len(),
-            "Event published"
-        );
+        for attempt in 1..=config.retry_count() {
+            let pending = report.pending_urls();
+            if pending.is_empty() {
+                break;
+            }
 
-        Ok(output.val)
+            let delay = config.retry_delay(attempt);
+            tracing::debug!(
+                event_id = %event.id,
+                attempt,
+                pending_count = pending.len(),
+                ?delay,
+                "Retrying publish on failed relays"
+            );
+            tokio::time::sleep(delay).await;
+
+            match client.send_event_to(pending, event).await {
+                Ok(output) => report.record_output(&output),
+                Err(err) => report.record_failure_for_pending(&err.to_string()),
+            }
+        }
+
+        report.log();
+
+        if !report.primary_accepted() && report.accepted_count() > 0 {
+            tracing::warn!(event_id = %event.id, "Primary relay rejected event, relying on backup relays");
+        }
+
+        if !report.is_sufficient() {
+            return Err(RelayError::InsufficientAcks(Box::new(report)));
+        }
+
+        Ok(report)
     }
 
     #[instrument(skip(self, builder), level = "debug")]
     pub async fn publish(&self, builder: EventBuilder) -> Result<EventId, RelayError> {
-        tracing::debug!("Building and publishing event");
+        Ok(self.publish_with_report(builder).await?.event_id)
+    }
 
-        let output = self.reader.inner_client().send_event_builder(builder).await?;
+    /// Sign the builder once and publish the resulting event, so retries resend the same event id.
+    #[instrument(skip(self, builder), level = "debug")]
+    pub async fn publish_with_report(&self, builder: EventBuilder) -> Result<PublishReport, RelayError> {
+        tracing::debug!("Building and publishing event");
 
-        tracing::debug!(
-            event_id = %output.val,
-            success_count = output.success.len(),
-            failed_count = output.failed.len(),
-            "Event published"
-        );
+        let event = self.reader.inner_client().sign_event_builder(builder).await?;
 
-        Ok(output.val)
+        self.publish_event_with_report(&event).await
     }
 
     pub async fn publish_option_created(&self, event: &OptionCreatedEvent) -> Result<EventId, RelayError> {
@@ -74,18 +122,93 @@ impl PublishingClient {
         self.publish(builder).await
     }
 
+    /// Publish an option offer. Offers are addressable, so publishing the same offer again
+    /// (e.g. with an updated remaining size) replaces the previous version on relays.
     pub async fn publish_option_offer_created(&self, event: &OptionOfferCreatedEvent) -> Result<EventId, RelayError> {
         let pubkey = self.public_key().await?;
         let builder = event.to_event_builder(pubkey)?;
         self.publish(builder).await
     }
 
+    /// Request deletion (NIP-09) of one of our option offers, e.g. after it was cancelled or fully taken.
+    ///
+    /// Relays only honour deletions signed by the offer's author.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn delete_option_offer(
+        &self,
+        offer_event_id: EventId,
+        taproot_pubkey_gen: &str,
+        reason: &str,
+    ) -> Result<EventId, RelayError> {
+        let pubkey = self.public_key().await?;
+        let coordinate = Coordinate::new(OPTION_OFFER_CREATED, pubkey).identifier(taproot_pubkey_gen);
+        self.publish(option_offer_deletion(offer_event_id, coordinate, reason)).await
+    }
+
+    pub async fn publish_option_rfq(&self, event: &OptionRfqEvent) -> Result<EventId, RelayError> {
+        let pubkey = self.public_key().await?;
+        let builder = event.to_event_builder(pubkey);
+        self.publish(builder).await
+    }
+
+    /// Answer an RFQ with a ready-to-take option offer.
+    pub async fn publish_option_rfq_quote(&self, event: &OptionRfqQuoteEvent) -> Result<EventId, RelayError> {
+        let builder = event.to_event_builder()?;
+        self.publish(builder).await
+    }
+
     pub async fn publish_action_completed(&self, event: &ActionCompletedEvent) -> Result<EventId, RelayError> {
         let pubkey = self.public_key().await?;
         let builder = event.to_event_builder(pubkey);
         self.publish(builder).await
     }
 
+    /// Send an encrypted negotiation message to `receiver` as a NIP-17 direct message.
+    ///
+    /// The message is sealed and gift-wrapped (NIP-59) with NIP-44 encryption, so relays only
+    /// see an ephemeral wrapper key and the recipient.
+    #[instrument(skip(self, message), level = "debug")]
+    pub async fn send_negotiation_message(
+        &self,
+        receiver: PublicKey,
+        message: &NegotiationMessage,
+    ) -> Result<EventId, RelayError> {
+        let signer = self.signer().await?;
+        let event = EventBuilder::private_msg(&signer, receiver, message.to_content()?, message.rumor_tags()).await?;
+        self.publish_event(&event).await
+    }
+
+    /// Fetch and decrypt negotiation messages addressed to us, oldest first.
+    ///
+    /// Gift wraps that cannot be decrypted and direct messages that are not negotiations are skipped.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn fetch_negotiation_messages(&self) -> Result<Vec<ReceivedNegotiationMessage>, RelayError> {
+        let pubkey = self.public_key().await?;
+        let events = self.fetch_events(filters::gift_wraps_for(pubkey)).await?;
+        let client = self.reader.inner_client();
+
+        let mut messages = Vec::new();
+        for event in events.iter() {
+            let unwrapped = match client.unwrap_gift_wrap(event).await {
+                Ok(unwrapped) => unwrapped,
+                Err(e) => {
+                    tracing::debug!(event_id = %event.id, error = %e, "Skipping undecryptable gift wrap");
+                    continue;
+                }
+            };
+
+            match ReceivedNegotiationMessage::from_rumor(unwrapped.sender, &unwrapped.rumor) {
+                Ok(Some(message)) => messages.push(message),
+                Ok(None) => {}
+                Err(e) => tracing::debug!(event_id = %event.id, error = %e, "Skipping invalid negotiation message"),
+            }
+        }
+
+        messages.sort_by_key(|m| m.created_at);
+
+        Ok(messages)
+    }
+
     #[instrument(skip(self), level = "debug")]
     pub async fn fetch_events(&self, filter: Filter) -> Result<Events, RelayError> {
         self.reader.fetch_events(filter).await
@@ -105,6 +228,18 @@ impl PublishingClient {
         self.reader.fetch_option_offers(network).await
     }
 
+    pub async fn fetch_option_rfqs(&self) -> Result<Vec<Result<OptionRfqEvent, ParseError>>, RelayError> {
+        self.reader.fetch_option_rfqs().await
+    }
+
+    pub async fn fetch_option_rfq_quotes(
+        &self,
+        rfq_event_id: EventId,
+        network: SimplicityNetwork,
+    ) -> Result<Vec<Result<OptionRfqQuoteEvent, ParseError>>, RelayError> {
+        self.reader.fetch_option_rfq_quotes(rfq_event_id, network).await
+    }
+
     pub async fn fetch_actions_for_event(
         &self,
         original_event_id: EventId,
@@ -112,6 +247,13 @@ impl PublishingClient {
         self.reader.fetch_actions_for_event(original_event_id).await
     }
 
+    pub async fn fetch_offer_actions(
+        &self,
+        offer: &OptionOfferCreatedEvent,
+    ) -> Result<Vec<ActionCompletedEvent>, RelayError> {
+        self.reader.fetch_offer_actions(offer).await
+    }
+
     #[instrument(skip(self), level = "debug")]
     pub async fn subscribe(&self, filter: Filter) -> Result<SubscriptionId, RelayError> {
         self.reader.subscribe(filter).await
@@ -1,12 +1,16 @@
 use crate::config::NostrRelayConfig;
 use crate::error::{ParseError, RelayError};
-use crate::events::kinds::TAG_EXPIRY;
-use crate::events::{ActionCompletedEvent, OptionCreatedEvent, OptionOfferCreatedEvent, filters};
+use crate::events::kinds::{OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY, TAG_EXPIRY};
+use crate::events::{
+    ActionCompletedEvent, DeletionIndex, OptionCreatedEvent, OptionOfferCreatedEvent, OptionRfqEvent,
+    OptionRfqQuoteEvent, filters, latest_addressable,
+};
 
 use nostr::prelude::*;
-use nostr_sdk::Client;
 use nostr_sdk::prelude::Events;
+use nostr_sdk::{Client, RelayPoolNotification, RelayStatus};
 use simplicityhl_core::SimplicityNetwork;
+use tokio::sync::broadcast;
 use tracing::instrument;
 
 /// Check if an event is still active (not expired) based on its expiry tag.
@@ -21,6 +25,61 @@ fn is_active(event: &Event) -> bool {
         .is_some_and(|expiry| expiry > now)
 }
 
+/// Parse option creation events, skipping expired ones.
+pub fn parse_options<'a>(
+    events: impl IntoIterator<Item = &'a Event>,
+    network: SimplicityNetwork,
+) -> Vec<Result<OptionCreatedEvent, ParseError>> {
+    events
+        .into_iter()
+        .filter(|e| is_active(e))
+        .map(|e| OptionCreatedEvent::from_event(e, network))
+        .collect()
+}
+
+/// Parse option offer creation events, skipping expired ones.
+///
+/// Deletion requests mixed into `events` hide the offers they target, and only the newest
+/// version of each replaceable offer is kept.
+pub fn parse_option_offers<'a>(
+    events: impl IntoIterator<Item = &'a Event>,
+    network: SimplicityNetwork,
+) -> Vec<Result<OptionOfferCreatedEvent, ParseError>> {
+    let events: Vec<&Event> = events.into_iter().collect();
+    let deletions = DeletionIndex::from_events(events.iter().copied());
+
+    latest_addressable(
+        events
+            .into_iter()
+            .filter(|e| e.kind == OPTION_OFFER_CREATED || e.kind == OPTION_OFFER_CREATED_LEGACY),
+    )
+    .into_iter()
+    .filter(|e| is_active(e) && !deletions.is_deleted(e))
+    .map(|e| OptionOfferCreatedEvent::from_event(e, network))
+    .collect()
+}
+
+/// Parse RFQ events, skipping those whose expiry window has passed.
+pub fn parse_option_rfqs<'a>(events: impl IntoIterator<Item = &'a Event>) -> Vec<Result<OptionRfqEvent, ParseError>> {
+    events
+        .into_iter()
+        .filter(|e| is_active(e))
+        .map(OptionRfqEvent::from_event)
+        .collect()
+}
+
+/// Parse RFQ quotes, skipping those whose offer has expired.
+pub fn parse_option_rfq_quotes<'a>(
+    events: impl IntoIterator<Item = &'a Event>,
+    network: SimplicityNetwork,
+) -> Vec<Result<OptionRfqQuoteEvent, ParseError>> {
+    events
+        .into_iter()
+        .filter(|e| is_active(e))
+        .map(|e| OptionRfqQuoteEvent::from_event(e, network))
+        .collect()
+}
+
 #[derive(Debug, Clone)]
 pub struct ReadOnlyClient {
     client: Client,
@@ -56,28 +115,54 @@ impl ReadOnlyClient {
         Ok(self.client.fetch_combined_events(filter, self.config.timeout()).await?)
     }
 
+    /// Fetch events from a single configured relay.
+    ///
+    /// Waits up to the configured timeout for the relay to connect, then fails with
+    /// [`RelayError::RelayNotConnected`] instead of returning no events when it is still down, so
+    /// callers keeping a per-relay cursor do not advance it past events they never saw.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn fetch_events_from(&self, relay_url: &str, filter: Filter) -> Result<Events, RelayError> {
+        let url = Url::parse(relay_url)?;
+        let relay = self.client.relay(&url).await?;
+        relay.wait_for_connection(self.config.timeout()).await;
+        if relay.status() != RelayStatus::Connected {
+            return Err(RelayError::RelayNotConnected(relay_url.to_string()));
+        }
+
+        tracing::debug!(?filter, relay_url, "Fetching events from relay");
+
+        Ok(self.client.fetch_events_from([url], filter, self.config.timeout()).await?)
+    }
+
     pub async fn fetch_options(
         &self,
         network: SimplicityNetwork,
     ) -> Result<Vec<Result<OptionCreatedEvent, ParseError>>, RelayError> {
         let events = self.fetch_events(filters::option_created()).await?;
-        Ok(events
-            .iter()
-            .filter(|e| is_active(e))
-            .map(|e| OptionCreatedEvent::from_event(e, network))
-            .collect())
+        Ok(parse_options(events.iter(), network))
     }
 
     pub async fn fetch_option_offers(
         &self,
         network: SimplicityNetwork,
     ) -> Result<Vec<Result<OptionOfferCreatedEvent, ParseError>>, RelayError> {
-        let events = self.fetch_events(filters::option_offer_created()).await?;
-        Ok(events
-            .iter()
-            .filter(|e| is_active(e))
-            .map(|e| OptionOfferCreatedEvent::from_event(e, network))
-            .collect())
+        let offers = self.fetch_events(filters::option_offer_created()).await?;
+        let deletions = self.fetch_events(filters::option_offer_deletions()).await?;
+        Ok(parse_option_offers(offers.iter().chain(deletions.iter()), network))
+    }
+
+    pub async fn fetch_option_rfqs(&self) -> Result<Vec<Result<OptionRfqEvent, ParseError>>, RelayError> {
+        let events = self.fetch_events(filters::option_rfq()).await?;
+        Ok(parse_option_rfqs(events.iter()))
+    }
+
+    pub async fn fetch_option_rfq_quotes(
+        &self,
+        rfq_event_id: EventId,
+        network: SimplicityNetwork,
+    ) -> Result<Vec<Result<OptionRfqQuoteEvent, ParseError>>, RelayError> {
+        let events = self.fetch_events(filters::option_rfq_quotes_for_event(rfq_event_id)).await?;
+        Ok(parse_option_rfq_quotes(events.iter(), network))
     }
 
     pub async fn fetch_actions_for_event(
@@ -90,6 +175,32 @@ impl ReadOnlyClient {
         Ok(events.iter().map(ActionCompletedEvent::from_event).collect())
     }
 
+    /// Fetch the well-formed actions published against an offer, referencing either this
+    /// version's event id or the offer's coordinate.
+    ///
+    /// Pass them to [`crate::ListingValidator::offer_depth`] to get the offer's remaining size.
+    pub async fn fetch_offer_actions(
+        &self,
+        offer: &OptionOfferCreatedEvent,
+    ) -> Result<Vec<ActionCompletedEvent>, RelayError> {
+        let coordinate = offer.coordinate(offer.pubkey);
+        let mut events: Vec<Event> = self
+            .fetch_events(filters::action_completed_for_event(offer.event_id))
+            .await?
+            .into_iter()
+            .collect();
+        events.extend(self.fetch_events(filters::action_completed_for_offer(&coordinate)).await?);
+
+        // Actions carrying both references come back from both queries.
+        events.sort_by_key(|event| event.id);
+        events.dedup_by_key(|event| event.id);
+
+        Ok(events
+            .iter()
+            .filter_map(|event| ActionCompletedEvent::from_event(event).ok())
+            .collect())
+    }
+
     #[instrument(skip(self), level = "debug")]
     pub async fn subscribe(&self, filter: Filter) -> Result<SubscriptionId, RelayError> {
         tracing::debug!(?filter, "Subscribing to events");
@@ -104,6 +215,14 @@ impl ReadOnlyClient {
         self.client.unsubscribe(subscription_id).await;
     }
 
+    /// Receiver for events delivered to active subscriptions.
+    ///
+    /// Only notifications sent after this call are received, so call it before [`Self::subscribe`].
+    #[must_use]
+    pub fn notifications(&self) -> broadcast::Receiver<RelayPoolNotification> {
+        self.client.notifications()
+    }
+
     #[instrument(skip(self), level = "debug")]
     pub async fn disconnect(&self) {
         tracing::debug!("Disconnecting from all relays");
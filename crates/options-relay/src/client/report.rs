@@ -0,0 +1,258 @@
+use std::fmt;
+
+use nostr::EventId;
+use nostr_sdk::prelude::Output;
+
+use crate::config::NostrRelayConfig;
+
+/// Whether a relay was configured as the primary relay or as a backup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayRole {
+    Primary,
+    Backup,
+}
+
+impl RelayRole {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Primary => "primary",
+            Self::Backup => "backup",
+        }
+    }
+}
+
+/// Outcome of publishing a single event to a single relay.
+#[derive(Debug, Clone)]
+pub struct RelayPublishStatus {
+    pub url: String,
+    pub role: RelayRole,
+    pub accepted: bool,
+    /// Number of send attempts made against this relay.
+    pub attempts: u32,
+    /// Last rejection or transport error reported by the relay.
+    pub error: Option<String>,
+}
+
+/// Per-relay result of a publish, including retries.
+#[derive(Debug, Clone)]
+pub struct PublishReport {
+    pub event_id: EventId,
+    pub required_acks: usize,
+    pub statuses: Vec<RelayPublishStatus>,
+}
+
+impl PublishReport {
+    #[must_use]
+    pub fn new(event_id: EventId, config: &NostrRelayConfig) -> Self {
+        let statuses = config
+            .all_relays()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, url)| RelayPublishStatus {
+                url: url.to_string(),
+                role: if idx == 0 { RelayRole::Primary } else { RelayRole::Backup },
+                accepted: false,
+                attempts: 0,
+                error: None,
+            })
+            .collect();
+
+        Self {
+            event_id,
+            required_acks: config.min_acks(),
+            statuses,
+        }
+    }
+
+    /// Record the outcome of a send attempt.
+    ///
+    /// Only relays that are still pending are updated, so a relay that accepted
+    /// the event on an earlier attempt is never downgraded.
+    pub fn record<'a>(
+        &mut self,
+        success: impl IntoIterator<Item = &'a str>,
+        failed: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) {
+        for url in success {
+            if let Some(status) = self.pending_status_mut(url) {
+                status.accepted = true;
+                status.attempts += 1;
+                status.error = None;
+            }
+        }
+
+        for (url, error) in failed {
+            if let Some(status) = self.pending_status_mut(url) {
+                status.attempts += 1;
+                status.error = Some(error.to_string());
+            }
+        }
+    }
+
+    /// Record a `nostr-sdk` send output.
+    pub fn record_output(&mut self, output: &Output<EventId>) {
+        self.record(
+            output.success.iter().map(|url| url.as_str()),
+            output.failed.iter().map(|(url, err)| (url.as_str(), err.as_str())),
+        );
+    }
+
+    /// Mark every pending relay as failed with the same error, e.g. when the whole send call errored out.
+    pub fn record_failure_for_pending(&mut self, error: &str) {
+        for status in self.statuses.iter_mut().filter(|s| !s.accepted) {
+            status.attempts += 1;
+            status.error = Some(error.to_string());
+        }
+    }
+
+    /// URLs of relays that have not accepted the event yet.
+    #[must_use]
+    pub fn pending_urls(&self) -> Vec<String> {
+        self.statuses
+            .iter()
+            .filter(|s| !s.accepted)
+            .map(|s| s.url.clone())
+            .collect()
+    }
+
+    #[must_use]
+    pub fn accepted_count(&self) -> usize {
+        self.statuses.iter().filter(|s| s.accepted).count()
+    }
+
+    #[must_use]
+    pub fn primary_accepted(&self) -> bool {
+        self.statuses
+            .iter()
+            .any(|s| s.role == RelayRole::Primary && s.accepted)
+    }
+
+    #[must_use]
+    pub fn is_sufficient(&self) -> bool {
+        self.accepted_count() >= self.required_acks
+    }
+
+    /// Emit one log line per relay.
+    pub fn log(&self) {
+        for status in &self.statuses {
+            if status.accepted {
+                tracing::info!(
+                    event_id = %self.event_id,
+                    relay = %status.url,
+                    role = status.role.as_str(),
+                    attempts = status.attempts,
+                    "Relay accepted event"
+                );
+            } else {
+                tracing::warn!(
+                    event_id = %self.event_id,
+                    relay = %status.url,
+                    role = status.role.as_str(),
+                    attempts = status.attempts,
+                    error = status.error.as_deref().unwrap_or("unknown"),
+                    "Relay did not accept event"
+                );
+            }
+        }
+    }
+
+    fn pending_status_mut(&mut self, url: &str) -> Option<&mut RelayPublishStatus> {
+        self.statuses
+            .iter_mut()
+            .find(|s| !s.accepted && same_relay(&s.url, url))
+    }
+}
+
+impl fmt::Display for PublishReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} relays accepted (required {})",
+            self.accepted_count(),
+            self.statuses.len(),
+            self.required_acks
+        )?;
+
+        for status in self.statuses.iter().filter(|s| !s.accepted) {
+            write!(
+                f,
+                "; {} ({}): {}",
+                status.url,
+                status.role.as_str(),
+                status.error.as_deref().unwrap_or("no response")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Relay URLs coming back from the pool are normalized, so ignore a trailing slash when comparing.
+fn same_relay(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_report() -> PublishReport {
+        let config = NostrRelayConfig::new("wss://primary.example.com")
+            .add_backup_relay("wss://backup1.example.com/")
+            .add_backup_relay("wss://backup2.example.com")
+            .with_min_acks(2);
+
+        PublishReport::new(EventId::all_zeros(), &config)
+    }
+
+    #[test]
+    fn test_report_roles() {
+        let report = get_report();
+
+        assert_eq!(report.statuses[0].role, RelayRole::Primary);
+        assert_eq!(report.statuses[1].role, RelayRole::Backup);
+        assert_eq!(report.statuses[2].role, RelayRole::Backup);
+        assert_eq!(report.pending_urls().len(), 3);
+    }
+
+    #[test]
+    fn test_report_record_and_retry() {
+        let mut report = get_report();
+
+        report.record(
+            ["wss://backup1.example.com"],
+            [
+                ("wss://primary.example.com/", "timeout"),
+                ("wss://backup2.example.com/", "blocked"),
+            ],
+        );
+
+        assert_eq!(report.accepted_count(), 1);
+        assert!(!report.primary_accepted());
+        assert!(!report.is_sufficient());
+        assert_eq!(report.pending_urls().len(), 2);
+
+        report.record(["wss://primary.example.com/"], [("wss://backup2.example.com", "blocked")]);
+
+        assert_eq!(report.accepted_count(), 2);
+        assert!(report.primary_accepted());
+        assert!(report.is_sufficient());
+        assert_eq!(report.statuses[0].attempts, 2);
+        assert_eq!(report.statuses[1].attempts, 1);
+        assert_eq!(report.statuses[2].attempts, 2);
+        assert_eq!(report.statuses[2].error.as_deref(), Some("blocked"));
+    }
+
+    #[test]
+    fn test_report_accepted_relay_not_downgraded() {
+        let mut report = get_report();
+
+        report.record(["wss://primary.example.com"], []);
+        report.record_failure_for_pending("connection refused");
+
+        assert!(report.primary_accepted());
+        assert_eq!(report.statuses[0].attempts, 1);
+        assert_eq!(report.statuses[1].error.as_deref(), Some("connection refused"));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::RelayError;
+
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -6,11 +8,17 @@ pub struct NostrRelayConfig {
     backup_relays: Vec<String>,
     timeout: Duration,
     retry_count: u32,
+    retry_backoff: Duration,
+    min_acks: usize,
 }
 
 impl NostrRelayConfig {
     pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
     pub const DEFAULT_RETRY_COUNT: u32 = 3;
+    pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+    /// Longest wait between two publish attempts, however many retries came before.
+    pub const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(10);
+    pub const DEFAULT_MIN_ACKS: usize = 1;
 
     #[must_use]
     pub fn new(primary_relay: impl Into<String>) -> Self {
@@ -19,6 +27,8 @@ impl NostrRelayConfig {
             backup_relays: Vec::new(),
             timeout: Self::DEFAULT_TIMEOUT,
             retry_count: Self::DEFAULT_RETRY_COUNT,
+            retry_backoff: Self::DEFAULT_RETRY_BACKOFF,
+            min_acks: Self::DEFAULT_MIN_ACKS,
         }
     }
 
@@ -46,11 +56,48 @@ impl NostrRelayConfig {
         self
     }
 
+    /// Wait before the first retry; each further retry waits twice as long, up to [`Self::MAX_RETRY_BACKOFF`].
+    #[must_use]
+    pub const fn with_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Minimum number of relays that must accept an event for a publish to succeed.
+    ///
+    /// Checked by [`Self::validate`] when a publishing client is connected.
+    #[must_use]
+    pub const fn with_min_acks(mut self, min_acks: usize) -> Self {
+        self.min_acks = min_acks;
+        self
+    }
+
+    /// Check that `min_acks` can be reached with the configured relays.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RelayError::InvalidMinAcks`] if `min_acks` is zero or larger than the number of relays.
+    pub fn validate(&self) -> Result<(), RelayError> {
+        let relays = self.all_relays().len();
+        if self.min_acks == 0 || self.min_acks > relays {
+            return Err(RelayError::InvalidMinAcks {
+                min_acks: self.min_acks,
+                relays,
+            });
+        }
+        Ok(())
+    }
+
     #[must_use]
     pub fn primary_relay(&self) -> &str {
         &self.primary_relay
     }
 
+    #[must_use]
+    pub fn backup_relays(&self) -> &[String] {
+        &self.backup_relays
+    }
+
     #[must_use]
     pub fn all_relays(&self) -> Vec<&str> {
         std::iter::once(self.primary_relay.as_str())
@@ -67,6 +114,23 @@ impl NostrRelayConfig {
     pub const fn retry_count(&self) -> u32 {
         self.retry_count
     }
+
+    #[must_use]
+    pub const fn retry_backoff(&self) -> Duration {
+        self.retry_backoff
+    }
+
+    /// Wait before retry number `attempt`, counting from 1.
+    #[must_use]
+    pub fn retry_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.retry_backoff.saturating_mul(factor).min(Self::MAX_RETRY_BACKOFF)
+    }
+
+    #[must_use]
+    pub const fn min_acks(&self) -> usize {
+        self.min_acks
+    }
 }
 
 #[cfg(test)]
@@ -81,6 +145,7 @@ mod tests {
         assert_eq!(config.all_relays().len(), 1);
         assert_eq!(config.timeout(), NostrRelayConfig::DEFAULT_TIMEOUT);
         assert_eq!(config.retry_count(), NostrRelayConfig::DEFAULT_RETRY_COUNT);
+        assert_eq!(config.min_acks(), NostrRelayConfig::DEFAULT_MIN_ACKS);
     }
 
     #[test]
@@ -94,15 +159,44 @@ mod tests {
         assert_eq!(all[0], "wss://primary.example.com");
         assert_eq!(all[1], "wss://backup1.example.com");
         assert_eq!(all[2], "wss://backup2.example.com");
+        assert_eq!(config.backup_relays().len(), 2);
     }
 
     #[test]
     fn test_config_with_custom_settings() {
         let config = NostrRelayConfig::new("wss://relay.example.com")
             .with_timeout(Duration::from_secs(60))
-            .with_retry_count(5);
+            .with_retry_count(5)
+            .with_min_acks(2);
 
         assert_eq!(config.timeout(), Duration::from_secs(60));
         assert_eq!(config.retry_count(), 5);
+        assert_eq!(config.min_acks(), 2);
+    }
+
+    #[test]
+    fn test_config_validate_min_acks() {
+        let config = NostrRelayConfig::new("wss://primary.example.com").add_backup_relay("wss://backup.example.com");
+
+        assert!(config.clone().validate().is_ok());
+        assert!(config.clone().with_min_acks(2).validate().is_ok());
+        assert!(matches!(
+            config.clone().with_min_acks(0).validate(),
+            Err(RelayError::InvalidMinAcks { min_acks: 0, relays: 2 })
+        ));
+        assert!(matches!(
+            config.with_min_acks(3).validate(),
+            Err(RelayError::InvalidMinAcks { min_acks: 3, relays: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_config_retry_delay() {
+        let config = NostrRelayConfig::new("wss://relay.example.com").with_retry_backoff(Duration::from_secs(1));
+
+        assert_eq!(config.retry_delay(1), Duration::from_secs(1));
+        assert_eq!(config.retry_delay(2), Duration::from_secs(2));
+        assert_eq!(config.retry_delay(3), Duration::from_secs(4));
+        assert_eq!(config.retry_delay(40), NostrRelayConfig::MAX_RETRY_BACKOFF);
     }
 }
@@ -1,3 +1,5 @@
+use crate::client::PublishReport;
+
 use contracts::error::TaprootPubkeyGenError;
 
 use nostr::SignerError;
@@ -14,15 +16,37 @@ pub enum RelayError {
     #[error("No relays configured")]
     NoRelaysConfigured,
 
+    #[error("min_acks must be between 1 and the number of relays ({relays}), got {min_acks}")]
+    InvalidMinAcks { min_acks: usize, relays: usize },
+
     #[error("Signer error")]
     Signer(#[from] SignerError),
 
     #[error("Nostr client error")]
     NostrClient(#[from] nostr_sdk::client::Error),
 
+    #[error("Relay {0} is not connected")]
+    RelayNotConnected(String),
+
     #[error("No events found")]
     NoEventsFound,
 
+    #[error("Event builder error")]
+    EventBuilder(#[from] nostr::event::builder::Error),
+
+    #[error("Relay information request failed")]
+    RelayInformation(#[from] minreq::Error),
+
+    #[error("Relay information request returned status {0}")]
+    RelayInformationStatus(i32),
+
+    /// Triggered when fewer relays than `NostrRelayConfig::min_acks` accepted an event after all retries.
+    #[error("Not enough relays accepted the event: {0}")]
+    InsufficientAcks(Box<PublishReport>),
+
+    #[error("Message encoding error")]
+    MessageEncoding(#[from] serde_json::Error),
+
     /// Triggered when encoding contract arguments (e.g., `OptionsArguments`, `SwapWithChangeArguments`)
     /// to hex/bincode format for NOSTR event tags fails.
     #[error("Encoding error")]
@@ -37,12 +61,31 @@ pub enum ParseError {
     #[error("Invalid event kind")]
     InvalidKind,
 
+    /// Triggered for events written with a schema this client does not know, e.g. by a newer release.
+    #[error("Unsupported event schema version {0}; upgrade the client to read this event")]
+    UnsupportedVersion(String),
+
     #[error("Missing required tag: {0}")]
     MissingTag(&'static str),
 
     #[error("Invalid action type")]
     InvalidAction,
 
+    #[error("Invalid amount in tag: {0}")]
+    InvalidAmount(&'static str),
+
+    #[error("Invalid asset id in tag: {0}")]
+    InvalidAsset(&'static str),
+
+    #[error("Invalid range in tag: {0}")]
+    InvalidRange(&'static str),
+
+    #[error("Invalid message payload")]
+    InvalidMessage(#[from] serde_json::Error),
+
+    #[error("Message sender does not match its seal")]
+    InvalidSender,
+
     #[error("Invalid outpoint")]
     InvalidOutpoint(#[from] ParseOutPointError),
 
@@ -2,12 +2,14 @@ use crate::error::ParseError;
 use crate::events::kinds::{
     ACTION_COMPLETED, ACTION_OPTION_CANCELLED, ACTION_OPTION_CREATED, ACTION_OPTION_EXERCISED, ACTION_OPTION_EXPIRED,
     ACTION_OPTION_FUNDED, ACTION_OPTION_OFFER_CANCELLED, ACTION_OPTION_OFFER_CREATED, ACTION_OPTION_OFFER_EXERCISED,
-    ACTION_SETTLEMENT_CLAIMED, TAG_ACTION, TAG_OUTPOINT,
+    ACTION_SETTLEMENT_CLAIMED, TAG_ACTION, TAG_FILLED_AMOUNT, TAG_OUTPOINT, TAG_REMAINING_COLLATERAL,
 };
+use crate::events::schema::{schema_version, schema_version_tag};
 
 use std::str::FromStr;
 
-use nostr::{Event, EventBuilder, EventId, PublicKey, Tag, TagKind, Timestamp};
+use nostr::nips::nip01::Coordinate;
+use nostr::{Alphabet, Event, EventBuilder, EventId, PublicKey, SingleLetterTag, Tag, TagKind, Timestamp};
 use simplicityhl::elements::OutPoint;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -67,6 +69,12 @@ pub struct ActionCompletedEvent {
     pub original_event_id: EventId,
     pub action: ActionType,
     pub outpoint: OutPoint,
+    /// Collateral received by the taker, set on partial or full offer fills.
+    pub filled_amount: Option<u64>,
+    /// Collateral left in the offer after a fill.
+    pub remaining_collateral: Option<u64>,
+    /// Coordinate of the addressable offer the action applies to, which outlives republished versions.
+    pub offer_coordinate: Option<Coordinate>,
 }
 
 impl ActionCompletedEvent {
@@ -79,16 +87,65 @@ impl ActionCompletedEvent {
             original_event_id,
             action,
             outpoint,
+            filled_amount: None,
+            remaining_collateral: None,
+            offer_coordinate: None,
         }
     }
 
+    /// Attach the filled amount and the collateral left in the offer.
+    #[must_use]
+    pub const fn with_fill(mut self, filled_amount: u64, remaining_collateral: u64) -> Self {
+        self.filled_amount = Some(filled_amount);
+        self.remaining_collateral = Some(remaining_collateral);
+        self
+    }
+
+    /// Reference the offer by its coordinate as well as by the version's event id.
+    #[must_use]
+    pub fn with_offer_coordinate(mut self, coordinate: Coordinate) -> Self {
+        self.offer_coordinate = Some(coordinate);
+        self
+    }
+
+    /// Whether the action applies to the offer published as `offer_event_id` or to any
+    /// version of the offer at `coordinate`.
+    #[must_use]
+    pub fn references_offer(&self, offer_event_id: EventId, coordinate: &Coordinate) -> bool {
+        self.original_event_id == offer_event_id || self.offer_coordinate.as_ref() == Some(coordinate)
+    }
+
     #[must_use]
     pub fn to_event_builder(&self, creator_pubkey: PublicKey) -> EventBuilder {
-        EventBuilder::new(ACTION_COMPLETED, "")
+        let mut builder = EventBuilder::new(ACTION_COMPLETED, "")
+            .tag(schema_version_tag())
             .tag(Tag::public_key(creator_pubkey))
             .tag(Tag::event(self.original_event_id))
             .tag(Tag::custom(TagKind::custom(TAG_ACTION), [self.action.as_str()]))
-            .tag(Tag::custom(TagKind::custom(TAG_OUTPOINT), [self.outpoint.to_string()]))
+            .tag(Tag::custom(TagKind::custom(TAG_OUTPOINT), [self.outpoint.to_string()]));
+
+        if let Some(filled_amount) = self.filled_amount {
+            builder = builder.tag(Tag::custom(
+                TagKind::custom(TAG_FILLED_AMOUNT),
+                [filled_amount.to_string()],
+            ));
+        }
+
+        if let Some(remaining_collateral) = self.remaining_collateral {
+            builder = builder.tag(Tag::custom(
+                TagKind::custom(TAG_REMAINING_COLLATERAL),
+                [remaining_collateral.to_string()],
+            ));
+        }
+
+        if let Some(coordinate) = &self.offer_coordinate {
+            builder = builder.tag(Tag::custom(
+                TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::A)),
+                [coordinate.to_string()],
+            ));
+        }
+
+        builder
     }
 
     pub fn from_event(event: &Event) -> Result<Self, ParseError> {
@@ -98,6 +155,14 @@ impl ActionCompletedEvent {
             return Err(ParseError::InvalidKind);
         }
 
+        match schema_version(event)? {
+            1 => Self::from_event_v1(event),
+            version => Err(ParseError::UnsupportedVersion(version.to_string())),
+        }
+    }
+
+    /// Version 1: the action and outpoint in custom tags, fill amounts in optional ones.
+    fn from_event_v1(event: &Event) -> Result<Self, ParseError> {
         let original_event_id = event
             .tags
             .iter()
@@ -131,13 +196,28 @@ impl ActionCompletedEvent {
             original_event_id,
             action,
             outpoint,
+            filled_amount: optional_amount_tag(event, TAG_FILLED_AMOUNT)?,
+            remaining_collateral: optional_amount_tag(event, TAG_REMAINING_COLLATERAL)?,
+            offer_coordinate: event.tags.coordinates().next().cloned(),
         })
     }
 }
 
+/// Parse an optional numeric tag. Missing tags yield `None`; malformed ones are an error.
+pub(crate) fn optional_amount_tag(event: &Event, tag: &'static str) -> Result<Option<u64>, ParseError> {
+    event
+        .tags
+        .iter()
+        .find(|t| matches!(t.kind(), TagKind::Custom(s) if s.as_ref() == tag))
+        .and_then(|t| t.content())
+        .map(|s| s.parse::<u64>().map_err(|_| ParseError::InvalidAmount(tag)))
+        .transpose()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::events::kinds::OPTION_OFFER_CREATED;
     use nostr::{Keys, hashes::Hash};
     use simplicityhl::elements::Txid;
 
@@ -181,6 +261,30 @@ mod tests {
         assert_eq!(parsed.original_event_id, original_event_id);
         assert_eq!(parsed.action, ActionType::OptionExercised);
         assert_eq!(parsed.outpoint, dummy_outpoint());
+        assert_eq!(parsed.filled_amount, None);
+        assert_eq!(parsed.remaining_collateral, None);
+        assert_eq!(parsed.offer_coordinate, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn action_completed_event_fill_roundtrip() -> anyhow::Result<()> {
+        let keys = Keys::generate();
+
+        let coordinate = Coordinate::new(OPTION_OFFER_CREATED, keys.public_key()).identifier("offer");
+
+        let event = ActionCompletedEvent::new(EventId::all_zeros(), ActionType::OptionOfferExercised, dummy_outpoint())
+            .with_fill(300, 700)
+            .with_offer_coordinate(coordinate.clone());
+
+        let built_event = event.to_event_builder(keys.public_key()).sign_with_keys(&keys)?;
+        let parsed = ActionCompletedEvent::from_event(&built_event)?;
+
+        assert_eq!(parsed.filled_amount, Some(300));
+        assert_eq!(parsed.remaining_collateral, Some(700));
+        assert_eq!(parsed.offer_coordinate, Some(coordinate.clone()));
+        assert!(parsed.references_offer(EventId::from_byte_array([1; 32]), &coordinate));
 
         Ok(())
     }
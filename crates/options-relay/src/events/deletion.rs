@@ -0,0 +1,159 @@
+use crate::events::kinds::{OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY};
+
+use std::collections::{HashMap, HashSet};
+
+use nostr::nips::nip01::Coordinate;
+use nostr::nips::nip09::EventDeletionRequest;
+use nostr::{Alphabet, Event, EventBuilder, EventId, Kind, PublicKey, SingleLetterTag, Tag, TagKind, Timestamp};
+
+/// Build a NIP-09 deletion request for an option offer.
+///
+/// Both the event id and the addressable coordinate are referenced, so relays drop the
+/// original event as well as any replacement published before the request.
+#[must_use]
+pub fn option_offer_deletion(offer_event_id: EventId, coordinate: Coordinate, reason: &str) -> EventBuilder {
+    let request = EventDeletionRequest::new()
+        .id(offer_event_id)
+        .coordinate(coordinate)
+        .reason(reason);
+
+    EventBuilder::delete(request)
+        .tag(kind_tag(OPTION_OFFER_CREATED))
+        .tag(kind_tag(OPTION_OFFER_CREATED_LEGACY))
+}
+
+fn kind_tag(kind: Kind) -> Tag {
+    Tag::custom(
+        TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::K)),
+        [kind.as_u16().to_string()],
+    )
+}
+
+/// Index of NIP-09 deletion requests, used to hide deleted events on the client side
+/// for relays that do not honour deletions themselves.
+#[derive(Debug, Default)]
+pub struct DeletionIndex {
+    ids: HashSet<(PublicKey, EventId)>,
+    coordinates: HashMap<(PublicKey, Kind, String), Timestamp>,
+}
+
+impl DeletionIndex {
+    /// Build the index from deletion events. Events of other kinds and unverifiable events are ignored.
+    #[must_use]
+    pub fn from_events<'a>(events: impl IntoIterator<Item = &'a Event>) -> Self {
+        let mut index = Self::default();
+
+        for event in events {
+            if event.kind != Kind::EventDeletion || event.verify().is_err() {
+                continue;
+            }
+
+            for id in event.tags.event_ids() {
+                index.ids.insert((event.pubkey, *id));
+            }
+
+            // A deletion can only target the author's own coordinates.
+            for coordinate in event.tags.coordinates().filter(|c| c.public_key == event.pubkey) {
+                let key = (coordinate.public_key, coordinate.kind, coordinate.identifier.clone());
+                let deleted_until = index.coordinates.entry(key).or_insert(event.created_at);
+                *deleted_until = (*deleted_until).max(event.created_at);
+            }
+        }
+
+        index
+    }
+
+    /// Whether the event was deleted by its author.
+    ///
+    /// Addressable events are deleted by coordinate only up to the deletion's `created_at`,
+    /// so a newer replacement published afterwards stays visible.
+    #[must_use]
+    pub fn is_deleted(&self, event: &Event) -> bool {
+        if self.ids.contains(&(event.pubkey, event.id)) {
+            return true;
+        }
+
+        event.tags.identifier().is_some_and(|identifier| {
+            self.coordinates
+                .get(&(event.pubkey, event.kind, identifier.to_string()))
+                .is_some_and(|deleted_until| event.created_at <= *deleted_until)
+        })
+    }
+}
+
+/// Keep only the newest event per addressable coordinate (author, kind, `d` tag).
+///
+/// Regular events are passed through unchanged. Relays already do this for their own storage,
+/// but results merged from several relays can still contain superseded versions.
+#[must_use]
+pub fn latest_addressable<'a>(events: impl IntoIterator<Item = &'a Event>) -> Vec<&'a Event> {
+    let mut regular = Vec::new();
+    let mut latest: HashMap<(PublicKey, Kind, &'a str), &'a Event> = HashMap::new();
+
+    for event in events {
+        match event.tags.identifier().filter(|_| event.kind.is_addressable()) {
+            Some(identifier) => {
+                let slot = latest.entry((event.pubkey, event.kind, identifier)).or_insert(event);
+                if (event.created_at, event.id) > (slot.created_at, slot.id) {
+                    *slot = event;
+                }
+            }
+            None => regular.push(event),
+        }
+    }
+
+    regular.extend(latest.into_values());
+    regular
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use nostr::Keys;
+
+    fn offer(keys: &Keys, identifier: &str, created_at: u64) -> anyhow::Result<Event> {
+        Ok(EventBuilder::new(OPTION_OFFER_CREATED, "")
+            .tag(Tag::identifier(identifier))
+            .custom_created_at(Timestamp::from_secs(created_at))
+            .sign_with_keys(keys)?)
+    }
+
+    #[test]
+    fn latest_addressable_keeps_newest() -> anyhow::Result<()> {
+        let keys = Keys::generate();
+        let old = offer(&keys, "offer", 100)?;
+        let new = offer(&keys, "offer", 200)?;
+        let other = offer(&keys, "other", 100)?;
+
+        let latest = latest_addressable([&old, &new, &other]);
+
+        assert_eq!(latest.len(), 2);
+        assert!(latest.iter().any(|e| e.id == new.id));
+        assert!(latest.iter().any(|e| e.id == other.id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn deletion_hides_only_authors_events() -> anyhow::Result<()> {
+        let maker = Keys::generate();
+        let stranger = Keys::generate();
+
+        let deleted = offer(&maker, "offer", 100)?;
+        let replacement = offer(&maker, "offer", 300)?;
+        let coordinate = Coordinate::new(OPTION_OFFER_CREATED, maker.public_key()).identifier("offer");
+
+        let deletion = option_offer_deletion(deleted.id, coordinate.clone(), "cancelled")
+            .custom_created_at(Timestamp::from_secs(200))
+            .sign_with_keys(&maker)?;
+        let forged = option_offer_deletion(replacement.id, coordinate, "forged").sign_with_keys(&stranger)?;
+
+        let index = DeletionIndex::from_events([&deletion, &forged]);
+
+        assert!(index.is_deleted(&deleted));
+        assert!(!index.is_deleted(&replacement));
+
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
-use nostr::Filter;
+use nostr::nips::nip01::Coordinate;
+use nostr::{Alphabet, Filter, Kind, SingleLetterTag};
 
-use crate::events::kinds::{ACTION_COMPLETED, OPTION_CREATED, OPTION_OFFER_CREATED};
+use crate::events::kinds::{
+    ACTION_COMPLETED, OPTION_CREATED, OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY, OPTION_RFQ, OPTION_RFQ_QUOTE,
+};
 
 #[must_use]
 pub fn option_created() -> Filter {
@@ -14,12 +17,44 @@ pub fn option_created_by_pubkey(pubkey: nostr::PublicKey) -> Filter {
 
 #[must_use]
 pub fn option_offer_created() -> Filter {
-    Filter::new().kind(OPTION_OFFER_CREATED)
+    Filter::new().kinds([OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY])
 }
 
 #[must_use]
 pub fn option_offer_created_by_pubkey(pubkey: nostr::PublicKey) -> Filter {
-    Filter::new().kind(OPTION_OFFER_CREATED).author(pubkey)
+    Filter::new()
+        .kinds([OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY])
+        .author(pubkey)
+}
+
+/// NIP-09 deletion requests that target option offers (matched by their `k` tag).
+#[must_use]
+pub fn option_offer_deletions() -> Filter {
+    Filter::new().kind(Kind::EventDeletion).custom_tags(
+        SingleLetterTag::lowercase(Alphabet::K),
+        [OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY].map(|kind| kind.as_u16().to_string()),
+    )
+}
+
+#[must_use]
+pub fn option_rfq() -> Filter {
+    Filter::new().kind(OPTION_RFQ)
+}
+
+#[must_use]
+pub fn option_rfq_by_pubkey(pubkey: nostr::PublicKey) -> Filter {
+    Filter::new().kind(OPTION_RFQ).author(pubkey)
+}
+
+#[must_use]
+pub fn option_rfq_quotes_for_event(rfq_event_id: nostr::EventId) -> Filter {
+    Filter::new().kind(OPTION_RFQ_QUOTE).event(rfq_event_id)
+}
+
+/// Quotes answering any RFQ published by `requester`.
+#[must_use]
+pub fn option_rfq_quotes_for_requester(requester: nostr::PublicKey) -> Filter {
+    Filter::new().kind(OPTION_RFQ_QUOTE).pubkey(requester)
 }
 
 #[must_use]
@@ -32,7 +67,31 @@ pub fn action_completed_for_event(original_event_id: nostr::EventId) -> Filter {
     Filter::new().kind(ACTION_COMPLETED).event(original_event_id)
 }
 
+/// Actions referencing an addressable offer by its `a` coordinate, whichever version they followed.
+#[must_use]
+pub fn action_completed_for_offer(coordinate: &Coordinate) -> Filter {
+    Filter::new()
+        .kind(ACTION_COMPLETED)
+        .custom_tag(SingleLetterTag::lowercase(Alphabet::A), coordinate.to_string())
+}
+
+/// NIP-59 gift wraps addressed to `pubkey`, which carry NIP-17 direct messages.
+#[must_use]
+pub fn gift_wraps_for(pubkey: nostr::PublicKey) -> Filter {
+    Filter::new().kind(Kind::GiftWrap).pubkey(pubkey)
+}
+
 #[must_use]
 pub fn all_option_events() -> Filter {
-    Filter::new().kinds([OPTION_CREATED, OPTION_OFFER_CREATED, ACTION_COMPLETED])
+    Filter::new().kinds([
+        OPTION_CREATED,
+        OPTION_OFFER_CREATED,
+        OPTION_OFFER_CREATED_LEGACY,
+        ACTION_COMPLETED,
+    ])
+}
+
+#[must_use]
+pub fn all_option_events_since(since: nostr::Timestamp) -> Filter {
+    all_option_events().since(since)
 }
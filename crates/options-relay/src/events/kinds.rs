@@ -1,8 +1,18 @@
 use nostr::Kind;
 
 pub const OPTION_CREATED: Kind = Kind::Custom(9910);
-pub const OPTION_OFFER_CREATED: Kind = Kind::Custom(9911);
+/// Addressable offer kind; the `d` tag holds the taproot pubkey gen, so republishing an offer replaces it.
+pub const OPTION_OFFER_CREATED: Kind = Kind::Custom(39911);
+/// Regular offer kind used before offers became addressable. Still accepted when parsing.
+pub const OPTION_OFFER_CREATED_LEGACY: Kind = Kind::Custom(9911);
 pub const ACTION_COMPLETED: Kind = Kind::Custom(9912);
+/// Request for quote broadcast by a taker looking for an option offer.
+pub const OPTION_RFQ: Kind = Kind::Custom(9913);
+/// Maker's answer to an RFQ, carrying a ready-to-take option offer.
+pub const OPTION_RFQ_QUOTE: Kind = Kind::Custom(9914);
+
+/// Ephemeral kind used by relay health checks; relays acknowledge it without storing it.
+pub const RELAY_PROBE: Kind = Kind::Custom(29910);
 
 pub const TAG_OPTIONS_ARGS: &str = "options_args";
 pub const TAG_OPTIONS_UTXO: &str = "options_utxo";
@@ -12,6 +22,22 @@ pub const TAG_TAPROOT_GEN: &str = "t";
 pub const TAG_ACTION: &str = "action";
 pub const TAG_OUTPOINT: &str = "outpoint";
 pub const TAG_EXPIRY: &str = "expiry";
+pub const TAG_NEGOTIATION: &str = "negotiation";
+pub const TAG_SCHEMA_VERSION: &str = "version";
+pub const TAG_COLLATERAL_AMOUNT: &str = "collateral_amount";
+pub const TAG_FILLED_AMOUNT: &str = "filled_amount";
+pub const TAG_REMAINING_COLLATERAL: &str = "remaining_collateral";
+pub const TAG_COLLATERAL_ASSET: &str = "collateral_asset";
+pub const TAG_SETTLEMENT_ASSET: &str = "settlement_asset";
+pub const TAG_STRIKE_RANGE: &str = "strike_range";
+pub const TAG_EXPIRY_WINDOW: &str = "expiry_window";
+
+/// Schema version written into every event of the kinds above, bumped whenever a tag's encoding
+/// (e.g. the bincode layout of contract arguments) changes.
+pub const EVENT_SCHEMA_VERSION: u16 = 1;
+
+/// Version of the negotiation direct message payload format.
+pub const NEGOTIATION_PROTOCOL_VERSION: &str = "1";
 
 pub const ACTION_OPTION_CREATED: &str = "option_created";
 pub const ACTION_OPTION_FUNDED: &str = "option_funded";
@@ -1,10 +1,22 @@
 mod action_completed;
+mod deletion;
+mod negotiation;
 pub mod filters;
 pub mod kinds;
+mod offer_depth;
 mod option_created;
 mod option_offer_created;
+mod option_rfq;
+mod option_rfq_quote;
+mod schema;
 
 pub use action_completed::{ActionCompletedEvent, ActionType};
+pub use deletion::{DeletionIndex, latest_addressable, option_offer_deletion};
 pub use kinds::*;
+pub use negotiation::{NegotiationMessage, NegotiationPayload, ReceivedNegotiationMessage};
+pub use offer_depth::OfferDepth;
 pub use option_created::OptionCreatedEvent;
-pub use option_offer_created::OptionOfferCreatedEvent;
+pub use option_offer_created::{OptionOfferCreatedEvent, option_offer_coordinate};
+pub use option_rfq::OptionRfqEvent;
+pub use option_rfq_quote::OptionRfqQuoteEvent;
+pub use schema::{schema_version, schema_version_tag};
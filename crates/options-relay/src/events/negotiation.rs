@@ -0,0 +1,142 @@
+use crate::error::ParseError;
+use crate::events::kinds::{NEGOTIATION_PROTOCOL_VERSION, TAG_NEGOTIATION};
+
+use nostr::{EventId, Kind, PublicKey, Tag, TagKind, Timestamp, UnsignedEvent};
+use serde::{Deserialize, Serialize};
+
+/// Typed payload of a negotiation direct message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NegotiationPayload {
+    /// Taker asks the maker to quote a fill of `collateral_amount`.
+    QuoteRequest { collateral_amount: u64 },
+    /// Either side proposes a price (settlement per collateral) for `collateral_amount`.
+    CounterPrice { collateral_amount: u64, price: u64 },
+    /// Either side accepts the last proposed terms.
+    Accept { collateral_amount: u64, price: u64 },
+    /// A base64-encoded PSET for the counterparty to inspect or sign.
+    PsetAttachment { pset: String },
+}
+
+impl NegotiationPayload {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::QuoteRequest { .. } => "quote_request",
+            Self::CounterPrice { .. } => "counter_price",
+            Self::Accept { .. } => "accept",
+            Self::PsetAttachment { .. } => "pset_attachment",
+        }
+    }
+}
+
+/// A negotiation message about a specific option offer.
+///
+/// Sent as a NIP-17 private direct message: the JSON-encoded message is the rumor content,
+/// sealed and gift-wrapped with NIP-44 encryption for the recipient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegotiationMessage {
+    pub offer_event_id: EventId,
+    #[serde(flatten)]
+    pub payload: NegotiationPayload,
+}
+
+impl NegotiationMessage {
+    #[must_use]
+    pub const fn new(offer_event_id: EventId, payload: NegotiationPayload) -> Self {
+        Self {
+            offer_event_id,
+            payload,
+        }
+    }
+
+    pub fn to_content(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Extra rumor tags marking the message as a negotiation and threading it to the offer.
+    #[must_use]
+    pub fn rumor_tags(&self) -> Vec<Tag> {
+        vec![
+            Tag::custom(TagKind::custom(TAG_NEGOTIATION), [NEGOTIATION_PROTOCOL_VERSION]),
+            Tag::event(self.offer_event_id),
+        ]
+    }
+}
+
+/// A negotiation message received from a counterparty.
+#[derive(Debug, Clone)]
+pub struct ReceivedNegotiationMessage {
+    /// Sender, authenticated by the NIP-59 seal.
+    pub sender: PublicKey,
+    pub created_at: Timestamp,
+    pub message: NegotiationMessage,
+}
+
+impl ReceivedNegotiationMessage {
+    /// Parse an unwrapped rumor. Returns `Ok(None)` for direct messages that are not negotiations.
+    pub fn from_rumor(sender: PublicKey, rumor: &UnsignedEvent) -> Result<Option<Self>, ParseError> {
+        if rumor.kind != Kind::PrivateDirectMessage {
+            return Ok(None);
+        }
+
+        let is_negotiation = rumor
+            .tags
+            .iter()
+            .any(|t| matches!(t.kind(), TagKind::Custom(s) if s.as_ref() == TAG_NEGOTIATION));
+        if !is_negotiation {
+            return Ok(None);
+        }
+
+        // The seal is signed by the sender, so a rumor claiming another author is forged.
+        if rumor.pubkey != sender {
+            return Err(ParseError::InvalidSender);
+        }
+
+        let message: NegotiationMessage = serde_json::from_str(&rumor.content)?;
+
+        Ok(Some(Self {
+            sender,
+            created_at: rumor.created_at,
+            message,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use nostr::{EventBuilder, Keys};
+
+    #[test]
+    fn negotiation_message_rumor_roundtrip() -> anyhow::Result<()> {
+        let sender = Keys::generate();
+        let receiver = Keys::generate();
+
+        let message = NegotiationMessage::new(
+            EventId::all_zeros(),
+            NegotiationPayload::CounterPrice {
+                collateral_amount: 500,
+                price: 42,
+            },
+        );
+
+        let rumor = EventBuilder::private_msg_rumor(receiver.public_key(), message.to_content()?)
+            .tags(message.rumor_tags())
+            .build(sender.public_key());
+
+        let received = ReceivedNegotiationMessage::from_rumor(sender.public_key(), &rumor)?.expect("negotiation");
+        assert_eq!(received.message, message);
+
+        assert!(matches!(
+            ReceivedNegotiationMessage::from_rumor(receiver.public_key(), &rumor),
+            Err(ParseError::InvalidSender)
+        ));
+
+        let plain = EventBuilder::private_msg_rumor(receiver.public_key(), "hello").build(sender.public_key());
+        assert!(ReceivedNegotiationMessage::from_rumor(sender.public_key(), &plain)?.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use crate::events::{ActionCompletedEvent, ActionType};
+use crate::validation::Spend;
+
+use std::collections::HashMap;
+
+use nostr::EventId;
+use nostr::nips::nip01::Coordinate;
+use simplicityhl::elements::OutPoint;
+
+/// Remaining size of an option offer, derived from the actions published against it.
+///
+/// Anyone can publish an action, so only cancellations signed by the offer's author and fills
+/// whose transaction spent the previous offer output in a block are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OfferDepth {
+    /// Collateral deposited when the offer was published, if announced.
+    pub deposited: Option<u64>,
+    /// Total collateral taken by fills.
+    pub filled: u64,
+    /// Collateral still available, if known.
+    pub remaining: Option<u64>,
+    /// Number of fills seen.
+    pub fill_count: usize,
+    /// Offer UTXO created by the latest counted fill, if any.
+    pub latest_outpoint: Option<OutPoint>,
+    pub cancelled: bool,
+}
+
+impl OfferDepth {
+    /// Fold the actions against the offer version `offer_event_id`, or any version at
+    /// `coordinate`, with its collateral at `utxo`. Cancellations count only from the author
+    /// named in the coordinate.
+    ///
+    /// `spends` holds the confirmed spends of the offer outputs known so far, e.g. from
+    /// [`crate::ListingValidator::offer_depth`]. Without them no fill is counted.
+    #[must_use]
+    pub fn from_actions(
+        offer_event_id: EventId,
+        coordinate: &Coordinate,
+        utxo: OutPoint,
+        deposited: Option<u64>,
+        actions: &[ActionCompletedEvent],
+        spends: &HashMap<OutPoint, Spend>,
+    ) -> Self {
+        let mut relevant: Vec<&ActionCompletedEvent> = actions
+            .iter()
+            .filter(|a| a.references_offer(offer_event_id, coordinate))
+            .collect();
+        relevant.sort_by_key(|a| a.created_at);
+
+        let mut depth = Self {
+            deposited,
+            filled: 0,
+            remaining: deposited,
+            fill_count: 0,
+            latest_outpoint: None,
+            cancelled: false,
+        };
+
+        let mut current = utxo;
+        for action in relevant {
+            match action.action {
+                ActionType::OptionOfferExercised => {
+                    let confirmed = spends
+                        .get(&current)
+                        .is_some_and(|spend| spend.confirmed && spend.txid == action.outpoint.txid);
+                    if !confirmed {
+                        continue;
+                    }
+
+                    current = action.outpoint;
+                    depth.fill_count += 1;
+                    depth.filled = depth.filled.saturating_add(action.filled_amount.unwrap_or_default());
+                    depth.latest_outpoint = Some(action.outpoint);
+
+                    // Prefer the announced remaining amount; fall back to deducting the fill.
+                    depth.remaining = match (action.remaining_collateral, action.filled_amount) {
+                        (Some(remaining), _) => Some(remaining),
+                        (None, Some(filled)) => depth.remaining.map(|r| r.saturating_sub(filled)),
+                        (None, None) => None,
+                    };
+                }
+                ActionType::OptionOfferCancelled if action.pubkey == coordinate.public_key => {
+                    depth.cancelled = true;
+                    depth.remaining = Some(0);
+                }
+                _ => {}
+            }
+        }
+
+        depth
+    }
+
+    /// Whether nothing is left to take.
+    #[must_use]
+    pub const fn is_exhausted(&self) -> bool {
+        self.cancelled || matches!(self.remaining, Some(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::events::kinds::OPTION_OFFER_CREATED;
+    use crate::test_chain::outpoint;
+
+    use nostr::hashes::Hash;
+    use nostr::{Keys, PublicKey, Timestamp};
+
+    fn slot(maker: PublicKey) -> Coordinate {
+        Coordinate::new(OPTION_OFFER_CREATED, maker).identifier("offer")
+    }
+
+    fn action(offer: EventId, kind: ActionType, at: u64, by: PublicKey, outpoint: OutPoint) -> ActionCompletedEvent {
+        let mut action = ActionCompletedEvent::new(offer, kind, outpoint);
+        action.pubkey = by;
+        action.created_at = Timestamp::from_secs(at);
+        action
+    }
+
+    fn fill(
+        offer: EventId,
+        at: u64,
+        by: PublicKey,
+        outpoint: OutPoint,
+        filled: Option<u64>,
+        remaining: Option<u64>,
+    ) -> ActionCompletedEvent {
+        let mut action = action(offer, ActionType::OptionOfferExercised, at, by, outpoint);
+        action.filled_amount = filled;
+        action.remaining_collateral = remaining;
+        action
+    }
+
+    fn confirmed(txid_byte: u8) -> Spend {
+        Spend {
+            txid: outpoint(txid_byte).txid,
+            confirmed: true,
+        }
+    }
+
+    #[test]
+    fn depth_tracks_partial_fills() {
+        let offer = EventId::all_zeros();
+        let other = EventId::from_byte_array([1; 32]);
+        let (maker, taker) = (Keys::generate().public_key(), Keys::generate().public_key());
+
+        let actions = [
+            fill(offer, 200, taker, outpoint(3), Some(200), Some(500)),
+            fill(offer, 100, taker, outpoint(2), Some(300), Some(700)),
+            fill(other, 150, taker, outpoint(9), Some(1000), Some(0)),
+        ];
+        let spends = HashMap::from([(outpoint(1), confirmed(2)), (outpoint(2), confirmed(3))]);
+
+        let depth = OfferDepth::from_actions(offer, &slot(maker), outpoint(1), Some(1000), &actions, &spends);
+
+        assert_eq!(depth.filled, 500);
+        assert_eq!(depth.remaining, Some(500));
+        assert_eq!(depth.fill_count, 2);
+        assert_eq!(depth.latest_outpoint, Some(outpoint(3)));
+        assert!(!depth.is_exhausted());
+    }
+
+    #[test]
+    fn depth_falls_back_to_deducting_fills() {
+        let offer = EventId::all_zeros();
+        let (maker, taker) = (Keys::generate().public_key(), Keys::generate().public_key());
+        let spends = HashMap::from([(outpoint(1), confirmed(2))]);
+
+        let actions = [fill(offer, 100, taker, outpoint(2), Some(1000), None)];
+        let depth = OfferDepth::from_actions(offer, &slot(maker), outpoint(1), Some(1000), &actions, &spends);
+        assert_eq!(depth.remaining, Some(0));
+        assert!(depth.is_exhausted());
+
+        let actions = [fill(offer, 100, taker, outpoint(2), None, None)];
+        let unknown = OfferDepth::from_actions(offer, &slot(maker), outpoint(1), None, &actions, &spends);
+        assert_eq!(unknown.remaining, None);
+        assert!(!unknown.is_exhausted());
+    }
+
+    #[test]
+    fn depth_ignores_forged_actions() {
+        let offer = EventId::all_zeros();
+        let maker = Keys::generate().public_key();
+        let stranger = Keys::generate().public_key();
+
+        // The offer output was spent by a real fill of 100, confirmed on chain.
+        let spends = HashMap::from([(outpoint(1), confirmed(2))]);
+        let actions = [
+            action(offer, ActionType::OptionOfferCancelled, 50, stranger, outpoint(7)),
+            // Claims to drain the offer with a transaction that never spent it.
+            fill(offer, 60, stranger, outpoint(8), Some(1000), Some(0)),
+            fill(offer, 100, stranger, outpoint(2), Some(100), Some(900)),
+            // Follows the real fill, but its transaction is not on chain.
+            fill(offer, 200, stranger, outpoint(9), Some(900), Some(0)),
+        ];
+
+        let depth = OfferDepth::from_actions(offer, &slot(maker), outpoint(1), Some(1000), &actions, &spends);
+        assert!(!depth.cancelled);
+        assert_eq!(depth.fill_count, 1);
+        assert_eq!(depth.remaining, Some(900));
+        assert_eq!(depth.latest_outpoint, Some(outpoint(2)));
+        assert!(!depth.is_exhausted());
+
+        let unconfirmed = HashMap::from([(
+            outpoint(1),
+            Spend {
+                txid: outpoint(2).txid,
+                confirmed: false,
+            },
+        )]);
+        let pending = OfferDepth::from_actions(offer, &slot(maker), outpoint(1), Some(1000), &actions, &unconfirmed);
+        assert_eq!(pending.fill_count, 0);
+        assert_eq!(pending.remaining, Some(1000));
+
+        let cancel = action(offer, ActionType::OptionOfferCancelled, 300, maker, outpoint(1));
+        let depth = OfferDepth::from_actions(offer, &slot(maker), outpoint(1), Some(1000), &[cancel], &HashMap::new());
+        assert!(depth.cancelled);
+        assert!(depth.is_exhausted());
+    }
+
+    #[test]
+    fn depth_follows_republished_versions() {
+        let (previous, current) = (EventId::all_zeros(), EventId::from_byte_array([1; 32]));
+        let (maker, taker) = (Keys::generate().public_key(), Keys::generate().public_key());
+        let spends = HashMap::from([(outpoint(2), confirmed(3))]);
+
+        // The fill names the version the taker saw; the coordinate ties it to the current one.
+        let taken = fill(previous, 100, taker, outpoint(3), Some(200), Some(300));
+        let followed = taken.clone().with_offer_coordinate(slot(maker));
+        let other_maker = taken.with_offer_coordinate(slot(taker));
+        let depth_with =
+            |action| OfferDepth::from_actions(current, &slot(maker), outpoint(2), Some(500), &[action], &spends);
+
+        let depth = depth_with(followed);
+        assert_eq!(depth.fill_count, 1);
+        assert_eq!(depth.remaining, Some(300));
+
+        assert_eq!(depth_with(other_maker).fill_count, 0);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::error::{ParseError, RelayError};
 use crate::events::kinds::{OPTION_CREATED, TAG_EXPIRY, TAG_OPTIONS_ARGS, TAG_OPTIONS_UTXO, TAG_TAPROOT_GEN};
+use crate::events::schema::{schema_version, schema_version_tag};
 
 use contracts::options::{OptionsArguments, get_options_address};
 use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
@@ -34,6 +35,7 @@ impl OptionCreatedEvent {
         let args_hex = self.options_args.to_hex()?;
 
         Ok(EventBuilder::new(OPTION_CREATED, "")
+            .tag(schema_version_tag())
             .tag(Tag::public_key(creator_pubkey))
             .tag(Tag::custom(TagKind::custom(TAG_OPTIONS_ARGS), [args_hex]))
             .tag(Tag::custom(TagKind::custom(TAG_OPTIONS_UTXO), [self.utxo.to_string()]))
@@ -54,6 +56,14 @@ impl OptionCreatedEvent {
             return Err(ParseError::InvalidKind);
         }
 
+        match schema_version(event)? {
+            1 => Self::from_event_v1(event, network),
+            version => Err(ParseError::UnsupportedVersion(version.to_string())),
+        }
+    }
+
+    /// Version 1: `options_args` holds the bincode-encoded `OptionsArguments` as hex.
+    fn from_event_v1(event: &Event, network: SimplicityNetwork) -> Result<Self, ParseError> {
         let args_hex = event
             .tags
             .iter()
@@ -97,6 +107,8 @@ impl OptionCreatedEvent {
 mod tests {
     use super::*;
 
+    use crate::events::kinds::TAG_SCHEMA_VERSION;
+
     use nostr::{Keys, hashes::Hash};
 
     use contracts::sdk::taproot_pubkey_gen::get_random_seed;
@@ -146,4 +158,21 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn option_created_event_rejects_unknown_version() -> anyhow::Result<()> {
+        let keys = Keys::generate();
+
+        let future_event = EventBuilder::new(OPTION_CREATED, "")
+            .tag(Tag::custom(TagKind::custom(TAG_SCHEMA_VERSION), ["2"]))
+            .tag(Tag::custom(TagKind::custom(TAG_OPTIONS_ARGS), ["00"]))
+            .sign_with_keys(&keys)?;
+
+        assert!(matches!(
+            OptionCreatedEvent::from_event(&future_event, SimplicityNetwork::LiquidTestnet),
+            Err(ParseError::UnsupportedVersion(v)) if v == "2"
+        ));
+
+        Ok(())
+    }
 }
@@ -1,14 +1,29 @@
 use crate::error::{ParseError, RelayError};
+use crate::events::action_completed::optional_amount_tag;
 use crate::events::kinds::{
-    OPTION_OFFER_CREATED, TAG_EXPIRY, TAG_OPTION_OFFER_ARGS, TAG_OPTION_OFFER_UTXO, TAG_TAPROOT_GEN,
+    OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY, TAG_COLLATERAL_AMOUNT, TAG_EXPIRY, TAG_OPTION_OFFER_ARGS,
+    TAG_OPTION_OFFER_UTXO, TAG_TAPROOT_GEN,
 };
+use crate::events::schema::{schema_version, schema_version_tag};
+use crate::events::{ActionCompletedEvent, OfferDepth};
+use crate::validation::Spend;
+
+use std::collections::HashMap;
 
 use contracts::option_offer::{OptionOfferArguments, get_option_offer_address};
 use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
+use nostr::nips::nip01::Coordinate;
 use nostr::{Event, EventBuilder, EventId, PublicKey, Tag, TagKind, Timestamp};
 use simplicityhl::elements::OutPoint;
 use simplicityhl_core::{Encodable, SimplicityNetwork};
 
+/// Address of `maker`'s offer for the contract at `taproot_pubkey_gen`, shared by every version
+/// of the offer.
+#[must_use]
+pub fn option_offer_coordinate(maker: PublicKey, taproot_pubkey_gen: &TaprootPubkeyGen) -> Coordinate {
+    Coordinate::new(OPTION_OFFER_CREATED, maker).identifier(taproot_pubkey_gen.to_string())
+}
+
 #[derive(Debug, Clone)]
 pub struct OptionOfferCreatedEvent {
     pub event_id: EventId,
@@ -17,6 +32,8 @@ pub struct OptionOfferCreatedEvent {
     pub option_offer_args: OptionOfferArguments,
     pub utxo: OutPoint,
     pub taproot_pubkey_gen: TaprootPubkeyGen,
+    /// Collateral locked in the offer when it was (re)published.
+    pub collateral_amount: Option<u64>,
 }
 
 impl OptionOfferCreatedEvent {
@@ -29,36 +46,86 @@ impl OptionOfferCreatedEvent {
             option_offer_args,
             utxo,
             taproot_pubkey_gen,
+            collateral_amount: None,
         }
     }
 
-    pub fn to_event_builder(&self, creator_pubkey: PublicKey) -> Result<EventBuilder, RelayError> {
-        let args_hex = self.option_offer_args.to_hex()?;
+    #[must_use]
+    pub const fn with_collateral_amount(mut self, collateral_amount: u64) -> Self {
+        self.collateral_amount = Some(collateral_amount);
+        self
+    }
 
+    /// Compute the offer's remaining size from the actions published against it, counting
+    /// only fills backed by the confirmed `spends` of its outputs.
+    #[must_use]
+    pub fn depth(&self, actions: &[ActionCompletedEvent], spends: &HashMap<OutPoint, Spend>) -> OfferDepth {
+        OfferDepth::from_actions(
+            self.event_id,
+            &self.coordinate(self.pubkey),
+            self.utxo,
+            self.collateral_amount,
+            actions,
+            spends,
+        )
+    }
+
+    /// Address of the offer: one replaceable slot per maker and taproot pubkey gen.
+    #[must_use]
+    pub fn coordinate(&self, creator_pubkey: PublicKey) -> Coordinate {
+        option_offer_coordinate(creator_pubkey, &self.taproot_pubkey_gen)
+    }
+
+    pub fn to_event_builder(&self, creator_pubkey: PublicKey) -> Result<EventBuilder, RelayError> {
         Ok(EventBuilder::new(OPTION_OFFER_CREATED, "")
+            .tag(schema_version_tag())
+            .tag(Tag::identifier(self.taproot_pubkey_gen.to_string()))
             .tag(Tag::public_key(creator_pubkey))
-            .tag(Tag::custom(TagKind::custom(TAG_OPTION_OFFER_ARGS), [args_hex]))
-            .tag(Tag::custom(
-                TagKind::custom(TAG_OPTION_OFFER_UTXO),
-                [self.utxo.to_string()],
-            ))
-            .tag(Tag::custom(
-                TagKind::custom(TAG_TAPROOT_GEN),
-                [self.taproot_pubkey_gen.to_string()],
-            ))
-            .tag(Tag::custom(
+            .tags(self.contract_tags()?))
+    }
+
+    /// Tags describing the offer contract, shared with RFQ quotes that embed a ready-to-take offer.
+    pub(crate) fn contract_tags(&self) -> Result<Vec<Tag>, RelayError> {
+        let args_hex = self.option_offer_args.to_hex()?;
+
+        let mut tags = vec![
+            Tag::custom(TagKind::custom(TAG_OPTION_OFFER_ARGS), [args_hex]),
+            Tag::custom(TagKind::custom(TAG_OPTION_OFFER_UTXO), [self.utxo.to_string()]),
+            Tag::custom(TagKind::custom(TAG_TAPROOT_GEN), [self.taproot_pubkey_gen.to_string()]),
+            Tag::custom(
                 TagKind::custom(TAG_EXPIRY),
                 [self.option_offer_args.expiry_time().to_string()],
-            )))
+            ),
+        ];
+
+        if let Some(amount) = self.collateral_amount {
+            tags.push(Tag::custom(TagKind::custom(TAG_COLLATERAL_AMOUNT), [amount.to_string()]));
+        }
+
+        Ok(tags)
     }
 
     pub fn from_event(event: &Event, network: SimplicityNetwork) -> Result<Self, ParseError> {
         event.verify()?;
 
-        if event.kind != OPTION_OFFER_CREATED {
+        if event.kind != OPTION_OFFER_CREATED && event.kind != OPTION_OFFER_CREATED_LEGACY {
             return Err(ParseError::InvalidKind);
         }
 
+        Self::from_contract_tags(event, network)
+    }
+
+    /// Parse the offer contract from an event's tags without checking its kind or signature,
+    /// dispatching on the event's schema version.
+    pub(crate) fn from_contract_tags(event: &Event, network: SimplicityNetwork) -> Result<Self, ParseError> {
+        match schema_version(event)? {
+            1 => Self::from_contract_tags_v1(event, network),
+            version => Err(ParseError::UnsupportedVersion(version.to_string())),
+        }
+    }
+
+    /// Version 1: `option_offer_args` holds the bincode-encoded `OptionOfferArguments` as hex.
+    fn from_contract_tags_v1(event: &Event, network: SimplicityNetwork) -> Result<Self, ParseError> {
         let args_hex = event
             .tags
             .iter()
@@ -94,6 +161,7 @@ impl OptionOfferCreatedEvent {
             option_offer_args,
             utxo,
             taproot_pubkey_gen,
+            collateral_amount: optional_amount_tag(event, TAG_COLLATERAL_AMOUNT)?,
         })
     }
 }
@@ -133,7 +201,8 @@ mod tests {
         let (args, taproot_pubkey_gen) = get_mocked_data()?;
         let utxo = OutPoint::new(Txid::all_zeros(), 0);
 
-        let event = OptionOfferCreatedEvent::new(args.clone(), utxo, taproot_pubkey_gen.clone());
+        let event =
+            OptionOfferCreatedEvent::new(args.clone(), utxo, taproot_pubkey_gen.clone()).with_collateral_amount(1000);
 
         let builder = event.to_event_builder(keys.public_key())?;
         let built_event = builder.sign_with_keys(&keys)?;
@@ -143,6 +212,10 @@ mod tests {
         assert_eq!(parsed.option_offer_args, args);
         assert_eq!(parsed.utxo, utxo);
         assert_eq!(parsed.taproot_pubkey_gen.to_string(), taproot_pubkey_gen.to_string());
+        assert_eq!(parsed.collateral_amount, Some(1000));
+
+        assert!(built_event.kind.is_addressable());
+        assert_eq!(built_event.tags.identifier(), Some(taproot_pubkey_gen.to_string().as_str()));
 
         Ok(())
     }
@@ -0,0 +1,220 @@
+use crate::error::ParseError;
+use crate::events::OptionOfferCreatedEvent;
+use crate::events::kinds::{
+    OPTION_RFQ, TAG_COLLATERAL_AMOUNT, TAG_COLLATERAL_ASSET, TAG_EXPIRY, TAG_EXPIRY_WINDOW, TAG_SETTLEMENT_ASSET,
+    TAG_STRIKE_RANGE,
+};
+use crate::events::schema::{schema_version, schema_version_tag};
+
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use nostr::{Event, EventBuilder, EventId, PublicKey, Tag, TagKind, Timestamp};
+use simplicityhl::elements::AssetId;
+
+/// A taker's request for quote: the option offer they would like makers to publish.
+///
+/// Strikes are expressed like `collateral_per_contract` on offers (settlement per collateral),
+/// and the expiry window bounds the offer's expiry time.
+#[derive(Debug, Clone)]
+pub struct OptionRfqEvent {
+    pub event_id: EventId,
+    pub pubkey: PublicKey,
+    pub created_at: Timestamp,
+    pub collateral_asset: AssetId,
+    pub settlement_asset: AssetId,
+    /// Collateral the taker wants to take.
+    pub collateral_amount: u64,
+    pub strike_range: RangeInclusive<u64>,
+    pub expiry_window: RangeInclusive<u32>,
+}
+
+impl OptionRfqEvent {
+    #[must_use]
+    pub fn new(
+        collateral_asset: AssetId,
+        settlement_asset: AssetId,
+        collateral_amount: u64,
+        strike_range: RangeInclusive<u64>,
+        expiry_window: RangeInclusive<u32>,
+    ) -> Self {
+        Self {
+            event_id: EventId::all_zeros(),
+            pubkey: PublicKey::from_slice(&[1; 32]).unwrap(),
+            created_at: Timestamp::now(),
+            collateral_asset,
+            settlement_asset,
+            collateral_amount,
+            strike_range,
+            expiry_window,
+        }
+    }
+
+    /// Whether `offer` satisfies this request: same assets, strike and expiry within range,
+    /// and enough collateral if the offer announces its size.
+    #[must_use]
+    pub fn matches_offer(&self, offer: &OptionOfferCreatedEvent) -> bool {
+        let args = &offer.option_offer_args;
+
+        args.get_collateral_asset_id() == self.collateral_asset
+            && args.get_settlement_asset_id() == self.settlement_asset
+            && self.strike_range.contains(&args.collateral_per_contract())
+            && self.expiry_window.contains(&args.expiry_time())
+            && offer.collateral_amount.is_none_or(|amount| amount >= self.collateral_amount)
+    }
+
+    #[must_use]
+    pub fn to_event_builder(&self, creator_pubkey: PublicKey) -> EventBuilder {
+        EventBuilder::new(OPTION_RFQ, "")
+            .tag(schema_version_tag())
+            .tag(Tag::public_key(creator_pubkey))
+            .tag(Tag::custom(
+                TagKind::custom(TAG_COLLATERAL_ASSET),
+                [self.collateral_asset.to_string()],
+            ))
+            .tag(Tag::custom(
+                TagKind::custom(TAG_SETTLEMENT_ASSET),
+                [self.settlement_asset.to_string()],
+            ))
+            .tag(Tag::custom(
+                TagKind::custom(TAG_COLLATERAL_AMOUNT),
+                [self.collateral_amount.to_string()],
+            ))
+            .tag(Tag::custom(
+                TagKind::custom(TAG_STRIKE_RANGE),
+                [self.strike_range.start().to_string(), self.strike_range.end().to_string()],
+            ))
+            .tag(Tag::custom(
+                TagKind::custom(TAG_EXPIRY_WINDOW),
+                [self.expiry_window.start().to_string(), self.expiry_window.end().to_string()],
+            ))
+            // The request is pointless once no acceptable expiry is left.
+            .tag(Tag::custom(
+                TagKind::custom(TAG_EXPIRY),
+                [self.expiry_window.end().to_string()],
+            ))
+    }
+
+    pub fn from_event(event: &Event) -> Result<Self, ParseError> {
+        event.verify()?;
+
+        if event.kind != OPTION_RFQ {
+            return Err(ParseError::InvalidKind);
+        }
+
+        match schema_version(event)? {
+            1 => Self::from_event_v1(event),
+            version => Err(ParseError::UnsupportedVersion(version.to_string())),
+        }
+    }
+
+    /// Version 1: assets as hex ids, each range as one tag holding its minimum and maximum.
+    fn from_event_v1(event: &Event) -> Result<Self, ParseError> {
+        let collateral_amount = find_tag(event, TAG_COLLATERAL_AMOUNT)
+            .and_then(|t| t.content())
+            .ok_or(ParseError::MissingTag(TAG_COLLATERAL_AMOUNT))?
+            .parse::<u64>()
+            .map_err(|_| ParseError::InvalidAmount(TAG_COLLATERAL_AMOUNT))?;
+
+        Ok(Self {
+            event_id: event.id,
+            pubkey: event.pubkey,
+            created_at: event.created_at,
+            collateral_asset: asset_tag(event, TAG_COLLATERAL_ASSET)?,
+            settlement_asset: asset_tag(event, TAG_SETTLEMENT_ASSET)?,
+            collateral_amount,
+            strike_range: range_tag(event, TAG_STRIKE_RANGE)?,
+            expiry_window: range_tag(event, TAG_EXPIRY_WINDOW)?,
+        })
+    }
+}
+
+fn find_tag<'a>(event: &'a Event, tag: &str) -> Option<&'a Tag> {
+    event
+        .tags
+        .iter()
+        .find(|t| matches!(t.kind(), TagKind::Custom(s) if s.as_ref() == tag))
+}
+
+fn asset_tag(event: &Event, tag: &'static str) -> Result<AssetId, ParseError> {
+    let value = find_tag(event, tag)
+        .and_then(|t| t.content())
+        .ok_or(ParseError::MissingTag(tag))?;
+
+    AssetId::from_str(value).map_err(|_| ParseError::InvalidAsset(tag))
+}
+
+/// Parse a `[name, min, max]` tag into an inclusive range.
+fn range_tag<T: FromStr + PartialOrd>(event: &Event, tag: &'static str) -> Result<RangeInclusive<T>, ParseError> {
+    let values = find_tag(event, tag)
+        .map(Tag::as_slice)
+        .ok_or(ParseError::MissingTag(tag))?;
+
+    let [_, min, max, ..] = values else {
+        return Err(ParseError::InvalidRange(tag));
+    };
+
+    let min: T = min.parse().map_err(|_| ParseError::InvalidRange(tag))?;
+    let max: T = max.parse().map_err(|_| ParseError::InvalidRange(tag))?;
+
+    if min > max {
+        return Err(ParseError::InvalidRange(tag));
+    }
+
+    Ok(min..=max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use contracts::option_offer::{OptionOfferArguments, get_option_offer_address};
+    use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
+    use nostr::{Keys, hashes::Hash};
+    use simplicityhl::elements::{OutPoint, Txid};
+    use simplicityhl_core::{LIQUID_TESTNET_BITCOIN_ASSET, LIQUID_TESTNET_TEST_ASSET_ID_STR, SimplicityNetwork};
+
+    #[test]
+    fn option_rfq_event_roundtrip_and_matching() -> anyhow::Result<()> {
+        let keys = Keys::generate();
+        let settlement_asset_id = AssetId::from_slice(&hex::decode(LIQUID_TESTNET_TEST_ASSET_ID_STR)?)?;
+
+        let rfq = OptionRfqEvent::new(
+            *LIQUID_TESTNET_BITCOIN_ASSET,
+            settlement_asset_id,
+            500,
+            900..=1100,
+            1_700_000_000..=1_800_000_000,
+        );
+
+        let built_event = rfq.to_event_builder(keys.public_key()).sign_with_keys(&keys)?;
+        let parsed = OptionRfqEvent::from_event(&built_event)?;
+
+        assert_eq!(parsed.collateral_asset, *LIQUID_TESTNET_BITCOIN_ASSET);
+        assert_eq!(parsed.settlement_asset, settlement_asset_id);
+        assert_eq!(parsed.collateral_amount, 500);
+        assert_eq!(parsed.strike_range, 900..=1100);
+        assert_eq!(parsed.expiry_window, 1_700_000_000..=1_800_000_000);
+
+        let offer_with = |strike: u64, amount: u64| -> anyhow::Result<OptionOfferCreatedEvent> {
+            let args = OptionOfferArguments::new(
+                *LIQUID_TESTNET_BITCOIN_ASSET,
+                settlement_asset_id,
+                settlement_asset_id,
+                strike,
+                50,
+                1_750_000_000,
+                [1; 32],
+            );
+            let tpg = TaprootPubkeyGen::from(&args, SimplicityNetwork::LiquidTestnet, &get_option_offer_address)?;
+            Ok(OptionOfferCreatedEvent::new(args, OutPoint::new(Txid::all_zeros(), 0), tpg)
+                .with_collateral_amount(amount))
+        };
+
+        assert!(parsed.matches_offer(&offer_with(1000, 500)?));
+        assert!(!parsed.matches_offer(&offer_with(1200, 500)?));
+        assert!(!parsed.matches_offer(&offer_with(1000, 100)?));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use crate::error::{ParseError, RelayError};
+use crate::events::kinds::OPTION_RFQ_QUOTE;
+use crate::events::schema::schema_version_tag;
+use crate::events::{OptionOfferCreatedEvent, OptionRfqEvent};
+
+use nostr::{Event, EventBuilder, EventId, PublicKey, Tag, TagKind, Timestamp};
+use simplicityhl_core::SimplicityNetwork;
+
+/// A maker's answer to an RFQ, carrying a ready-to-take option offer.
+///
+/// The embedded offer's `event_id` and `pubkey` are those of the quote event, so fills can be
+/// reported against the quote like against any other offer.
+#[derive(Debug, Clone)]
+pub struct OptionRfqQuoteEvent {
+    pub event_id: EventId,
+    pub pubkey: PublicKey,
+    pub created_at: Timestamp,
+    pub rfq_event_id: EventId,
+    /// Author of the RFQ, tagged so they can find quotes addressed to them.
+    pub requester: PublicKey,
+    pub offer: OptionOfferCreatedEvent,
+}
+
+impl OptionRfqQuoteEvent {
+    #[must_use]
+    pub fn new(rfq: &OptionRfqEvent, offer: OptionOfferCreatedEvent) -> Self {
+        Self {
+            event_id: EventId::all_zeros(),
+            pubkey: PublicKey::from_slice(&[1; 32]).unwrap(),
+            created_at: Timestamp::now(),
+            rfq_event_id: rfq.event_id,
+            requester: rfq.pubkey,
+            offer,
+        }
+    }
+
+    pub fn to_event_builder(&self) -> Result<EventBuilder, RelayError> {
+        Ok(EventBuilder::new(OPTION_RFQ_QUOTE, "")
+            .tag(schema_version_tag())
+            .tag(Tag::event(self.rfq_event_id))
+            .tag(Tag::public_key(self.requester))
+            .tags(self.offer.contract_tags()?))
+    }
+
+    pub fn from_event(event: &Event, network: SimplicityNetwork) -> Result<Self, ParseError> {
+        event.verify()?;
+
+        if event.kind != OPTION_RFQ_QUOTE {
+            return Err(ParseError::InvalidKind);
+        }
+
+        let rfq_event_id = event
+            .tags
+            .iter()
+            .find(|t| t.kind() == TagKind::e())
+            .and_then(|t| t.content())
+            .and_then(|s| EventId::from_hex(s).ok())
+            .ok_or(ParseError::MissingTag("e"))?;
+
+        let requester = event
+            .tags
+            .iter()
+            .find(|t| t.kind() == TagKind::p())
+            .and_then(|t| t.content())
+            .and_then(|s| PublicKey::from_hex(s).ok())
+            .ok_or(ParseError::MissingTag("p"))?;
+
+        Ok(Self {
+            event_id: event.id,
+            pubkey: event.pubkey,
+            created_at: event.created_at,
+            rfq_event_id,
+            requester,
+            offer: OptionOfferCreatedEvent::from_contract_tags(event, network)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use contracts::option_offer::{OptionOfferArguments, get_option_offer_address};
+    use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
+    use nostr::{Keys, hashes::Hash};
+    use simplicityhl::elements::{AssetId, OutPoint, Txid};
+    use simplicityhl_core::{LIQUID_TESTNET_BITCOIN_ASSET, LIQUID_TESTNET_TEST_ASSET_ID_STR};
+
+    #[test]
+    fn option_rfq_quote_event_roundtrip() -> anyhow::Result<()> {
+        let taker = Keys::generate();
+        let maker = Keys::generate();
+        let settlement_asset_id = AssetId::from_slice(&hex::decode(LIQUID_TESTNET_TEST_ASSET_ID_STR)?)?;
+
+        let rfq_event = OptionRfqEvent::new(
+            *LIQUID_TESTNET_BITCOIN_ASSET,
+            settlement_asset_id,
+            500,
+            900..=1100,
+            1_700_000_000..=1_800_000_000,
+        )
+        .to_event_builder(taker.public_key())
+        .sign_with_keys(&taker)?;
+        let rfq = OptionRfqEvent::from_event(&rfq_event)?;
+
+        let args = OptionOfferArguments::new(
+            *LIQUID_TESTNET_BITCOIN_ASSET,
+            settlement_asset_id,
+            settlement_asset_id,
+            1000,
+            50,
+            1_750_000_000,
+            [1; 32],
+        );
+        let tpg = TaprootPubkeyGen::from(&args, SimplicityNetwork::LiquidTestnet, &get_option_offer_address)?;
+        let offer = OptionOfferCreatedEvent::new(args.clone(), OutPoint::new(Txid::all_zeros(), 0), tpg)
+            .with_collateral_amount(500);
+
+        let built_event = OptionRfqQuoteEvent::new(&rfq, offer)
+            .to_event_builder()?
+            .sign_with_keys(&maker)?;
+        let parsed = OptionRfqQuoteEvent::from_event(&built_event, SimplicityNetwork::LiquidTestnet)?;
+
+        assert_eq!(parsed.rfq_event_id, rfq.event_id);
+        assert_eq!(parsed.requester, taker.public_key());
+        assert_eq!(parsed.offer.option_offer_args, args);
+        assert_eq!(parsed.offer.event_id, built_event.id);
+        assert_eq!(parsed.offer.pubkey, maker.public_key());
+        assert!(rfq.matches_offer(&parsed.offer));
+
+        Ok(())
+    }
+}
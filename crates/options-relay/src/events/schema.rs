@@ -0,0 +1,61 @@
+use crate::error::ParseError;
+use crate::events::kinds::{EVENT_SCHEMA_VERSION, TAG_SCHEMA_VERSION};
+
+use nostr::{Event, Tag, TagKind};
+
+/// Version assumed for events published before the version tag existed; they use the v1 layout.
+const UNVERSIONED_SCHEMA_VERSION: u16 = 1;
+
+/// Tag marking an event with the schema version this client writes.
+#[must_use]
+pub fn schema_version_tag() -> Tag {
+    Tag::custom(
+        TagKind::custom(TAG_SCHEMA_VERSION),
+        [EVENT_SCHEMA_VERSION.to_string()],
+    )
+}
+
+/// Read an event's schema version, so parsers can dispatch on it.
+///
+/// Events without a version tag predate versioning and are treated as version 1.
+pub fn schema_version(event: &Event) -> Result<u16, ParseError> {
+    let Some(value) = event
+        .tags
+        .iter()
+        .find(|t| matches!(t.kind(), TagKind::Custom(s) if s.as_ref() == TAG_SCHEMA_VERSION))
+        .and_then(|t| t.content())
+    else {
+        return Ok(UNVERSIONED_SCHEMA_VERSION);
+    };
+
+    value
+        .parse()
+        .map_err(|_| ParseError::UnsupportedVersion(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use nostr::{EventBuilder, Keys, Kind};
+
+    #[test]
+    fn schema_version_defaults_and_rejects_garbage() -> anyhow::Result<()> {
+        let keys = Keys::generate();
+        let event_with = |tags: Vec<Tag>| EventBuilder::new(Kind::Custom(9910), "").tags(tags).sign_with_keys(&keys);
+
+        assert_eq!(schema_version(&event_with(vec![])?)?, UNVERSIONED_SCHEMA_VERSION);
+        assert_eq!(schema_version(&event_with(vec![schema_version_tag()])?)?, EVENT_SCHEMA_VERSION);
+
+        let future = Tag::custom(TagKind::custom(TAG_SCHEMA_VERSION), ["7"]);
+        assert_eq!(schema_version(&event_with(vec![future])?)?, 7);
+
+        let garbage = Tag::custom(TagKind::custom(TAG_SCHEMA_VERSION), ["v2"]);
+        assert!(matches!(
+            schema_version(&event_with(vec![garbage])?),
+            Err(ParseError::UnsupportedVersion(v)) if v == "v2"
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use crate::client::RelayRole;
+use crate::config::NostrRelayConfig;
+use crate::error::RelayError;
+use crate::events::filters;
+use crate::events::kinds::RELAY_PROBE;
+
+use std::time::{Duration, Instant};
+
+use nostr::nips::nip11::RelayInformationDocument;
+use nostr::prelude::*;
+use nostr_sdk::Client;
+use tracing::instrument;
+
+/// Connectivity and storage diagnostics for a single configured relay.
+#[derive(Debug, Clone)]
+pub struct RelayHealth {
+    pub url: String,
+    pub role: RelayRole,
+    /// Time to open the websocket, `None` if the connection failed.
+    pub connect_latency: Option<Duration>,
+    /// Round-trip of a REQ until EOSE, `None` if the fetch failed.
+    pub fetch_latency: Option<Duration>,
+    /// Whether the relay acknowledged an ephemeral probe event, `None` if not tested.
+    pub publish_ack: Option<bool>,
+    /// Number of option events authored by the checked pubkey that the relay holds.
+    pub stored_events: Option<usize>,
+    /// First error encountered while probing this relay.
+    pub error: Option<String>,
+}
+
+impl RelayHealth {
+    fn new(url: &str, role: RelayRole) -> Self {
+        Self {
+            url: url.to_string(),
+            role,
+            connect_latency: None,
+            fetch_latency: None,
+            publish_ack: None,
+            stored_events: None,
+            error: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_reachable(&self) -> bool {
+        self.connect_latency.is_some()
+    }
+}
+
+/// Probe every configured relay independently.
+///
+/// Each relay gets its own short-lived client so latencies are not mixed between relays.
+/// Stored events are counted for the author of `keys`. When `publish_probe` is set, an
+/// ephemeral probe event signed with `keys` is published to measure the publish ack.
+#[instrument(skip_all, level = "debug")]
+pub async fn check_relays(config: &NostrRelayConfig, keys: &Keys, publish_probe: bool) -> Vec<RelayHealth> {
+    let mut results = Vec::with_capacity(config.all_relays().len());
+
+    for (idx, url) in config.all_relays().into_iter().enumerate() {
+        let role = if idx == 0 { RelayRole::Primary } else { RelayRole::Backup };
+        let mut health = RelayHealth::new(url, role);
+
+        if let Err(err) = probe_relay(&mut health, config.timeout(), keys, publish_probe).await {
+            tracing::debug!(relay = %url, error = %err, "Relay probe failed");
+            health.error.get_or_insert_with(|| err.to_string());
+        }
+
+        results.push(health);
+    }
+
+    results
+}
+
+async fn probe_relay(
+    health: &mut RelayHealth,
+    timeout: Duration,
+    keys: &Keys,
+    publish_probe: bool,
+) -> Result<(), RelayError> {
+    let url = health.url.clone();
+    let client = Client::default();
+
+    client.add_relay(Url::parse(&url)?).await?;
+
+    let started = Instant::now();
+    client.try_connect_relay(url.as_str(), timeout).await?;
+    health.connect_latency = Some(started.elapsed());
+
+    let filter = filters::all_option_events().author(keys.public_key());
+
+    let started = Instant::now();
+    match client.fetch_events_from([url.as_str()], filter, timeout).await {
+        Ok(events) => {
+            health.fetch_latency = Some(started.elapsed());
+            health.stored_events = Some(events.len());
+        }
+        Err(err) => {
+            health.error = Some(err.to_string());
+        }
+    }
+
+    if publish_probe {
+        let probe = EventBuilder::new(RELAY_PROBE, "").sign_with_keys(keys)?;
+
+        match client.send_event_to([url.as_str()], &probe).await {
+            Ok(output) => {
+                health.publish_ack = Some(!output.success.is_empty());
+                if let Some(err) = output.failed.values().next() {
+                    health.error.get_or_insert_with(|| err.clone());
+                }
+            }
+            Err(err) => {
+                health.publish_ack = Some(false);
+                health.error.get_or_insert_with(|| err.to_string());
+            }
+        }
+    }
+
+    client.disconnect().await;
+
+    Ok(())
+}
+
+/// Fetch the NIP-11 relay information document over HTTP(S).
+///
+/// This call is blocking; run it off the async runtime (e.g. with `spawn_blocking`).
+pub fn fetch_relay_information(url: &str, timeout: Duration) -> Result<RelayInformationDocument, RelayError> {
+    let http_url = relay_http_url(url);
+
+    let response = minreq::get(&http_url)
+        .with_header("Accept", "application/nostr+json")
+        .with_timeout(timeout.as_secs())
+        .send()?;
+
+    if response.status_code != 200 {
+        return Err(RelayError::RelayInformationStatus(response.status_code));
+    }
+
+    Ok(response.json::<RelayInformationDocument>()?)
+}
+
+/// NIP-11 documents are served from the same host over HTTP(S).
+fn relay_http_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("wss://") {
+        format!("https://{rest}")
+    } else if let Some(rest) = url.strip_prefix("ws://") {
+        format!("http://{rest}")
+    } else {
+        url.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relay_http_url() {
+        assert_eq!(relay_http_url("wss://relay.damus.io/"), "https://relay.damus.io/");
+        assert_eq!(relay_http_url("ws://127.0.0.1:7777"), "http://127.0.0.1:7777");
+        assert_eq!(relay_http_url("https://relay.example.com"), "https://relay.example.com");
+    }
+}
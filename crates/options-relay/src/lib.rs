@@ -5,13 +5,30 @@ pub mod client;
 pub mod config;
 pub mod error;
 pub mod events;
+pub mod health;
+pub mod order_book;
+pub mod reputation;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+#[cfg(test)]
+mod test_chain;
+pub mod validation;
 
-pub use client::{PublishingClient, ReadOnlyClient};
+pub use client::{
+    PublishReport, PublishingClient, ReadOnlyClient, RelayPublishStatus, RelayRole, parse_option_offers,
+    parse_option_rfq_quotes, parse_option_rfqs, parse_options,
+};
 pub use config::NostrRelayConfig;
 pub use error::{ParseError, RelayError};
 pub use events::{
     ACTION_COMPLETED, ACTION_OPTION_CANCELLED, ACTION_OPTION_CREATED, ACTION_OPTION_EXERCISED, ACTION_OPTION_EXPIRED,
     ACTION_OPTION_FUNDED, ACTION_OPTION_OFFER_CANCELLED, ACTION_OPTION_OFFER_CREATED, ACTION_OPTION_OFFER_EXERCISED,
-    ACTION_SETTLEMENT_CLAIMED, ActionCompletedEvent, ActionType, OPTION_CREATED, OPTION_OFFER_CREATED,
-    OptionCreatedEvent, OptionOfferCreatedEvent,
+    ACTION_SETTLEMENT_CLAIMED, ActionCompletedEvent, ActionType, DeletionIndex, EVENT_SCHEMA_VERSION,
+    NegotiationMessage, NegotiationPayload, OPTION_CREATED, OPTION_OFFER_CREATED, OPTION_OFFER_CREATED_LEGACY,
+    OPTION_RFQ, OPTION_RFQ_QUOTE, OfferDepth, OptionCreatedEvent, OptionOfferCreatedEvent, OptionRfqEvent,
+    OptionRfqQuoteEvent, RELAY_PROBE, ReceivedNegotiationMessage,
 };
+pub use health::{RelayHealth, check_relays, fetch_relay_information};
+pub use order_book::{BookEntry, BookLevel, BookSort, ListingKind, MarketKey, OrderBook};
+pub use reputation::{CounterpartyStats, ReputationCalculator};
+pub use validation::{BogusReason, ChainBackend, ListingStatus, ListingValidator, Spend};
@@ -0,0 +1,319 @@
+use crate::events::{OfferDepth, OptionCreatedEvent, OptionOfferCreatedEvent};
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use nostr::{EventId, PublicKey};
+use simplicityhl::elements::AssetId;
+
+/// Width of an expiry bucket: listings expiring on the same UTC day share a market.
+pub const EXPIRY_BUCKET_SECS: u32 = 86_400;
+
+/// Listings trading the same collateral for the same settlement asset and expiring in the same bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MarketKey {
+    pub collateral_asset: AssetId,
+    pub settlement_asset: AssetId,
+    /// Start of the expiry bucket, as a Unix timestamp.
+    pub expiry_bucket: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingKind {
+    Option,
+    OptionOffer,
+}
+
+/// A priced listing in the order book.
+#[derive(Debug, Clone)]
+pub struct BookEntry {
+    pub event_id: EventId,
+    pub pubkey: PublicKey,
+    pub kind: ListingKind,
+    pub collateral_asset: AssetId,
+    pub settlement_asset: AssetId,
+    pub expiry_time: u32,
+    /// Settlement asset paid per unit of collateral.
+    pub price: f64,
+    /// Premium paid to the taker per unit of collateral, and its asset.
+    pub premium: Option<(u64, AssetId)>,
+    /// Collateral still available, if known.
+    pub available: Option<u64>,
+}
+
+impl BookEntry {
+    /// Option contracts are priced at their strike; their remaining size is not announced.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_option(event: &OptionCreatedEvent) -> Self {
+        let args = &event.options_args;
+
+        Self {
+            event_id: event.event_id,
+            pubkey: event.pubkey,
+            kind: ListingKind::Option,
+            collateral_asset: args.get_collateral_asset_id(),
+            settlement_asset: args.get_settlement_asset_id(),
+            expiry_time: args.expiry_time(),
+            price: args.settlement_per_contract() as f64 / args.collateral_per_contract().max(1) as f64,
+            premium: None,
+            available: None,
+        }
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_option_offer(event: &OptionOfferCreatedEvent, depth: &OfferDepth) -> Self {
+        let args = &event.option_offer_args;
+        let premium = args.premium_per_collateral();
+
+        Self {
+            event_id: event.event_id,
+            pubkey: event.pubkey,
+            kind: ListingKind::OptionOffer,
+            collateral_asset: args.get_collateral_asset_id(),
+            settlement_asset: args.get_settlement_asset_id(),
+            expiry_time: args.expiry_time(),
+            price: args.collateral_per_contract() as f64,
+            premium: (premium > 0).then(|| (premium, args.get_premium_asset_id())),
+            available: depth.remaining,
+        }
+    }
+
+    #[must_use]
+    pub const fn market(&self) -> MarketKey {
+        MarketKey {
+            collateral_asset: self.collateral_asset,
+            settlement_asset: self.settlement_asset,
+            expiry_bucket: self.expiry_time - self.expiry_time % EXPIRY_BUCKET_SECS,
+        }
+    }
+
+    /// Settlement paid per unit of collateral once the premium is accounted for.
+    ///
+    /// A premium in the settlement asset is deducted from the price, and one in the collateral
+    /// asset adds to the collateral received. A premium in any other asset cannot be priced
+    /// against the settlement asset and is ignored.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn effective_price(&self) -> f64 {
+        match self.premium {
+            Some((premium, asset)) if asset == self.settlement_asset => self.price - premium as f64,
+            Some((premium, asset)) if asset == self.collateral_asset => self.price / (1.0 + premium as f64),
+            _ => self.price,
+        }
+    }
+}
+
+/// Order of listings within a market.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BookSort {
+    /// Lowest effective price first.
+    #[default]
+    Price,
+    /// Soonest expiry first.
+    Expiry,
+    /// Largest available size first.
+    Depth,
+}
+
+impl FromStr for BookSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "price" => Ok(Self::Price),
+            "expiry" => Ok(Self::Expiry),
+            "depth" => Ok(Self::Depth),
+            other => Err(format!("Unknown sort order '{other}', expected price, expiry or depth")),
+        }
+    }
+}
+
+impl fmt::Display for BookSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Price => "price",
+            Self::Expiry => "expiry",
+            Self::Depth => "depth",
+        })
+    }
+}
+
+/// A listing together with the collateral available from the top of its market down to it.
+#[derive(Debug, Clone, Copy)]
+pub struct BookLevel<'a> {
+    pub entry: &'a BookEntry,
+    pub cumulative_depth: u64,
+}
+
+/// Listings grouped into markets, ordered by collateral asset, settlement asset and expiry.
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    markets: BTreeMap<MarketKey, Vec<BookEntry>>,
+}
+
+impl OrderBook {
+    #[must_use]
+    pub fn new(entries: impl IntoIterator<Item = BookEntry>) -> Self {
+        let mut markets: BTreeMap<MarketKey, Vec<BookEntry>> = BTreeMap::new();
+        for entry in entries {
+            markets.entry(entry.market()).or_default().push(entry);
+        }
+
+        Self { markets }
+    }
+
+    /// Keep only the listings matching `keep`, dropping markets left empty.
+    #[must_use]
+    pub fn filter(mut self, mut keep: impl FnMut(&BookEntry) -> bool) -> Self {
+        for entries in self.markets.values_mut() {
+            entries.retain(&mut keep);
+        }
+        self.markets.retain(|_, entries| !entries.is_empty());
+        self
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.markets.is_empty()
+    }
+
+    /// Number of listings across all markets.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.markets.values().map(Vec::len).sum()
+    }
+
+    /// Markets in key order, each with its listings sorted by `sort` and their cumulative depth.
+    ///
+    /// Listings of unknown size do not add to the cumulative depth.
+    #[must_use]
+    pub fn markets(&self, sort: BookSort) -> Vec<(MarketKey, Vec<BookLevel<'_>>)> {
+        self.markets
+            .iter()
+            .map(|(key, entries)| {
+                let mut sorted: Vec<&BookEntry> = entries.iter().collect();
+                sorted.sort_by(|a, b| compare(a, b, sort));
+
+                let mut cumulative_depth = 0u64;
+                let levels = sorted
+                    .into_iter()
+                    .map(|entry| {
+                        cumulative_depth = cumulative_depth.saturating_add(entry.available.unwrap_or_default());
+                        BookLevel {
+                            entry,
+                            cumulative_depth,
+                        }
+                    })
+                    .collect();
+
+                (*key, levels)
+            })
+            .collect()
+    }
+
+    /// Lowest effective price in a market.
+    #[must_use]
+    pub fn best_price(&self, market: &MarketKey) -> Option<f64> {
+        self.markets
+            .get(market)?
+            .iter()
+            .map(BookEntry::effective_price)
+            .min_by(f64::total_cmp)
+    }
+
+    /// Total known collateral available in a market.
+    #[must_use]
+    pub fn total_depth(&self, market: &MarketKey) -> u64 {
+        self.markets.get(market).map_or(0, |entries| {
+            entries
+                .iter()
+                .filter_map(|entry| entry.available)
+                .fold(0u64, u64::saturating_add)
+        })
+    }
+}
+
+/// Ties fall back to best price, then to the larger listing.
+fn compare(a: &BookEntry, b: &BookEntry, sort: BookSort) -> Ordering {
+    let by_price = || a.effective_price().total_cmp(&b.effective_price());
+    let by_depth = || b.available.cmp(&a.available);
+
+    match sort {
+        BookSort::Price => by_price().then_with(by_depth),
+        BookSort::Expiry => a
+            .expiry_time
+            .cmp(&b.expiry_time)
+            .then_with(by_price)
+            .then_with(by_depth),
+        BookSort::Depth => by_depth().then_with(by_price),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use nostr::Keys;
+
+    fn asset(byte: u8) -> AssetId {
+        AssetId::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn entry(settlement: u8, price: f64, premium: Option<(u64, u8)>, available: Option<u64>) -> BookEntry {
+        BookEntry {
+            event_id: EventId::all_zeros(),
+            pubkey: Keys::generate().public_key(),
+            kind: ListingKind::OptionOffer,
+            collateral_asset: asset(1),
+            settlement_asset: asset(settlement),
+            expiry_time: 1_700_000_000,
+            price,
+            premium: premium.map(|(amount, byte)| (amount, asset(byte))),
+            available,
+        }
+    }
+
+    #[test]
+    fn effective_price_accounts_for_premium() {
+        assert!((entry(2, 10.0, Some((3, 2)), None).effective_price() - 7.0).abs() < f64::EPSILON);
+        assert!((entry(2, 10.0, Some((1, 1)), None).effective_price() - 5.0).abs() < f64::EPSILON);
+        assert!((entry(2, 10.0, Some((3, 9)), None).effective_price() - 10.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn order_book_groups_sorts_and_accumulates_depth() {
+        let mut later = entry(2, 8.0, None, Some(50));
+        later.expiry_time += 2 * EXPIRY_BUCKET_SECS;
+
+        let book = OrderBook::new([
+            entry(2, 10.0, None, Some(100)),
+            entry(2, 12.0, Some((4, 2)), Some(200)),
+            entry(2, 9.0, None, None),
+            entry(3, 1.0, None, Some(10)),
+            later,
+        ]);
+
+        let markets = book.markets(BookSort::Price);
+        assert_eq!(markets.len(), 3);
+        assert_eq!(book.len(), 5);
+
+        let (key, levels) = &markets[0];
+        let prices: Vec<f64> = levels.iter().map(|level| level.entry.effective_price()).collect();
+        let depths: Vec<u64> = levels.iter().map(|level| level.cumulative_depth).collect();
+        assert_eq!(prices, vec![8.0, 9.0, 10.0]);
+        assert_eq!(depths, vec![200, 200, 300]);
+        assert_eq!(book.best_price(key), Some(8.0));
+        assert_eq!(book.total_depth(key), 300);
+
+        let by_depth = book.markets(BookSort::Depth);
+        assert_eq!(by_depth[0].1[0].entry.available, Some(200));
+
+        let filtered = book.filter(|entry| entry.settlement_asset == asset(3));
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered.markets(BookSort::Price).len(), 1);
+    }
+}
@@ -0,0 +1,304 @@
+use crate::events::{ActionCompletedEvent, ActionType, OptionOfferCreatedEvent, option_offer_coordinate};
+use crate::validation::ChainBackend;
+
+use std::collections::HashMap;
+
+use nostr::PublicKey;
+use simplicityhl::elements::OutPoint;
+
+/// Settlement history of a maker, derived from their offers and the actions published against them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CounterpartyStats {
+    pub pubkey: PublicKey,
+    /// Distinct offers published (republished versions of an offer count once).
+    pub offers_published: u32,
+    /// Offers with at least one fill confirmed on chain.
+    pub offers_honoured: u32,
+    /// Offers the maker withdrew before expiry without any fill.
+    pub offers_cancelled_early: u32,
+    /// Offers whose advertised funding output does not exist on chain.
+    pub offers_bogus: u32,
+    /// Fills confirmed on chain.
+    pub fills: u32,
+    /// Collateral taken across confirmed fills, where fills announced their amount.
+    pub total_filled: u64,
+}
+
+impl CounterpartyStats {
+    #[must_use]
+    pub const fn new(pubkey: PublicKey) -> Self {
+        Self {
+            pubkey,
+            offers_published: 0,
+            offers_honoured: 0,
+            offers_cancelled_early: 0,
+            offers_bogus: 0,
+            fills: 0,
+            total_filled: 0,
+        }
+    }
+
+    /// Average collateral per confirmed fill.
+    #[must_use]
+    pub fn average_fill(&self) -> Option<u64> {
+        (self.fills > 0).then(|| self.total_filled / u64::from(self.fills))
+    }
+
+    /// Score from 0 to 100, or `None` without any settled offer.
+    ///
+    /// Honoured offers count in favour, early cancellations against, and bogus listings
+    /// count twice against since they can only be published on purpose.
+    #[must_use]
+    pub fn score(&self) -> Option<u8> {
+        let honoured = u64::from(self.offers_honoured);
+        let settled = honoured + u64::from(self.offers_cancelled_early) + 2 * u64::from(self.offers_bogus);
+
+        if settled == 0 {
+            return None;
+        }
+
+        u8::try_from(honoured * 100 / settled).ok()
+    }
+}
+
+/// Computes [`CounterpartyStats`] per maker, only counting actions the chain confirms.
+///
+/// Fill events are signed by takers, so a fill is counted only if the offer UTXO it follows was
+/// spent by the fill's transaction in a block. Cancellations must additionally be signed by the maker.
+#[derive(Debug, Clone)]
+pub struct ReputationCalculator<B> {
+    backend: B,
+}
+
+impl<B: ChainBackend> ReputationCalculator<B> {
+    #[must_use]
+    pub const fn new(backend: B) -> Self {
+        Self { backend }
+    }
+
+    /// Compute stats for every maker in `offers`.
+    ///
+    /// `offers` should include expired and deleted offers, since those make up most of a maker's history.
+    pub fn compute(
+        &self,
+        offers: &[OptionOfferCreatedEvent],
+        actions: &[ActionCompletedEvent],
+    ) -> Result<HashMap<PublicKey, CounterpartyStats>, B::Error> {
+        // Republished versions of an offer share the maker and taproot pubkey gen.
+        let mut grouped: HashMap<(PublicKey, String), Vec<&OptionOfferCreatedEvent>> = HashMap::new();
+        for offer in offers {
+            grouped
+                .entry((offer.pubkey, offer.taproot_pubkey_gen.to_string()))
+                .or_default()
+                .push(offer);
+        }
+
+        let mut stats: HashMap<PublicKey, CounterpartyStats> = HashMap::new();
+
+        for ((maker, _), mut versions) in grouped {
+            versions.sort_by_key(|offer| offer.created_at);
+            let entry = stats.entry(maker).or_insert_with(|| CounterpartyStats::new(maker));
+            entry.offers_published += 1;
+
+            self.apply_offer(entry, &versions, actions)?;
+        }
+
+        Ok(stats)
+    }
+
+    fn apply_offer(
+        &self,
+        stats: &mut CounterpartyStats,
+        versions: &[&OptionOfferCreatedEvent],
+        actions: &[ActionCompletedEvent],
+    ) -> Result<(), B::Error> {
+        let first = versions[0];
+
+        let funded = self
+            .backend
+            .get_output(first.utxo)?
+            .is_some_and(|txout| txout.script_pubkey == first.taproot_pubkey_gen.address.script_pubkey());
+        if !funded {
+            stats.offers_bogus += 1;
+            return Ok(());
+        }
+
+        // Relays keep only the newest version, so fills against older versions match by coordinate.
+        let coordinate = option_offer_coordinate(stats.pubkey, &first.taproot_pubkey_gen);
+        let mut relevant: Vec<&ActionCompletedEvent> = actions
+            .iter()
+            .filter(|action| {
+                versions
+                    .iter()
+                    .any(|offer| action.references_offer(offer.event_id, &coordinate))
+            })
+            .collect();
+        relevant.sort_by_key(|action| action.created_at);
+
+        let expiry = u64::from(first.option_offer_args.expiry_time());
+        let mut current: OutPoint = first.utxo;
+        let mut fills = 0;
+
+        for action in relevant {
+            match action.action {
+                ActionType::OptionOfferExercised => {
+                    let confirmed = self
+                        .backend
+                        .get_spend(current)?
+                        .is_some_and(|spend| spend.confirmed && spend.txid == action.outpoint.txid);
+                    if !confirmed {
+                        continue;
+                    }
+
+                    fills += 1;
+                    stats.fills += 1;
+                    stats.total_filled = stats.total_filled.saturating_add(action.filled_amount.unwrap_or_default());
+                    current = action.outpoint;
+                }
+                ActionType::OptionOfferCancelled if action.pubkey == stats.pubkey => {
+                    if fills == 0 && action.created_at.as_secs() < expiry && self.backend.is_spent(current)? {
+                        stats.offers_cancelled_early += 1;
+                    }
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        if fills > 0 {
+            stats.offers_honoured += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_chain::{FakeChain, outpoint};
+
+    use contracts::option_offer::{OptionOfferArguments, get_option_offer_address};
+    use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
+    use nostr::hashes::Hash;
+    use nostr::{EventId, Keys, Timestamp};
+    use simplicityhl::elements::confidential::{Asset, Nonce, Value};
+    use simplicityhl::elements::{AssetId, TxOut, TxOutWitness};
+    use simplicityhl_core::{LIQUID_TESTNET_BITCOIN_ASSET, LIQUID_TESTNET_TEST_ASSET_ID_STR, SimplicityNetwork};
+
+    fn offer(maker: PublicKey, utxo: OutPoint, salt: u8) -> anyhow::Result<OptionOfferCreatedEvent> {
+        let settlement_asset_id = AssetId::from_slice(&hex::decode(LIQUID_TESTNET_TEST_ASSET_ID_STR)?)?;
+        let args = OptionOfferArguments::new(
+            *LIQUID_TESTNET_BITCOIN_ASSET,
+            settlement_asset_id,
+            settlement_asset_id,
+            1000,
+            50,
+            1_700_000_000,
+            [salt; 32],
+        );
+        let tpg = TaprootPubkeyGen::from(&args, SimplicityNetwork::LiquidTestnet, &get_option_offer_address)?;
+
+        let mut event = OptionOfferCreatedEvent::new(args, utxo, tpg);
+        event.event_id = EventId::from_byte_array([salt; 32]);
+        event.pubkey = maker;
+        event.created_at = Timestamp::from_secs(1_600_000_000);
+        Ok(event)
+    }
+
+    fn action(
+        offer: &OptionOfferCreatedEvent,
+        kind: ActionType,
+        outpoint: OutPoint,
+        by: PublicKey,
+    ) -> ActionCompletedEvent {
+        let mut action = ActionCompletedEvent::new(offer.event_id, kind, outpoint).with_fill(100, 900);
+        action.pubkey = by;
+        action.created_at = Timestamp::from_secs(1_650_000_000);
+        action
+    }
+
+    fn fund(chain: &mut FakeChain, offer: &OptionOfferCreatedEvent) {
+        chain.outputs.insert(
+            offer.utxo,
+            TxOut {
+                asset: Asset::Explicit(*LIQUID_TESTNET_BITCOIN_ASSET),
+                value: Value::Explicit(1000),
+                nonce: Nonce::Null,
+                script_pubkey: offer.taproot_pubkey_gen.address.script_pubkey(),
+                witness: TxOutWitness::default(),
+            },
+        );
+    }
+
+    #[test]
+    fn reputation_counts_only_confirmed_history() -> anyhow::Result<()> {
+        let maker = Keys::generate().public_key();
+        let taker = Keys::generate().public_key();
+
+        let honoured = offer(maker, outpoint(1), 1)?;
+        let cancelled = offer(maker, outpoint(2), 2)?;
+        let fake_fill = offer(maker, outpoint(3), 3)?;
+        let bogus = offer(maker, outpoint(4), 4)?;
+
+        let mut chain = FakeChain::default();
+        for funded in [&honoured, &cancelled, &fake_fill] {
+            fund(&mut chain, funded);
+        }
+        chain.spend(honoured.utxo, outpoint(10).txid);
+        chain.spend(cancelled.utxo, outpoint(12).txid);
+        // Spent, but by another transaction than the fill claims.
+        chain.spend(fake_fill.utxo, outpoint(20).txid);
+
+        let actions = [
+            action(&honoured, ActionType::OptionOfferExercised, outpoint(10), taker),
+            // A taker cannot cancel on the maker's behalf.
+            action(&honoured, ActionType::OptionOfferCancelled, outpoint(11), taker),
+            action(&cancelled, ActionType::OptionOfferCancelled, outpoint(12), maker),
+            // Claims a fill the chain does not back.
+            action(&fake_fill, ActionType::OptionOfferExercised, outpoint(13), taker),
+        ];
+
+        let stats = ReputationCalculator::new(chain).compute(&[honoured, cancelled, fake_fill, bogus], &actions)?;
+        let maker_stats = stats[&maker];
+
+        assert_eq!(maker_stats.offers_published, 4);
+        assert_eq!(maker_stats.offers_honoured, 1);
+        assert_eq!(maker_stats.offers_cancelled_early, 1);
+        assert_eq!(maker_stats.offers_bogus, 1);
+        assert_eq!(maker_stats.fills, 1);
+        assert_eq!(maker_stats.average_fill(), Some(100));
+        assert_eq!(maker_stats.score(), Some(25));
+
+        Ok(())
+    }
+    #[test]
+    fn reputation_counts_fills_of_superseded_versions_by_coordinate() -> anyhow::Result<()> {
+        let maker = Keys::generate().public_key();
+        let taker = Keys::generate().public_key();
+
+        let superseded = offer(maker, outpoint(1), 1)?;
+        let mut latest = superseded.clone();
+        latest.event_id = EventId::from_byte_array([2; 32]);
+        latest.created_at = Timestamp::from_secs(1_610_000_000);
+
+        let mut chain = FakeChain::default();
+        fund(&mut chain, &superseded);
+        chain.spend(superseded.utxo, outpoint(10).txid);
+
+        // The relay only returns the latest version; the fill points at the one it replaced.
+        let fill = action(&superseded, ActionType::OptionOfferExercised, outpoint(10), taker)
+            .with_offer_coordinate(option_offer_coordinate(maker, &superseded.taproot_pubkey_gen));
+
+        let stats = ReputationCalculator::new(chain).compute(&[latest], &[fill])?;
+        let maker_stats = stats[&maker];
+
+        assert_eq!(maker_stats.offers_published, 1);
+        assert_eq!(maker_stats.offers_honoured, 1);
+        assert_eq!(maker_stats.fills, 1);
+        assert_eq!(maker_stats.score(), Some(100));
+
+        Ok(())
+    }
+}
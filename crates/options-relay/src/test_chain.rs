@@ -0,0 +1,55 @@
+//! In-memory chain shared by the unit tests that check NOSTR events against the chain.
+
+use crate::validation::{ChainBackend, Spend};
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use simplicityhl::elements::confidential::{Asset, Nonce, Value};
+use simplicityhl::elements::hashes::Hash;
+use simplicityhl::elements::{AssetId, OutPoint, Script, TxOut, TxOutWitness, Txid};
+
+#[derive(Debug, Default)]
+pub struct FakeChain {
+    pub outputs: HashMap<OutPoint, TxOut>,
+    pub spends: HashMap<OutPoint, Spend>,
+}
+
+impl FakeChain {
+    /// Record `outpoint` as spent by the confirmed transaction `txid`.
+    pub fn spend(&mut self, outpoint: OutPoint, txid: Txid) {
+        self.spends.insert(outpoint, Spend { txid, confirmed: true });
+    }
+
+    /// Record `outpoint` as spent by `txid`, which is still in the mempool.
+    pub fn spend_unconfirmed(&mut self, outpoint: OutPoint, txid: Txid) {
+        self.spends.insert(outpoint, Spend { txid, confirmed: false });
+    }
+}
+
+impl ChainBackend for FakeChain {
+    type Error = Infallible;
+
+    fn get_output(&self, outpoint: OutPoint) -> Result<Option<TxOut>, Self::Error> {
+        Ok(self.outputs.get(&outpoint).cloned())
+    }
+
+    fn get_spend(&self, outpoint: OutPoint) -> Result<Option<Spend>, Self::Error> {
+        Ok(self.spends.get(&outpoint).copied())
+    }
+}
+
+pub fn explicit_output(script_pubkey: Script, asset: AssetId, value: u64) -> TxOut {
+    TxOut {
+        asset: Asset::Explicit(asset),
+        value: Value::Explicit(value),
+        nonce: Nonce::Null,
+        script_pubkey,
+        witness: TxOutWitness::default(),
+    }
+}
+
+/// Output 0 of a transaction whose txid is `byte` repeated.
+pub fn outpoint(byte: u8) -> OutPoint {
+    OutPoint::new(Txid::from_byte_array([byte; 32]), 0)
+}
@@ -0,0 +1,3 @@
+mod mock_relay;
+
+pub use mock_relay::MockRelay;
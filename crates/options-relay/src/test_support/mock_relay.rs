@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use nostr::{Event, Filter, TagKind};
+use serde_json::{Value, json};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Capacity of the live event channel; slow subscribers lag instead of blocking publishers.
+const LIVE_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Default)]
+struct RelayState {
+    events: Vec<Event>,
+    connections: usize,
+    reject_events: Option<String>,
+}
+
+/// In-process NOSTR relay for tests.
+///
+/// Speaks enough NIP-01 over a localhost websocket for the clients in this crate: `EVENT` with
+/// `OK` replies, `REQ` with stored events followed by `EOSE` and live events afterwards, and `CLOSE`.
+/// Ephemeral events are forwarded but not stored, and addressable events replace older versions.
+/// The relay shuts down when dropped.
+#[derive(Debug)]
+pub struct MockRelay {
+    url: String,
+    state: Arc<Mutex<RelayState>>,
+    task: JoinHandle<()>,
+}
+
+impl MockRelay {
+    /// Start a relay on a random localhost port.
+    pub async fn run() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr: SocketAddr = listener.local_addr()?;
+
+        let state = Arc::new(Mutex::new(RelayState::default()));
+        let (live, _) = broadcast::channel(LIVE_CHANNEL_CAPACITY);
+
+        let task = tokio::spawn({
+            let state = Arc::clone(&state);
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(handle_connection(stream, Arc::clone(&state), live.clone()));
+                }
+            }
+        });
+
+        Ok(Self {
+            url: format!("ws://{addr}"),
+            state,
+            task,
+        })
+    }
+
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Events currently stored, in arrival order.
+    #[must_use]
+    pub fn stored_events(&self) -> Vec<Event> {
+        lock(&self.state).events.clone()
+    }
+
+    /// Store an event directly, e.g. to seed the relay before a client connects.
+    pub fn insert_event(&self, event: Event) {
+        store_event(&mut lock(&self.state), event);
+    }
+
+    /// Reject every incoming `EVENT` with `reason`, or accept them again with `None`.
+    pub fn reject_events(&self, reason: Option<&str>) {
+        lock(&self.state).reject_events = reason.map(ToString::to_string);
+    }
+
+    /// Number of websocket connections currently open.
+    #[must_use]
+    pub fn connections(&self) -> usize {
+        lock(&self.state).connections
+    }
+
+    /// Wait until at least `count` clients are connected, so tests do not race the client's
+    /// background connection task. Returns `false` on timeout.
+    pub async fn wait_for_connections(&self, count: usize, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        while self.connections() < count {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        true
+    }
+}
+
+impl Drop for MockRelay {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn lock(state: &Mutex<RelayState>) -> MutexGuard<'_, RelayState> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn store_event(state: &mut RelayState, event: Event) -> bool {
+    if event.kind.is_ephemeral() || state.events.iter().any(|e| e.id == event.id) {
+        return false;
+    }
+
+    if event.kind.is_addressable() {
+        let identifier = event.tags.identifier().unwrap_or_default().to_string();
+        let is_same_slot = |e: &Event| {
+            e.pubkey == event.pubkey && e.kind == event.kind && e.tags.identifier().unwrap_or_default() == identifier
+        };
+
+        if state
+            .events
+            .iter()
+            .any(|e| is_same_slot(e) && e.created_at > event.created_at)
+        {
+            return false;
+        }
+        state.events.retain(|e| !is_same_slot(e));
+    }
+
+    state.events.push(event);
+    true
+}
+
+async fn handle_connection(stream: TcpStream, state: Arc<Mutex<RelayState>>, live: broadcast::Sender<Event>) {
+    let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (mut sink, mut source) = ws.split();
+    let mut live_events = live.subscribe();
+    let mut subscriptions: HashMap<String, Vec<Filter>> = HashMap::new();
+
+    lock(&state).connections += 1;
+
+    loop {
+        let replies = tokio::select! {
+            message = source.next() => match message {
+                Some(Ok(Message::Text(text))) => handle_message(&text, &state, &live, &mut subscriptions),
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Binary(_) | Message::Frame(_))) => Vec::new(),
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+            },
+            event = live_events.recv() => match event {
+                Ok(event) => subscriptions
+                    .iter()
+                    .filter(|(_, filters)| filters.iter().any(|f| matches_filter(f, &event)))
+                    .map(|(id, _)| json!(["EVENT", id, event]))
+                    .collect(),
+                Err(broadcast::error::RecvError::Lagged(_)) => Vec::new(),
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+
+        for reply in replies {
+            if sink.send(Message::text(reply.to_string())).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    lock(&state).connections -= 1;
+}
+
+fn handle_message(
+    text: &str,
+    state: &Mutex<RelayState>,
+    live: &broadcast::Sender<Event>,
+    subscriptions: &mut HashMap<String, Vec<Filter>>,
+) -> Vec<Value> {
+    let Ok(Value::Array(message)) = serde_json::from_str::<Value>(text) else {
+        return vec![json!(["NOTICE", "invalid message"])];
+    };
+
+    match message.first().and_then(Value::as_str) {
+        Some("EVENT") => {
+            let Some(Ok(event)) = message.get(1).cloned().map(serde_json::from_value::<Event>) else {
+                return vec![json!(["NOTICE", "invalid event"])];
+            };
+
+            if event.verify().is_err() {
+                return vec![json!(["OK", event.id, false, "invalid: bad signature"])];
+            }
+
+            let mut state = lock(state);
+            if let Some(reason) = &state.reject_events {
+                return vec![json!(["OK", event.id, false, reason])];
+            }
+
+            let id = event.id;
+            if event.kind.is_ephemeral() || store_event(&mut state, event.clone()) {
+                drop(state);
+                let _ = live.send(event);
+            }
+
+            vec![json!(["OK", id, true, ""])]
+        }
+        Some("REQ") => {
+            let Some(subscription_id) = message.get(1).and_then(Value::as_str).map(ToString::to_string) else {
+                return vec![json!(["NOTICE", "invalid subscription id"])];
+            };
+            let filters: Vec<Filter> = message[2..]
+                .iter()
+                .filter_map(|f| serde_json::from_value(f.clone()).ok())
+                .collect();
+
+            let mut replies: Vec<Value> = stored_matches(&lock(state).events, &filters)
+                .into_iter()
+                .map(|event| json!(["EVENT", subscription_id, event]))
+                .collect();
+            replies.push(json!(["EOSE", subscription_id]));
+
+            subscriptions.insert(subscription_id, filters);
+            replies
+        }
+        Some("CLOSE") => {
+            if let Some(subscription_id) = message.get(1).and_then(Value::as_str) {
+                subscriptions.remove(subscription_id);
+            }
+            Vec::new()
+        }
+        _ => vec![json!(["NOTICE", "unsupported message"])],
+    }
+}
+
+/// Stored events matching any filter, newest first and truncated per filter `limit`.
+fn stored_matches(events: &[Event], filters: &[Filter]) -> Vec<Event> {
+    let mut matched: Vec<Event> = Vec::new();
+
+    for filter in filters {
+        let mut hits: Vec<&Event> = events.iter().filter(|e| matches_filter(filter, e)).collect();
+        hits.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        if let Some(limit) = filter.limit {
+            hits.truncate(limit);
+        }
+
+        for event in hits {
+            if !matched.iter().any(|e| e.id == event.id) {
+                matched.push(event.clone());
+            }
+        }
+    }
+
+    matched
+}
+
+/// NIP-01 filter matching.
+fn matches_filter(filter: &Filter, event: &Event) -> bool {
+    filter.ids.as_ref().is_none_or(|ids| ids.contains(&event.id))
+        && filter.authors.as_ref().is_none_or(|authors| authors.contains(&event.pubkey))
+        && filter.kinds.as_ref().is_none_or(|kinds| kinds.contains(&event.kind))
+        && filter.since.is_none_or(|since| event.created_at >= since)
+        && filter.until.is_none_or(|until| event.created_at <= until)
+        && filter.generic_tags.iter().all(|(tag, values)| {
+            event.tags.iter().any(|t| {
+                t.kind() == TagKind::SingleLetter(*tag) && t.content().is_some_and(|content| values.contains(content))
+            })
+        })
+}
@@ -0,0 +1,395 @@
+use crate::events::{ActionCompletedEvent, ActionType, OfferDepth, OptionCreatedEvent, OptionOfferCreatedEvent};
+
+use std::collections::HashMap;
+use std::fmt;
+
+use simplicityhl::elements::secp256k1_zkp::SECP256K1;
+use simplicityhl::elements::{Address, AssetId, OutPoint, TxOut, Txid};
+use simplicityhl_core::derive_public_blinder_key;
+
+/// A transaction spending an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spend {
+    pub txid: Txid,
+    /// Whether the spending transaction is in a block rather than only in the mempool.
+    pub confirmed: bool,
+}
+
+/// Read access to the chain, so listings can be checked against an explorer or a fake in tests.
+pub trait ChainBackend {
+    type Error: fmt::Display;
+
+    /// Fetch the output at `outpoint`. Returns `None` if the transaction or output does not exist.
+    fn get_output(&self, outpoint: OutPoint) -> Result<Option<TxOut>, Self::Error>;
+
+    /// Find the transaction that spent the output at `outpoint`, if any.
+    fn get_spend(&self, outpoint: OutPoint) -> Result<Option<Spend>, Self::Error>;
+
+    /// Check whether the output at `outpoint` has been spent, in a block or in the mempool.
+    fn is_spent(&self, outpoint: OutPoint) -> Result<bool, Self::Error> {
+        Ok(self.get_spend(outpoint)?.is_some())
+    }
+}
+
+/// Why a listing cannot be backed by the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BogusReason {
+    /// The advertised outpoint does not exist.
+    MissingOutput,
+    /// The output is not locked to the contract address derived from the arguments.
+    WrongAddress,
+    /// The output holds a different asset than the contract's collateral.
+    WrongAsset,
+}
+
+impl BogusReason {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::MissingOutput => "missing output",
+            Self::WrongAddress => "wrong address",
+            Self::WrongAsset => "wrong asset",
+        }
+    }
+}
+
+/// On-chain state of an advertised contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingStatus {
+    /// Unspent and funded. `collateral` is `None` if the amount is blinded with a private key.
+    Live { collateral: Option<u64> },
+    /// The advertised output has already been spent.
+    Spent,
+    /// The output holds less collateral than advertised.
+    Underfunded { expected: u64, actual: u64 },
+    /// The listing does not match the chain at all.
+    Bogus(BogusReason),
+}
+
+impl ListingStatus {
+    #[must_use]
+    pub const fn is_live(&self) -> bool {
+        matches!(self, Self::Live { .. })
+    }
+}
+
+impl fmt::Display for ListingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Live { .. } => write!(f, "live"),
+            Self::Spent => write!(f, "spent"),
+            Self::Underfunded { expected, actual } => write!(f, "underfunded ({actual}/{expected})"),
+            Self::Bogus(reason) => write!(f, "bogus ({})", reason.as_str()),
+        }
+    }
+}
+
+/// Checks NOSTR-advertised contracts against the chain before they are listed.
+#[derive(Debug, Clone)]
+pub struct ListingValidator<B> {
+    backend: B,
+}
+
+impl<B: ChainBackend> ListingValidator<B> {
+    #[must_use]
+    pub const fn new(backend: B) -> Self {
+        Self { backend }
+    }
+
+    /// Validate an option: its funding output must hold at least one contract's worth of collateral.
+    pub fn validate_option(&self, event: &OptionCreatedEvent) -> Result<ListingStatus, B::Error> {
+        let args = &event.options_args;
+
+        self.validate_output(
+            event.utxo,
+            &event.taproot_pubkey_gen.address,
+            args.get_collateral_asset_id(),
+            args.collateral_per_contract(),
+        )
+    }
+
+    /// Compute an offer's remaining size, counting only the fills the chain confirms.
+    ///
+    /// The offer is followed from its advertised output through the confirmed transactions
+    /// spending it. A fill counts if it names the spending transaction and, unless that
+    /// transaction took the offer in full, the output at the offer address it left behind.
+    /// After a fill the remaining size is read from that output rather than from the fill.
+    pub fn offer_depth(
+        &self,
+        event: &OptionOfferCreatedEvent,
+        actions: &[ActionCompletedEvent],
+    ) -> Result<OfferDepth, B::Error> {
+        let script = event.taproot_pubkey_gen.address.script_pubkey();
+        let coordinate = event.coordinate(event.pubkey);
+        let fills: Vec<&ActionCompletedEvent> = actions
+            .iter()
+            .filter(|action| {
+                action.action == ActionType::OptionOfferExercised
+                    && action.references_offer(event.event_id, &coordinate)
+            })
+            .collect();
+
+        let mut spends = HashMap::new();
+        let mut backed = Vec::new();
+        let mut current = event.utxo;
+        let mut taken_in_full = false;
+
+        loop {
+            let spend = match self.backend.get_spend(current)? {
+                Some(spend) if spend.confirmed => spend,
+                _ => break,
+            };
+            spends.insert(current, spend);
+
+            let claimed: Vec<OutPoint> = fills
+                .iter()
+                .map(|fill| fill.outpoint)
+                .filter(|outpoint| outpoint.txid == spend.txid)
+                .collect();
+
+            let mut next = None;
+            for outpoint in &claimed {
+                if self
+                    .backend
+                    .get_output(*outpoint)?
+                    .is_some_and(|txout| txout.script_pubkey == script)
+                {
+                    next = Some(*outpoint);
+                    break;
+                }
+            }
+
+            match next {
+                Some(outpoint) => {
+                    backed.push(outpoint);
+                    current = outpoint;
+                }
+                None => {
+                    taken_in_full = !claimed.is_empty();
+                    backed.extend(claimed);
+                    break;
+                }
+            }
+        }
+
+        let backed_actions: Vec<ActionCompletedEvent> = actions
+            .iter()
+            .filter(|action| action.action != ActionType::OptionOfferExercised || backed.contains(&action.outpoint))
+            .cloned()
+            .collect();
+        let mut depth = event.depth(&backed_actions, &spends);
+
+        // Fill amounts are the taker's claims; the chain has the actual remaining collateral.
+        if taken_in_full {
+            depth.remaining = Some(0);
+        } else if depth.fill_count > 0 {
+            depth.remaining = match self.backend.get_output(current)? {
+                Some(txout) => read_asset_and_value(&txout).map(|(_, value)| value),
+                None => None,
+            };
+        }
+
+        Ok(depth)
+    }
+
+    /// Validate an option offer at the output left by its last confirmed fill against its
+    /// remaining size, returning the depth it was checked against.
+    pub fn validate_option_offer(
+        &self,
+        event: &OptionOfferCreatedEvent,
+        actions: &[ActionCompletedEvent],
+    ) -> Result<(ListingStatus, OfferDepth), B::Error> {
+        let depth = self.offer_depth(event, actions)?;
+        if depth.fill_count > 0 && depth.remaining == Some(0) {
+            return Ok((ListingStatus::Spent, depth));
+        }
+
+        let status = self.validate_output(
+            depth.latest_outpoint.unwrap_or(event.utxo),
+            &event.taproot_pubkey_gen.address,
+            event.option_offer_args.get_collateral_asset_id(),
+            depth.remaining.unwrap_or(1),
+        )?;
+
+        Ok((status, depth))
+    }
+
+    /// Classify a single contract output.
+    pub fn validate_output(
+        &self,
+        outpoint: OutPoint,
+        address: &Address,
+        collateral_asset: AssetId,
+        expected_collateral: u64,
+    ) -> Result<ListingStatus, B::Error> {
+        let Some(txout) = self.backend.get_output(outpoint)? else {
+            return Ok(ListingStatus::Bogus(BogusReason::MissingOutput));
+        };
+
+        if txout.script_pubkey != address.script_pubkey() {
+            return Ok(ListingStatus::Bogus(BogusReason::WrongAddress));
+        }
+
+        if self.backend.is_spent(outpoint)? {
+            return Ok(ListingStatus::Spent);
+        }
+
+        let Some((asset, value)) = read_asset_and_value(&txout) else {
+            return Ok(ListingStatus::Live { collateral: None });
+        };
+
+        if asset != collateral_asset {
+            return Ok(ListingStatus::Bogus(BogusReason::WrongAsset));
+        }
+
+        if value < expected_collateral {
+            return Ok(ListingStatus::Underfunded {
+                expected: expected_collateral,
+                actual: value,
+            });
+        }
+
+        Ok(ListingStatus::Live {
+            collateral: Some(value),
+        })
+    }
+}
+
+/// Read an output's asset and value, unblinding with the public blinder key if needed.
+fn read_asset_and_value(txout: &TxOut) -> Option<(AssetId, u64)> {
+    if let (Some(asset), Some(value)) = (txout.asset.explicit(), txout.value.explicit()) {
+        return Some((asset, value));
+    }
+
+    let blinder = derive_public_blinder_key();
+    txout
+        .unblind(SECP256K1, blinder.secret_key())
+        .ok()
+        .map(|secrets| (secrets.asset, secrets.value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_chain::{FakeChain, explicit_output, outpoint};
+
+    use contracts::option_offer::{OptionOfferArguments, get_option_offer_address};
+    use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
+    use simplicityhl::elements::Script;
+    use simplicityhl_core::{LIQUID_TESTNET_BITCOIN_ASSET, LIQUID_TESTNET_TEST_ASSET_ID_STR, SimplicityNetwork};
+
+    fn offer_args() -> anyhow::Result<(OptionOfferArguments, TaprootPubkeyGen)> {
+        let settlement_asset_id = AssetId::from_slice(&hex::decode(LIQUID_TESTNET_TEST_ASSET_ID_STR)?)?;
+        let args = OptionOfferArguments::new(
+            *LIQUID_TESTNET_BITCOIN_ASSET,
+            settlement_asset_id,
+            settlement_asset_id,
+            1000,
+            50,
+            1_700_000_000,
+            [1; 32],
+        );
+        let tpg = TaprootPubkeyGen::from(&args, SimplicityNetwork::LiquidTestnet, &get_option_offer_address)?;
+        Ok((args, tpg))
+    }
+
+    #[test]
+    fn validate_output_classifies_listings() -> anyhow::Result<()> {
+        let settlement_asset_id = AssetId::from_slice(&hex::decode(LIQUID_TESTNET_TEST_ASSET_ID_STR)?)?;
+        let (_, tpg) = offer_args()?;
+        let script = tpg.address.script_pubkey();
+
+        let (live, spent, underfunded, wrong_asset, wrong_address, missing) =
+            (outpoint(1), outpoint(2), outpoint(3), outpoint(4), outpoint(5), outpoint(6));
+
+        let mut chain = FakeChain::default();
+        let lbtc = *LIQUID_TESTNET_BITCOIN_ASSET;
+        chain.outputs.insert(live, explicit_output(script.clone(), lbtc, 1000));
+        chain.outputs.insert(spent, explicit_output(script.clone(), lbtc, 1000));
+        chain.outputs.insert(underfunded, explicit_output(script.clone(), lbtc, 10));
+        chain
+            .outputs
+            .insert(wrong_asset, explicit_output(script, settlement_asset_id, 1000));
+        chain
+            .outputs
+            .insert(wrong_address, explicit_output(Script::new(), lbtc, 1000));
+        chain.spend_unconfirmed(spent, outpoint(7).txid);
+
+        let validator = ListingValidator::new(chain);
+        let check = |outpoint| validator.validate_output(outpoint, &tpg.address, lbtc, 100).unwrap();
+
+        assert_eq!(check(live), ListingStatus::Live { collateral: Some(1000) });
+        assert_eq!(check(spent), ListingStatus::Spent);
+        assert_eq!(
+            check(underfunded),
+            ListingStatus::Underfunded {
+                expected: 100,
+                actual: 10
+            }
+        );
+        assert_eq!(check(wrong_asset), ListingStatus::Bogus(BogusReason::WrongAsset));
+        assert_eq!(check(wrong_address), ListingStatus::Bogus(BogusReason::WrongAddress));
+        assert_eq!(check(missing), ListingStatus::Bogus(BogusReason::MissingOutput));
+
+        Ok(())
+    }
+
+    #[test]
+    fn offer_depth_follows_the_chain() -> anyhow::Result<()> {
+        let (args, tpg) = offer_args()?;
+        let script = tpg.address.script_pubkey();
+        let lbtc = *LIQUID_TESTNET_BITCOIN_ASSET;
+        let taker = nostr::Keys::generate().public_key();
+
+        let mut event = OptionOfferCreatedEvent::new(args, outpoint(1), tpg).with_collateral_amount(1000);
+        event.event_id = nostr::EventId::from_slice(&[7; 32])?;
+
+        let fill = |outpoint: OutPoint, filled: u64, remaining: u64| {
+            let mut action = ActionCompletedEvent::new(event.event_id, ActionType::OptionOfferExercised, outpoint);
+            action.pubkey = taker;
+            action.filled_amount = Some(filled);
+            action.remaining_collateral = Some(remaining);
+            action
+        };
+
+        // A real fill left 700 at the offer address, then a second transaction took the rest.
+        let mut chain = FakeChain::default();
+        chain.outputs.insert(outpoint(1), explicit_output(script.clone(), lbtc, 1000));
+        chain.outputs.insert(outpoint(2), explicit_output(script.clone(), lbtc, 700));
+        chain.outputs.insert(outpoint(3), explicit_output(Script::new(), lbtc, 700));
+        chain.spend(outpoint(1), outpoint(2).txid);
+        chain.spend(outpoint(2), outpoint(3).txid);
+
+        let validator = ListingValidator::new(chain);
+
+        // The taker understates the first fill; the chain has the remaining collateral.
+        let depth = validator.offer_depth(&event, &[fill(outpoint(2), 100, 900)])?;
+        assert_eq!(depth.fill_count, 1);
+        assert_eq!(depth.remaining, Some(700));
+        assert_eq!(depth.latest_outpoint, Some(outpoint(2)));
+
+        // Claims a fill with a transaction that never spent the offer.
+        let depth = validator.offer_depth(&event, &[fill(outpoint(9), 1000, 0)])?;
+        assert_eq!(depth.fill_count, 0);
+        assert_eq!(depth.remaining, Some(1000));
+
+        let actions = [fill(outpoint(2), 300, 700), fill(outpoint(3), 700, 0)];
+        let depth = validator.offer_depth(&event, &actions)?;
+        assert_eq!(depth.fill_count, 2);
+        assert_eq!(depth.remaining, Some(0));
+        assert!(depth.is_exhausted());
+
+        // A forged fill pointing at an unspent output does not move where the offer is checked.
+        let mut chain = FakeChain::default();
+        chain.outputs.insert(outpoint(1), explicit_output(script.clone(), lbtc, 1000));
+        chain.outputs.insert(outpoint(4), explicit_output(script, lbtc, 5000));
+        let validator = ListingValidator::new(chain);
+
+        let (status, depth) = validator.validate_option_offer(&event, &[fill(outpoint(4), 1, 5000)])?;
+        assert_eq!(status, ListingStatus::Live { collateral: Some(1000) });
+        assert_eq!(depth.latest_outpoint, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use std::time::Duration;
+
+use contracts::option_offer::{OptionOfferArguments, get_option_offer_address};
+use contracts::sdk::taproot_pubkey_gen::TaprootPubkeyGen;
+use nostr::hashes::Hash;
+use nostr::{EventBuilder, Keys};
+use nostr_sdk::RelayPoolNotification;
+use options_relay::events::filters;
+use options_relay::test_support::MockRelay;
+use options_relay::{
+    NostrRelayConfig, OPTION_RFQ, OptionOfferCreatedEvent, OptionRfqEvent, PublishingClient, ReadOnlyClient,
+    RelayError,
+};
+use simplicityhl::elements::{AssetId, OutPoint, Txid};
+use simplicityhl_core::{LIQUID_TESTNET_BITCOIN_ASSET, LIQUID_TESTNET_TEST_ASSET_ID_STR, SimplicityNetwork};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn config(relays: &[&MockRelay]) -> NostrRelayConfig {
+    NostrRelayConfig::new(relays[0].url())
+        .add_backup_relays(relays[1..].iter().map(|relay| relay.url()))
+        .with_timeout(TIMEOUT)
+        .with_retry_count(1)
+        .with_retry_backoff(Duration::from_millis(10))
+}
+
+async fn publisher(relays: &[&MockRelay], keys: &Keys) -> anyhow::Result<PublishingClient> {
+    let client = PublishingClient::connect(config(relays), keys.clone()).await?;
+    for relay in relays {
+        assert!(relay.wait_for_connections(1, TIMEOUT).await, "client did not connect");
+    }
+    Ok(client)
+}
+
+fn settlement_asset() -> anyhow::Result<AssetId> {
+    Ok(AssetId::from_slice(&hex::decode(LIQUID_TESTNET_TEST_ASSET_ID_STR)?)?)
+}
+
+fn rfq() -> anyhow::Result<OptionRfqEvent> {
+    Ok(OptionRfqEvent::new(
+        *LIQUID_TESTNET_BITCOIN_ASSET,
+        settlement_asset()?,
+        500,
+        900..=1100,
+        1_700_000_000..=4_000_000_000,
+    ))
+}
+
+fn offer(collateral_amount: u64) -> anyhow::Result<OptionOfferCreatedEvent> {
+    let args = OptionOfferArguments::new(
+        *LIQUID_TESTNET_BITCOIN_ASSET,
+        settlement_asset()?,
+        settlement_asset()?,
+        1000,
+        50,
+        4_000_000_000,
+        [7; 32],
+    );
+    let tpg = TaprootPubkeyGen::from(&args, SimplicityNetwork::LiquidTestnet, &get_option_offer_address)?;
+
+    Ok(OptionOfferCreatedEvent::new(args, OutPoint::new(Txid::all_zeros(), 0), tpg)
+        .with_collateral_amount(collateral_amount))
+}
+
+#[tokio::test]
+async fn publish_and_fetch_rfq() -> anyhow::Result<()> {
+    let relay = MockRelay::run().await?;
+    let keys = Keys::generate();
+    let client = publisher(&[&relay], &keys).await?;
+
+    let event_id = client.publish_option_rfq(&rfq()?).await?;
+    assert_eq!(relay.stored_events().len(), 1);
+
+    let reader = ReadOnlyClient::connect(config(&[&relay])).await?;
+    let fetched = reader.fetch_option_rfqs().await?;
+
+    assert_eq!(fetched.len(), 1);
+    let fetched = fetched.into_iter().next().unwrap()?;
+    assert_eq!(fetched.event_id, event_id);
+    assert_eq!(fetched.pubkey, keys.public_key());
+    assert_eq!(fetched.collateral_amount, 500);
+
+    client.disconnect().await;
+    reader.disconnect().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn republished_offer_replaces_previous_version_and_deletion_hides_it() -> anyhow::Result<()> {
+    let relay = MockRelay::run().await?;
+    let client = publisher(&[&relay], &Keys::generate()).await?;
+
+    let first = offer(1000)?;
+    client.publish_option_offer_created(&first).await?;
+    let latest_id = client.publish_option_offer_created(&offer(400)?).await?;
+
+    let offers = client.fetch_option_offers(SimplicityNetwork::LiquidTestnet).await?;
+    assert_eq!(offers.len(), 1);
+    let latest = offers.into_iter().next().unwrap()?;
+    assert_eq!(latest.event_id, latest_id);
+    assert_eq!(latest.collateral_amount, Some(400));
+
+    client
+        .delete_option_offer(latest_id, &first.taproot_pubkey_gen.to_string(), "cancelled")
+        .await?;
+    assert!(client.fetch_option_offers(SimplicityNetwork::LiquidTestnet).await?.is_empty());
+
+    client.disconnect().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn subscription_receives_live_events() -> anyhow::Result<()> {
+    let relay = MockRelay::run().await?;
+    let client = publisher(&[&relay], &Keys::generate()).await?;
+
+    let reader = ReadOnlyClient::connect(config(&[&relay])).await?;
+    assert!(relay.wait_for_connections(2, TIMEOUT).await);
+
+    let mut notifications = reader.notifications();
+    let subscription_id = reader.subscribe(filters::option_rfq()).await?;
+
+    // Unrelated kinds must not be delivered to the subscription.
+    client.publish(EventBuilder::text_note("hello")).await?;
+    let event_id = client.publish_option_rfq(&rfq()?).await?;
+
+    let received = tokio::time::timeout(TIMEOUT, async {
+        loop {
+            if let Ok(RelayPoolNotification::Event {
+                subscription_id: id,
+                event,
+                ..
+            }) = notifications.recv().await
+                && id == subscription_id
+            {
+                return event;
+            }
+        }
+    })
+    .await?;
+
+    assert_eq!(received.id, event_id);
+    assert_eq!(received.kind, OPTION_RFQ);
+
+    reader.unsubscribe(&subscription_id).await;
+    client.disconnect().await;
+    reader.disconnect().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn fetch_deduplicates_events_across_relays() -> anyhow::Result<()> {
+    let (primary, backup) = (MockRelay::run().await?, MockRelay::run().await?);
+    let client = publisher(&[&primary, &backup], &Keys::generate()).await?;
+
+    let report = client.publish_with_report(rfq()?.to_event_builder(client.public_key().await?)).await?;
+    assert_eq!(report.accepted_count(), 2);
+
+    // An event only the backup relay has is still found.
+    let other = Keys::generate();
+    let backup_only = rfq()?.to_event_builder(other.public_key()).sign_with_keys(&other)?;
+    backup.insert_event(backup_only.clone());
+
+    let events = client.fetch_events(filters::option_rfq()).await?;
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().any(|e| e.id == report.event_id));
+    assert!(events.iter().any(|e| e.id == backup_only.id));
+
+    client.disconnect().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn publish_fails_without_enough_acks() -> anyhow::Result<()> {
+    let (primary, backup) = (MockRelay::run().await?, MockRelay::run().await?);
+    backup.reject_events(Some("blocked: test"));
+
+    let keys = Keys::generate();
+    let client = PublishingClient::connect(config(&[&primary, &backup]).with_min_acks(2), keys).await?;
+    assert!(primary.wait_for_connections(1, TIMEOUT).await);
+    assert!(backup.wait_for_connections(1, TIMEOUT).await);
+
+    let Err(RelayError::InsufficientAcks(report)) = client.publish_option_rfq(&rfq()?).await else {
+        panic!("publish should fail when a required relay rejects the event");
+    };
+
+    assert_eq!(report.accepted_count(), 1);
+    assert!(report.primary_accepted());
+    assert_eq!(primary.stored_events().len(), 1);
+    assert!(backup.stored_events().is_empty());
+
+    client.disconnect().await;
+    Ok(())
+}
@@ -1,8 +1,9 @@
 #![warn(clippy::all, clippy::pedantic)]
 #![allow(clippy::missing_errors_doc)]
 
-use simplicityhl::elements::secp256k1_zkp::{self as secp256k1, Keypair, Message, schnorr::Signature};
-use simplicityhl::elements::{Address, Transaction, TxOut};
+use simplicityhl::elements::hashes::{Hash as _, HashEngine, hmac, sha256, sha512};
+use simplicityhl::elements::secp256k1_zkp::{self as secp256k1, Keypair, Message, SecretKey, schnorr::Signature};
+use simplicityhl::elements::{Address, Script, Transaction, TxOut};
 use simplicityhl::simplicity::bitcoin::XOnlyPublicKey;
 use simplicityhl::simplicity::hashes::Hash as _;
 use simplicityhl_core::{
@@ -23,6 +24,7 @@ pub enum SignerError {
 
 pub struct Signer {
     keypair: Keypair,
+    master_blinding_key: [u8; 32],
 }
 
 impl Signer {
@@ -35,7 +37,28 @@ impl Signer {
 
         let keypair = Keypair::from_secret_key(&secp, &secret_key);
 
-        Ok(Self { keypair })
+        Ok(Self {
+            keypair,
+            master_blinding_key: slip77_master_blinding_key(seed),
+        })
+    }
+
+    /// SLIP-77 private blinding key for `script_pubkey`, which the wallet blinds its change to.
+    pub fn blinding_key(&self, script_pubkey: &Script) -> Result<SecretKey, SignerError> {
+        let mut engine = hmac::HmacEngine::<sha256::Hash>::new(&self.master_blinding_key);
+        engine.input(script_pubkey.as_bytes());
+
+        Ok(SecretKey::from_slice(&hmac::Hmac::from_engine(engine).to_byte_array())?)
+    }
+
+    /// Private blinding key of confidential receive address `index` for `script_pubkey`: the SLIP-77
+    /// derivation over the script followed by the big-endian index.
+    pub fn receive_blinding_key(&self, script_pubkey: &Script, index: u32) -> Result<SecretKey, SignerError> {
+        let mut engine = hmac::HmacEngine::<sha256::Hash>::new(&self.master_blinding_key);
+        engine.input(script_pubkey.as_bytes());
+        engine.input(&index.to_be_bytes());
+
+        Ok(SecretKey::from_slice(&hmac::Hmac::from_engine(engine).to_byte_array())?)
     }
 
     #[must_use]
@@ -111,3 +134,18 @@ impl Signer {
         Ok(self.keypair.sign_schnorr(sighash_all))
     }
 }
+
+/// SLIP-77 master blinding key: the SLIP-21 node for the label `SLIP-0077` under the seed.
+fn slip77_master_blinding_key(seed: &[u8]) -> [u8; 32] {
+    let mut engine = hmac::HmacEngine::<sha512::Hash>::new(b"Symmetric key seed");
+    engine.input(seed);
+    let root = hmac::Hmac::<sha512::Hash>::from_engine(engine).to_byte_array();
+
+    let mut engine = hmac::HmacEngine::<sha512::Hash>::new(&root[..32]);
+    engine.input(b"\x00SLIP-0077");
+    let node = hmac::Hmac::<sha512::Hash>::from_engine(engine).to_byte_array();
+
+    let mut master_blinding_key = [0; 32];
+    master_blinding_key.copy_from_slice(&node[32..]);
+    master_blinding_key
+}